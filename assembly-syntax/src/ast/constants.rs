@@ -1,4 +1,4 @@
-use alloc::{boxed::Box, string::String, sync::Arc};
+use alloc::{boxed::Box, string::String, sync::Arc, vec::Vec};
 use core::fmt;
 
 use miden_core::FieldElement;
@@ -6,7 +6,7 @@ use miden_core::FieldElement;
 use super::DocString;
 use crate::{
     Felt, SourceSpan, Span, Spanned,
-    ast::Ident,
+    ast::{Ident, StructPath},
     parser::{ParsingError, WordValue},
 };
 
@@ -14,6 +14,7 @@ use crate::{
 // ================================================================================================
 
 /// Represents a constant definition in Miden Assembly syntax, i.e. `const.FOO = 1 + 1`.
+#[derive(Clone)]
 pub struct Constant {
     /// The source span of the definition.
     pub span: SourceSpan,
@@ -98,6 +99,40 @@ pub enum ConstantExpr {
     },
     String(Ident),
     Word(Span<WordValue>),
+    /// The size of a struct in field elements, i.e. `sizeof(NOTE)`.
+    SizeOf {
+        span: SourceSpan,
+        ty: StructPath,
+    },
+    /// The offset of a field from the start of a struct in field elements, i.e.
+    /// `offsetof(NOTE.key)`.
+    OffsetOf {
+        span: SourceSpan,
+        ty: StructPath,
+        field: Ident,
+    },
+    /// A conditional expression, i.e. `N > 0 ? N : 1`.
+    ///
+    /// Only the branch selected by the condition is evaluated.
+    Ternary {
+        span: SourceSpan,
+        cond: Box<ConstantExpr>,
+        then_expr: Box<ConstantExpr>,
+        else_expr: Box<ConstantExpr>,
+    },
+    /// The RPO digest of constant data, i.e. `hash(1, 2, 3)` or `hash("miden")`.
+    ///
+    /// The data is either a single string, which is hashed as bytes, or a sequence of field
+    /// elements and words, which are hashed as field elements.
+    Hash {
+        span: SourceSpan,
+        args: Vec<ConstantExpr>,
+    },
+    /// The ID of the event with the given name, i.e. `event("miden::transfer")`.
+    Event {
+        span: SourceSpan,
+        name: Box<ConstantExpr>,
+    },
 }
 
 impl ConstantExpr {
@@ -120,6 +155,23 @@ impl ConstantExpr {
         }
     }
 
+    /// Returns true if this expression refers to the layout of a struct defined in another module.
+    ///
+    /// Such expressions cannot be evaluated during semantic analysis of the module in which they
+    /// appear, and are instead evaluated when that module is linked.
+    pub fn is_deferred(&self) -> bool {
+        match self {
+            Self::SizeOf { ty, .. } | Self::OffsetOf { ty, .. } => ty.is_imported(),
+            Self::BinaryOp { lhs, rhs, .. } => lhs.is_deferred() || rhs.is_deferred(),
+            Self::Ternary { cond, then_expr, else_expr, .. } => {
+                cond.is_deferred() || then_expr.is_deferred() || else_expr.is_deferred()
+            },
+            Self::Hash { args, .. } => args.iter().any(Self::is_deferred),
+            Self::Event { name, .. } => name.is_deferred(),
+            Self::Literal(_) | Self::Var(_) | Self::String(_) | Self::Word(_) => false,
+        }
+    }
+
     /// Attempt to fold to a single value.
     ///
     /// This will only succeed if the expression has no references to other constants.
@@ -128,89 +180,58 @@ impl ConstantExpr {
     /// Returns an error if an invalid expression is found while folding, such as division by zero.
     pub fn try_fold(self) -> Result<Self, ParsingError> {
         match self {
-            Self::String(_) | Self::Literal(_) | Self::Var(_) | Self::Word(_) => Ok(self),
+            Self::String(_)
+            | Self::Literal(_)
+            | Self::Var(_)
+            | Self::Word(_)
+            | Self::SizeOf { .. }
+            | Self::OffsetOf { .. } => Ok(self),
             Self::BinaryOp { span, op, lhs, rhs } => {
-                if rhs.is_literal() {
-                    let rhs = Self::into_inner(rhs).try_fold()?;
-                    match rhs {
-                        Self::String(ident) => {
-                            Err(ParsingError::StringInArithmeticExpression { span: ident.span() })
-                        },
-                        Self::Literal(rhs) => {
-                            let lhs = Self::into_inner(lhs).try_fold()?;
-                            match lhs {
-                                Self::String(ident) => {
-                                    Err(ParsingError::StringInArithmeticExpression {
-                                        span: ident.span(),
-                                    })
-                                },
-                                Self::Literal(lhs) => {
-                                    let lhs = lhs.into_inner();
-                                    let rhs = rhs.into_inner();
-                                    let is_division =
-                                        matches!(op, ConstantOp::Div | ConstantOp::IntDiv);
-                                    let is_division_by_zero = is_division && rhs == Felt::ZERO;
-                                    if is_division_by_zero {
-                                        return Err(ParsingError::DivisionByZero { span });
-                                    }
-                                    match op {
-                                        ConstantOp::Add => {
-                                            Ok(Self::Literal(Span::new(span, lhs + rhs)))
-                                        },
-                                        ConstantOp::Sub => {
-                                            Ok(Self::Literal(Span::new(span, lhs - rhs)))
-                                        },
-                                        ConstantOp::Mul => {
-                                            Ok(Self::Literal(Span::new(span, lhs * rhs)))
-                                        },
-                                        ConstantOp::Div => {
-                                            Ok(Self::Literal(Span::new(span, lhs / rhs)))
-                                        },
-                                        ConstantOp::IntDiv => Ok(Self::Literal(Span::new(
-                                            span,
-                                            Felt::new(lhs.as_int() / rhs.as_int()),
-                                        ))),
-                                    }
-                                },
-                                lhs => Ok(Self::BinaryOp {
-                                    span,
-                                    op,
-                                    lhs: Box::new(lhs),
-                                    rhs: Box::new(Self::Literal(rhs)),
-                                }),
-                            }
-                        },
-                        rhs => {
-                            let lhs = Self::into_inner(lhs).try_fold()?;
-                            Ok(Self::BinaryOp {
-                                span,
-                                op,
-                                lhs: Box::new(lhs),
-                                rhs: Box::new(rhs),
-                            })
-                        },
-                    }
-                } else {
-                    let lhs = Self::into_inner(lhs).try_fold()?;
-                    Ok(Self::BinaryOp { span, op, lhs: Box::new(lhs), rhs })
+                let lhs = lhs.try_fold()?;
+                let rhs = rhs.try_fold()?;
+                match (lhs, rhs) {
+                    (Self::String(ident), _) | (_, Self::String(ident)) => {
+                        Err(ParsingError::StringInArithmeticExpression { span: ident.span() })
+                    },
+                    (Self::Literal(lhs), Self::Literal(rhs)) => op
+                        .eval(lhs.into_inner(), rhs.into_inner())
+                        .map(|value| Self::Literal(Span::new(span, value)))
+                        .map_err(|error| ParsingError::from_const_eval_error(error, span)),
+                    (lhs, rhs) => Ok(Self::BinaryOp {
+                        span,
+                        op,
+                        lhs: Box::new(lhs),
+                        rhs: Box::new(rhs),
+                    }),
                 }
             },
+            Self::Ternary { span, cond, then_expr, else_expr } => match cond.try_fold()? {
+                Self::String(ident) => {
+                    Err(ParsingError::StringInArithmeticExpression { span: ident.span() })
+                },
+                Self::Literal(cond) if cond.into_inner() == Felt::ZERO => else_expr.try_fold(),
+                Self::Literal(_) => then_expr.try_fold(),
+                cond => Ok(Self::Ternary {
+                    span,
+                    cond: Box::new(cond),
+                    then_expr: Box::new(then_expr.try_fold()?),
+                    else_expr: Box::new(else_expr.try_fold()?),
+                }),
+            },
+            Self::Hash { span, args } => {
+                let args = args.into_iter().map(Self::try_fold).collect::<Result<Vec<_>, _>>()?;
+                match Self::eval_hash(&args) {
+                    Ok(Some(digest)) => Ok(Self::Word(Span::new(span, WordValue(*digest)))),
+                    Ok(None) => Ok(Self::Hash { span, args }),
+                    Err(error) => Err(ParsingError::from_const_eval_error(error, span)),
+                }
+            },
+            Self::Event { span, name } => match name.try_fold()? {
+                Self::String(name) => Ok(Self::Literal(Span::new(span, Self::eval_event(&name)))),
+                name => Ok(Self::Event { span, name: Box::new(name) }),
+            },
         }
     }
-
-    fn is_literal(&self) -> bool {
-        match self {
-            Self::Literal(_) | Self::String(_) | Self::Word(_) => true,
-            Self::Var(_) => false,
-            Self::BinaryOp { lhs, rhs, .. } => lhs.is_literal() && rhs.is_literal(),
-        }
-    }
-
-    #[inline(always)]
-    #[allow(clippy::boxed_local)]
-    fn into_inner(self: Box<Self>) -> Self {
-        *self
-    }
 }
 
 impl Eq for ConstantExpr {}
@@ -219,16 +240,92 @@ impl PartialEq for ConstantExpr {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
             (Self::Literal(l), Self::Literal(y)) => l == y,
-            (Self::Var(l), Self::Var(y)) => l == y,
+            (Self::Var(l), Self::Var(y)) | (Self::String(l), Self::String(y)) => l == y,
+            (Self::Word(l), Self::Word(y)) => l == y,
             (
                 Self::BinaryOp { op: lop, lhs: llhs, rhs: lrhs, .. },
                 Self::BinaryOp { op: rop, lhs: rlhs, rhs: rrhs, .. },
             ) => lop == rop && llhs == rlhs && lrhs == rrhs,
+            (Self::SizeOf { ty: l, .. }, Self::SizeOf { ty: r, .. }) => l == r,
+            (
+                Self::OffsetOf { ty: lty, field: lfield, .. },
+                Self::OffsetOf { ty: rty, field: rfield, .. },
+            ) => lty == rty && lfield == rfield,
+            (
+                Self::Ternary {
+                    cond: lcond,
+                    then_expr: lthen,
+                    else_expr: lelse,
+                    ..
+                },
+                Self::Ternary {
+                    cond: rcond,
+                    then_expr: rthen,
+                    else_expr: relse,
+                    ..
+                },
+            ) => lcond == rcond && lthen == rthen && lelse == relse,
+            (Self::Hash { args: l, .. }, Self::Hash { args: r, .. }) => l == r,
+            (Self::Event { name: l, .. }, Self::Event { name: r, .. }) => l == r,
             _ => false,
         }
     }
 }
 
+impl Ord for ConstantExpr {
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        let rank = |expr: &Self| match expr {
+            Self::Literal(_) => 0,
+            Self::Var(_) => 1,
+            Self::BinaryOp { .. } => 2,
+            Self::String(_) => 3,
+            Self::Word(_) => 4,
+            Self::SizeOf { .. } => 5,
+            Self::OffsetOf { .. } => 6,
+            Self::Ternary { .. } => 7,
+            Self::Hash { .. } => 8,
+            Self::Event { .. } => 9,
+        };
+        match (self, other) {
+            (Self::Literal(l), Self::Literal(r)) => l.as_int().cmp(&r.as_int()),
+            (Self::Var(l), Self::Var(r)) | (Self::String(l), Self::String(r)) => l.cmp(r),
+            (Self::Word(l), Self::Word(r)) => l.cmp(r),
+            (
+                Self::BinaryOp { op: lop, lhs: llhs, rhs: lrhs, .. },
+                Self::BinaryOp { op: rop, lhs: rlhs, rhs: rrhs, .. },
+            ) => lop.cmp(rop).then_with(|| llhs.cmp(rlhs)).then_with(|| lrhs.cmp(rrhs)),
+            (Self::SizeOf { ty: l, .. }, Self::SizeOf { ty: r, .. }) => l.cmp(r),
+            (
+                Self::OffsetOf { ty: lty, field: lfield, .. },
+                Self::OffsetOf { ty: rty, field: rfield, .. },
+            ) => lty.cmp(rty).then_with(|| lfield.cmp(rfield)),
+            (
+                Self::Ternary {
+                    cond: lcond,
+                    then_expr: lthen,
+                    else_expr: lelse,
+                    ..
+                },
+                Self::Ternary {
+                    cond: rcond,
+                    then_expr: rthen,
+                    else_expr: relse,
+                    ..
+                },
+            ) => lcond.cmp(rcond).then_with(|| lthen.cmp(rthen)).then_with(|| lelse.cmp(relse)),
+            (Self::Hash { args: l, .. }, Self::Hash { args: r, .. }) => l.cmp(r),
+            (Self::Event { name: l, .. }, Self::Event { name: r, .. }) => l.cmp(r),
+            _ => rank(self).cmp(&rank(other)),
+        }
+    }
+}
+
+impl PartialOrd for ConstantExpr {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
 impl fmt::Debug for ConstantExpr {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
@@ -238,6 +335,15 @@ impl fmt::Debug for ConstantExpr {
             Self::BinaryOp { op, lhs, rhs, .. } => {
                 f.debug_tuple(op.name()).field(lhs).field(rhs).finish()
             },
+            Self::SizeOf { ty, .. } => f.debug_tuple("SizeOf").field(ty).finish(),
+            Self::OffsetOf { ty, field, .. } => {
+                f.debug_tuple("OffsetOf").field(ty).field(field).finish()
+            },
+            Self::Ternary { cond, then_expr, else_expr, .. } => {
+                f.debug_tuple("Ternary").field(cond).field(then_expr).field(else_expr).finish()
+            },
+            Self::Hash { args, .. } => f.debug_tuple("Hash").field(args).finish(),
+            Self::Event { name, .. } => f.debug_tuple("Event").field(name).finish(),
         }
     }
 }
@@ -249,12 +355,57 @@ impl crate::prettier::PrettyPrint for ConstantExpr {
         match self {
             Self::Literal(literal) => display(literal),
             Self::Word(spanned) => spanned.render(),
-            Self::Var(ident) | Self::String(ident) => display(ident),
+            Self::Var(ident) => display(ident),
+            Self::String(ident) => text(format!("\"{ident}\"")),
             Self::BinaryOp { op, lhs, rhs, .. } => {
-                let single_line = lhs.render() + display(op) + rhs.render();
-                let multi_line = lhs.render() + nl() + (display(op)) + rhs.render();
+                // Operands which bind less tightly than `op` must be parenthesized, as must those
+                // of the same precedence unless `op` associates towards them: `**` is
+                // right-associative, comparisons are non-associative, and the rest associate left
+                let associates = |is_rhs: bool| match op.precedence() {
+                    1 => false,
+                    _ => is_rhs == matches!(op, ConstantOp::Pow),
+                };
+                let operand = |expr: &Self, is_rhs: bool| match expr {
+                    Self::BinaryOp { op: inner, .. }
+                        if inner.precedence() < op.precedence()
+                            || (inner.precedence() == op.precedence() && !associates(is_rhs)) =>
+                    {
+                        const_text("(") + expr.render() + const_text(")")
+                    },
+                    Self::Ternary { .. } => const_text("(") + expr.render() + const_text(")"),
+                    _ => expr.render(),
+                };
+                let single_line = operand(lhs, false) + display(op) + operand(rhs, true);
+                let multi_line = operand(lhs, false) + nl() + display(op) + operand(rhs, true);
                 single_line | multi_line
             },
+            Self::SizeOf { ty, .. } => const_text("sizeof(") + display(ty) + const_text(")"),
+            Self::OffsetOf { ty, field, .. } => {
+                const_text("offsetof(")
+                    + display(ty)
+                    + const_text(".")
+                    + display(field)
+                    + const_text(")")
+            },
+            Self::Ternary { cond, then_expr, else_expr, .. } => {
+                let cond = match cond.as_ref() {
+                    Self::Ternary { .. } => const_text("(") + cond.render() + const_text(")"),
+                    cond => cond.render(),
+                };
+                cond + const_text(" ? ")
+                    + then_expr.render()
+                    + const_text(" : ")
+                    + else_expr.render()
+            },
+            Self::Hash { args, .. } => {
+                let args = args
+                    .iter()
+                    .map(|arg| arg.render())
+                    .reduce(|acc, arg| acc + const_text(", ") + arg)
+                    .unwrap_or(Document::Empty);
+                const_text("hash(") + args + const_text(")")
+            },
+            Self::Event { name, .. } => const_text("event(") + name.render() + const_text(")"),
         }
     }
 }
@@ -265,7 +416,12 @@ impl Spanned for ConstantExpr {
             Self::Literal(spanned) => spanned.span(),
             Self::Word(spanned) => spanned.span(),
             Self::Var(spanned) | Self::String(spanned) => spanned.span(),
-            Self::BinaryOp { span, .. } => *span,
+            Self::BinaryOp { span, .. }
+            | Self::SizeOf { span, .. }
+            | Self::OffsetOf { span, .. }
+            | Self::Ternary { span, .. }
+            | Self::Hash { span, .. }
+            | Self::Event { span, .. } => *span,
         }
     }
 }
@@ -273,14 +429,29 @@ impl Spanned for ConstantExpr {
 // CONSTANT OPERATION
 // ================================================================================================
 
-/// Represents the set of binary arithmetic operators supported in Miden Assembly syntax.
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+/// Represents the set of binary operators supported in Miden Assembly constant expressions.
+///
+/// See [ConstantOp::eval] for their semantics.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub enum ConstantOp {
     Add,
     Sub,
     Mul,
     Div,
     IntDiv,
+    Mod,
+    Pow,
+    And,
+    Or,
+    Xor,
+    Shl,
+    Shr,
+    Eq,
+    Neq,
+    Lt,
+    Lte,
+    Gt,
+    Gte,
 }
 
 impl ConstantOp {
@@ -291,6 +462,33 @@ impl ConstantOp {
             Self::Mul => "Mul",
             Self::Div => "Div",
             Self::IntDiv => "IntDiv",
+            Self::Mod => "Mod",
+            Self::Pow => "Pow",
+            Self::And => "And",
+            Self::Or => "Or",
+            Self::Xor => "Xor",
+            Self::Shl => "Shl",
+            Self::Shr => "Shr",
+            Self::Eq => "Eq",
+            Self::Neq => "Neq",
+            Self::Lt => "Lt",
+            Self::Lte => "Lte",
+            Self::Gt => "Gt",
+            Self::Gte => "Gte",
+        }
+    }
+
+    /// Returns the binding power of this operator, higher values binding more tightly.
+    pub const fn precedence(&self) -> u8 {
+        match self {
+            Self::Eq | Self::Neq | Self::Lt | Self::Lte | Self::Gt | Self::Gte => 1,
+            Self::Or => 2,
+            Self::Xor => 3,
+            Self::And => 4,
+            Self::Shl | Self::Shr => 5,
+            Self::Add | Self::Sub => 6,
+            Self::Mul | Self::Div | Self::IntDiv | Self::Mod => 7,
+            Self::Pow => 8,
         }
     }
 }
@@ -303,6 +501,19 @@ impl fmt::Display for ConstantOp {
             Self::Mul => f.write_str("*"),
             Self::Div => f.write_str("/"),
             Self::IntDiv => f.write_str("//"),
+            Self::Mod => f.write_str("%"),
+            Self::Pow => f.write_str("**"),
+            Self::And => f.write_str("&"),
+            Self::Or => f.write_str("|"),
+            Self::Xor => f.write_str("^"),
+            Self::Shl => f.write_str("<<"),
+            Self::Shr => f.write_str(">>"),
+            Self::Eq => f.write_str("=="),
+            Self::Neq => f.write_str("!="),
+            Self::Lt => f.write_str("<"),
+            Self::Lte => f.write_str("<="),
+            Self::Gt => f.write_str(">"),
+            Self::Gte => f.write_str(">="),
         }
     }
 }
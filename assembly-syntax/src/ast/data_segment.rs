@@ -0,0 +1,49 @@
+use alloc::{string::String, vec::Vec};
+use core::ops::Range;
+
+use miden_core::crypto::hash::Rpo256;
+
+use super::DocString;
+use crate::{Felt, SourceSpan, Span, Word};
+
+// Static data which the program copies into memory before executing its entrypoint.
+// ==================================================================================
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DataSegment {
+    /// The source span of the definition.
+    pub span: SourceSpan,
+    /// The documentation string attached to this definition.
+    pub docs: Option<DocString>,
+    /// The memory address at which the first element of the segment is written.
+    pub address: Span<u32>,
+    /// The elements written to memory, a whole number of words.
+    pub data: Vec<Felt>,
+}
+
+impl DataSegment {
+    pub fn new(span: SourceSpan, address: Span<u32>, data: Vec<Felt>) -> Self {
+        Self { span, docs: None, address, data }
+    }
+
+    /// Adds documentation to this data segment.
+    pub fn with_docs(mut self, docs: Option<Span<String>>) -> Self {
+        self.docs = docs.map(DocString::new);
+        self
+    }
+
+    /// Returns the range of memory addresses written by this segment.
+    ///
+    /// The range is computed with saturating arithmetic, so that it stays meaningful in error
+    /// messages about segments which do not fit in memory.
+    pub fn addresses(&self) -> Range<u64> {
+        let start = u64::from(*self.address.inner());
+        start..start.saturating_add(self.data.len() as u64)
+    }
+
+    /// Returns the commitment to the data of this segment, under which it is stored in the advice
+    /// map, and against which it is verified when copied into memory.
+    pub fn commitment(&self) -> Word {
+        Rpo256::hash_elements(&self.data)
+    }
+}
@@ -1,6 +1,6 @@
 use alloc::string::String;
 
-use super::{AdviceMapEntry, Block, Constant, Export, Import};
+use super::{AdviceMapEntry, Attribute, Block, Constant, DataSegment, Export, Import, Struct};
 use crate::{SourceSpan, Span, Spanned};
 
 /// This type represents the top-level forms of a Miden Assembly module
@@ -10,16 +10,22 @@ pub enum Form {
     ModuleDoc(Span<String>),
     /// A documentation string
     Doc(Span<String>),
+    /// An attribute which applies to the entire module, e.g. `@!allow(unused_constant)`
+    Attribute(Attribute),
     /// An import from another module
     Import(Import),
     /// A constant definition, possibly unresolved
     Constant(Constant),
+    /// A struct definition, describing the layout of a record in memory
+    Struct(Struct),
     /// An executable block, represents a program entrypoint
     Begin(Block),
     /// A procedure
     Procedure(Export),
     /// An entry into the Advice Map
     AdviceMapEntry(AdviceMapEntry),
+    /// Static data copied into memory when the program starts
+    DataSegment(DataSegment),
 }
 
 impl From<Span<String>> for Form {
@@ -40,6 +46,12 @@ impl From<Constant> for Form {
     }
 }
 
+impl From<Struct> for Form {
+    fn from(def: Struct) -> Self {
+        Self::Struct(def)
+    }
+}
+
 impl From<Block> for Form {
     fn from(block: Block) -> Self {
         Self::Begin(block)
@@ -58,7 +70,10 @@ impl Spanned for Form {
             Self::ModuleDoc(spanned) | Self::Doc(spanned) => spanned.span(),
             Self::Import(Import { span, .. })
             | Self::Constant(Constant { span, .. })
-            | Self::AdviceMapEntry(AdviceMapEntry { span, .. }) => *span,
+            | Self::Struct(Struct { span, .. })
+            | Self::AdviceMapEntry(AdviceMapEntry { span, .. })
+            | Self::DataSegment(DataSegment { span, .. }) => *span,
+            Self::Attribute(spanned) => spanned.span(),
             Self::Begin(spanned) => spanned.span(),
             Self::Procedure(spanned) => spanned.span(),
         }
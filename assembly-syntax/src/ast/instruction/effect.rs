@@ -0,0 +1,143 @@
+use super::Instruction;
+use crate::{
+    ast::{Immediate, StackEffect},
+    parser::IntValue,
+};
+
+impl Instruction {
+    /// Returns the effect of this instruction on the operand stack, in terms of field elements.
+    ///
+    /// Returns `None` if the effect cannot be determined from the instruction alone, i.e. for
+    /// procedure invocations, and for pushes of constants which have not yet been evaluated.
+    pub fn stack_effect(&self) -> Option<StackEffect> {
+        use Instruction::*;
+
+        let (inputs, outputs) = match self {
+            Nop | Breakpoint | Debug(_) | Emit(_) | Trace(_) => (0, 0),
+            // system events only read from the operand stack
+            SysEvent(_) => (0, 0),
+
+            Assert | AssertWithError(_) | Assertz | AssertzWithError(_) => (1, 0),
+            AssertEq | AssertEqWithError(_) => (2, 0),
+            AssertEqw | AssertEqwWithError(_) => (8, 0),
+
+            Add | Sub | Mul | Div | Exp | ExpBitLength(_) | And | Or | Xor | Eq | Neq | Lt
+            | Lte | Gt | Gte => (2, 1),
+            AddImm(_) | SubImm(_) | MulImm(_) | DivImm(_) | ExpImm(_) | EqImm(_) | NeqImm(_)
+            | Neg | ILog2 | Inv | Incr | Pow2 | Not | IsOdd => (1, 1),
+            Eqw => (8, 9),
+
+            Ext2Add | Ext2Sub | Ext2Mul | Ext2Div => (4, 2),
+            Ext2Neg | Ext2Inv => (2, 2),
+
+            U32Test => (1, 2),
+            U32TestW => (4, 5),
+            U32Assert | U32AssertWithError(_) | U32Cast => (1, 1),
+            U32Assert2 | U32Assert2WithError(_) => (2, 2),
+            U32AssertW | U32AssertWWithError(_) => (4, 4),
+            U32Split => (1, 2),
+            U32WrappingAdd | U32WrappingSub | U32WrappingMul | U32Div | U32Mod | U32And | U32Or
+            | U32Xor | U32Shr | U32Shl | U32Rotr | U32Rotl | U32Lt | U32Lte | U32Gt | U32Gte
+            | U32Min | U32Max => (2, 1),
+            U32WrappingAddImm(_) | U32WrappingSubImm(_) | U32WrappingMulImm(_) | U32DivImm(_)
+            | U32ModImm(_) | U32Not | U32ShrImm(_) | U32ShlImm(_) | U32RotrImm(_)
+            | U32RotlImm(_) | U32Popcnt | U32Ctz | U32Clz | U32Clo | U32Cto => (1, 1),
+            U32OverflowingAdd | U32OverflowingSub | U32OverflowingMul | U32DivMod => (2, 2),
+            U32OverflowingAddImm(_)
+            | U32OverflowingSubImm(_)
+            | U32OverflowingMulImm(_)
+            | U32DivModImm(_) => (1, 2),
+            U32OverflowingAdd3 | U32OverflowingMadd => (3, 2),
+            U32WrappingAdd3 | U32WrappingMadd => (3, 1),
+
+            Drop => (1, 0),
+            DropW => (4, 0),
+            PadW => (0, 4),
+            Dup0 => (1, 2),
+            Dup1 => (2, 3),
+            Dup2 => (3, 4),
+            Dup3 => (4, 5),
+            Dup4 => (5, 6),
+            Dup5 => (6, 7),
+            Dup6 => (7, 8),
+            Dup7 => (8, 9),
+            Dup8 => (9, 10),
+            Dup9 => (10, 11),
+            Dup10 => (11, 12),
+            Dup11 => (12, 13),
+            Dup12 => (13, 14),
+            Dup13 => (14, 15),
+            Dup14 => (15, 16),
+            Dup15 => (16, 17),
+            DupW0 => (4, 8),
+            DupW1 => (8, 12),
+            DupW2 => (12, 16),
+            DupW3 => (16, 20),
+            Swap1 => (2, 2),
+            Swap2 | MovUp2 | MovDn2 => (3, 3),
+            Swap3 | MovUp3 | MovDn3 => (4, 4),
+            Swap4 | MovUp4 | MovDn4 => (5, 5),
+            Swap5 | MovUp5 | MovDn5 => (6, 6),
+            Swap6 | MovUp6 | MovDn6 => (7, 7),
+            Swap7 | MovUp7 | MovDn7 | SwapW1 => (8, 8),
+            Swap8 | MovUp8 | MovDn8 => (9, 9),
+            Swap9 | MovUp9 | MovDn9 => (10, 10),
+            Swap10 | MovUp10 | MovDn10 => (11, 11),
+            Swap11 | MovUp11 | MovDn11 | SwapW2 | MovUpW2 | MovDnW2 => (12, 12),
+            Swap12 | MovUp12 | MovDn12 => (13, 13),
+            Swap13 | MovUp13 | MovDn13 => (14, 14),
+            Swap14 | MovUp14 | MovDn14 => (15, 15),
+            Swap15 | MovUp15 | MovDn15 | SwapW3 | MovUpW3 | MovDnW3 | SwapDw => (16, 16),
+            CSwap => (3, 2),
+            CSwapW => (9, 8),
+            CDrop => (3, 1),
+            CDropW => (9, 4),
+
+            Push(Immediate::Value(value)) => match value.inner() {
+                IntValue::Word(_) => (0, 4),
+                _ => (0, 1),
+            },
+            Push(Immediate::Constant(_)) => return None,
+            PushU8(_) | PushU16(_) | PushU32(_) | PushFelt(_) => (0, 1),
+            PushWord(_) => (0, 4),
+            PushU8List(values) => (0, values.len()),
+            PushU16List(values) => (0, values.len()),
+            PushU32List(values) => (0, values.len()),
+            PushFeltList(values) => (0, values.len()),
+            Locaddr(_) | Sdepth | Clk => (0, 1),
+            Caller => (4, 4),
+
+            MemLoad => (1, 1),
+            MemLoadImm(_) | LocLoad(_) => (0, 1),
+            MemLoadW => (5, 4),
+            MemLoadWImm(_) | LocLoadW(_) => (4, 4),
+            MemStore => (2, 0),
+            MemStoreImm(_) | LocStore(_) => (1, 0),
+            MemStoreW => (5, 4),
+            MemStoreWImm(_) | LocStoreW(_) => (4, 4),
+            MemStream | AdvPipe => (13, 13),
+            AdvPush(n) => match n {
+                Immediate::Value(n) => (0, n.into_inner() as usize),
+                Immediate::Constant(_) => return None,
+            },
+            AdvLoadW => (4, 4),
+
+            Hash => (4, 4),
+            HMerge => (8, 4),
+            HPerm => (12, 12),
+            MTreeGet => (6, 8),
+            MTreeSet => (10, 8),
+            MTreeMerge => (8, 4),
+            MTreeVerify | MTreeVerifyWithError(_) => (10, 10),
+
+            FriExt2Fold4 => (17, 16),
+            HornerBase | HornerExt => (16, 16),
+            ArithmeticCircuitEval => (3, 3),
+
+            ProcRef(_) => (0, 4),
+            Exec(_) | Call(_) | SysCall(_) | DynExec | DynCall => return None,
+        };
+
+        Some(StackEffect::new(inputs, outputs))
+    }
+}
@@ -1,5 +1,6 @@
 pub mod advice;
 pub mod debug;
+mod effect;
 mod print;
 
 use alloc::vec::Vec;
@@ -282,6 +282,9 @@ impl PrettyPrint for Instruction {
             Self::Exec(InvocationTarget::AbsoluteProcedurePath { name, path }) => {
                 const_text("exec") + const_text(".") + text(format!("::{path}::{name}"))
             },
+            Self::Exec(target @ InvocationTarget::Instantiation { .. }) => {
+                flatten(const_text("exec") + const_text(".") + target.render())
+            },
             Self::Call(InvocationTarget::MastRoot(root)) => {
                 const_text("call")
                     + const_text(".")
@@ -296,6 +299,9 @@ impl PrettyPrint for Instruction {
             Self::Call(InvocationTarget::AbsoluteProcedurePath { name, path }) => {
                 const_text("call") + const_text(".") + text(format!("::{path}::{name}"))
             },
+            Self::Call(target @ InvocationTarget::Instantiation { .. }) => {
+                flatten(const_text("call") + const_text(".") + target.render())
+            },
             Self::SysCall(InvocationTarget::MastRoot(root)) => {
                 const_text("syscall")
                     + const_text(".")
@@ -310,6 +316,9 @@ impl PrettyPrint for Instruction {
             Self::SysCall(InvocationTarget::AbsoluteProcedurePath { name, path }) => {
                 const_text("syscall") + const_text(".") + text(format!("::{path}::{name}"))
             },
+            Self::SysCall(target @ InvocationTarget::Instantiation { .. }) => {
+                flatten(const_text("syscall") + const_text(".") + target.render())
+            },
             Self::DynExec => const_text("dynexec"),
             Self::DynCall => const_text("dyncall"),
             Self::ProcRef(InvocationTarget::MastRoot(_)) => {
@@ -324,6 +333,9 @@ impl PrettyPrint for Instruction {
             Self::ProcRef(InvocationTarget::AbsoluteProcedurePath { name, path }) => {
                 flatten(const_text("procref") + const_text(".") + text(format!("::{path}::{name}")))
             },
+            Self::ProcRef(target @ InvocationTarget::Instantiation { .. }) => {
+                flatten(const_text("procref") + const_text(".") + target.render())
+            },
 
             // ----- debug decorators -------------------------------------------------------------
             Self::Breakpoint => const_text("breakpoint"),
@@ -1,8 +1,9 @@
+use alloc::vec::Vec;
 use core::fmt;
 
 use crate::{
     LibraryPath, SourceSpan, Span, Spanned, Word,
-    ast::{Ident, ProcedureName},
+    ast::{ConstantExpr, Ident, ProcedureName},
 };
 
 // INVOKE
@@ -77,6 +78,15 @@ pub enum InvocationTarget {
     /// A fully-resolved procedure path, which refers to a specific externally-defined procedure
     /// with its full path.
     AbsoluteProcedurePath { name: ProcedureName, path: LibraryPath },
+    /// An instantiation of a locally-defined generic procedure with the given constant arguments,
+    /// e.g. `exec.foo(4, OFFSET)`.
+    ///
+    /// Instantiations are expanded during semantic analysis, and replaced with a reference to the
+    /// procedure instantiated for the values of the arguments, so they never reach the assembler.
+    Instantiation {
+        name: ProcedureName,
+        args: Vec<ConstantExpr>,
+    },
 }
 
 impl Spanned for InvocationTarget {
@@ -84,9 +94,9 @@ impl Spanned for InvocationTarget {
         match self {
             Self::MastRoot(spanned) => spanned.span(),
             Self::ProcedureName(spanned) => spanned.span(),
-            Self::ProcedurePath { name, .. } | Self::AbsoluteProcedurePath { name, .. } => {
-                name.span()
-            },
+            Self::ProcedurePath { name, .. }
+            | Self::AbsoluteProcedurePath { name, .. }
+            | Self::Instantiation { name, .. } => name.span(),
         }
     }
 }
@@ -102,6 +112,14 @@ impl crate::prettier::PrettyPrint for InvocationTarget {
             Self::ProcedureName(name) => display(name),
             Self::ProcedurePath { name, module } => display(format_args!("{module}::{name}")),
             Self::AbsoluteProcedurePath { name, path } => display(format_args!("::{path}::{name}")),
+            Self::Instantiation { name, args } => {
+                let args = args
+                    .iter()
+                    .map(|arg| arg.render())
+                    .reduce(|acc, arg| acc + const_text(", ") + arg)
+                    .unwrap_or(Document::Empty);
+                display(name) + const_text("(") + args + const_text(")")
+            },
         }
     }
 }
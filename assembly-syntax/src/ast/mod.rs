@@ -4,6 +4,7 @@ mod advice_map_entry;
 mod attribute;
 mod block;
 mod constants;
+mod data_segment;
 mod docstring;
 mod form;
 mod ident;
@@ -14,6 +15,7 @@ mod invocation_target;
 mod module;
 mod op;
 mod procedure;
+mod structs;
 #[cfg(test)]
 mod tests;
 pub mod visit;
@@ -26,6 +28,7 @@ pub use self::{
     },
     block::Block,
     constants::{Constant, ConstantExpr, ConstantOp},
+    data_segment::DataSegment,
     docstring::DocString,
     form::Form,
     ident::{CaseKindError, Ident, IdentError},
@@ -34,8 +37,9 @@ pub use self::{
     instruction::{DebugOptions, Instruction, SystemEventNode},
     invocation_target::{InvocationTarget, Invoke, InvokeKind},
     module::{Module, ModuleKind},
-    op::Op,
+    op::{MatchCase, Op},
     procedure::*,
+    structs::{FieldType, Struct, StructField, StructPath},
     visit::{Visit, VisitMut},
 };
 
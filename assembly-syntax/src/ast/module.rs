@@ -7,8 +7,8 @@ use miden_core::{
 };
 
 use super::{
-    DocString, Export, Import, LocalNameResolver, ProcedureIndex, ProcedureName,
-    QualifiedProcedureName, ResolvedProcedure,
+    Constant, DataSegment, DocString, Export, Import, LocalNameResolver, ProcedureIndex,
+    ProcedureName, QualifiedProcedureName, ResolvedProcedure, Struct,
 };
 use crate::{
     LibraryNamespace, LibraryPath, SourceSpan, Span, Spanned,
@@ -123,6 +123,12 @@ pub struct Module {
     kind: ModuleKind,
     /// The imports defined in the module body.
     pub(crate) imports: Vec<Import>,
+    /// The structs defined in the module body, with their layouts.
+    pub(crate) structs: Vec<Struct>,
+    /// The constants whose values refer to the layout of structs defined in other modules, and
+    /// which are therefore evaluated when the module is linked, rather than during semantic
+    /// analysis.
+    pub(crate) deferred_constants: Vec<Constant>,
     /// The procedures (defined or re-exported) in the module body.
     ///
     /// NOTE: Despite the name, the procedures in this set are not necessarily exported, the
@@ -130,6 +136,9 @@ pub struct Module {
     pub(crate) procedures: Vec<Export>,
     /// AdviceMap that this module expects to be loaded in the host before executing.
     pub(crate) advice_map: AdviceMap,
+    /// The static data segments which an executable module copies into memory before executing
+    /// its entrypoint, in the order in which they are declared.
+    pub(crate) data_segments: Vec<DataSegment>,
 }
 
 /// Constants
@@ -155,8 +164,11 @@ impl Module {
             path,
             kind,
             imports: Default::default(),
+            structs: Default::default(),
+            deferred_constants: Default::default(),
             procedures: Default::default(),
             advice_map: Default::default(),
+            data_segments: Default::default(),
         }
     }
 
@@ -321,6 +333,35 @@ impl Module {
         &self.advice_map
     }
 
+    /// Get an iterator over the static data segments declared in this module.
+    pub fn data_segments(&self) -> core::slice::Iter<'_, DataSegment> {
+        self.data_segments.iter()
+    }
+
+    /// Get an iterator over the structs defined in this module.
+    pub fn structs(&self) -> core::slice::Iter<'_, Struct> {
+        self.structs.iter()
+    }
+
+    /// Returns the struct named `name`, if it is defined in this module.
+    pub fn get_struct(&self, name: &Ident) -> Option<&Struct> {
+        self.structs.iter().find(|def| &def.name == name)
+    }
+
+    /// Returns the constants of this module which could not be evaluated during semantic analysis,
+    /// because they refer to the layout of structs defined in other modules.
+    ///
+    /// Immediates referring to these constants are left unresolved until the module is linked.
+    pub fn deferred_constants(&self) -> &[Constant] {
+        &self.deferred_constants
+    }
+
+    /// Removes the deferred constants from this module, once they have been evaluated and the
+    /// immediates referring to them resolved.
+    pub fn take_deferred_constants(&mut self) -> Vec<Constant> {
+        core::mem::take(&mut self.deferred_constants)
+    }
+
     /// Get an iterator over the procedures defined in this module.
     ///
     /// The entity returned is an [Export], which abstracts over locally-defined procedures and
@@ -488,6 +529,8 @@ impl PartialEq for Module {
             && self.path == other.path
             && self.docs == other.docs
             && self.imports == other.imports
+            && self.structs == other.structs
+            && self.deferred_constants == other.deferred_constants
             && self.procedures == other.procedures
     }
 }
@@ -500,6 +543,8 @@ impl fmt::Debug for Module {
             .field("path", &self.path)
             .field("kind", &self.kind)
             .field("imports", &self.imports)
+            .field("structs", &self.structs)
+            .field("deferred_constants", &self.deferred_constants)
             .field("procedures", &self.procedures)
             .finish()
     }
@@ -543,6 +588,14 @@ impl crate::prettier::PrettyPrint for Module {
             doc += nl();
         }
 
+        for def in self.structs.iter() {
+            doc += nl() + def.render();
+        }
+
+        for constant in self.deferred_constants.iter() {
+            doc += nl() + constant.render() + nl();
+        }
+
         let mut export_index = 0;
         for export in self.procedures.iter() {
             if export.is_main() {
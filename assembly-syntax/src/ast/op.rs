@@ -1,6 +1,7 @@
+use alloc::vec::Vec;
 use core::fmt;
 
-use super::{Block, Instruction};
+use super::{Block, Immediate, Instruction};
 use crate::{SourceSpan, Span, Spanned};
 
 /// Represents the Miden Assembly instruction set syntax
@@ -25,14 +26,49 @@ pub enum Op {
     /// Represents a counter-controlled loop.
     ///
     /// NOTE: The iteration count must be known at compile-time, so this is _not_ used for general
-    /// `for`-style loops where the iteration count is dynamic.
+    /// `for`-style loops where the iteration count is dynamic. It may however be given by a
+    /// constant, which is replaced with its value during semantic analysis.
     Repeat {
         span: SourceSpan,
-        count: u32,
+        count: Immediate<u32>,
         body: Block,
     } = 2,
     /// A primitive operation, e.g. `add`
     Inst(Span<Instruction>) = 3,
+    /// Represents a multi-way branch on a u32 value
+    ///
+    /// The value on top of the stack is consumed, and the body of the case with that value is
+    /// executed, or the `default` body if there is no such case.
+    Match {
+        span: SourceSpan,
+        cases: Vec<MatchCase>,
+        default: Block,
+    } = 4,
+    /// Represents a counter-controlled loop with a loop counter
+    ///
+    /// The body is executed once for each value of the counter in `start..end`, in increasing
+    /// order, with the value of the counter pushed on top of the stack. Unlike [Op::Repeat], the
+    /// body is not unrolled. The bounds may be given by constants, which are replaced with their
+    /// values during semantic analysis.
+    For {
+        span: SourceSpan,
+        /// The first value of the counter, which is `0` if omitted, i.e. for `for.N`
+        start: Option<Immediate<u32>>,
+        /// The value of the counter at which the loop exits
+        end: Immediate<u32>,
+        body: Block,
+    } = 5,
+}
+
+/// Represents a single `case` of a [Op::Match]
+#[derive(Clone)]
+pub struct MatchCase {
+    pub span: SourceSpan,
+    /// The value for which this case is taken
+    ///
+    /// It may be given by a constant, which is replaced with its value during semantic analysis.
+    pub value: Immediate<u32>,
+    pub body: Block,
 }
 
 impl crate::prettier::PrettyPrint for Op {
@@ -47,6 +83,18 @@ impl crate::prettier::PrettyPrint for Op {
             Self::Repeat { count, body, .. } => {
                 display(format!("repeat.{count}")) + body.render() + text("end")
             },
+            Self::Match { cases, default, .. } => {
+                let cases = cases.iter().fold(Document::Empty, |acc, case| {
+                    acc + display(format!("case.{}", case.value)) + case.body.render()
+                });
+                text("match") + nl() + cases + text("default") + default.render() + text("end")
+            },
+            Self::For { start: Some(start), end, body, .. } => {
+                display(format!("for.{start}..{end}")) + body.render() + text("end")
+            },
+            Self::For { start: None, end, body, .. } => {
+                display(format!("for.{end}")) + body.render() + text("end")
+            },
             Self::Inst(inst) => inst.render(),
         }
     }
@@ -62,11 +110,29 @@ impl fmt::Debug for Op {
             Self::Repeat { count, body, .. } => {
                 f.debug_struct("Repeat").field("count", count).field("body", body).finish()
             },
+            Self::Match { cases, default, .. } => {
+                f.debug_struct("Match").field("cases", cases).field("default", default).finish()
+            },
+            Self::For { start, end, body, .. } => f
+                .debug_struct("For")
+                .field("start", start)
+                .field("end", end)
+                .field("body", body)
+                .finish(),
             Self::Inst(inst) => fmt::Debug::fmt(&**inst, f),
         }
     }
 }
 
+impl fmt::Debug for MatchCase {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Case")
+            .field("value", &self.value)
+            .field("body", &self.body)
+            .finish()
+    }
+}
+
 impl Eq for Op {}
 
 impl PartialEq for Op {
@@ -81,6 +147,18 @@ impl PartialEq for Op {
                 Self::Repeat { count: lcount, body: lbody, .. },
                 Self::Repeat { count: rcount, body: rbody, .. },
             ) => lcount == rcount && lbody == rbody,
+            (
+                Self::Match { cases: lcases, default: ldefault, .. },
+                Self::Match { cases: rcases, default: rdefault, .. },
+            ) => lcases == rcases && ldefault == rdefault,
+            (
+                Self::For {
+                    start: lstart, end: lend, body: lbody, ..
+                },
+                Self::For {
+                    start: rstart, end: rend, body: rbody, ..
+                },
+            ) => lstart == rstart && lend == rend && lbody == rbody,
             (Self::Inst(l), Self::Inst(r)) => l == r,
             _ => false,
         }
@@ -90,8 +168,26 @@ impl PartialEq for Op {
 impl Spanned for Op {
     fn span(&self) -> SourceSpan {
         match self {
-            Self::If { span, .. } | Self::While { span, .. } | Self::Repeat { span, .. } => *span,
+            Self::If { span, .. }
+            | Self::While { span, .. }
+            | Self::Repeat { span, .. }
+            | Self::Match { span, .. }
+            | Self::For { span, .. } => *span,
             Self::Inst(spanned) => spanned.span(),
         }
     }
 }
+
+impl Eq for MatchCase {}
+
+impl PartialEq for MatchCase {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value && self.body == other.body
+    }
+}
+
+impl Spanned for MatchCase {
+    fn span(&self) -> SourceSpan {
+        self.span
+    }
+}
@@ -166,7 +166,8 @@ impl TryFrom<InvocationTarget> for AliasTarget {
             InvocationTarget::AbsoluteProcedurePath { name, path: module } => {
                 Ok(Self::AbsoluteProcedurePath(QualifiedProcedureName { span, module, name }))
             },
-            target @ InvocationTarget::ProcedureName(_) => Err(target),
+            target @ (InvocationTarget::ProcedureName(_)
+            | InvocationTarget::Instantiation { .. }) => Err(target),
         }
     }
 }
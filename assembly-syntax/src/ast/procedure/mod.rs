@@ -4,6 +4,7 @@ mod name;
 #[allow(clippy::module_inception)]
 mod procedure;
 mod resolver;
+mod signature;
 
 use alloc::string::String;
 
@@ -11,8 +12,9 @@ pub use self::{
     alias::{AliasTarget, ProcedureAlias},
     id::ProcedureIndex,
     name::{ProcedureName, QualifiedProcedureName},
-    procedure::{Procedure, Visibility},
+    procedure::{InlineHint, Procedure, Visibility},
     resolver::{LocalNameResolver, ResolvedProcedure},
+    signature::{ProcedureSignature, StackEffect, StackType},
 };
 use crate::{
     SourceSpan, Span, Spanned,
@@ -67,6 +69,16 @@ impl Export {
         }
     }
 
+    /// Returns the signature of this procedure, if known.
+    ///
+    /// The signatures of re-exported procedures are not known until they are resolved.
+    pub fn signature(&self) -> Option<&ProcedureSignature> {
+        match self {
+            Self::Procedure(proc) => proc.signature(),
+            Self::Alias(_) => None,
+        }
+    }
+
     /// Returns the visibility of this procedure (e.g. public or private).
     ///
     /// See [Visibility] for more details on what visibilities are supported.
@@ -1,10 +1,10 @@
-use alloc::{collections::BTreeSet, string::String};
+use alloc::{collections::BTreeSet, string::String, vec::Vec};
 use core::fmt;
 
-use super::ProcedureName;
+use super::{ProcedureName, ProcedureSignature};
 use crate::{
-    SourceSpan, Span, Spanned,
-    ast::{Attribute, AttributeSet, Block, DocString, Invoke},
+    LibraryNamespace, LibraryPath, SourceSpan, Span, Spanned,
+    ast::{Attribute, AttributeSet, Block, DocString, Ident, Invoke},
 };
 
 // PROCEDURE VISIBILITY
@@ -21,14 +21,17 @@ pub enum Visibility {
     /// The procedure is visible only locally to the exec instruction.
     #[default]
     Private = 2,
+    /// The procedure is visible to the other modules in the same library namespace, but is not
+    /// exported from the library.
+    Internal = 3,
 }
 
 impl fmt::Display for Visibility {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        if self.is_exported() {
-            f.write_str("export")
-        } else {
-            f.write_str("proc")
+        match self {
+            Self::Public | Self::Syscall => f.write_str("export"),
+            Self::Internal => f.write_str("internal"),
+            Self::Private => f.write_str("proc"),
         }
     }
 }
@@ -43,6 +46,58 @@ impl Visibility {
     pub fn is_syscall(&self) -> bool {
         matches!(self, Self::Syscall)
     }
+
+    /// Returns true if the procedure is internal to the library namespace of its module
+    pub fn is_internal(&self) -> bool {
+        matches!(self, Self::Internal)
+    }
+
+    /// Returns true if a procedure with this visibility, defined in the module `callee`, may be
+    /// referenced from the module `caller`.
+    ///
+    /// The modules of a kernel are assembled as a unit, so they may also reference each other's
+    /// private procedures.
+    pub fn is_visible_from(&self, caller: &LibraryPath, callee: &LibraryPath) -> bool {
+        match self {
+            Self::Public | Self::Syscall => true,
+            Self::Internal => caller.namespace() == callee.namespace(),
+            Self::Private => {
+                caller == callee
+                    || (caller.namespace() == &LibraryNamespace::Kernel
+                        && callee.namespace() == &LibraryNamespace::Kernel)
+            },
+        }
+    }
+}
+
+// INLINE HINT
+// ================================================================================================
+
+/// Represents the inlining behavior requested for a procedure via its attributes.
+///
+/// Procedures without a hint are inlined, or not, according to the heuristic of the assembler.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum InlineHint {
+    /// The procedure is annotated with `@inline`, and is always inlined into its `exec` callers.
+    Always,
+    /// The procedure is annotated with `@noinline`, and is never inlined into its callers.
+    Never,
+}
+
+impl InlineHint {
+    /// The name of the attribute which requests that a procedure is always inlined
+    pub const INLINE_ATTRIBUTE: &'static str = "inline";
+    /// The name of the attribute which requests that a procedure is never inlined
+    pub const NOINLINE_ATTRIBUTE: &'static str = "noinline";
+}
+
+impl fmt::Display for InlineHint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Always => f.write_str(Self::INLINE_ATTRIBUTE),
+            Self::Never => f.write_str(Self::NOINLINE_ATTRIBUTE),
+        }
+    }
 }
 
 // PROCEDURE
@@ -57,10 +112,14 @@ pub struct Procedure {
     docs: Option<DocString>,
     /// The attributes attached to this procedure
     attrs: AttributeSet,
+    /// The signature of this procedure, if declared via its attributes
+    signature: Option<ProcedureSignature>,
     /// The local name of this procedure
     name: ProcedureName,
     /// The visibility of this procedure (i.e. whether it is exported or not)
     visibility: Visibility,
+    /// The constant parameters of this procedure, if it is generic
+    params: Vec<Ident>,
     /// The number of locals to allocate for this procedure
     num_locals: u16,
     /// The body of the procedure
@@ -84,8 +143,10 @@ impl Procedure {
             span,
             docs: None,
             attrs: Default::default(),
+            signature: None,
             name,
             visibility,
+            params: Vec::new(),
             num_locals,
             invoked: Default::default(),
             body,
@@ -107,6 +168,28 @@ impl Procedure {
         self
     }
 
+    /// Declares the constant parameters of this procedure, making it a generic procedure if
+    /// `params` is non-empty.
+    ///
+    /// A generic procedure is not assembled as-is, instead, each instantiation of it with a
+    /// distinct set of arguments produces a separate procedure during semantic analysis.
+    pub fn with_params(mut self, params: Vec<Ident>) -> Self {
+        self.params = params;
+        self
+    }
+
+    /// Creates an instance of this generic procedure called `name`.
+    ///
+    /// The instance is a private, non-generic copy of this procedure, in which references to the
+    /// parameters of this procedure remain to be replaced with the values they are bound to.
+    pub(crate) fn instantiate(&self, name: ProcedureName) -> Self {
+        let mut instance = self.clone();
+        instance.name = name;
+        instance.visibility = Visibility::Private;
+        instance.params.clear();
+        instance
+    }
+
     /// Modifies the visibility of this procedure.
     ///
     /// This is made crate-local as the visibility of a procedure is virtually always determined
@@ -116,6 +199,14 @@ impl Procedure {
     pub(crate) fn set_visibility(&mut self, visibility: Visibility) {
         self.visibility = visibility;
     }
+
+    /// Sets the signature of this procedure.
+    ///
+    /// The signature is derived from the `@inputs` and `@outputs` attributes of the procedure
+    /// during semantic analysis.
+    pub(crate) fn set_signature(&mut self, signature: Option<ProcedureSignature>) {
+        self.signature = signature;
+    }
 }
 
 /// Metadata
@@ -130,6 +221,17 @@ impl Procedure {
         self.visibility
     }
 
+    /// Returns the constant parameters of this procedure, which are empty unless it is generic.
+    pub fn params(&self) -> &[Ident] {
+        &self.params
+    }
+
+    /// Returns true if this procedure is parameterized by constants, i.e. it is a template from
+    /// which procedures are instantiated, rather than a procedure in its own right.
+    pub fn is_generic(&self) -> bool {
+        !self.params.is_empty()
+    }
+
     /// Returns the number of locals allocated by this procedure.
     pub fn num_locals(&self) -> u16 {
         self.num_locals
@@ -158,6 +260,23 @@ impl Procedure {
         &mut self.attrs
     }
 
+    /// Returns the inlining behavior requested via the `@inline` or `@noinline` attributes of this
+    /// procedure, if any.
+    pub fn inline_hint(&self) -> Option<InlineHint> {
+        if self.attrs.has(InlineHint::NOINLINE_ATTRIBUTE) {
+            Some(InlineHint::Never)
+        } else if self.attrs.has(InlineHint::INLINE_ATTRIBUTE) {
+            Some(InlineHint::Always)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the signature of this procedure, if one was declared.
+    pub fn signature(&self) -> Option<&ProcedureSignature> {
+        self.signature.as_ref()
+    }
+
     /// Returns true if this procedure has an attribute named `name`
     #[inline]
     pub fn has_attribute(&self, name: impl AsRef<str>) -> bool {
@@ -255,13 +374,23 @@ impl crate::prettier::PrettyPrint for Procedure {
                 .iter()
                 .map(|attr| attr.render())
                 .reduce(|acc, attr| acc + nl() + attr)
-                .unwrap_or(Document::Empty);
+                .unwrap_or(Document::Empty)
+                + nl();
         }
 
         if self.is_entrypoint() {
             doc += const_text("begin");
         } else {
             doc += display(self.visibility) + const_text(".") + display(&self.name);
+            if self.is_generic() {
+                let params = self
+                    .params
+                    .iter()
+                    .map(display)
+                    .reduce(|acc, param| acc + const_text(", ") + param)
+                    .unwrap_or(Document::Empty);
+                doc += const_text("(") + params + const_text(")");
+            }
             if self.num_locals > 0 {
                 doc += const_text(".") + display(self.num_locals);
             }
@@ -276,8 +405,10 @@ impl fmt::Debug for Procedure {
         f.debug_struct("Procedure")
             .field("docs", &self.docs)
             .field("attrs", &self.attrs)
+            .field("signature", &self.signature)
             .field("name", &self.name)
             .field("visibility", &self.visibility)
+            .field("params", &self.params)
             .field("num_locals", &self.num_locals)
             .field("body", &self.body)
             .field("invoked", &self.invoked)
@@ -291,6 +422,7 @@ impl PartialEq for Procedure {
     fn eq(&self, other: &Self) -> bool {
         self.name == other.name
             && self.visibility == other.visibility
+            && self.params == other.params
             && self.num_locals == other.num_locals
             && self.body == other.body
             && self.attrs == other.attrs
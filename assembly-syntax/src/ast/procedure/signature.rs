@@ -0,0 +1,257 @@
+use alloc::vec::Vec;
+use core::{fmt, str::FromStr};
+
+use miden_core::utils::{
+    ByteReader, ByteWriter, Deserializable, DeserializationError, Serializable,
+};
+
+// STACK TYPE
+// ================================================================================================
+
+/// The type of a value passed to, or returned from, a procedure on the operand stack.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(u8)]
+pub enum StackType {
+    /// A single field element.
+    Felt = 0,
+    /// A word, i.e. four field elements.
+    Word = 1,
+    /// A 32-bit unsigned integer, represented as a single field element.
+    U32 = 2,
+    /// A 64-bit unsigned integer, represented as two 32-bit limbs with the high limb on top.
+    U64 = 3,
+}
+
+impl StackType {
+    /// Returns the number of field elements a value of this type occupies on the operand stack.
+    pub const fn size_in_felts(&self) -> usize {
+        match self {
+            Self::Felt | Self::U32 => 1,
+            Self::Word => 4,
+            Self::U64 => 2,
+        }
+    }
+
+    /// Returns the name of this type as it appears in Miden Assembly syntax.
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Felt => "felt",
+            Self::Word => "word",
+            Self::U32 => "u32",
+            Self::U64 => "u64",
+        }
+    }
+}
+
+impl fmt::Display for StackType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for StackType {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "felt" => Ok(Self::Felt),
+            "word" => Ok(Self::Word),
+            "u32" => Ok(Self::U32),
+            "u64" => Ok(Self::U64),
+            _ => Err(()),
+        }
+    }
+}
+
+impl Serializable for StackType {
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        target.write_u8(*self as u8);
+    }
+}
+
+impl Deserializable for StackType {
+    fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
+        match source.read_u8()? {
+            0 => Ok(Self::Felt),
+            1 => Ok(Self::Word),
+            2 => Ok(Self::U32),
+            3 => Ok(Self::U64),
+            tag => Err(DeserializationError::InvalidValue(format!("invalid stack type tag {tag}"))),
+        }
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl proptest::prelude::Arbitrary for StackType {
+    type Parameters = ();
+
+    fn arbitrary_with(_args: Self::Parameters) -> Self::Strategy {
+        use proptest::prelude::*;
+
+        prop_oneof![Just(Self::Felt), Just(Self::Word), Just(Self::U32), Just(Self::U64)].boxed()
+    }
+
+    type Strategy = proptest::prelude::BoxedStrategy<Self>;
+}
+
+// PROCEDURE SIGNATURE
+// ================================================================================================
+
+/// The types of the values a procedure consumes from, and produces onto, the operand stack.
+///
+/// In Miden Assembly, signatures are declared using the `@inputs` and `@outputs` attributes, each
+/// of which lists types starting from the top of the stack, e.g.:
+///
+/// ```masm,ignore
+/// @inputs(u64, u64)
+/// @outputs(u64)
+/// export.add
+///     ...
+/// end
+/// ```
+///
+/// Either attribute may be omitted if the procedure has no inputs or outputs, respectively.
+#[derive(Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "arbitrary", derive(proptest_derive::Arbitrary))]
+pub struct ProcedureSignature {
+    /// The types of the inputs, starting from the top of the stack.
+    pub inputs: Vec<StackType>,
+    /// The types of the outputs, starting from the top of the stack.
+    pub outputs: Vec<StackType>,
+}
+
+impl ProcedureSignature {
+    /// The name of the attribute used to declare the inputs of a procedure.
+    pub const INPUTS_ATTRIBUTE: &'static str = "inputs";
+    /// The name of the attribute used to declare the outputs of a procedure.
+    pub const OUTPUTS_ATTRIBUTE: &'static str = "outputs";
+
+    /// Creates a new [ProcedureSignature] from the given input and output types.
+    pub fn new<I, O>(inputs: I, outputs: O) -> Self
+    where
+        I: IntoIterator<Item = StackType>,
+        O: IntoIterator<Item = StackType>,
+    {
+        Self {
+            inputs: inputs.into_iter().collect(),
+            outputs: outputs.into_iter().collect(),
+        }
+    }
+
+    /// Returns the number of field elements consumed from the operand stack.
+    pub fn num_input_felts(&self) -> usize {
+        self.inputs.iter().map(StackType::size_in_felts).sum()
+    }
+
+    /// Returns the number of field elements produced onto the operand stack.
+    pub fn num_output_felts(&self) -> usize {
+        self.outputs.iter().map(StackType::size_in_felts).sum()
+    }
+
+    /// Returns the effect of a procedure with this signature on the operand stack.
+    pub fn stack_effect(&self) -> StackEffect {
+        StackEffect::new(self.num_input_felts(), self.num_output_felts())
+    }
+}
+
+impl fmt::Display for ProcedureSignature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn write_types(f: &mut fmt::Formatter<'_>, types: &[StackType]) -> fmt::Result {
+            f.write_str("(")?;
+            for (i, ty) in types.iter().enumerate() {
+                if i > 0 {
+                    f.write_str(", ")?;
+                }
+                write!(f, "{ty}")?;
+            }
+            f.write_str(")")
+        }
+
+        write_types(f, &self.inputs)?;
+        f.write_str(" -> ")?;
+        write_types(f, &self.outputs)
+    }
+}
+
+impl Serializable for ProcedureSignature {
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        target.write_usize(self.inputs.len());
+        target.write_many(&self.inputs);
+        target.write_usize(self.outputs.len());
+        target.write_many(&self.outputs);
+    }
+}
+
+impl Deserializable for ProcedureSignature {
+    fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
+        let num_inputs = source.read_usize()?;
+        let inputs = source.read_many(num_inputs)?;
+        let num_outputs = source.read_usize()?;
+        let outputs = source.read_many(num_outputs)?;
+        Ok(Self { inputs, outputs })
+    }
+}
+
+// STACK EFFECT
+// ================================================================================================
+
+/// The effect of executing some code on the operand stack, in terms of field elements.
+///
+/// `inputs` is the depth of the stack accessed by the code, and `outputs` is the number of
+/// elements which take the place of those `inputs` once the code has executed. For instance,
+/// `add` has the effect `2 -> 1`, and `dup.2` has the effect `3 -> 4`.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct StackEffect {
+    /// The number of elements accessed from the top of the stack.
+    pub inputs: usize,
+    /// The number of elements left in place of the inputs.
+    pub outputs: usize,
+}
+
+impl StackEffect {
+    /// Creates a new [StackEffect] which replaces `inputs` elements by `outputs` elements.
+    pub const fn new(inputs: usize, outputs: usize) -> Self {
+        Self { inputs, outputs }
+    }
+
+    /// Returns the change in stack depth caused by this effect.
+    pub fn net(&self) -> isize {
+        self.outputs as isize - self.inputs as isize
+    }
+
+    /// Returns the effect of executing code with this effect, followed by code with effect `next`.
+    pub fn then(self, next: Self) -> Self {
+        if next.inputs <= self.outputs {
+            Self::new(self.inputs, self.outputs - next.inputs + next.outputs)
+        } else {
+            Self::new(self.inputs + next.inputs - self.outputs, next.outputs)
+        }
+    }
+
+    /// Returns this effect expressed over `inputs` elements, which must be at least the number of
+    /// inputs of this effect, by treating the additional elements as passed through unchanged.
+    pub fn widen(self, inputs: usize) -> Self {
+        debug_assert!(inputs >= self.inputs);
+        Self::new(inputs, self.outputs + inputs - self.inputs)
+    }
+
+    /// Returns the effect of code which may have either this effect or `other`, if both have the
+    /// same net effect on the stack depth.
+    pub fn join(self, other: Self) -> Option<Self> {
+        let inputs = self.inputs.max(other.inputs);
+        let (lhs, rhs) = (self.widen(inputs), other.widen(inputs));
+        (lhs == rhs).then_some(lhs)
+    }
+}
+
+impl fmt::Display for StackEffect {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} -> {}", self.inputs, self.outputs)
+    }
+}
+
+impl From<&ProcedureSignature> for StackEffect {
+    fn from(signature: &ProcedureSignature) -> Self {
+        signature.stack_effect()
+    }
+}
@@ -0,0 +1,273 @@
+use alloc::{boxed::Box, string::String, vec::Vec};
+use core::fmt;
+
+use super::{DocString, Immediate, StackType};
+use crate::{SourceSpan, Span, Spanned, ast::Ident};
+
+// STRUCT
+// ================================================================================================
+
+/// Represents a struct definition in Miden Assembly syntax, i.e.:
+///
+/// ```masm,ignore
+/// struct.NOTE
+///     id: felt
+///     key: word
+///     assets: [word; 4]
+/// end
+/// ```
+///
+/// A struct describes the layout of a record stored in memory or in procedure locals, so that the
+/// offsets of its fields can be computed using `offsetof(NOTE.key)`, and its size using
+/// `sizeof(NOTE)`, rather than being hardcoded.
+///
+/// Fields are laid out in the order in which they are declared, with each field aligned so that
+/// fields which contain words can be accessed using word-aligned instructions, e.g. `mem_loadw`.
+/// Sizes and offsets are measured in field elements, and are computed during semantic analysis.
+#[derive(Clone)]
+pub struct Struct {
+    /// The source span of the definition.
+    pub span: SourceSpan,
+    /// The documentation string attached to this definition.
+    pub docs: Option<DocString>,
+    /// The name of the struct.
+    pub name: Ident,
+    /// The fields of the struct, in the order in which they are laid out.
+    pub fields: Vec<StructField>,
+    /// The size of the struct in field elements, including any padding.
+    pub size: u32,
+    /// The alignment of the struct in field elements.
+    pub align: u32,
+}
+
+impl Struct {
+    /// Creates a new [Struct] from the given source span, name, and fields.
+    ///
+    /// The layout of the struct is computed during semantic analysis.
+    pub fn new(span: SourceSpan, name: Ident, fields: Vec<StructField>) -> Self {
+        Self {
+            span,
+            docs: None,
+            name,
+            fields,
+            size: 0,
+            align: 1,
+        }
+    }
+
+    /// Adds documentation to this struct definition.
+    pub fn with_docs(mut self, docs: Option<Span<String>>) -> Self {
+        self.docs = docs.map(DocString::new);
+        self
+    }
+
+    /// Returns the field named `name`, if this struct has such a field.
+    pub fn field(&self, name: &Ident) -> Option<&StructField> {
+        self.fields.iter().find(|field| &field.name == name)
+    }
+}
+
+impl fmt::Debug for Struct {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Struct")
+            .field("docs", &self.docs)
+            .field("name", &self.name)
+            .field("fields", &self.fields)
+            .field("size", &self.size)
+            .field("align", &self.align)
+            .finish()
+    }
+}
+
+impl crate::prettier::PrettyPrint for Struct {
+    fn render(&self) -> crate::prettier::Document {
+        use crate::prettier::*;
+
+        let doc = self
+            .docs
+            .as_ref()
+            .map(|docstring| docstring.render())
+            .unwrap_or(Document::Empty);
+
+        let fields = self
+            .fields
+            .iter()
+            .map(PrettyPrint::render)
+            .reduce(|acc, field| acc + nl() + field)
+            .unwrap_or(Document::Empty);
+
+        doc + const_text("struct")
+            + const_text(".")
+            + display(&self.name)
+            + indent(4, nl() + fields)
+            + nl()
+            + const_text("end")
+            + nl()
+    }
+}
+
+impl Eq for Struct {}
+
+impl PartialEq for Struct {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name && self.fields == other.fields
+    }
+}
+
+impl Spanned for Struct {
+    fn span(&self) -> SourceSpan {
+        self.span
+    }
+}
+
+// STRUCT FIELD
+// ================================================================================================
+
+/// Represents a field of a [Struct], i.e. `key: word`.
+#[derive(Clone)]
+pub struct StructField {
+    /// The source span of the field.
+    pub span: SourceSpan,
+    /// The name of the field.
+    pub name: Ident,
+    /// The type of the field.
+    pub ty: FieldType,
+    /// The offset of the field from the start of the struct in field elements, as computed during
+    /// semantic analysis.
+    pub offset: u32,
+}
+
+impl StructField {
+    /// Creates a new [StructField] from the given source span, name, and type.
+    pub fn new(span: SourceSpan, name: Ident, ty: FieldType) -> Self {
+        Self { span, name, ty, offset: 0 }
+    }
+}
+
+impl fmt::Debug for StructField {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("StructField")
+            .field("name", &self.name)
+            .field("ty", &self.ty)
+            .field("offset", &self.offset)
+            .finish()
+    }
+}
+
+impl crate::prettier::PrettyPrint for StructField {
+    fn render(&self) -> crate::prettier::Document {
+        use crate::prettier::*;
+
+        display(&self.name) + const_text(":") + const_text(" ") + display(&self.ty)
+    }
+}
+
+impl Eq for StructField {}
+
+impl PartialEq for StructField {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name && self.ty == other.ty
+    }
+}
+
+impl Spanned for StructField {
+    fn span(&self) -> SourceSpan {
+        self.span
+    }
+}
+
+// FIELD TYPE
+// ================================================================================================
+
+/// Represents the type of a [StructField].
+#[derive(Debug, Clone)]
+pub enum FieldType {
+    /// A value of one of the types which can be passed on the operand stack, e.g. `word`.
+    Value(Span<StackType>),
+    /// A struct defined earlier in the same module, e.g. `HEADER`.
+    Struct(Ident),
+    /// A fixed number of consecutive elements of the same type, e.g. `[word; 4]`.
+    Array {
+        span: SourceSpan,
+        element: Box<FieldType>,
+        len: Immediate<u32>,
+    },
+}
+
+impl Eq for FieldType {}
+
+impl PartialEq for FieldType {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Value(l), Self::Value(r)) => l == r,
+            (Self::Struct(l), Self::Struct(r)) => l == r,
+            (
+                Self::Array { element: le, len: ll, .. },
+                Self::Array { element: re, len: rl, .. },
+            ) => le == re && ll == rl,
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for FieldType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Value(ty) => fmt::Display::fmt(&**ty, f),
+            Self::Struct(name) => fmt::Display::fmt(name, f),
+            Self::Array { element, len, .. } => write!(f, "[{element}; {len}]"),
+        }
+    }
+}
+
+impl Spanned for FieldType {
+    fn span(&self) -> SourceSpan {
+        match self {
+            Self::Value(ty) => ty.span(),
+            Self::Struct(name) => name.span(),
+            Self::Array { span, .. } => *span,
+        }
+    }
+}
+
+// STRUCT PATH
+// ================================================================================================
+
+/// A reference to a [Struct] in a constant expression, e.g. `NOTE` in `sizeof(NOTE)`, or
+/// `note::NOTE` when the struct is defined in the imported module `note`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct StructPath {
+    /// The imported module in which the struct is defined, if it is not defined in the current
+    /// module.
+    pub module: Option<Ident>,
+    /// The name of the struct.
+    pub name: Ident,
+}
+
+impl StructPath {
+    /// Returns true if this path refers to a struct defined in another module.
+    pub fn is_imported(&self) -> bool {
+        self.module.is_some()
+    }
+}
+
+impl fmt::Display for StructPath {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.module {
+            Some(module) => write!(f, "{module}::{}", &self.name),
+            None => fmt::Display::fmt(&self.name, f),
+        }
+    }
+}
+
+impl Spanned for StructPath {
+    fn span(&self) -> SourceSpan {
+        match &self.module {
+            Some(module) => SourceSpan::new(
+                module.span().source_id(),
+                module.span().start()..self.name.span().end(),
+            ),
+            None => self.name.span(),
+        }
+    }
+}
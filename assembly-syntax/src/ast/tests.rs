@@ -3,8 +3,9 @@ use alloc::{string::ToString, sync::Arc, vec::Vec};
 use pretty_assertions::assert_eq;
 
 use crate::{
-    Felt, LibraryNamespace, LibraryPath, Span, assert_diagnostic, assert_diagnostic_lines, ast::*,
-    diagnostics::Report, parser::WordValue, regex, source_file, testing::SyntaxTestContext,
+    Felt, LibraryNamespace, LibraryPath, Lint, LintLevel, Span, assert_diagnostic,
+    assert_diagnostic_lines, ast::*, diagnostics::Report, parser::WordValue, prettier::PrettyPrint,
+    regex, source_file, testing::SyntaxTestContext,
 };
 
 macro_rules! id {
@@ -455,6 +456,26 @@ fn test_ast_parsing_module() -> Result<(), Report> {
     Ok(())
 }
 
+#[test]
+fn test_ast_parsing_internal_procedure() -> Result<(), Report> {
+    // internal procedures are not reported as unused, as they may be used by other modules
+    let context = SyntaxTestContext::new().with_warnings_as_errors(true);
+    let source = source_file!(
+        &context,
+        r#"
+    internal.foo
+        push.1
+    end"#
+    );
+    let module = context.parse_module(source)?;
+    let procedure = module.procedures().next().unwrap();
+    assert_eq!(procedure.visibility(), Visibility::Internal);
+    assert!(!procedure.visibility().is_exported());
+    assert_eq!(module.exported_procedures().count(), 0);
+    assert!(module.to_string().contains("internal.foo"));
+    Ok(())
+}
+
 #[test]
 fn test_ast_parsing_adv_ops() -> Result<(), Report> {
     let context = SyntaxTestContext::new();
@@ -686,96 +707,1587 @@ fn test_ast_parsing_attributes() -> Result<(), Report> {
     Ok(())
 }
 
-// PROCEDURE IMPORTS
+// PROCEDURE SIGNATURES
 // ================================================================================================
 
 #[test]
-fn test_missing_import() {
+fn test_procedure_signatures() -> Result<(), Report> {
     let context = SyntaxTestContext::new();
     let source = source_file!(
         &context,
         r#"
-    begin
-        exec.u64::add
+    @inputs(u64, u64)
+    @outputs(u64)
+    export.add
+        exec.combine
+    end
+
+    # inputs which are not accessed are passed through
+    @inputs(u32, u32, u64)
+    @outputs(u64)
+    proc.combine
+        drop drop
+    end
+
+    @outputs(felt)
+    export.constant
+        repeat.2
+            push.1
+        end
+        if.true
+            push.2 add
+        else
+            push.3 mul
+        end
+    end
+
+    export.unchecked
+        push.1
     end"#
     );
 
-    assert_program_diagnostic_lines!(
-        context,
-        source,
-        "syntax error",
-        "help: see emitted diagnostics for details",
-        "missing import: the referenced module has not been imported",
-        regex!(r#",-\[test[\d]+:3:19\]"#),
-        "2 |     begin",
-        "3 |         exec.u64::add",
-        "  :                   ^|^",
-        "  :                    `-- this reference is invalid without a corresponding import",
-        "4 |     end",
-        "  `----"
+    let module = context.parse_module(source)?;
+    let signatures = module
+        .procedures()
+        .map(|export| (export.name().as_str(), export.signature().cloned()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        signatures,
+        [
+            ("add", Some(ProcedureSignature::new([StackType::U64; 2], [StackType::U64]))),
+            (
+                "combine",
+                Some(ProcedureSignature::new(
+                    [StackType::U32, StackType::U32, StackType::U64],
+                    [StackType::U64]
+                ))
+            ),
+            ("constant", Some(ProcedureSignature::new([], [StackType::Felt]))),
+            ("unchecked", None),
+        ]
     );
+    assert_eq!(signatures[1].1.as_ref().unwrap().to_string(), "(u32, u32, u64) -> (u64)");
+
+    Ok(())
 }
 
-// INVALID BODY TESTS
+#[test]
+fn test_procedure_signature_mismatch() {
+    let context = SyntaxTestContext::new();
+    let source = source_file!(
+        &context,
+        r#"
+    @inputs(felt, felt)
+    @outputs(felt)
+    export.foo
+        dup.1 add
+    end"#
+    );
+
+    let error = context
+        .parse_module(source)
+        .expect_err("expected diagnostic to be raised, but parsing succeeded");
+    assert_diagnostic!(
+        error,
+        regex!(r#"procedure signature mismatch: expected stack effect 2 -> 1,\s+but got 2 -> 2"#)
+    );
+}
+
+#[test]
+fn test_for_loop_signature_mismatch() {
+    let context = SyntaxTestContext::new();
+    let source = source_file!(
+        &context,
+        r#"
+    @inputs(felt)
+    @outputs(felt)
+    export.foo
+        for.3
+            dup add
+        end
+    end"#
+    );
+
+    // the loop counter pushed before each iteration is left on the stack
+    let error = context
+        .parse_module(source)
+        .expect_err("expected diagnostic to be raised, but parsing succeeded");
+    assert_diagnostic!(
+        error,
+        regex!(r#"procedure signature mismatch: expected stack effect 1 -> 1,\s+but got 1 -> 4"#)
+    );
+}
+
+#[test]
+fn test_procedure_signature_call_site_mismatch() {
+    let context = SyntaxTestContext::new();
+    let source = source_file!(
+        &context,
+        r#"
+    @inputs(word)
+    proc.consume
+        dropw
+    end
+
+    @inputs(felt, felt)
+    export.foo
+        drop
+        exec.consume
+    end"#
+    );
+
+    let error = context
+        .parse_module(source)
+        .expect_err("expected diagnostic to be raised, but parsing succeeded");
+    assert_diagnostic!(
+        error,
+        regex!(r#"the callee consumes 4 elements, but only 1\s+elements are available"#)
+    );
+}
+
+#[test]
+fn test_invalid_procedure_signature() {
+    let context = SyntaxTestContext::new();
+    let source = source_file!(
+        &context,
+        r#"
+    @inputs(felt, bool)
+    export.foo
+        drop
+    end"#
+    );
+
+    let error = context
+        .parse_module(source)
+        .expect_err("expected diagnostic to be raised, but parsing succeeded");
+    assert_diagnostic!(error, regex!(r#"invalid procedure signature\s+,-\[test[\d]+:2:19\]"#));
+}
+
+// STACK ANALYSIS
 // ================================================================================================
 
 #[test]
-fn test_use_in_proc_body() {
-    let context = SyntaxTestContext::default();
+fn test_stack_analysis_accepts_checked_code() -> Result<(), Report> {
+    let context = SyntaxTestContext::new().with_warnings_as_errors(true);
     let source = source_file!(
         &context,
         r#"
-    export.foo.1
-        loc_load.0
-        use
+    @inputs(u32, u32)
+    @outputs(u32)
+    export.checked
+        u32wrapping_add
+        adv_push.2 u32assert2 u32wrapping_add
+        u32wrapping_add
+        push.1 push.2 add u32split u32overflowing_add drop drop
+    end
+
+    export.unknown_inputs
+        u32wrapping_add
+        mem_load u32wrapping_add
+    end
+
+    export.balanced
+        if.true
+            push.1
+        else
+            push.2 push.3 add
+        end
+        push.1
+        while.true
+            dup push.0 gt
+        end
+        repeat.2
+            push.1
+        end
     end"#
     );
 
-    assert_parse_diagnostic_lines!(
-        source,
-        "invalid syntax",
-        regex!(r#",-\[test[\d]+:4:9\]"#),
-        "3 |         loc_load.0",
-        "4 |         use",
-        " :         ^|^",
-        "  :          `-- found a use here",
-        "5 |     end",
-        "  `----",
-        r#" help: expected primitive opcode (e.g. "add"), or "end", or control flow opcode (e.g. "if.true")"#
+    context.parse_module(source)?;
+
+    Ok(())
+}
+
+#[test]
+fn test_stack_analysis_unchecked_u32_operand() {
+    let context = SyntaxTestContext::new().with_warnings_as_errors(true);
+    let source = source_file!(
+        &context,
+        r#"
+    @inputs(u32, felt)
+    export.foo
+        swap push.1 add swap
+        u32wrapping_add
+    end"#
     );
+
+    let error = context
+        .parse_module(source)
+        .expect_err("expected diagnostic to be raised, but parsing succeeded");
+    assert_diagnostic!(error, regex!(r#"unchecked u32 operand\s+,-\[test[\d]+:5:9\]"#));
 }
 
 #[test]
-fn test_unterminated_proc() {
-    let context = SyntaxTestContext::default();
-    let source = source_file!(&context, "proc.foo add mul begin push.1 end");
+fn test_stack_analysis_unchecked_advice() {
+    let context = SyntaxTestContext::new().with_warnings_as_errors(true);
+    let source = source_file!(
+        &context,
+        r#"
+    export.foo
+        adv_push.1
+        repeat.4
+            dup push.2 u32assert mul
+        end
+        u32div
+    end"#
+    );
 
-    assert_parse_diagnostic_lines!(
-        source,
-        "invalid syntax",
-        regex!(r#",-\[test[\d]+:1:18\]"#),
-        "1 | proc.foo add mul begin push.1 end",
-        "  :                  ^^|^^",
-        "  :                    `-- found a begin here",
-        "  `----",
-        r#" help: expected ".", or primitive opcode (e.g. "add"), or "end", or control flow opcode (e.g. "if.true")"#
+    let error = context
+        .parse_module(source)
+        .expect_err("expected diagnostic to be raised, but parsing succeeded");
+    assert_diagnostic!(error, regex!(r#"unchecked u32 operand\s+,-\[test[\d]+:7:9\]"#));
+}
+
+#[test]
+fn test_stack_analysis_loop_with_deferred_count() -> Result<(), Report> {
+    let context = SyntaxTestContext::new().with_warnings_as_errors(true);
+    let source = source_file!(
+        &context,
+        r#"
+    use.lib::note
+    const.SIZE=sizeof(note::NOTE)
+    const.KEY=offsetof(note::NOTE.key)
+
+    export.foo
+        adv_push.1
+        repeat.SIZE
+            u32assert
+        end
+        push.2 u32div
+    end
+
+    export.bar
+        adv_push.1
+        for.KEY..8
+            drop u32assert
+        end
+        push.2 u32div
+    end"#
     );
+
+    // the loop counts are only known once the struct is resolved, so nothing is known about the
+    // stack after the loops
+    context.parse_module(source)?;
+
+    Ok(())
 }
 
 #[test]
-fn test_unterminated_if() {
-    let context = SyntaxTestContext::default();
-    let source = source_file!(&context, "proc.foo add mul if.true add.2 begin push.1 end");
+fn test_stack_analysis_unbalanced_branches() {
+    let context = SyntaxTestContext::new().with_warnings_as_errors(true);
+    let source = source_file!(
+        &context,
+        r#"
+    export.foo
+        if.true
+            drop
+        end
+    end"#
+    );
 
-    assert_parse_diagnostic_lines!(
-        source,
-        "invalid syntax",
-        regex!(r#",-\[test[\d]+:1:32\]"#),
-        "1 | proc.foo add mul if.true add.2 begin push.1 end",
-        "  :                                ^^|^^",
-        "  :                                  `-- found a begin here",
+    let error = context
+        .parse_module(source)
+        .expect_err("expected diagnostic to be raised, but parsing succeeded");
+    assert_diagnostic!(
+        error,
+        regex!(
+            r#"the `then` branch changes the stack depth by -1, but the\s+`else` branch changes it by 0"#
+        )
+    );
+}
+
+#[test]
+fn test_stack_analysis_stack_depth_changing_loop() {
+    let context = SyntaxTestContext::new().with_warnings_as_errors(true);
+    let source = source_file!(
+        &context,
+        r#"
+    export.foo
+        while.true
+            push.1 push.1
+        end
+    end"#
+    );
+
+    let error = context
+        .parse_module(source)
+        .expect_err("expected diagnostic to be raised, but parsing succeeded");
+    assert_diagnostic!(
+        error,
+        regex!(
+            r#"each iteration of this loop changes the stack depth by 1,\s+excluding the loop condition"#
+        )
+    );
+}
+
+// LINTS
+// ================================================================================================
+
+#[test]
+fn test_lint_unused_constant() {
+    let context = SyntaxTestContext::new().with_warnings_as_errors(true);
+    let source = source_file!(
+        &context,
+        r#"
+    const.USED=1
+    const.UNUSED=2
+
+    export.foo
+        push.USED
+    end"#
+    );
+
+    let error = context
+        .parse_module(source)
+        .expect_err("expected diagnostic to be raised, but parsing succeeded");
+    assert_diagnostic!(error, regex!(r#"unused constant `UNUSED`\s+,-\[test[\d]+:3:5\]"#));
+}
+
+#[test]
+fn test_lint_unused_procedure() {
+    let context = SyntaxTestContext::new().with_warnings_as_errors(true);
+    let source = source_file!(
+        &context,
+        r#"
+    proc.used
+        push.1
+    end
+
+    proc.unused
+        push.2
+    end
+
+    export.foo
+        exec.used
+    end"#
+    );
+
+    let error = context
+        .parse_module(source)
+        .expect_err("expected diagnostic to be raised, but parsing succeeded");
+    assert_diagnostic!(error, regex!(r#"unused procedure `unused`\s+,-\[test[\d]+:6:10\]"#));
+}
+
+#[test]
+fn test_lint_redundant_push_drop() {
+    let context = SyntaxTestContext::new().with_warnings_as_errors(true);
+    let source = source_file!(
+        &context,
+        r#"
+    export.foo
+        push.1 drop
+    end"#
+    );
+
+    let error = context
+        .parse_module(source)
+        .expect_err("expected diagnostic to be raised, but parsing succeeded");
+    assert_diagnostic!(
+        error,
+        regex!(
+            r#"value is pushed onto the stack and then immediately dropped\s+,-\[test[\d]+:3:9\]"#
+        )
+    );
+}
+
+#[test]
+fn test_lint_allow_attributes() -> Result<(), Report> {
+    let context = SyntaxTestContext::new().with_warnings_as_errors(true);
+    let source = source_file!(
+        &context,
+        r#"
+    @!allow(unused_constant)
+
+    const.UNUSED=2
+
+    @allow(unused_procedure, redundant_push_drop)
+    proc.unused
+        padw dropw
+    end
+
+    export.foo
+        push.1
+    end"#
+    );
+
+    context.parse_module(source)?;
+
+    Ok(())
+}
+
+#[test]
+fn test_lint_module_deny_attribute() {
+    let context = SyntaxTestContext::new();
+    let source = source_file!(
+        &context,
+        r#"
+    @!deny(magic_emit_event)
+
+    export.foo
+        emit.42
+    end"#
+    );
+
+    let error = context
+        .parse_module(source)
+        .expect_err("expected diagnostic to be raised, but parsing succeeded");
+    assert_diagnostic!(
+        error,
+        regex!(r#"event id 42 should be given a name using a constant\s+,-\[test[\d]+:5:9\]"#)
+    );
+}
+
+#[test]
+fn test_lint_procedure_attribute_overrides_module_attribute() -> Result<(), Report> {
+    let context = SyntaxTestContext::new().with_warnings_as_errors(true);
+    let source = source_file!(
+        &context,
+        r#"
+    @!warn(trivial_exec)
+
+    proc.add_one
+        add.1
+    end
+
+    @allow(trivial_exec)
+    export.foo
+        exec.add_one
+    end"#
+    );
+
+    context.parse_module(source)?;
+
+    Ok(())
+}
+
+#[test]
+fn test_lint_trivial_exec() {
+    let context = SyntaxTestContext::new()
+        .with_warnings_as_errors(true)
+        .with_lint_level(Lint::TrivialExec, LintLevel::Warn);
+    let source = source_file!(
+        &context,
+        r#"
+    proc.add_one
+        add.1
+    end
+
+    export.foo
+        exec.add_one
+    end"#
+    );
+
+    let error = context
+        .parse_module(source)
+        .expect_err("expected diagnostic to be raised, but parsing succeeded");
+    assert_diagnostic!(
+        error,
+        regex!(
+            r#"`add_one` consists of a single instruction, which could be used\s+directly instead"#
+        )
+    );
+}
+
+#[test]
+fn test_lint_missing_docs_configured_globally() {
+    let context = SyntaxTestContext::new().with_lint_level(Lint::MissingDocs, LintLevel::Deny);
+    let source = source_file!(
+        &context,
+        r#"
+    #! Documented
+    export.foo
+        push.1
+    end
+
+    export.bar
+        push.1
+    end"#
+    );
+
+    let error = context
+        .parse_module(source)
+        .expect_err("expected diagnostic to be raised, but parsing succeeded");
+    assert_diagnostic!(
+        error,
+        regex!(r#"missing docstring for exported procedure `bar`\s+,-\[test[\d]+:7:12\]"#)
+    );
+}
+
+#[test]
+fn test_lint_unknown_lint() {
+    let context = SyntaxTestContext::new().with_warnings_as_errors(true);
+    let source = source_file!(
+        &context,
+        r#"
+    @!allow(no_such_lint)
+
+    export.foo
+        push.1
+    end"#
+    );
+
+    let error = context
+        .parse_module(source)
+        .expect_err("expected diagnostic to be raised, but parsing succeeded");
+    assert_diagnostic!(error, regex!(r#"unknown lint\s+,-\[test[\d]+:2:13\]"#));
+}
+
+#[test]
+fn test_invalid_module_attribute() {
+    let context = SyntaxTestContext::new();
+    let source = source_file!(
+        &context,
+        r#"
+    @!inputs(u32)
+
+    export.foo
+        push.1
+    end"#
+    );
+
+    let error = context
+        .parse_module(source)
+        .expect_err("expected diagnostic to be raised, but parsing succeeded");
+    assert_diagnostic!(error, "invalid module attribute");
+}
+
+#[test]
+fn test_conflicting_inline_attributes() {
+    let context = SyntaxTestContext::new();
+    let source = source_file!(
+        &context,
+        r#"
+    @inline
+    @noinline
+    export.foo
+        push.1
+    end"#
+    );
+
+    let error = context
+        .parse_module(source)
+        .expect_err("expected diagnostic to be raised, but parsing succeeded");
+    assert_diagnostic!(error, regex!(r#"conflicting inline attributes\s+,-\[test[\d]+:2:5\]"#));
+}
+
+#[test]
+fn test_invalid_inline_attribute() {
+    let context = SyntaxTestContext::new();
+    let source = source_file!(
+        &context,
+        r#"
+    @inline(always)
+    export.foo
+        push.1
+    end"#
+    );
+
+    let error = context
+        .parse_module(source)
+        .expect_err("expected diagnostic to be raised, but parsing succeeded");
+    assert_diagnostic!(error, regex!(r#"invalid inline attribute\s+,-\[test[\d]+:2:5\]"#));
+}
+
+// GENERIC PROCEDURES
+// ================================================================================================
+
+#[test]
+fn test_generic_procedure_instantiation() -> Result<(), Report> {
+    let context = SyntaxTestContext::new();
+    let source = source_file!(
+        &context,
+        r#"
+    const.OFFSET=8
+
+    proc.load_words(N, ADDR).1
+        repeat.N
+            padw mem_loadw.ADDR
+        end
+        loc_store.0
+    end
+
+    proc.load_pairs(N)
+        exec.load_words(N * 2, OFFSET)
+    end
+
+    export.foo
+        exec.load_words(2, OFFSET)
+        exec.load_pairs(1)
+        call.load_words(4, 0)
+        procref.load_words(4, 0)
+    end"#
+    );
+
+    let module = context.parse_module(source)?;
+    let names = module.procedures().map(|export| export.name().as_str()).collect::<Vec<_>>();
+    assert_eq!(names, ["foo", "load_words<2,8>", "load_pairs<1>", "load_words<4,0>"]);
+
+    let Some(Export::Procedure(instance)) = module.procedures().nth(1) else {
+        panic!("expected an instance of `load_words`");
+    };
+    assert!(!instance.is_generic());
+    assert_eq!(instance.visibility(), Visibility::Private);
+    assert_eq!(instance.num_locals(), 1);
+    assert_eq!(
+        instance.to_pretty_string(),
+        "\
+proc.load_words<2,8>.1
+    repeat.2
+        padw
+        mem_loadw.8
+    end
+    loc_store.0
+end
+"
+    );
+
+    let Some(Export::Procedure(instance)) = module.procedures().nth(2) else {
+        panic!("expected an instance of `load_pairs`");
+    };
+    assert_eq!(
+        instance.to_pretty_string(),
+        "proc.load_pairs<1>\n    exec.load_words<2,8>\nend\n"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_generic_procedure_not_instantiated() {
+    let context = SyntaxTestContext::new();
+    let source = source_file!(
+        &context,
+        r#"
+    proc.shift(N)
+        u32shl.N
+    end
+
+    export.foo
+        exec.shift
+    end"#
+    );
+
+    let error = context
+        .parse_module(source)
+        .expect_err("expected diagnostic to be raised, but parsing succeeded");
+    assert_diagnostic!(
+        error,
+        regex!(r#"generic procedures must be instantiated\s+,-\[test[\d]+:7:14\]"#)
+    );
+}
+
+#[test]
+fn test_generic_procedure_arity_mismatch() {
+    let context = SyntaxTestContext::new();
+    let source = source_file!(
+        &context,
+        r#"
+    proc.shift(N)
+        u32shl.N
+    end
+
+    export.foo
+        exec.shift(1, 2)
+    end"#
+    );
+
+    let error = context
+        .parse_module(source)
+        .expect_err("expected diagnostic to be raised, but parsing succeeded");
+    assert_diagnostic!(error, regex!(r#"expected 1 arguments, but got 2\s+,-\[test[\d]+:7:14\]"#));
+}
+
+#[test]
+fn test_generic_procedure_argument_out_of_range() {
+    let context = SyntaxTestContext::new();
+    let source = source_file!(
+        &context,
+        r#"
+    proc.shift(N)
+        u32shl.N
+    end
+
+    export.foo
+        exec.shift(256)
+    end"#
+    );
+
+    let error = context
+        .parse_module(source)
+        .expect_err("expected diagnostic to be raised, but parsing succeeded");
+    assert_diagnostic!(
+        error,
+        regex!(r#"value is larger than expected range\s+,-\[test[\d]+:3:16\]"#)
+    );
+}
+
+#[test]
+fn test_instantiation_of_non_generic_procedure() {
+    let context = SyntaxTestContext::new();
+    let source = source_file!(
+        &context,
+        r#"
+    proc.add_one
+        add.1
+    end
+
+    export.foo
+        exec.add_one(1)
+    end"#
+    );
+
+    let error = context
+        .parse_module(source)
+        .expect_err("expected diagnostic to be raised, but parsing succeeded");
+    assert_diagnostic!(error, "callee is not a generic procedure");
+}
+
+#[test]
+fn test_generic_procedure_recursive_instantiation() {
+    let context = SyntaxTestContext::new();
+    let source = source_file!(
+        &context,
+        r#"
+    proc.unroll(N)
+        push.N
+        exec.unroll(N - 1)
+    end
+
+    export.foo
+        exec.unroll(4)
+    end"#
+    );
+
+    let error = context
+        .parse_module(source)
+        .expect_err("expected diagnostic to be raised, but parsing succeeded");
+    assert_diagnostic!(error, regex!(r#"invalid recursive instantiation\s+,-\[test[\d]+:4:14\]"#));
+}
+
+#[test]
+fn test_generic_procedure_cannot_be_exported() {
+    let context = SyntaxTestContext::new();
+    let source = source_file!(
+        &context,
+        r#"
+    export.shift(N)
+        u32shl.N
+    end"#
+    );
+
+    let error = context
+        .parse_module(source)
+        .expect_err("expected diagnostic to be raised, but parsing succeeded");
+    assert_diagnostic!(error, "generic procedures cannot be exported");
+}
+
+#[test]
+fn test_repeat_count_constant() {
+    let context = SyntaxTestContext::new();
+    let source = source_file!(
+        &context,
+        r#"
+    const.NONE=0
+
+    export.foo
+        repeat.NONE
+            push.1
+        end
+    end"#
+    );
+
+    let error = context
+        .parse_module(source)
+        .expect_err("expected diagnostic to be raised, but parsing succeeded");
+    assert_diagnostic!(error, "loops must repeat at least once");
+}
+
+#[test]
+fn test_match_roundtrip_formatting() {
+    let source = "\
+const.TWO=2
+begin
+    match
+        case.TWO push.20
+        case.0 push.10
+    default
+        push.99
+    end
+end
+";
+
+    let context = SyntaxTestContext::default();
+    let source = source_file!(&context, source);
+
+    let module = Module::parse(
+        LibraryPath::new_from_components(LibraryNamespace::Exec, []),
+        ModuleKind::Executable,
+        source,
+    )
+    .unwrap_or_else(|err| panic!("{err}"));
+
+    let formatted = module.to_string();
+    let expected = "\
+begin
+    match
+    case.2
+        push.20
+    case.0
+        push.10
+    default
+        push.99
+    end
+end
+";
+
+    assert_eq!(&formatted, expected);
+}
+
+#[test]
+fn test_match_duplicate_case() {
+    let context = SyntaxTestContext::new();
+    let source = source_file!(
+        &context,
+        r#"
+    const.ONE=1
+
+    export.foo
+        match
+            case.1 push.1
+            case.ONE push.2
+        default
+            push.0
+        end
+    end"#
+    );
+
+    let error = context
+        .parse_module(source)
+        .expect_err("expected diagnostic to be raised, but parsing succeeded");
+    assert_diagnostic!(error, "duplicate case in match: 1 is already handled by a previous case");
+}
+
+#[test]
+fn test_for_roundtrip_formatting() {
+    let source = "\
+const.START=2
+begin
+    for.10
+        drop
+    end
+    for.START..0x10
+        drop
+    end
+end
+";
+
+    let context = SyntaxTestContext::default();
+    let source = source_file!(&context, source);
+
+    let module = Module::parse(
+        LibraryPath::new_from_components(LibraryNamespace::Exec, []),
+        ModuleKind::Executable,
+        source,
+    )
+    .unwrap_or_else(|err| panic!("{err}"));
+
+    let formatted = module.to_string();
+    let expected = "\
+begin
+    for.10
+        drop
+    end
+    for.2..16
+        drop
+    end
+end
+";
+
+    assert_eq!(&formatted, expected);
+}
+
+#[test]
+fn test_for_empty_range() {
+    let context = SyntaxTestContext::new();
+    let source = source_file!(
+        &context,
+        r#"
+    const.END=4
+
+    export.foo
+        for.4..END
+            drop
+        end
+    end"#
+    );
+
+    let error = context
+        .parse_module(source)
+        .expect_err("expected diagnostic to be raised, but parsing succeeded");
+    assert_diagnostic!(
+        error,
+        "invalid loop range: 4..4 is empty, but loops must iterate at least once"
+    );
+}
+
+// DATA SEGMENTS
+// ================================================================================================
+
+#[test]
+fn test_data_segments() -> Result<(), Report> {
+    let context = SyntaxTestContext::new();
+    let source = source_file!(
+        &context,
+        r#"
+    #! lookup table
+    data.8 = [1, 2, 3, 4, 5, 6, 7, 8]
+    data.0x10 = [9, 10, 11, 12]
+
+    begin
+        mem_load.8 drop
+    end"#
+    );
+
+    let program = context.parse_program(source)?;
+    let segments = program.data_segments().collect::<Vec<_>>();
+    assert_eq!(segments.len(), 2);
+    assert_eq!(segments[0].addresses(), 8..16);
+    assert_eq!(segments[0].docs.as_ref().map(|docs| docs.as_str()), Some("lookup table\n"));
+    assert_eq!(segments[1].addresses(), 16..20);
+    assert_eq!(segments[1].data, [9, 10, 11, 12].map(Felt::new));
+    Ok(())
+}
+
+#[test]
+fn test_invalid_data_segments() {
+    let context = SyntaxTestContext::new();
+    let source = source_file!(
+        &context,
+        r#"
+    data.2 = [1, 2, 3, 4]
+
+    begin
+        nop
+    end"#
+    );
+    let error = context
+        .parse_program(source)
+        .expect_err("expected diagnostic to be raised, but parsing succeeded");
+    assert_diagnostic!(error, "invalid data segment: address 2 is not word-aligned");
+
+    let source = source_file!(
+        &context,
+        r#"
+    data.0 = [1, 2, 3]
+
+    begin
+        nop
+    end"#
+    );
+    let error = context
+        .parse_program(source)
+        .expect_err("expected diagnostic to be raised, but parsing succeeded");
+    assert_diagnostic!(
+        error,
+        "invalid data segment: expected a non-empty list of words, but got 3 elements"
+    );
+
+    let source = source_file!(
+        &context,
+        r#"
+    data.0 = [1, 2, 3, 4, 5, 6, 7, 8]
+    data.4 = [1, 2, 3, 4]
+
+    begin
+        nop
+    end"#
+    );
+    let error = context
+        .parse_program(source)
+        .expect_err("expected diagnostic to be raised, but parsing succeeded");
+    assert_diagnostic!(error, "invalid data segment: overlaps a previously declared segment");
+
+    let source = source_file!(
+        &context,
+        r#"
+    data.0 = [1, 2, 3, 4]
+
+    export.foo
+        nop
+    end"#
+    );
+    let error = context
+        .parse_module(source)
+        .expect_err("expected diagnostic to be raised, but parsing succeeded");
+    assert_diagnostic!(
+        error,
+        "invalid data segment: data segments can only be declared in executable modules"
+    );
+}
+
+// STRUCTS
+// ================================================================================================
+
+#[test]
+fn test_struct_layout() -> Result<(), Report> {
+    let context = SyntaxTestContext::new();
+    let source = source_file!(
+        &context,
+        r#"
+    const.NUM_ASSETS=2
+
+    struct.HEADER
+        id: felt
+        flags: u32
+    end
+
+    struct.NOTE
+        header: HEADER
+        key: word
+        assets: [word; NUM_ASSETS]
+        amount: u64
+    end
+
+    const.KEY=offsetof(NOTE.key)
+    const.AMOUNT=offsetof(NOTE.amount)
+    const.NOTE_SIZE=sizeof(NOTE)
+    const.PAIR_SIZE=sizeof(NOTE) * 2
+
+    export.foo.40
+        loc_storew.KEY
+        push.AMOUNT
+        push.NOTE_SIZE
+        push.PAIR_SIZE
+    end"#
+    );
+
+    let module = context.parse_module(source)?;
+    let structs = module.structs().collect::<Vec<_>>();
+    assert_eq!(structs.len(), 2);
+    assert_eq!((structs[0].size, structs[0].align), (2, 1));
+    assert_eq!((structs[1].size, structs[1].align), (20, 4));
+    let offsets = structs[1].fields.iter().map(|field| field.offset).collect::<Vec<_>>();
+    assert_eq!(offsets, [0, 4, 8, 16]);
+
+    let Some(Export::Procedure(procedure)) = module.procedures().next() else {
+        panic!("expected procedure `foo`");
+    };
+    assert_eq!(
+        procedure.to_pretty_string(),
+        "\
+export.foo.40
+    loc_storew.4
+    push.16
+    push.20
+    push.40
+end
+"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_struct_undefined_field() {
+    let context = SyntaxTestContext::new();
+    let source = source_file!(
+        &context,
+        r#"
+    struct.NOTE
+        key: word
+    end
+
+    const.ID=offsetof(NOTE.id)
+
+    export.foo
+        push.ID
+    end"#
+    );
+
+    let error = context
+        .parse_module(source)
+        .expect_err("expected diagnostic to be raised, but parsing succeeded");
+    assert_diagnostic!(error, "the struct has no field with this name");
+}
+
+#[test]
+fn test_struct_duplicate_field() {
+    let context = SyntaxTestContext::new();
+    let source = source_file!(
+        &context,
+        r#"
+    struct.NOTE
+        key: word
+        key: felt
+    end"#
+    );
+
+    let error = context
+        .parse_module(source)
+        .expect_err("expected diagnostic to be raised, but parsing succeeded");
+    assert_diagnostic!(error, "found duplicate definitions of the same name");
+}
+
+#[test]
+fn test_struct_invalid_field_type() {
+    let context = SyntaxTestContext::new();
+    let source = source_file!(
+        &context,
+        r#"
+    struct.NOTE
+        key: dword
+    end"#
+    );
+
+    let error = context
+        .parse_module(source)
+        .expect_err("expected diagnostic to be raised, but parsing succeeded");
+    assert_diagnostic!(error, "invalid struct field type");
+}
+
+#[test]
+fn test_struct_field_of_undefined_struct() {
+    let context = SyntaxTestContext::new();
+    let source = source_file!(
+        &context,
+        r#"
+    struct.NOTE
+        header: HEADER
+    end
+
+    struct.HEADER
+        id: felt
+    end"#
+    );
+
+    let error = context
+        .parse_module(source)
+        .expect_err("expected diagnostic to be raised, but parsing succeeded");
+    assert_diagnostic!(error, "no such name found in scope");
+}
+
+#[test]
+fn test_imported_struct_layout_is_deferred() -> Result<(), Report> {
+    let context = SyntaxTestContext::new();
+    let source = source_file!(
+        &context,
+        r#"
+    use.lib::note
+
+    const.KEY=offsetof(note::NOTE.key) + 4
+
+    export.foo.8
+        loc_storew.KEY
+    end"#
+    );
+
+    let module = context.parse_module(source)?;
+    assert!(module.imports().next().unwrap().is_used());
+    let deferred = module.deferred_constants();
+    assert_eq!(deferred.len(), 1);
+    assert!(deferred[0].value.is_deferred());
+
+    let Some(Export::Procedure(procedure)) = module.procedures().next() else {
+        panic!("expected procedure `foo`");
+    };
+    assert_eq!(procedure.to_pretty_string(), "export.foo.8\n    loc_storew.KEY\nend\n");
+
+    Ok(())
+}
+
+#[test]
+fn test_imported_struct_requires_import() {
+    let context = SyntaxTestContext::new();
+    let source = source_file!(
+        &context,
+        r#"
+    const.KEY=offsetof(note::NOTE.key)
+
+    export.foo.8
+        loc_storew.KEY
+    end"#
+    );
+
+    let error = context
+        .parse_module(source)
+        .expect_err("expected diagnostic to be raised, but parsing succeeded");
+    assert_diagnostic!(error, "the referenced module has not been imported");
+}
+
+// CONDITIONAL COMPILATION
+// ================================================================================================
+
+const CFG_MODULE: &str = r#"
+    @cfg(feature = "debug")
+    const.VERBOSITY=2
+    @cfg(not_feature = "debug")
+    const.VERBOSITY=0
+
+    #! Logs the top of the stack
+    @cfg(feature = "debug")
+    proc.log
+        debug.stack
+    end
+
+    #! Does nothing in production builds
+    @cfg(not_feature = "debug")
+    proc.log
+        nop
+    end
+
+    export.foo
+        push.VERBOSITY
+        @cfg(feature = "debug")
+        exec.log
+        if.true
+            @cfg(feature = "debug") @cfg(not_feature = "trace")
+            push.1
+        else
+            push.2
+        end
+    end"#;
+
+#[test]
+fn test_cfg_removes_disabled_items() -> Result<(), Report> {
+    let context = SyntaxTestContext::new();
+    let module = context.parse_module(source_file!(&context, CFG_MODULE))?;
+    assert_eq!(
+        module.to_pretty_string(),
+        "\
+#! Does nothing in production builds
+@cfg(not_feature = \"debug\")
+proc.log
+    nop
+end
+
+export.foo
+    push.0
+    if.true
+        nop
+    else
+        push.2
+    end
+end
+"
+    );
+
+    let context = SyntaxTestContext::new().with_cfg_flag("debug");
+    let module = context.parse_module(source_file!(&context, CFG_MODULE))?;
+    assert_eq!(
+        module.to_pretty_string(),
+        "\
+#! Logs the top of the stack
+@cfg(feature = \"debug\")
+proc.log
+    debug.stack
+end
+
+export.foo
+    push.2
+    exec.log
+    if.true
+        push.1
+    else
+        push.2
+    end
+end
+"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_cfg_invalid_attribute() {
+    let context = SyntaxTestContext::new();
+    let source = source_file!(
+        &context,
+        r#"
+    @cfg(debug)
+    export.foo
+        push.1
+    end"#
+    );
+
+    let error = context
+        .parse_module(source)
+        .expect_err("expected diagnostic to be raised, but parsing succeeded");
+    assert_diagnostic!(error, "invalid @cfg attribute");
+}
+
+#[test]
+fn test_cfg_unsupported_attribute() {
+    let context = SyntaxTestContext::new();
+    let source = source_file!(
+        &context,
+        r#"
+    export.foo
+        @inline
+        push.1
+    end"#
+    );
+
+    let error = context
+        .parse_module(source)
+        .expect_err("expected diagnostic to be raised, but parsing succeeded");
+    assert_diagnostic!(error, "unsupported attribute");
+}
+
+// PROCEDURE IMPORTS
+// ================================================================================================
+
+#[test]
+fn test_missing_import() {
+    let context = SyntaxTestContext::new();
+    let source = source_file!(
+        &context,
+        r#"
+    begin
+        exec.u64::add
+    end"#
+    );
+
+    assert_program_diagnostic_lines!(
+        context,
+        source,
+        "syntax error",
+        "help: see emitted diagnostics for details",
+        "missing import: the referenced module has not been imported",
+        regex!(r#",-\[test[\d]+:3:19\]"#),
+        "2 |     begin",
+        "3 |         exec.u64::add",
+        "  :                   ^|^",
+        "  :                    `-- this reference is invalid without a corresponding import",
+        "4 |     end",
+        "  `----"
+    );
+}
+
+// INVALID BODY TESTS
+// ================================================================================================
+
+#[test]
+fn test_use_in_proc_body() {
+    let context = SyntaxTestContext::default();
+    let source = source_file!(
+        &context,
+        r#"
+    export.foo.1
+        loc_load.0
+        use
+    end"#
+    );
+
+    assert_parse_diagnostic_lines!(
+        source,
+        "invalid syntax",
+        regex!(r#",-\[test[\d]+:4:9\]"#),
+        "3 |         loc_load.0",
+        "4 |         use",
+        " :         ^|^",
+        "  :          `-- found a use here",
+        "5 |     end",
+        "  `----",
+        r#" help: expected "@", or primitive opcode (e.g. "add"), or "end", or control flow opcode (e.g. "if.true")"#
+    );
+}
+
+#[test]
+fn test_unterminated_proc() {
+    let context = SyntaxTestContext::default();
+    let source = source_file!(&context, "proc.foo add mul begin push.1 end");
+
+    assert_parse_diagnostic_lines!(
+        source,
+        "invalid syntax",
+        regex!(r#",-\[test[\d]+:1:18\]"#),
+        "1 | proc.foo add mul begin push.1 end",
+        "  :                  ^^|^^",
+        "  :                    `-- found a begin here",
+        "  `----",
+        r#" help: expected ".", or "@", or primitive opcode (e.g. "add"), or "end", or control flow opcode (e.g. "if.true")"#
+    );
+}
+
+#[test]
+fn test_unterminated_if() {
+    let context = SyntaxTestContext::default();
+    let source = source_file!(&context, "proc.foo add mul if.true add.2 begin push.1 end");
+
+    assert_parse_diagnostic_lines!(
+        source,
+        "invalid syntax",
+        regex!(r#",-\[test[\d]+:1:32\]"#),
+        "1 | proc.foo add mul if.true add.2 begin push.1 end",
+        "  :                                ^^|^^",
+        "  :                                  `-- found a begin here",
+        "  `----",
+        r#" help: expected "@", or primitive opcode (e.g. "add"), or "else", or "end", or control flow opcode (e.g. "if.true")"#
+    );
+}
+
+#[test]
+fn test_multiple_syntax_errors() {
+    let context = SyntaxTestContext::default();
+    let source = source_file!(
+        &context,
+        r#"
+    const.A = 1 +
+
+    proc.foo
+        push.1 add.
+        exec.bar
+    end
+
+    export.baz
+        use
+        exec.foo
+    end
+
+    export.foo
+        push.1
+    end"#
+    );
+
+    // All of the syntax errors are reported, along with the errors found by semantic analysis of
+    // the rest of the module. As the definition of `bar` may be in the part of the module which
+    // failed to parse, it isn't reported as undefined
+    let error = context
+        .parse_module(source)
+        .expect_err("expected diagnostic to be raised, but parsing succeeded");
+    assert_diagnostic!(
+        error,
+        regex!(
+            r#"(?s)syntax error.*invalid syntax\s+,-\[test[\d]+:4:5\].*found a proc here.*invalid syntax\s+,-\[test[\d]+:6:9\].*found a exec here.*invalid syntax\s+,-\[test[\d]+:10:9\].*found a use here.*symbol conflict.*,-\[test[\d]+:4:10\]"#
+        )
+    );
+}
+
+#[test]
+fn test_syntax_error_recovery_internal_procedure() {
+    let context = SyntaxTestContext::default();
+    let source = source_file!(
+        &context,
+        r#"
+    export.foo
+        push.1 add.
+    end
+
+    internal.bar
+        while mul end
+    end"#
+    );
+
+    // The error in `bar` is reported separately from the one in `foo`, rather than being treated
+    // as a consequence of it
+    let error = context
+        .parse_module(source)
+        .expect_err("expected diagnostic to be raised, but parsing succeeded");
+    assert_diagnostic!(
+        error,
+        regex!(
+            r#"(?s)invalid syntax\s+,-\[test[\d]+:4:5\].*found a end here.*invalid syntax\s+,-\[test[\d]+:7:15\].*found a mul here"#
+        )
+    );
+}
+
+#[test]
+fn test_syntax_error_recovery_data_segment() {
+    let context = SyntaxTestContext::default();
+    let source = source_file!(
+        &context,
+        r#"
+    export.foo
+        push.1 add.
+    end
+
+    data.8 = [1, 2, mul]"#
+    );
+
+    // The error in the data segment is reported separately from the one in `foo`, rather than
+    // being treated as a consequence of it
+    let error = context
+        .parse_module(source)
+        .expect_err("expected diagnostic to be raised, but parsing succeeded");
+    assert_diagnostic!(
+        error,
+        regex!(
+            r#"(?s)invalid syntax\s+,-\[test[\d]+:4:5\].*found a end here.*invalid syntax\s+,-\[test[\d]+:6:21\].*found a mul here"#
+        )
+    );
+}
+
+#[test]
+fn test_one_syntax_error_per_form() {
+    let context = SyntaxTestContext::default();
+    let source = source_file!(
+        &context,
+        r#"
+    export.foo
+        push.1
+        while mul end
+    end"#
+    );
+
+    // Skipping `while` leaves its `end` unmatched, which is not reported as a separate error
+    let error = context
+        .parse_module(source)
+        .expect_err("expected diagnostic to be raised, but parsing succeeded");
+    assert_diagnostic_lines!(
+        error,
+        "invalid syntax",
+        regex!(r#",-\[test[\d]+:4:15\]"#),
+        "3 |         push.1",
+        "4 |         while mul end",
+        "  :               ^|^",
+        "  :                `-- found a mul here",
+        "5 |     end",
+        "  `----",
+        r#" help: expected ".""#
+    );
+}
+
+#[test]
+fn test_syntax_error_recovery_skips_stack_checks() {
+    let context = SyntaxTestContext::default();
+    let source = source_file!(
+        &context,
+        r#"
+    @outputs(felt)
+    export.foo
+        push.1 push.2 add.
+    end
+
+    proc.bar
+        push.1
+    end"#
+    );
+
+    // The body of `foo` is incomplete, so its signature isn't checked, and `bar` isn't reported
+    // as unused, as it may have been invoked from the part of the module that failed to parse
+    let error = context
+        .parse_module(source)
+        .expect_err("expected diagnostic to be raised, but parsing succeeded");
+    assert_diagnostic_lines!(
+        error,
+        "invalid syntax",
+        regex!(r#",-\[test[\d]+:5:5\]"#),
+        "4 |         push.1 push.2 add.",
+        "5 |     end",
+        "  :     ^|^",
+        "  :      `-- found a end here",
+        "6 |",
         "  `----",
-        r#" help: expected primitive opcode (e.g. "add"), or "else", or "end", or control flow opcode (e.g. "if.true")"#
+        r#" help: expected "[", or constant identifier, or hex-encoded literal, or integer literal"#
     );
 }
 
@@ -1033,7 +2545,7 @@ fn test_ast_parsing_module_docs_fail() {
         "4 |         loc_load.0",
         "5 |     end",
         "  `----",
-        r#" help: expected primitive opcode (e.g. "add"), or control flow opcode (e.g. "if.true")"#
+        r#" help: expected "@", or primitive opcode (e.g. "add"), or control flow opcode (e.g. "if.true")"#
     );
 }
 
@@ -1059,7 +2571,7 @@ fn assert_parsing_line_unmatched_begin() {
         "4 |         add",
         "5 |         mul",
         "  `----",
-        r#"help: expected ".", or primitive opcode (e.g. "add"), or "end", or control flow opcode (e.g. "if.true")"#
+        r#"help: expected ".", or "@", or primitive opcode (e.g. "add"), or "end", or control flow opcode (e.g. "if.true")"#
     );
 }
 
@@ -1083,7 +2595,7 @@ fn assert_parsing_line_extra_param() {
         "  :                `-- found a . here",
         "3 |         end",
         "  `----",
-        r#" help: expected primitive opcode (e.g. "add"), or "end", or control flow opcode (e.g. "if.true")"#
+        r#" help: expected "@", or primitive opcode (e.g. "add"), or "end", or control flow opcode (e.g. "if.true")"#
     );
 }
 
@@ -1135,7 +2647,7 @@ fn assert_parsing_line_invalid_op() {
         "   :                      `-- found a identifier here",
         "29 |         end",
         "   `----",
-        r#" help: expected ".", or primitive opcode (e.g. "add"), or "end", or control flow opcode (e.g. "if.true")"#
+        r#" help: expected ".", or "@", or primitive opcode (e.g. "add"), or "end", or control flow opcode (e.g. "if.true")"#
     );
 }
 
@@ -1160,7 +2672,7 @@ fn assert_parsing_line_unexpected_token() {
         "  :     ^|^",
         "  :      `-- found a mul here",
         "  `----",
-        r#" help: expected "@", or "adv_map", or "begin", or "const", or "export", or "proc", or "use", or end of file, or doc comment"#
+        r#" help: expected "@", or "adv_map", or "begin", or "const", or "data", or "export", or "internal", or "proc", or "struct", or "use", or end of file, or doc comment"#
     );
 }
 
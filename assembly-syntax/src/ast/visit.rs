@@ -279,7 +279,25 @@ where
             visitor.visit_block(then_blk)?;
             visitor.visit_block(else_blk)
         },
-        Op::While { body, .. } | Op::Repeat { body, .. } => visitor.visit_block(body),
+        Op::While { body, .. } => visitor.visit_block(body),
+        Op::Repeat { count, body, .. } => {
+            visitor.visit_immediate_u32(count)?;
+            visitor.visit_block(body)
+        },
+        Op::Match { cases, default, .. } => {
+            for case in cases.iter() {
+                visitor.visit_immediate_u32(&case.value)?;
+                visitor.visit_block(&case.body)?;
+            }
+            visitor.visit_block(default)
+        },
+        Op::For { start, end, body, .. } => {
+            if let Some(start) = start {
+                visitor.visit_immediate_u32(start)?;
+            }
+            visitor.visit_immediate_u32(end)?;
+            visitor.visit_block(body)
+        },
         Op::Inst(inst) => visitor.visit_inst(inst),
     }
 }
@@ -873,7 +891,25 @@ where
             visitor.visit_mut_block(then_blk)?;
             visitor.visit_mut_block(else_blk)
         },
-        Op::While { body, .. } | Op::Repeat { body, .. } => visitor.visit_mut_block(body),
+        Op::While { body, .. } => visitor.visit_mut_block(body),
+        Op::Repeat { count, body, .. } => {
+            visitor.visit_mut_immediate_u32(count)?;
+            visitor.visit_mut_block(body)
+        },
+        Op::Match { cases, default, .. } => {
+            for case in cases.iter_mut() {
+                visitor.visit_mut_immediate_u32(&mut case.value)?;
+                visitor.visit_mut_block(&mut case.body)?;
+            }
+            visitor.visit_mut_block(default)
+        },
+        Op::For { start, end, body, .. } => {
+            if let Some(start) = start {
+                visitor.visit_mut_immediate_u32(start)?;
+            }
+            visitor.visit_mut_immediate_u32(end)?;
+            visitor.visit_mut_block(body)
+        },
         Op::Inst(inst) => visitor.visit_mut_inst(inst),
     }
 }
@@ -0,0 +1,382 @@
+//! A source formatter for Miden Assembly.
+//!
+//! Unlike the [core::fmt::Display] implementation of [crate::ast::Module], which renders the AST
+//! and thus loses comments and the original layout of the code, the formatter operates on the
+//! token stream of a module, and only changes whitespace and the order of imports:
+//!
+//! * Comments and docstrings are preserved, with trailing comments kept on the line they follow.
+//! * Block keywords (`proc`, `export`, `begin`, `if.true`, `else`, `while.true`, `repeat`,
+//!   `struct`, and `end`) are placed on lines of their own, and the contents of each block are
+//!   indented by four spaces.
+//! * Instructions which were written on the same line are kept on the same line, separated by a
+//!   single space.
+//! * Consecutive blank lines are collapsed, blank lines at the start and end of blocks are removed,
+//!   and top-level items following a procedure are separated from it by a blank line.
+//! * Each group of consecutive `use` statements is sorted by path.
+//!
+//! Formatting is verified by parsing the formatted module, and comparing its forms against those
+//! of the original module, so the formatter will never change the meaning of a module. Items are
+//! kept regardless of their `@cfg` attributes when doing so, so gated code is verified too.
+use alloc::{
+    collections::BTreeSet,
+    string::{String, ToString},
+    sync::Arc,
+    vec::Vec,
+};
+
+use crate::{
+    ast::Form,
+    diagnostics::{Report, SourceFile, SourceLanguage},
+    parser::{CfgFlags, Lexer, Scanner, Token, parse_forms_internal},
+    report,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// The string used to indent each level of nesting.
+const INDENT: &str = "    ";
+
+/// Formats the Miden Assembly module in `source`.
+///
+/// Returns an error if `source` is not syntactically valid.
+pub fn format_source(source: Arc<SourceFile>) -> Result<String, Report> {
+    let mut interned = BTreeSet::default();
+    let cfg = CfgFlags::keep_all();
+    let forms = parse_forms_internal(source.clone(), &mut interned, &cfg)
+        .map_err(|err| Report::new(err).with_source_code(source.clone()))?;
+
+    let words = lex_words(&source);
+    let mut lines = group_lines(&source, words);
+    sort_imports(source.as_str(), &mut lines);
+    let formatted = Printer::new(&source).print(&lines);
+
+    // Make sure that formatting did not change the meaning of the module
+    let formatted_source = Arc::new(SourceFile::new(
+        source.id(),
+        SourceLanguage::Masm,
+        source.uri().clone(),
+        formatted.clone(),
+    ));
+    let formatted_forms = parse_forms_internal(formatted_source.clone(), &mut interned, &cfg)
+        .map_err(|err| Report::new(err).with_source_code(formatted_source))?;
+    if !same_forms(&forms, &formatted_forms) {
+        return Err(report!("formatting changed the meaning of '{}'", source.uri()));
+    }
+
+    Ok(formatted)
+}
+
+// WORDS
+// ================================================================================================
+
+/// The role of a [Word] in the layout of a module.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum WordKind {
+    /// A comment
+    Comment,
+    /// A docstring, which may span multiple lines
+    Doc,
+    /// A keyword which opens a block, e.g. `proc.foo` or `if.true`
+    Open,
+    /// The `else` keyword
+    Else,
+    /// The `end` keyword
+    End,
+    /// A `use` statement
+    Use,
+    /// Anything else, e.g. an instruction
+    Other,
+}
+
+/// A sequence of tokens which are not separated by whitespace in the source, e.g. `push.1`.
+#[derive(Debug, Copy, Clone)]
+struct Word {
+    kind: WordKind,
+    /// The byte offset of the start of the word
+    start: usize,
+    /// The byte offset of the end of the word, excluding trailing whitespace
+    end: usize,
+}
+
+/// Splits `source` into [Word]s.
+fn lex_words(source: &SourceFile) -> Vec<Word> {
+    let text = source.as_str();
+    let mut lexer = Lexer::new(source.id(), Scanner::new(text));
+    let mut words = Vec::<Word>::new();
+    // whether the current word is a `proc`/`export` header which may still turn out to be a
+    // procedure alias, i.e. `export.foo::bar`
+    let mut in_header = false;
+    // the number of unclosed parentheses, within which whitespace does not separate words, so that
+    // e.g. `proc.foo(N, M)` remains a single word
+    let mut depth = 0usize;
+    while let Some(Ok((start, token, end))) = lexer.lex() {
+        let (start, end) = (start as usize, end as usize);
+        let end = start + text[start..end].trim_end().len();
+
+        let kind = match token {
+            Token::Eof => break,
+            Token::Comment => WordKind::Comment,
+            Token::DocComment(_) => WordKind::Doc,
+            token => {
+                let nested = depth > 0;
+                match token {
+                    Token::Lparen => depth += 1,
+                    Token::Rparen => depth = depth.saturating_sub(1),
+                    _ => (),
+                }
+                if let Some(word) = words.last_mut() {
+                    let extends = (word.end == start || nested)
+                        && !matches!(word.kind, WordKind::Comment | WordKind::Doc);
+                    if extends {
+                        word.end = end;
+                        if in_header && matches!(token, Token::ColonColon | Token::Rstab) {
+                            word.kind = WordKind::Other;
+                        }
+                        continue;
+                    }
+                }
+                match token {
+                    Token::Proc
+                    | Token::Export
+                    | Token::Begin
+                    | Token::If
+                    | Token::While
+                    | Token::Repeat
+                    | Token::For
+                    | Token::Match
+                    | Token::Struct => WordKind::Open,
+                    Token::Else | Token::Case | Token::Default => WordKind::Else,
+                    Token::End => WordKind::End,
+                    Token::Use => WordKind::Use,
+                    _ => WordKind::Other,
+                }
+            },
+        };
+        in_header = kind == WordKind::Open;
+        words.push(Word { kind, start, end });
+    }
+
+    words
+}
+
+// LINES
+// ================================================================================================
+
+/// The words on a single line of the source.
+#[derive(Debug)]
+struct Line {
+    words: Vec<Word>,
+    /// Whether this line is preceded by at least one blank line
+    blank_before: bool,
+}
+
+impl Line {
+    /// Returns true if this line consists of a single `use` statement, possibly followed by a
+    /// comment.
+    fn is_import(&self) -> bool {
+        match self.words.as_slice() {
+            [word] => word.kind == WordKind::Use,
+            [word, comment] => word.kind == WordKind::Use && comment.kind == WordKind::Comment,
+            _ => false,
+        }
+    }
+}
+
+/// Groups `words` by the line of `source` on which they start.
+fn group_lines(source: &SourceFile, words: Vec<Word>) -> Vec<Line> {
+    let line_starts = source
+        .as_str()
+        .match_indices('\n')
+        .map(|(offset, _)| offset + 1)
+        .collect::<Vec<_>>();
+    let line_of = |offset: usize| line_starts.partition_point(|start| *start <= offset);
+
+    let mut lines = Vec::<Line>::new();
+    let mut last_line = None;
+    for word in words {
+        let first = line_of(word.start);
+        match last_line {
+            Some(last) if first == last => lines.last_mut().unwrap().words.push(word),
+            _ => lines.push(Line {
+                words: vec![word],
+                blank_before: last_line.is_some_and(|last| first > last + 1),
+            }),
+        }
+        last_line = Some(line_of(word.end));
+    }
+
+    lines
+}
+
+/// Sorts each group of consecutive `use` statements in `text` by path.
+fn sort_imports(text: &str, lines: &mut [Line]) {
+    let mut start = 0;
+    while start < lines.len() {
+        if !lines[start].is_import() {
+            start += 1;
+            continue;
+        }
+        let mut end = start + 1;
+        while end < lines.len() && lines[end].is_import() && !lines[end].blank_before {
+            end += 1;
+        }
+
+        // the blank line preceding the group, if any, stays in place
+        let blank_before = core::mem::take(&mut lines[start].blank_before);
+        lines[start..end].sort_by_key(|line| &text[line.words[0].start..line.words[0].end]);
+        lines[start].blank_before = blank_before;
+        start = end;
+    }
+}
+
+// PRINTER
+// ================================================================================================
+
+/// A line of formatted output.
+struct OutputLine {
+    indent: usize,
+    text: String,
+    /// Whether this line opens a block, i.e. is followed by the (indented) contents of the block
+    opens_block: bool,
+}
+
+/// Lays out [Line]s of words.
+struct Printer<'a> {
+    text: &'a str,
+    output: Vec<OutputLine>,
+    indent: usize,
+    /// The words of the line currently being printed
+    pending: Vec<&'a str>,
+}
+
+impl<'a> Printer<'a> {
+    fn new(source: &'a SourceFile) -> Self {
+        Self {
+            text: source.as_str(),
+            output: Vec::new(),
+            indent: 0,
+            pending: Vec::new(),
+        }
+    }
+
+    fn print(mut self, lines: &[Line]) -> String {
+        for line in lines {
+            if line.blank_before || (self.indent == 0 && self.follows_procedure()) {
+                self.blank_line();
+            }
+
+            for (i, word) in line.words.iter().enumerate() {
+                let text = &self.text[word.start..word.end];
+                match word.kind {
+                    WordKind::Comment if self.pending.is_empty() && i > 0 => {
+                        // a trailing comment following a block keyword
+                        let last = self.output.last_mut().expect("expected preceding line");
+                        last.text.push(' ');
+                        last.text.push_str(text);
+                    },
+                    WordKind::Comment => {
+                        self.pending.push(text);
+                        self.flush();
+                    },
+                    WordKind::Doc => {
+                        self.flush();
+                        for doc in text.lines() {
+                            self.push_line(doc.trim().to_string(), false);
+                        }
+                    },
+                    WordKind::Open => {
+                        self.flush();
+                        self.push_line(text.to_string(), true);
+                        self.indent += 1;
+                    },
+                    WordKind::Else => {
+                        self.flush();
+                        self.trim_blank_lines();
+                        self.indent = self.indent.saturating_sub(1);
+                        self.push_line(text.to_string(), true);
+                        self.indent += 1;
+                    },
+                    WordKind::End => {
+                        self.flush();
+                        self.trim_blank_lines();
+                        self.indent = self.indent.saturating_sub(1);
+                        self.push_line(text.to_string(), false);
+                    },
+                    WordKind::Use | WordKind::Other => self.pending.push(text),
+                }
+            }
+            self.flush();
+        }
+        self.trim_blank_lines();
+
+        let mut formatted = String::new();
+        for line in self.output {
+            if !line.text.is_empty() {
+                for _ in 0..line.indent {
+                    formatted.push_str(INDENT);
+                }
+                formatted.push_str(&line.text);
+            }
+            formatted.push('\n');
+        }
+        formatted
+    }
+
+    /// Returns true if the last line printed is the `end` of a top-level item.
+    fn follows_procedure(&self) -> bool {
+        self.output
+            .last()
+            .is_some_and(|line| line.indent == 0 && line.text.starts_with("end"))
+    }
+
+    fn push_line(&mut self, text: String, opens_block: bool) {
+        self.output.push(OutputLine { indent: self.indent, text, opens_block });
+    }
+
+    /// Prints the pending words, if any, on a line of their own.
+    fn flush(&mut self) {
+        if !self.pending.is_empty() {
+            let text = self.pending.join(" ");
+            self.pending.clear();
+            self.push_line(text, false);
+        }
+    }
+
+    /// Prints a blank line, unless at the start of the output or of a block.
+    fn blank_line(&mut self) {
+        self.flush();
+        let allowed = self
+            .output
+            .last()
+            .is_some_and(|line| !line.text.is_empty() && !line.opens_block);
+        if allowed {
+            self.push_line(String::new(), false);
+        }
+    }
+
+    /// Removes blank lines at the end of the output.
+    fn trim_blank_lines(&mut self) {
+        while self.output.last().is_some_and(|line| line.text.is_empty()) {
+            self.output.pop();
+        }
+    }
+}
+
+// VERIFICATION
+// ================================================================================================
+
+/// Returns true if `a` and `b` consist of the same forms, up to the order of imports.
+fn same_forms(a: &[Form], b: &[Form]) -> bool {
+    let (a_imports, a_rest): (Vec<_>, Vec<_>) =
+        a.iter().partition(|form| matches!(form, Form::Import(_)));
+    let (b_imports, b_rest): (Vec<_>, Vec<_>) =
+        b.iter().partition(|form| matches!(form, Form::Import(_)));
+
+    a_rest == b_rest
+        && a_imports.len() == b_imports.len()
+        && a_imports.iter().all(|import| {
+            let count = |imports: &[&Form]| imports.iter().filter(|i| *i == import).count();
+            count(&a_imports) == count(&b_imports)
+        })
+}
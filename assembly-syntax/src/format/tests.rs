@@ -0,0 +1,302 @@
+use alloc::{collections::BTreeSet, string::String, sync::Arc, vec::Vec};
+
+use pretty_assertions::assert_str_eq;
+
+use super::format_source;
+use crate::{
+    Report,
+    ast::Form,
+    diagnostics::{SourceFile, SourceLanguage},
+    parser::{CfgFlags, parse_forms_internal},
+};
+
+fn format(source: &str) -> Result<String, Report> {
+    let source = SourceFile::new(
+        Default::default(),
+        SourceLanguage::Masm,
+        "test.masm".into(),
+        String::from(source),
+    );
+    format_source(Arc::new(source))
+}
+
+#[test]
+fn format_preserves_comments_and_docs() -> Result<(), Report> {
+    let source = "\
+#! Module documentation
+#!   with an indented line
+
+use.std::math::u64 # trailing comment on an import
+
+#! Procedure documentation
+export.foo.2    # trailing comment on a procedure
+  # a comment inside a procedure
+      push.1   push.2 # trailing comment on an instruction
+  add
+end
+";
+    let expected = "\
+#! Module documentation
+#!   with an indented line
+
+use.std::math::u64 # trailing comment on an import
+
+#! Procedure documentation
+export.foo.2 # trailing comment on a procedure
+    # a comment inside a procedure
+    push.1 push.2 # trailing comment on an instruction
+    add
+end
+";
+    assert_str_eq!(format(source)?, expected);
+
+    Ok(())
+}
+
+#[test]
+fn format_normalizes_block_layout() -> Result<(), Report> {
+    let source = "\
+proc.foo push.1 if.true add else
+mul repeat.2 push.8 end end end
+
+
+proc.bar
+
+      while.true
+
+
+  push.0
+
+  end
+
+end
+begin push.1 exec.foo exec.bar end
+";
+    let expected = "\
+proc.foo
+    push.1
+    if.true
+        add
+    else
+        mul
+        repeat.2
+            push.8
+        end
+    end
+end
+
+proc.bar
+    while.true
+        push.0
+    end
+end
+
+begin
+    push.1 exec.foo exec.bar
+end
+";
+    assert_str_eq!(format(source)?, expected);
+
+    Ok(())
+}
+
+#[test]
+fn format_match_cases() -> Result<(), Report> {
+    let source = "\
+begin
+push.1
+match case.0 push.10
+    case.1
+push.11 default push.99 end
+end
+";
+    let expected = "\
+begin
+    push.1
+    match
+    case.0
+        push.10
+    case.1
+        push.11
+    default
+        push.99
+    end
+end
+";
+    assert_str_eq!(format(source)?, expected);
+
+    Ok(())
+}
+
+#[test]
+fn format_keeps_parameter_lists_together() -> Result<(), Report> {
+    let source = "\
+proc.scale(N, OFFSET).1 push.N mul end
+begin exec.scale(2, 8) exec.scale(3, 4) end
+";
+    let expected = "\
+proc.scale(N, OFFSET).1
+    push.N mul
+end
+
+begin
+    exec.scale(2, 8) exec.scale(3, 4)
+end
+";
+    assert_str_eq!(format(source)?, expected);
+
+    Ok(())
+}
+
+#[test]
+fn format_indents_struct_fields() -> Result<(), Report> {
+    let source = "\
+struct.NOTE id: felt
+  key: word
+      assets: [word; 4] end
+const.SIZE = sizeof(NOTE) * 2
+proc.foo push.SIZE end
+";
+    let expected = "\
+struct.NOTE
+    id: felt
+    key: word
+    assets: [word; 4]
+end
+
+const.SIZE = sizeof(NOTE) * 2
+proc.foo
+    push.SIZE
+end
+";
+    assert_str_eq!(format(source)?, expected);
+
+    Ok(())
+}
+
+#[test]
+fn format_sorts_imports() -> Result<(), Report> {
+    let source = "\
+use.std::math::u64
+use.std::crypto::hashes::rpo
+use.std::collections::smt
+
+use.miden::note
+use.miden::account
+
+export.u64::wrapping_add
+export.foo
+    exec.u64::wrapping_add
+end
+";
+    let expected = "\
+use.std::collections::smt
+use.std::crypto::hashes::rpo
+use.std::math::u64
+
+use.miden::account
+use.miden::note
+
+export.u64::wrapping_add
+export.foo
+    exec.u64::wrapping_add
+end
+";
+    assert_str_eq!(format(source)?, expected);
+
+    Ok(())
+}
+
+#[test]
+fn format_cfg_gated_procedures() -> Result<(), Report> {
+    let source = "\
+@cfg(feature = \"fast\")
+export.foo push.1 add end
+@cfg(not_feature = \"fast\")
+export.foo
+  push.1
+      @cfg(feature = \"checked\") u32assert
+  add
+end
+";
+    let expected = "\
+@cfg(feature = \"fast\")
+export.foo
+    push.1 add
+end
+
+@cfg(not_feature = \"fast\")
+export.foo
+    push.1
+    @cfg(feature = \"checked\") u32assert
+    add
+end
+";
+    assert_str_eq!(format(source)?, expected);
+
+    // Both variants of `foo` are kept when parsing for the formatter, so that formatting is
+    // verified for code which is disabled by default too
+    let source = Arc::new(SourceFile::new(
+        Default::default(),
+        SourceLanguage::Masm,
+        "test.masm".into(),
+        String::from(source),
+    ));
+    let mut interned = BTreeSet::default();
+    let count_procedures =
+        |forms: Vec<Form>| forms.iter().filter(|form| matches!(form, Form::Procedure(_))).count();
+    let forms = parse_forms_internal(source.clone(), &mut interned, &CfgFlags::default())?;
+    assert_eq!(count_procedures(forms), 1);
+    let forms = parse_forms_internal(source, &mut interned, &CfgFlags::keep_all())?;
+    assert_eq!(count_procedures(forms), 2);
+
+    Ok(())
+}
+
+#[test]
+fn format_is_idempotent() -> Result<(), Report> {
+    let source = "\
+const.A = 1 + 2 # a constant
+adv_map.B = [0x01]
+data.0 = [1, 2, 3, 4]
+
+@inline
+export.foo.1
+    push.A loc_store.0 if.true push.[1,2,3,4] dropw end
+end
+";
+    let formatted = format(source)?;
+    assert_str_eq!(format(&formatted)?, formatted);
+
+    Ok(())
+}
+
+#[test]
+fn format_rejects_invalid_source() {
+    assert!(format("proc.foo push.1").is_err());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn format_stdlib() -> Result<(), Report> {
+    use std::{fs, path::Path};
+
+    use crate::diagnostics::{IntoDiagnostic, WrapErr};
+
+    let mut dirs = vec![Path::new(env!("CARGO_MANIFEST_DIR")).join("../stdlib/asm")];
+    while let Some(dir) = dirs.pop() {
+        for entry in fs::read_dir(&dir).into_diagnostic()? {
+            let path = entry.into_diagnostic()?.path();
+            if path.is_dir() {
+                dirs.push(path);
+                continue;
+            }
+            let source = fs::read_to_string(&path).into_diagnostic()?;
+            let formatted = format(&source)
+                .wrap_err_with(|| format!("failed to format '{}'", path.display()))?;
+            assert_str_eq!(format(&formatted)?, formatted, "{}", path.display());
+        }
+    }
+
+    Ok(())
+}
@@ -10,6 +10,7 @@ pub use miden_core::{Felt, FieldElement, StarkField, Word, prettier, utils::Disp
 
 pub mod ast;
 pub mod diagnostics;
+pub mod format;
 pub mod library;
 mod parse;
 pub mod parser;
@@ -23,12 +24,13 @@ pub use self::{
         Spanned,
     },
     library::{
-        KernelLibrary, Library, LibraryError, LibraryNamespace, LibraryPath, LibraryPathComponent,
-        PathError, Version, VersionError,
+        KernelLibrary, Library, LibraryArchive, LibraryError, LibraryNamespace, LibraryPath,
+        LibraryPathComponent, PathError, Version, VersionError, VersionReq, VersionReqError,
     },
     parser::{ModuleParser, ParsingError},
 };
 pub use self::{
     parse::{Parse, ParseOptions},
-    sema::SemanticAnalysisError,
+    parser::CfgFlags,
+    sema::{ConstEvalError, Lint, LintConfig, LintLevel, SemanticAnalysisError},
 };
@@ -0,0 +1,308 @@
+use alloc::{collections::BTreeMap, string::String, sync::Arc, vec::Vec};
+
+use miden_core::{
+    AdviceMap, KernelVersion,
+    debuginfo::SourceMap,
+    mast::{MastForest, MastNodeId, MastNodeStore},
+    utils::{ByteReader, ByteWriter, Deserializable, DeserializationError, Serializable},
+};
+
+use super::{
+    Library, LibraryError, LibraryNamespace, Version, VersionReq, read_dependencies,
+    read_signatures, write_dependencies, write_signatures,
+};
+use crate::{
+    ast::{ProcedureSignature, QualifiedProcedureName},
+    parser::CfgFlags,
+};
+
+// LIBRARY ARCHIVE
+// ================================================================================================
+
+/// A collection of [`Library`]s whose MAST nodes are kept in a single content-addressed
+/// [`MastNodeStore`].
+///
+/// Subtrees which are present in several libraries of the archive (e.g. common helpers inlined
+/// into many packages) are stored only once, which makes the serialized archive much smaller than
+/// the sum of the serialized libraries. Libraries are read back out of the archive by extracting
+/// their procedures from the store.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LibraryArchive {
+    store: MastNodeStore,
+    entries: Vec<ArchivedLibrary>,
+}
+
+/// The parts of a [`Library`] which are not kept in the archive's node store.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ArchivedLibrary {
+    /// The procedure roots of the library's forest, as node IDs in the store.
+    roots: Vec<MastNodeId>,
+    /// The exports of the library, mapped to node IDs in the store.
+    exports: BTreeMap<QualifiedProcedureName, MastNodeId>,
+    /// The signatures of the exports of the library.
+    signatures: BTreeMap<QualifiedProcedureName, ProcedureSignature>,
+    /// The `@cfg` flags which were set when the library was assembled.
+    cfg_flags: CfgFlags,
+    /// The source map of the library.
+    source_map: SourceMap,
+    /// The version of the kernel targeted by the syscalls of the library, if any.
+    kernel_version: Option<KernelVersion>,
+    /// The version of the library, if any.
+    version: Option<Version>,
+    /// The version requirements of the library on its dependencies.
+    dependencies: BTreeMap<LibraryNamespace, VersionReq>,
+    /// The advice map of the library's forest.
+    advice_map: AdviceMap,
+}
+
+// ------------------------------------------------------------------------------------------------
+/// Constructors
+impl LibraryArchive {
+    /// Creates a new empty [`LibraryArchive`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `library` to this archive, and returns its index in the archive.
+    ///
+    /// Nodes of the library which are already present in the archive are not stored again.
+    pub fn add(&mut self, library: &Library) -> Result<usize, LibraryError> {
+        let forest = library.mast_forest();
+        let root_map = self.store.insert([forest.as_ref()]).map_err(LibraryError::MastForest)?;
+        let map_root = |node_id: &MastNodeId| {
+            root_map
+                .map_root(0, node_id)
+                .expect("every root should be mapped into the store")
+        };
+
+        let roots = forest.procedure_roots().iter().map(map_root).collect();
+        let exports = library
+            .exports
+            .iter()
+            .map(|(name, node_id)| (name.clone(), map_root(node_id)))
+            .collect();
+
+        self.entries.push(ArchivedLibrary {
+            roots,
+            exports,
+            signatures: library.signatures.clone(),
+            cfg_flags: library.cfg_flags.clone(),
+            source_map: library.source_map.clone(),
+            kernel_version: library.kernel_version.clone(),
+            version: library.version,
+            dependencies: library.dependencies.clone(),
+            advice_map: forest.advice_map().clone(),
+        });
+
+        Ok(self.entries.len() - 1)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+/// Public accessors
+impl LibraryArchive {
+    /// Returns the number of libraries in this archive.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if this archive contains no libraries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the store holding the MAST nodes of all libraries in this archive.
+    pub fn store(&self) -> &MastNodeStore {
+        &self.store
+    }
+
+    /// Reads the library at `index` out of this archive, or returns `None` if there is no such
+    /// library.
+    ///
+    /// The returned library exports the same procedures as the library which was added to the
+    /// archive, but the nodes of its [`MastForest`] may be in a different order.
+    pub fn get(&self, index: usize) -> Option<Result<Library, LibraryError>> {
+        self.entries.get(index).map(|entry| self.extract(entry))
+    }
+
+    /// Returns an iterator which reads all libraries out of this archive, in the order in which
+    /// they were added.
+    pub fn libraries(&self) -> impl Iterator<Item = Result<Library, LibraryError>> + '_ {
+        self.entries.iter().map(|entry| self.extract(entry))
+    }
+}
+
+/// Helpers
+impl LibraryArchive {
+    fn extract(&self, entry: &ArchivedLibrary) -> Result<Library, LibraryError> {
+        let (mut forest, node_id_map) = self
+            .store
+            .extract(entry.roots.iter().copied())
+            .map_err(LibraryError::MastForest)?;
+        forest.advice_map_mut().extend(entry.advice_map.clone());
+
+        let exports = entry
+            .exports
+            .iter()
+            .map(|(name, node_id)| (name.clone(), node_id_map[node_id]))
+            .collect();
+
+        Library::new(Arc::new(forest), exports).map(|library| Library {
+            kernel_version: entry.kernel_version.clone(),
+            version: entry.version,
+            dependencies: entry.dependencies.clone(),
+            ..library
+                .with_signatures(entry.signatures.clone())
+                .with_cfg_flags(entry.cfg_flags.clone())
+                .with_source_map(entry.source_map.clone())
+        })
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+/// Serialization
+///
+/// The serialization format of [`LibraryArchive`] is as follows:
+/// - `MAGIC_ARCHIVE`
+/// - `VERSION`
+/// - `store` (`MastNodeStore`)
+/// - `entries`, each of which consists of:
+///   - `roots` (node IDs in the store)
+///   - `exports` (procedure names and node IDs in the store)
+///   - `signatures`
+///   - `cfg_flags` (`CfgFlags`)
+///   - `source_map` (`SourceMap`)
+///   - `kernel_version` (`Option<KernelVersion>`)
+///   - `version` (`Option<Version>`)
+///   - `dependencies` (namespaces and version requirements)
+///   - `advice_map` (`AdviceMap`)
+impl LibraryArchive {
+    /// Magic string for detecting that a file is a serialized [`LibraryArchive`].
+    const MAGIC_ARCHIVE: &[u8; 5] = b"MASA\0";
+
+    /// The format version.
+    ///
+    /// If future modifications are made to this format, the version should be incremented by 1.
+    const VERSION: [u8; 3] = [0, 0, 5];
+}
+
+impl Serializable for LibraryArchive {
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        target.write_bytes(Self::MAGIC_ARCHIVE);
+        target.write_bytes(&Self::VERSION);
+
+        let Self { store, entries } = self;
+
+        store.write_into(target);
+
+        target.write_usize(entries.len());
+        for ArchivedLibrary {
+            roots,
+            exports,
+            signatures,
+            cfg_flags,
+            source_map,
+            kernel_version,
+            version,
+            dependencies,
+            advice_map,
+        } in entries
+        {
+            target.write_usize(roots.len());
+            for root in roots {
+                target.write_u32(root.as_u32());
+            }
+
+            target.write_usize(exports.len());
+            for (proc_name, proc_node_id) in exports {
+                proc_name.module.write_into(target);
+                proc_name.name.write_into(target);
+                target.write_u32(proc_node_id.as_u32());
+            }
+
+            write_signatures(signatures, target);
+            cfg_flags.write_into(target);
+            source_map.write_into(target);
+            kernel_version.write_into(target);
+            version.write_into(target);
+            write_dependencies(dependencies, target);
+            advice_map.write_into(target);
+        }
+    }
+}
+
+impl Deserializable for LibraryArchive {
+    fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
+        let magic: [u8; 5] = source.read_array()?;
+        if magic != *Self::MAGIC_ARCHIVE {
+            return Err(DeserializationError::InvalidValue(format!(
+                "invalid magic bytes. Expected '{:?}', got '{magic:?}'",
+                Self::MAGIC_ARCHIVE
+            )));
+        }
+
+        let version: [u8; 3] = source.read_array()?;
+        if version != Self::VERSION {
+            return Err(DeserializationError::InvalidValue(format!(
+                "unsupported version. Got '{version:?}', but only '{:?}' is supported",
+                Self::VERSION
+            )));
+        }
+
+        let store = MastNodeStore::read_from(source)?;
+        let store_forest: &MastForest = store.forest();
+
+        let num_entries = source.read_usize()?;
+        let mut entries = Vec::with_capacity(num_entries);
+        for _ in 0..num_entries {
+            let num_roots = source.read_usize()?;
+            let mut roots = Vec::with_capacity(num_roots);
+            for _ in 0..num_roots {
+                roots.push(MastNodeId::from_u32_safe(source.read_u32()?, store_forest)?);
+            }
+
+            let num_exports = source.read_usize()?;
+            if num_exports == 0 {
+                return Err(DeserializationError::InvalidValue(String::from(
+                    "No exported procedures",
+                )));
+            };
+            let mut exports = BTreeMap::new();
+            for _ in 0..num_exports {
+                let proc_module = source.read()?;
+                let proc_name = source.read()?;
+                let proc_name = QualifiedProcedureName::new(proc_module, proc_name);
+                let proc_node_id = MastNodeId::from_u32_safe(source.read_u32()?, store_forest)?;
+                if !roots.contains(&proc_node_id) {
+                    return Err(DeserializationError::InvalidValue(format!(
+                        "export {proc_name} is not a procedure root of its library"
+                    )));
+                }
+
+                exports.insert(proc_name, proc_node_id);
+            }
+
+            let signatures = read_signatures(source, &exports)?;
+            let cfg_flags = CfgFlags::read_from(source)?;
+            let source_map = SourceMap::read_from(source)?;
+            let kernel_version = Option::<KernelVersion>::read_from(source)?;
+            let version = Option::<Version>::read_from(source)?;
+            let dependencies = read_dependencies(source)?;
+            let advice_map = AdviceMap::read_from(source)?;
+
+            entries.push(ArchivedLibrary {
+                roots,
+                exports,
+                signatures,
+                cfg_flags,
+                source_map,
+                kernel_version,
+                version,
+                dependencies,
+                advice_map,
+            });
+        }
+
+        Ok(Self { store, entries })
+    }
+}
@@ -1,4 +1,4 @@
-use miden_core::errors::KernelError;
+use miden_core::{errors::KernelError, mast::MastForestError};
 
 use crate::{ast::QualifiedProcedureName, diagnostics::Diagnostic};
 
@@ -17,4 +17,7 @@ pub enum LibraryError {
     KernelConversion(KernelError),
     #[error("invalid export: no procedure root for {procedure_path} procedure")]
     NoProcedureRootForExport { procedure_path: QualifiedProcedureName },
+    // See the note on `KernelConversion` for why the error is included in the message.
+    #[error("invalid MAST forest: {0}")]
+    MastForest(MastForestError),
 }
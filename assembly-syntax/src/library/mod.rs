@@ -1,18 +1,26 @@
 use alloc::{
     collections::{BTreeMap, BTreeSet},
-    string::String,
+    string::{String, ToString},
     sync::Arc,
     vec::Vec,
 };
 
 use miden_core::{
-    AdviceMap, Kernel, Word,
-    mast::{MastForest, MastNodeId},
+    AdviceMap, Kernel, KernelVersion, Word,
+    debuginfo::SourceMap,
+    mast::{
+        CycleEstimate, CycleEstimator, MastCallGraph, MastForest, MastForestDiff, MastForestError,
+        MastNodeId,
+    },
     utils::{ByteReader, ByteWriter, Deserializable, DeserializationError, Serializable},
 };
 
-use crate::ast::QualifiedProcedureName;
+use crate::{
+    ast::{ProcedureName, ProcedureSignature, QualifiedProcedureName},
+    parser::CfgFlags,
+};
 
+mod archive;
 mod error;
 mod module;
 mod namespace;
@@ -22,10 +30,11 @@ mod version;
 pub use module::{ModuleInfo, ProcedureInfo};
 
 pub use self::{
+    archive::LibraryArchive,
     error::LibraryError,
     namespace::{LibraryNamespace, LibraryNamespaceError},
     path::{LibraryPath, LibraryPathComponent, PathError},
-    version::{Version, VersionError},
+    version::{Version, VersionError, VersionReq, VersionReqError},
 };
 
 // LIBRARY
@@ -50,6 +59,21 @@ pub struct Library {
     /// the procedures have the same MAST root and decorators, they will have the same
     /// `MastNodeId`.
     exports: BTreeMap<QualifiedProcedureName, MastNodeId>,
+    /// The signatures of the exported procedures which have one.
+    signatures: BTreeMap<QualifiedProcedureName, ProcedureSignature>,
+    /// The `@cfg` flags which were set when the library was assembled.
+    cfg_flags: CfgFlags,
+    /// The source map of the library, which is empty unless it was assembled in debug mode with a
+    /// source root.
+    source_map: SourceMap,
+    /// The version of the kernel targeted by the syscalls of the library, if it was assembled
+    /// against a versioned kernel.
+    kernel_version: Option<KernelVersion>,
+    /// The version of the library, if it was given one.
+    version: Option<Version>,
+    /// The versions of the libraries this library depends on which it is compatible with, by the
+    /// namespace of those libraries.
+    dependencies: BTreeMap<LibraryNamespace, VersionReq>,
     /// The MAST forest underlying this library.
     mast_forest: Arc<MastForest>,
 }
@@ -85,7 +109,68 @@ impl Library {
 
         let digest = compute_content_hash(&exports, &mast_forest);
 
-        Ok(Self { digest, exports, mast_forest })
+        Ok(Self {
+            digest,
+            exports,
+            signatures: BTreeMap::new(),
+            cfg_flags: CfgFlags::default(),
+            source_map: SourceMap::default(),
+            kernel_version: None,
+            version: None,
+            dependencies: BTreeMap::new(),
+            mast_forest,
+        })
+    }
+
+    /// Produces a new library where the provided signatures are attached to the corresponding
+    /// exported procedures.
+    ///
+    /// Signatures of procedures which are not exported from this library are ignored.
+    pub fn with_signatures<I>(mut self, signatures: I) -> Self
+    where
+        I: IntoIterator<Item = (QualifiedProcedureName, ProcedureSignature)>,
+    {
+        self.signatures
+            .extend(signatures.into_iter().filter(|(name, _)| self.exports.contains_key(name)));
+        self
+    }
+
+    /// Produces a new library which records that it was assembled with the `@cfg` flags
+    /// `cfg_flags`.
+    pub fn with_cfg_flags(mut self, cfg_flags: CfgFlags) -> Self {
+        self.cfg_flags = cfg_flags;
+        self
+    }
+
+    /// Produces a new library which ships `source_map`, so that the source locations of its
+    /// procedures can be resolved when the library is debugged.
+    ///
+    /// See [`SourceMap`] for details.
+    pub fn with_source_map(mut self, source_map: SourceMap) -> Self {
+        self.source_map = source_map;
+        self
+    }
+
+    /// Produces a new library which records that its syscalls target version `kernel_version` of
+    /// a kernel.
+    pub fn with_kernel_version(mut self, kernel_version: KernelVersion) -> Self {
+        self.kernel_version = Some(kernel_version);
+        self
+    }
+
+    /// Produces a new library with version `version`.
+    pub fn with_version(mut self, version: Version) -> Self {
+        self.version = Some(version);
+        self
+    }
+
+    /// Produces a new library which requires the library providing `namespace` to have a version
+    /// matching `requirement`.
+    ///
+    /// The requirement is checked by the assembler when both libraries are linked together.
+    pub fn with_dependency(mut self, namespace: LibraryNamespace, requirement: VersionReq) -> Self {
+        self.dependencies.insert(namespace, requirement);
+        self
     }
 
     /// Produces a new library with the existing [`MastForest`] and where all key/values in the
@@ -126,6 +211,58 @@ impl Library {
         *self.exports.get(proc_name).expect("procedure not exported from the library")
     }
 
+    /// Returns the signature of the specified exported procedure, if it is known.
+    pub fn get_procedure_signature(
+        &self,
+        proc_name: &QualifiedProcedureName,
+    ) -> Option<&ProcedureSignature> {
+        self.signatures.get(proc_name)
+    }
+
+    /// Returns the exported procedures of this library with known signatures, along with their
+    /// signatures.
+    pub fn signatures(
+        &self,
+    ) -> impl Iterator<Item = (&QualifiedProcedureName, &ProcedureSignature)> {
+        self.signatures.iter()
+    }
+
+    /// Returns the `@cfg` flags which were set when this library was assembled.
+    pub fn cfg_flags(&self) -> &CfgFlags {
+        &self.cfg_flags
+    }
+
+    /// Returns the source map of this library, which maps the source files referenced by the
+    /// decorators of its procedures to paths relative to a source root.
+    ///
+    /// The source files can be loaded into a source manager with
+    /// `SourceManagerExt::load_source_map`.
+    pub fn source_map(&self) -> &SourceMap {
+        &self.source_map
+    }
+
+    /// Returns the version of the kernel targeted by the syscalls of this library, if it was
+    /// assembled against a versioned kernel.
+    pub fn kernel_version(&self) -> Option<&KernelVersion> {
+        self.kernel_version.as_ref()
+    }
+
+    /// Returns the version of this library, if it was given one.
+    pub fn version(&self) -> Option<&Version> {
+        self.version.as_ref()
+    }
+
+    /// Returns the version requirements of this library on the libraries it depends on, by the
+    /// namespace of those libraries.
+    pub fn dependencies(&self) -> impl Iterator<Item = (&LibraryNamespace, &VersionReq)> {
+        self.dependencies.iter()
+    }
+
+    /// Returns the namespaces of the procedures exported by this library.
+    pub fn namespaces(&self) -> BTreeSet<&LibraryNamespace> {
+        self.exports.keys().map(|name| name.module.namespace()).collect()
+    }
+
     /// Returns true if the specified exported procedure is re-exported from a dependency.
     pub fn is_reexport(&self, proc_name: &QualifiedProcedureName) -> bool {
         self.exports
@@ -138,6 +275,42 @@ impl Library {
     pub fn mast_forest(&self) -> &Arc<MastForest> {
         &self.mast_forest
     }
+
+    /// Compares the exported procedures of this library against those of `other`.
+    ///
+    /// Procedures are matched by their fully-qualified names, so a procedure whose body changed is
+    /// reported as changed (along with the first point at which the bodies diverge), rather than
+    /// as a removal and an addition.
+    pub fn diff(
+        &self,
+        other: &Library,
+    ) -> Result<MastForestDiff<QualifiedProcedureName>, MastForestError> {
+        MastForestDiff::compute(
+            &self.mast_forest,
+            self.exports.iter().map(|(name, &node_id)| (name.clone(), node_id)),
+            &other.mast_forest,
+            other.exports.iter().map(|(name, &node_id)| (name.clone(), node_id)),
+        )
+    }
+
+    /// Returns static bounds on the number of VM cycles needed to execute each of the exported
+    /// procedures of this library.
+    pub fn estimate_cycles(&self) -> BTreeMap<QualifiedProcedureName, CycleEstimate> {
+        let mut estimator = CycleEstimator::new(&self.mast_forest);
+        self.exports
+            .iter()
+            .map(|(name, &node_id)| (name.clone(), estimator.estimate(node_id)))
+            .collect()
+    }
+
+    /// Builds the call graph of the procedures in this library, with the exported procedures named
+    /// by their fully-qualified names.
+    pub fn call_graph(&self) -> MastCallGraph {
+        MastCallGraph::build(
+            &self.mast_forest,
+            self.exports.iter().map(|(name, &node_id)| (name.to_string(), node_id)),
+        )
+    }
 }
 
 /// Conversions
@@ -167,9 +340,50 @@ impl Library {
     }
 }
 
+// ------------------------------------------------------------------------------------------------
+/// Serialization
+///
+/// The serialization format of [`Library`] is as follows:
+/// - `MAGIC_LIBRARY`
+/// - `VERSION`
+/// - `mast_forest` (`MastForest`)
+/// - `exports` (procedure names and node IDs)
+/// - `signatures`
+/// - `cfg_flags` (`CfgFlags`)
+/// - `source_map` (`SourceMap`)
+/// - `kernel_version` (`Option<KernelVersion>`)
+/// - `version` (`Option<Version>`)
+/// - `dependencies` (namespaces and version requirements)
+impl Library {
+    /// Magic string for detecting that a file is a serialized [`Library`].
+    const MAGIC_LIBRARY: &[u8; 5] = b"MASL\0";
+
+    /// The magic string of a serialized [`MastForest`], with which libraries serialized before
+    /// the format was versioned start.
+    const MAGIC_LEGACY: &[u8; 5] = b"MAST\0";
+
+    /// The format version.
+    ///
+    /// If future modifications are made to this format, the version should be incremented by 1.
+    const VERSION: [u8; 3] = [0, 0, 1];
+}
+
 impl Serializable for Library {
     fn write_into<W: ByteWriter>(&self, target: &mut W) {
-        let Self { digest: _, exports, mast_forest } = self;
+        target.write_bytes(Self::MAGIC_LIBRARY);
+        target.write_bytes(&Self::VERSION);
+
+        let Self {
+            digest: _,
+            exports,
+            signatures,
+            cfg_flags,
+            source_map,
+            kernel_version,
+            version,
+            dependencies,
+            mast_forest,
+        } = self;
 
         mast_forest.write_into(target);
 
@@ -179,11 +393,39 @@ impl Serializable for Library {
             proc_name.name.write_into(target);
             target.write_u32(proc_node_id.as_u32());
         }
+
+        write_signatures(signatures, target);
+        cfg_flags.write_into(target);
+        source_map.write_into(target);
+        kernel_version.write_into(target);
+        version.write_into(target);
+        write_dependencies(dependencies, target);
     }
 }
 
 impl Deserializable for Library {
     fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
+        let magic: [u8; 5] = source.read_array()?;
+        if magic == *Self::MAGIC_LEGACY {
+            return Err(DeserializationError::InvalidValue(String::from(
+                "the library was serialized in an unversioned legacy format, and must be rebuilt",
+            )));
+        }
+        if magic != *Self::MAGIC_LIBRARY {
+            return Err(DeserializationError::InvalidValue(format!(
+                "invalid magic bytes. Expected '{:?}', got '{magic:?}'",
+                Self::MAGIC_LIBRARY
+            )));
+        }
+
+        let version: [u8; 3] = source.read_array()?;
+        if version != Self::VERSION {
+            return Err(DeserializationError::InvalidValue(format!(
+                "unsupported version. Got '{version:?}', but only '{:?}' is supported",
+                Self::VERSION
+            )));
+        }
+
         let mast_forest = Arc::new(MastForest::read_from(source)?);
 
         let num_exports = source.read_usize()?;
@@ -200,12 +442,88 @@ impl Deserializable for Library {
             exports.insert(proc_name, proc_node_id);
         }
 
+        let signatures = read_signatures(source, &exports)?;
+        let cfg_flags = CfgFlags::read_from(source)?;
+        let source_map = SourceMap::read_from(source)?;
+        let kernel_version = Option::<KernelVersion>::read_from(source)?;
+        let version = Option::<Version>::read_from(source)?;
+        let dependencies = read_dependencies(source)?;
         let digest = compute_content_hash(&exports, &mast_forest);
 
-        Ok(Self { digest, exports, mast_forest })
+        Ok(Self {
+            digest,
+            exports,
+            signatures,
+            cfg_flags,
+            source_map,
+            kernel_version,
+            version,
+            dependencies,
+            mast_forest,
+        })
     }
 }
 
+fn write_signatures<W: ByteWriter>(
+    signatures: &BTreeMap<QualifiedProcedureName, ProcedureSignature>,
+    target: &mut W,
+) {
+    target.write_usize(signatures.len());
+    for (proc_name, signature) in signatures {
+        proc_name.module.write_into(target);
+        proc_name.name.write_into(target);
+        signature.write_into(target);
+    }
+}
+
+fn read_signatures<R: ByteReader, T>(
+    source: &mut R,
+    exports: &BTreeMap<QualifiedProcedureName, T>,
+) -> Result<BTreeMap<QualifiedProcedureName, ProcedureSignature>, DeserializationError> {
+    let num_signatures = source.read_usize()?;
+    let mut signatures = BTreeMap::new();
+    for _ in 0..num_signatures {
+        let proc_module = source.read()?;
+        let proc_name = source.read()?;
+        let proc_name = QualifiedProcedureName::new(proc_module, proc_name);
+        if !exports.contains_key(&proc_name) {
+            return Err(DeserializationError::InvalidValue(format!(
+                "signature of {proc_name} does not belong to an exported procedure"
+            )));
+        }
+        let signature = ProcedureSignature::read_from(source)?;
+
+        signatures.insert(proc_name, signature);
+    }
+
+    Ok(signatures)
+}
+
+fn write_dependencies<W: ByteWriter>(
+    dependencies: &BTreeMap<LibraryNamespace, VersionReq>,
+    target: &mut W,
+) {
+    target.write_usize(dependencies.len());
+    for (namespace, requirement) in dependencies {
+        namespace.write_into(target);
+        requirement.write_into(target);
+    }
+}
+
+fn read_dependencies<R: ByteReader>(
+    source: &mut R,
+) -> Result<BTreeMap<LibraryNamespace, VersionReq>, DeserializationError> {
+    let num_dependencies = source.read_usize()?;
+    let mut dependencies = BTreeMap::new();
+    for _ in 0..num_dependencies {
+        let namespace = LibraryNamespace::read_from(source)?;
+        let requirement = VersionReq::read_from(source)?;
+        dependencies.insert(namespace, requirement);
+    }
+
+    Ok(dependencies)
+}
+
 fn compute_content_hash(
     exports: &BTreeMap<QualifiedProcedureName, MastNodeId>,
     mast_forest: &MastForest,
@@ -228,44 +546,24 @@ impl Library {
     /// specific requirement that the extension be set, or the same as
     /// [`Self::LIBRARY_EXTENSION`].
     pub fn write_to_file(&self, path: impl AsRef<std::path::Path>) -> std::io::Result<()> {
-        let path = path.as_ref();
-
-        if let Some(dir) = path.parent() {
-            std::fs::create_dir_all(dir)?;
-        }
-
-        // NOTE: We catch panics due to i/o errors here due to the fact that the ByteWriter
-        // trait does not provide fallible APIs, so WriteAdapter will panic if the underlying
-        // writes fail. This needs to be addressed in winterfell at some point
-        std::panic::catch_unwind(|| {
-            let mut file = std::fs::File::create(path)?;
-            self.write_into(&mut file);
-            Ok(())
-        })
-        .map_err(|p| {
-            match p.downcast::<std::io::Error>() {
-                // SAFETY: It is guaranteed safe to read Box<std::io::Error>
-                Ok(err) => unsafe { core::ptr::read(&*err) },
-                Err(err) => std::panic::resume_unwind(err),
-            }
-        })?
+        write_to_file(self, path.as_ref())
     }
 
     pub fn deserialize_from_file(
         path: impl AsRef<std::path::Path>,
     ) -> Result<Self, DeserializationError> {
-        use miden_core::utils::ReadAdapter;
-
+        // NOTE: The file is read into memory rather than through a `ReadAdapter`, since the
+        // buffering of the latter can read past the data it has buffered, and panic, on some
+        // valid inputs
         let path = path.as_ref();
-        let mut file = std::fs::File::open(path).map_err(|err| {
+        let bytes = std::fs::read(path).map_err(|err| {
             DeserializationError::InvalidValue(format!(
                 "failed to open file at {}: {err}",
                 path.to_string_lossy()
             ))
         })?;
-        let mut adapter = ReadAdapter::new(&mut file);
 
-        Self::read_from(&mut adapter)
+        Self::read_from_bytes(&bytes)
     }
 }
 
@@ -278,11 +576,15 @@ impl Library {
 /// - All exported procedures must be exported directly from the kernel namespace (i.e., `$kernel`).
 /// - There must be at least one exported procedure.
 /// - The number of exported procedures cannot exceed [Kernel::MAX_NUM_PROCEDURES] (i.e., 256).
+///
+/// A kernel library may also have a name and version, see [KernelLibrary::with_version], which
+/// are recorded by the programs assembled against it.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct KernelLibrary {
     kernel: Kernel,
     kernel_info: ModuleInfo,
     library: Library,
+    version: Option<KernelVersion>,
 }
 
 impl AsRef<Library> for KernelLibrary {
@@ -293,11 +595,36 @@ impl AsRef<Library> for KernelLibrary {
 }
 
 impl KernelLibrary {
+    /// Produces a new kernel library with the name and version `version`.
+    pub fn with_version(mut self, version: KernelVersion) -> Self {
+        self.version = Some(version);
+        self
+    }
+
     /// Returns the [Kernel] for this kernel library.
     pub fn kernel(&self) -> &Kernel {
         &self.kernel
     }
 
+    /// Returns the name and version of this kernel library, if any.
+    pub fn version(&self) -> Option<&KernelVersion> {
+        self.version.as_ref()
+    }
+
+    /// Returns an iterator over the procedures exported by this kernel, along with their digest
+    /// and their signature, if it is known.
+    pub fn procedures(
+        &self,
+    ) -> impl Iterator<Item = (&ProcedureName, Word, Option<&ProcedureSignature>)> {
+        self.kernel_info.procedures().map(|(_, proc_info)| {
+            let name = QualifiedProcedureName::new(
+                self.kernel_info.path().clone(),
+                proc_info.name.clone(),
+            );
+            (&proc_info.name, proc_info.digest, self.library.get_procedure_signature(&name))
+        })
+    }
+
     /// Returns a reference to the inner [`MastForest`].
     pub fn mast_forest(&self) -> &Arc<MastForest> {
         self.library.mast_forest()
@@ -337,27 +664,36 @@ impl TryFrom<Library> for KernelLibrary {
             kernel,
             kernel_info: kernel_module,
             library,
+            version: None,
         })
     }
 }
 
 impl Serializable for KernelLibrary {
     fn write_into<W: ByteWriter>(&self, target: &mut W) {
-        let Self { kernel: _, kernel_info: _, library } = self;
+        let Self {
+            kernel: _,
+            kernel_info: _,
+            library,
+            version,
+        } = self;
 
         library.write_into(target);
+        version.write_into(target);
     }
 }
 
 impl Deserializable for KernelLibrary {
     fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
         let library = Library::read_from(source)?;
+        let version = Option::<KernelVersion>::read_from(source)?;
 
-        Self::try_from(library).map_err(|err| {
+        let kernel_library = Self::try_from(library).map_err(|err| {
             DeserializationError::InvalidValue(format!(
                 "Failed to deserialize kernel library: {err}"
             ))
-        })
+        })?;
+        Ok(Self { version, ..kernel_library })
     }
 }
 
@@ -365,6 +701,33 @@ impl Deserializable for KernelLibrary {
 impl KernelLibrary {
     /// Write the library to a target file
     pub fn write_to_file(&self, path: impl AsRef<std::path::Path>) -> std::io::Result<()> {
-        self.library.write_to_file(path)
+        write_to_file(self, path.as_ref())
     }
 }
+
+/// Writes `value` to the file at `path`, creating its parent directories if needed.
+#[cfg(feature = "std")]
+fn write_to_file<T>(value: &T, path: &std::path::Path) -> std::io::Result<()>
+where
+    T: Serializable + std::panic::RefUnwindSafe,
+{
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+
+    // NOTE: We catch panics due to i/o errors here due to the fact that the ByteWriter
+    // trait does not provide fallible APIs, so WriteAdapter will panic if the underlying
+    // writes fail. This needs to be addressed in winterfell at some point
+    std::panic::catch_unwind(|| {
+        let mut file = std::fs::File::create(path)?;
+        value.write_into(&mut file);
+        Ok(())
+    })
+    .map_err(|p| {
+        match p.downcast::<std::io::Error>() {
+            // SAFETY: It is guaranteed safe to read Box<std::io::Error>
+            Ok(err) => unsafe { core::ptr::read(&*err) },
+            Err(err) => std::panic::resume_unwind(err),
+        }
+    })?
+}
@@ -1,3 +1,4 @@
+use alloc::{string::String, vec, vec::Vec};
 use core::{
     fmt,
     str::{self, FromStr},
@@ -149,3 +150,379 @@ impl FromStr for Version {
         }
     }
 }
+
+// VERSION REQUIREMENTS
+// ================================================================================================
+
+/// Represents a requirement on the [Version] of a dependency, following the conventions of Cargo.
+///
+/// A requirement is a comma-separated list of comparators, all of which a version must satisfy,
+/// e.g. `>=1.2.0, <1.5.0`. The minor and patch components of a comparator may be omitted, e.g.
+/// `~1.2` or `<2`. The supported comparators are:
+///
+/// * `^1.2.3`, or just `1.2.3`, which allows any compatible version, i.e. one greater than or equal
+///   to `1.2.3` with the same left-most non-zero component
+/// * `~1.2.3`, which allows only patch updates, i.e. `>=1.2.3, <1.3.0`
+/// * `=1.2.3`, `>1.2.3`, `>=1.2.3`, `<1.2.3` and `<=1.2.3`, which compare versions directly
+/// * `*`, which allows any version
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct VersionReq {
+    comparators: Vec<Comparator>,
+}
+
+/// A single comparator of a [VersionReq], e.g. `>=1.2`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+struct Comparator {
+    op: ComparatorOp,
+    major: u16,
+    minor: Option<u16>,
+    patch: Option<u16>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[repr(u8)]
+enum ComparatorOp {
+    Caret,
+    Tilde,
+    Exact,
+    Greater,
+    GreaterEq,
+    Less,
+    LessEq,
+}
+
+impl VersionReq {
+    /// Returns the requirement matching any version, i.e. `*`.
+    pub const fn any() -> Self {
+        Self { comparators: Vec::new() }
+    }
+
+    /// Returns the requirement matching only `version`, i.e. `=MAJOR.MINOR.PATCH`.
+    pub fn exact(version: Version) -> Self {
+        let comparator = Comparator {
+            op: ComparatorOp::Exact,
+            major: version.major,
+            minor: Some(version.minor),
+            patch: Some(version.patch),
+        };
+        Self { comparators: vec![comparator] }
+    }
+
+    /// Returns true if this requirement matches any version.
+    pub fn is_any(&self) -> bool {
+        self.comparators.is_empty()
+    }
+
+    /// Returns true if `version` satisfies all the comparators of this requirement.
+    pub fn matches(&self, version: &Version) -> bool {
+        self.comparators.iter().all(|comparator| comparator.matches(version))
+    }
+}
+
+impl Comparator {
+    fn matches(&self, version: &Version) -> bool {
+        let (lower, upper) = self.bounds();
+        lower.is_none_or(|lower| version >= &lower) && upper.is_none_or(|upper| version < &upper)
+    }
+
+    /// Returns the inclusive lower bound and exclusive upper bound of the range of versions
+    /// matched by this comparator, where `None` means that the range is unbounded.
+    fn bounds(&self) -> (Option<Version>, Option<Version>) {
+        let lowest = Version {
+            major: self.major,
+            minor: self.minor.unwrap_or(0),
+            patch: self.patch.unwrap_or(0),
+        };
+        // The version following all the versions matching the components which are specified
+        let next = match (self.minor, self.patch) {
+            (None, _) => next_major(lowest),
+            (Some(_), None) => next_minor(lowest),
+            (Some(_), Some(_)) => next_patch(lowest),
+        };
+        match self.op {
+            ComparatorOp::Exact => (Some(lowest), next),
+            ComparatorOp::Greater => match next {
+                Some(next) => (Some(next), None),
+                // there is no greater version, so the range is empty
+                None => (Some(lowest), Some(lowest)),
+            },
+            ComparatorOp::GreaterEq => (Some(lowest), None),
+            ComparatorOp::Less => (None, Some(lowest)),
+            ComparatorOp::LessEq => (None, next),
+            ComparatorOp::Tilde => match self.minor {
+                None => (Some(lowest), next_major(lowest)),
+                Some(_) => (Some(lowest), next_minor(lowest)),
+            },
+            ComparatorOp::Caret => {
+                let upper = match (self.major, self.minor, self.patch) {
+                    (0, None, _) => next_major(lowest),
+                    (0, Some(0), None) => next_minor(lowest),
+                    (0, Some(0), Some(_)) => next_patch(lowest),
+                    (0, Some(_), _) => next_minor(lowest),
+                    _ => next_major(lowest),
+                };
+                (Some(lowest), upper)
+            },
+        }
+    }
+}
+
+/// Returns the first version of the next major release after `version`, if any.
+fn next_major(version: Version) -> Option<Version> {
+    version.major.checked_add(1).map(|major| Version { major, minor: 0, patch: 0 })
+}
+
+/// Returns the first version of the next minor release after `version`, if any.
+fn next_minor(version: Version) -> Option<Version> {
+    match version.minor.checked_add(1) {
+        Some(minor) => Some(Version { minor, patch: 0, ..version }),
+        None => next_major(version),
+    }
+}
+
+/// Returns the version of the next patch release after `version`, if any.
+fn next_patch(version: Version) -> Option<Version> {
+    match version.patch.checked_add(1) {
+        Some(patch) => Some(Version { patch, ..version }),
+        None => next_minor(version),
+    }
+}
+
+impl ComparatorOp {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Caret => "^",
+            Self::Tilde => "~",
+            Self::Exact => "=",
+            Self::Greater => ">",
+            Self::GreaterEq => ">=",
+            Self::Less => "<",
+            Self::LessEq => "<=",
+        }
+    }
+}
+
+impl fmt::Display for Comparator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.op.as_str(), self.major)?;
+        if let Some(minor) = self.minor {
+            write!(f, ".{minor}")?;
+        }
+        if let Some(patch) = self.patch {
+            write!(f, ".{patch}")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for VersionReq {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.comparators.is_empty() {
+            return f.write_str("*");
+        }
+        for (i, comparator) in self.comparators.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "{comparator}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Serializable for VersionReq {
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        target.write_usize(self.comparators.len());
+        for comparator in self.comparators.iter() {
+            target.write_u8(comparator.op as u8);
+            target.write_u16(comparator.major);
+            comparator.minor.write_into(target);
+            comparator.patch.write_into(target);
+        }
+    }
+}
+
+impl Deserializable for VersionReq {
+    fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
+        let len = source.read_usize()?;
+        let mut comparators = Vec::with_capacity(len);
+        for _ in 0..len {
+            let op = match source.read_u8()? {
+                0 => ComparatorOp::Caret,
+                1 => ComparatorOp::Tilde,
+                2 => ComparatorOp::Exact,
+                3 => ComparatorOp::Greater,
+                4 => ComparatorOp::GreaterEq,
+                5 => ComparatorOp::Less,
+                6 => ComparatorOp::LessEq,
+                n => {
+                    return Err(DeserializationError::InvalidValue(format!(
+                        "invalid version comparator tag: {n}"
+                    )));
+                },
+            };
+            let major = source.read_u16()?;
+            let minor = Option::<u16>::read_from(source)?;
+            let patch = Option::<u16>::read_from(source)?;
+            if minor.is_none() && patch.is_some() {
+                return Err(DeserializationError::InvalidValue(
+                    "invalid version comparator: patch component without minor component".into(),
+                ));
+            }
+            comparators.push(Comparator { op, major, minor, patch });
+        }
+        Ok(Self { comparators })
+    }
+}
+
+/// Represents errors that occur when parsing a [VersionReq]
+#[derive(Debug, thiserror::Error, Diagnostic)]
+pub enum VersionReqError {
+    #[error("invalid version requirement: cannot be empty")]
+    #[diagnostic(help("use `*` to allow any version"))]
+    Empty,
+    #[error("invalid version requirement: '*' cannot be combined with other comparators")]
+    #[diagnostic()]
+    UnexpectedWildcard,
+    #[error(
+        "invalid version requirement: could not parse version component '{component}': {error}"
+    )]
+    #[diagnostic()]
+    Component {
+        component: String,
+        error: core::num::ParseIntError,
+    },
+    #[error(
+        "invalid version requirement: too many version components, expected at most \
+        MAJOR.MINOR.PATCH"
+    )]
+    #[diagnostic(help("pre-release versions and build metadata are not supported"))]
+    TooManyComponents,
+}
+
+impl FromStr for VersionReq {
+    type Err = VersionReqError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim();
+        if value == "*" {
+            return Ok(Self::any());
+        }
+
+        let mut comparators = Vec::new();
+        for comparator in value.split(',') {
+            comparators.push(comparator.trim().parse::<Comparator>()?);
+        }
+        Ok(Self { comparators })
+    }
+}
+
+impl FromStr for Comparator {
+    type Err = VersionReqError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let ops = [
+            (">=", ComparatorOp::GreaterEq),
+            ("<=", ComparatorOp::LessEq),
+            (">", ComparatorOp::Greater),
+            ("<", ComparatorOp::Less),
+            ("=", ComparatorOp::Exact),
+            ("~", ComparatorOp::Tilde),
+            ("^", ComparatorOp::Caret),
+        ];
+        let (op, version) = ops
+            .iter()
+            .find_map(|(prefix, op)| {
+                value.strip_prefix(prefix).map(|rest| (*op, rest.trim_start()))
+            })
+            .unwrap_or((ComparatorOp::Caret, value));
+        if version.is_empty() {
+            return Err(VersionReqError::Empty);
+        }
+        if version == "*" {
+            return Err(VersionReqError::UnexpectedWildcard);
+        }
+
+        let mut components = version.split('.').map(|component| {
+            component
+                .parse::<u16>()
+                .map_err(|error| VersionReqError::Component { component: component.into(), error })
+        });
+        let major = components.next().expect("split always returns at least one component")?;
+        let minor = components.next().transpose()?;
+        let patch = components.next().transpose()?;
+        if components.next().is_some() {
+            return Err(VersionReqError::TooManyComponents);
+        }
+        Ok(Self { op, major, minor, patch })
+    }
+}
+
+// TESTS
+// ================================================================================================
+
+/// Tests
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+
+    use miden_core::{
+        assert_matches,
+        utils::{Deserializable, Serializable},
+    };
+
+    use super::{Version, VersionReq, VersionReqError};
+
+    fn matches(req: &str, version: &str) -> bool {
+        let req = req.parse::<VersionReq>().unwrap();
+        req.matches(&version.parse::<Version>().unwrap())
+    }
+
+    #[test]
+    fn caret_requirements() {
+        assert!(matches("1.2.3", "1.2.3"));
+        assert!(matches("^1.2.3", "1.9.0"));
+        assert!(!matches("^1.2.3", "1.2.2"));
+        assert!(!matches("^1.2.3", "2.0.0"));
+        assert!(matches("^0.2.3", "0.2.9"));
+        assert!(!matches("^0.2.3", "0.3.0"));
+        assert!(matches("^0.0.3", "0.0.3"));
+        assert!(!matches("^0.0.3", "0.0.4"));
+        assert!(matches("^1", "1.9.9"));
+        assert!(!matches("^0.0", "0.1.0"));
+    }
+
+    #[test]
+    fn comparator_requirements() {
+        assert!(matches("~1.2.3", "1.2.9"));
+        assert!(!matches("~1.2.3", "1.3.0"));
+        assert!(matches("~1", "1.5.0"));
+        assert!(matches("=1.2", "1.2.7"));
+        assert!(!matches("=1.2.3", "1.2.4"));
+        assert!(matches(">1.2", "1.3.0"));
+        assert!(!matches(">1.2", "1.2.9"));
+        assert!(matches("<=1.2", "1.2.9"));
+        assert!(matches(">=1.2.0, <1.5.0", "1.4.9"));
+        assert!(!matches(">=1.2.0, <1.5.0", "1.5.0"));
+        assert!(matches("*", "65535.65535.65535"));
+        assert!(!matches(">65535.65535.65535", "65535.65535.65535"));
+    }
+
+    #[test]
+    fn invalid_requirements() {
+        assert_matches!("".parse::<VersionReq>(), Err(VersionReqError::Empty));
+        assert_matches!(">=".parse::<VersionReq>(), Err(VersionReqError::Empty));
+        assert_matches!("1, *".parse::<VersionReq>(), Err(VersionReqError::UnexpectedWildcard));
+        assert_matches!("1.2.3.4".parse::<VersionReq>(), Err(VersionReqError::TooManyComponents));
+        assert_matches!("1.x".parse::<VersionReq>(), Err(VersionReqError::Component { .. }));
+    }
+
+    #[test]
+    fn requirement_roundtrip() {
+        let req = ">=1.2, <2".parse::<VersionReq>().unwrap();
+        assert_eq!(req.to_string(), ">=1.2, <2");
+        assert_eq!(VersionReq::read_from_bytes(&req.to_bytes()).unwrap(), req);
+        assert_eq!("1.2.3".parse::<VersionReq>().unwrap().to_string(), "^1.2.3");
+        assert_eq!(VersionReq::any().to_string(), "*");
+    }
+}
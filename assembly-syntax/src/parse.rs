@@ -7,12 +7,14 @@ use alloc::{
 };
 
 use crate::{
+    LintConfig,
     ast::{Module, ModuleKind},
     diagnostics::{
         IntoDiagnostic, NamedSource, Report, SourceCode, SourceContent, SourceFile, SourceLanguage,
         SourceManager, Uri, WrapErr,
     },
     library::{LibraryNamespace, LibraryPath},
+    parser::CfgFlags,
     report,
 };
 
@@ -28,6 +30,10 @@ pub struct ParseOptions {
     pub kind: ModuleKind,
     /// When true, promote warning diagnostics to errors
     pub warnings_as_errors: bool,
+    /// The levels of the lints run during semantic analysis
+    pub lints: LintConfig,
+    /// The flags against which `@cfg` attributes are evaluated
+    pub cfg: CfgFlags,
     /// The name to give the parsed [Module]
     ///
     /// This option overrides `namespace`.
@@ -43,6 +49,8 @@ impl Default for ParseOptions {
         Self {
             kind: ModuleKind::Executable,
             warnings_as_errors: false,
+            lints: LintConfig::default(),
+            cfg: CfgFlags::default(),
             path: None,
         }
     }
@@ -193,6 +201,8 @@ impl Parse for Arc<SourceFile> {
         };
         let mut parser = Module::parser(options.kind);
         parser.set_warnings_as_errors(options.warnings_as_errors);
+        parser.set_lints(options.lints);
+        parser.set_cfg_flags(options.cfg);
         parser.parse(path, source_file)
     }
 }
@@ -245,6 +255,8 @@ impl Parse for Box<str> {
         let name = Uri::from(path.path().into_owned().into_boxed_str());
         let mut parser = Module::parser(options.kind);
         parser.set_warnings_as_errors(options.warnings_as_errors);
+        parser.set_lints(options.lints);
+        parser.set_cfg_flags(options.cfg);
         let content = SourceContent::new(SourceLanguage::Masm, name.clone(), self);
         let source_file = source_manager.load_from_raw_parts(name, content);
         parser.parse(path, source_file)
@@ -344,6 +356,8 @@ where
         let source_file = source_manager.load_from_raw_parts(name, content);
         let mut parser = Module::parser(options.kind);
         parser.set_warnings_as_errors(options.warnings_as_errors);
+        parser.set_lints(options.lints);
+        parser.set_cfg_flags(options.cfg);
         parser.parse(path, source_file)
     }
 }
@@ -403,6 +417,9 @@ impl Parse for &std::path::Path {
             .into_diagnostic()
             .wrap_err("source manager is unable to load file")?;
         let mut parser = Module::parser(options.kind);
+        parser.set_warnings_as_errors(options.warnings_as_errors);
+        parser.set_lints(options.lints);
+        parser.set_cfg_flags(options.cfg);
         parser.parse(path, source_file)
     }
 }
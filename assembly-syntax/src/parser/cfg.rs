@@ -0,0 +1,137 @@
+use alloc::{collections::BTreeSet, string::String, sync::Arc};
+
+use miden_core::utils::{
+    ByteReader, ByteWriter, Deserializable, DeserializationError, Serializable,
+};
+
+use super::ParsingError;
+use crate::{
+    Spanned,
+    ast::{Attribute, MetaExpr},
+};
+
+// CFG FLAGS
+// ================================================================================================
+
+/// The set of flags against which `@cfg` attributes are evaluated.
+///
+/// Items annotated with `@cfg(feature = "name")` are only assembled when the flag `name` is set,
+/// and items annotated with `@cfg(not_feature = "name")` only when it is not. When both keys are
+/// given, or an item has several `@cfg` attributes, all of the conditions must hold.
+///
+/// Items whose conditions do not hold are removed while parsing, before semantic analysis, so
+/// that, for example, a module may define two variants of a procedure with the same name, as long
+/// as at most one of them is enabled.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct CfgFlags {
+    flags: BTreeSet<Arc<str>>,
+    /// Whether all conditions hold regardless of the flags, see [Self::keep_all]
+    keep_all: bool,
+}
+
+impl CfgFlags {
+    /// Returns flags under which the conditions of every `@cfg` attribute hold, so that no items
+    /// are removed while parsing.
+    ///
+    /// This is used by the formatter, which must also verify code that is disabled by default.
+    pub(crate) fn keep_all() -> Self {
+        Self { flags: BTreeSet::new(), keep_all: true }
+    }
+
+    /// Sets `flag`.
+    pub fn set(&mut self, flag: impl Into<Arc<str>>) {
+        self.flags.insert(flag.into());
+    }
+
+    /// Returns these flags with `flag` set.
+    pub fn with(mut self, flag: impl Into<Arc<str>>) -> Self {
+        self.set(flag);
+        self
+    }
+
+    /// Returns true if `flag` is set.
+    pub fn is_set(&self, flag: &str) -> bool {
+        self.flags.contains(flag)
+    }
+
+    /// Returns true if no flags are set.
+    pub fn is_empty(&self) -> bool {
+        self.flags.is_empty()
+    }
+
+    /// Returns the flags which are set, in lexicographical order.
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.flags.iter().map(|flag| flag.as_ref())
+    }
+
+    /// Returns true if `attribute` is a `@cfg` attribute.
+    pub fn is_cfg_attribute(attribute: &Attribute) -> bool {
+        attribute.name() == "cfg"
+    }
+
+    /// Evaluates the conditions of `attribute`, a `@cfg` attribute, against these flags.
+    ///
+    /// Returns an error if the attribute is malformed.
+    pub fn eval(&self, attribute: &Attribute) -> Result<bool, ParsingError> {
+        let Attribute::KeyValue(kv) = attribute else {
+            return Err(ParsingError::InvalidCfgAttribute { span: attribute.span() });
+        };
+        let mut enabled = true;
+        for (key, value) in kv.iter() {
+            let MetaExpr::String(flag) = value else {
+                return Err(ParsingError::InvalidCfgAttribute { span: value.span() });
+            };
+            enabled &= match key.as_str() {
+                "feature" => self.is_set(flag.as_str()),
+                "not_feature" => !self.is_set(flag.as_str()),
+                _ => return Err(ParsingError::InvalidCfgAttribute { span: key.span() }),
+            };
+        }
+        Ok(enabled || self.keep_all)
+    }
+
+    /// Evaluates `annotations`, the attributes of an item which only supports `@cfg` attributes.
+    ///
+    /// Returns true if the item is enabled, or an error if any of the attributes is not a valid
+    /// `@cfg` attribute.
+    pub(crate) fn eval_annotations(&self, annotations: &[Attribute]) -> Result<bool, ParsingError> {
+        let mut enabled = true;
+        for attribute in annotations {
+            if !Self::is_cfg_attribute(attribute) {
+                return Err(ParsingError::UnsupportedAttribute { span: attribute.span() });
+            }
+            enabled &= self.eval(attribute)?;
+        }
+        Ok(enabled)
+    }
+}
+
+impl<S: Into<Arc<str>>> FromIterator<S> for CfgFlags {
+    fn from_iter<I: IntoIterator<Item = S>>(iter: I) -> Self {
+        Self {
+            flags: iter.into_iter().map(Into::into).collect(),
+            keep_all: false,
+        }
+    }
+}
+
+impl Serializable for CfgFlags {
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        target.write_usize(self.flags.len());
+        for flag in self.flags.iter() {
+            flag.as_ref().write_into(target);
+        }
+    }
+}
+
+impl Deserializable for CfgFlags {
+    fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
+        let len = source.read_usize()?;
+        let mut flags = BTreeSet::new();
+        for _ in 0..len {
+            let flag: String = source.read()?;
+            flags.insert(Arc::from(flag.into_boxed_str()));
+        }
+        Ok(Self { flags, keep_all: false })
+    }
+}
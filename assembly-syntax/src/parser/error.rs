@@ -5,7 +5,7 @@ use alloc::{
 use core::{fmt, ops::Range};
 
 use super::{ParseError, SourceSpan};
-use crate::{SourceId, diagnostics::Diagnostic};
+use crate::{SourceId, diagnostics::Diagnostic, sema::ConstEvalError};
 
 // LITERAL ERROR KIND
 // ================================================================================================
@@ -168,6 +168,22 @@ pub enum ParsingError {
         #[label]
         span: SourceSpan,
     },
+    #[error("invalid constant expression: {kind}")]
+    #[diagnostic()]
+    InvalidConstantExpression {
+        #[label]
+        span: SourceSpan,
+        kind: ConstEvalError,
+    },
+    #[error("unknown function in constant expression")]
+    #[diagnostic(help(
+        "the functions available in constant expressions are `hash`, `event`, `sizeof` and \
+         `offsetof`"
+    ))]
+    UnknownConstantFunction {
+        #[label]
+        span: SourceSpan,
+    },
     #[error("unexpected string in an arithmetic expression")]
     #[diagnostic()]
     StringInArithmeticExpression {
@@ -281,6 +297,30 @@ pub enum ParsingError {
         #[label]
         span: SourceSpan,
     },
+    #[error("invalid struct field type")]
+    #[diagnostic(help(
+        "expected one of `felt`, `u32`, `u64`, `word`, the name of a struct, or an array such as `[word; 4]`"
+    ))]
+    InvalidFieldType {
+        #[label]
+        span: SourceSpan,
+    },
+    #[error("invalid @cfg attribute")]
+    #[diagnostic(help(
+        "expected `@cfg(feature = \"name\")` or `@cfg(not_feature = \"name\")`, or both"
+    ))]
+    InvalidCfgAttribute {
+        #[label]
+        span: SourceSpan,
+    },
+    #[error("unsupported attribute")]
+    #[diagnostic(help(
+        "only @cfg attributes can be applied to constants, instructions and blocks"
+    ))]
+    UnsupportedAttribute {
+        #[label]
+        span: SourceSpan,
+    },
 }
 
 impl ParsingError {
@@ -314,6 +354,10 @@ impl PartialEq for ParsingError {
                 Self::ImmediateOutOfRange { range: r, .. },
             ) => l == r,
             (Self::PushOverflow { count: l, .. }, Self::PushOverflow { count: r, .. }) => l == r,
+            (
+                Self::InvalidConstantExpression { kind: l, .. },
+                Self::InvalidConstantExpression { kind: r, .. },
+            ) => l == r,
             (
                 Self::UnrecognizedToken { token: ltok, expected: lexpect, .. },
                 Self::UnrecognizedToken { token: rtok, expected: rexpect, .. },
@@ -331,6 +375,15 @@ impl PartialEq for ParsingError {
 }
 
 impl ParsingError {
+    /// Converts an error raised while folding the constant expression at `span` into a
+    /// [ParsingError].
+    pub fn from_const_eval_error(error: ConstEvalError, span: SourceSpan) -> Self {
+        match error {
+            ConstEvalError::DivisionByZero => Self::DivisionByZero { span },
+            kind => Self::InvalidConstantExpression { span, kind },
+        }
+    }
+
     pub fn from_utf8_error(source_id: SourceId, err: core::str::Utf8Error) -> Self {
         let start = u32::try_from(err.valid_up_to()).ok().unwrap_or(u32::MAX);
         match err.error_len() {
@@ -399,7 +452,7 @@ fn simplify_expected_tokens(expected: Vec<String>) -> Vec<String> {
                 other => other[1..].strip_suffix('"').and_then(Token::parse),
             };
             match tok {
-                Some(Token::If | Token::While | Token::Repeat) => {
+                Some(Token::If | Token::While | Token::Repeat | Token::For | Token::Match) => {
                     if !has_ctrl {
                         has_ctrl = true;
                         Some("control flow opcode (e.g. \"if.true\")".to_string())
@@ -275,14 +275,36 @@ impl<'input> Lexer<'input> {
 
         match self.read() {
             '@' => pop!(self, Token::At),
-            '!' => pop!(self, Token::Bang),
+            '!' => match self.peek() {
+                '=' => pop2!(self, Token::BangEqual),
+                _ => pop!(self, Token::Bang),
+            },
             ':' => match self.peek() {
                 ':' => pop2!(self, Token::ColonColon),
-                _ => Err(ParsingError::InvalidToken { span: self.span() }),
+                _ => pop!(self, Token::Colon),
             },
+            ';' => pop!(self, Token::Semicolon),
             '.' => pop!(self, Token::Dot),
             ',' => pop!(self, Token::Comma),
-            '=' => pop!(self, Token::Equal),
+            '=' => match self.peek() {
+                '=' => pop2!(self, Token::EqualEqual),
+                _ => pop!(self, Token::Equal),
+            },
+            '<' => match self.peek() {
+                '<' => pop2!(self, Token::Lshift),
+                '=' => pop2!(self, Token::LangleEqual),
+                _ => pop!(self, Token::Langle),
+            },
+            '>' => match self.peek() {
+                '>' => pop2!(self, Token::Rshift),
+                '=' => pop2!(self, Token::RangleEqual),
+                _ => pop!(self, Token::Rangle),
+            },
+            '&' => pop!(self, Token::Ampersand),
+            '|' => pop!(self, Token::Pipe),
+            '^' => pop!(self, Token::Caret),
+            '%' => pop!(self, Token::Percent),
+            '?' => pop!(self, Token::Question),
             '(' => pop!(self, Token::Lparen),
             '[' => pop!(self, Token::Lbracket),
             ')' => pop!(self, Token::Rparen),
@@ -296,7 +318,10 @@ impl<'input> Lexer<'input> {
                 '/' => pop2!(self, Token::SlashSlash),
                 _ => pop!(self, Token::Slash),
             },
-            '*' => pop!(self, Token::Star),
+            '*' => match self.peek() {
+                '*' => pop2!(self, Token::StarStar),
+                _ => pop!(self, Token::Star),
+            },
             '$' => self.lex_special_identifier(),
             '"' => self.lex_quoted_identifier_or_string(),
             '0' => match self.peek() {
@@ -14,6 +14,7 @@ lalrpop_util::lalrpop_mod!(
     "/parser/grammar.rs"
 );
 
+mod cfg;
 mod error;
 mod lexer;
 mod scanner;
@@ -22,6 +23,7 @@ mod token;
 use alloc::{boxed::Box, collections::BTreeSet, string::ToString, sync::Arc, vec::Vec};
 
 pub use self::{
+    cfg::CfgFlags,
     error::{BinErrorKind, HexErrorKind, LiteralErrorKind, ParsingError},
     lexer::Lexer,
     scanner::Scanner,
@@ -69,6 +71,10 @@ pub struct ModuleParser {
     interned: BTreeSet<Arc<str>>,
     /// When true, all warning diagnostics are promoted to error severity
     warnings_as_errors: bool,
+    /// The levels of the lints run during semantic analysis
+    lints: sema::LintConfig,
+    /// The flags against which `@cfg` attributes are evaluated
+    cfg: CfgFlags,
 }
 
 impl ModuleParser {
@@ -78,6 +84,8 @@ impl ModuleParser {
             kind,
             interned: Default::default(),
             warnings_as_errors: false,
+            lints: Default::default(),
+            cfg: Default::default(),
         }
     }
 
@@ -86,15 +94,61 @@ impl ModuleParser {
         self.warnings_as_errors = yes;
     }
 
+    /// Configure the levels of the lints run on parsed modules.
+    pub fn set_lints(&mut self, lints: sema::LintConfig) {
+        self.lints = lints;
+    }
+
+    /// Configure the flags against which `@cfg` attributes are evaluated.
+    pub fn set_cfg_flags(&mut self, cfg: CfgFlags) {
+        self.cfg = cfg;
+    }
+
     /// Parse a [ast::Module] from `source`, and give it the provided `path`.
+    ///
+    /// Any warnings raised during semantic analysis are emitted to stderr.
     pub fn parse(
         &mut self,
         path: LibraryPath,
         source: Arc<SourceFile>,
     ) -> Result<Box<ast::Module>, Report> {
-        let forms = parse_forms_internal(source.clone(), &mut self.interned)
-            .map_err(|err| Report::new(err).with_source_code(source.clone()))?;
-        sema::analyze(source, self.kind, path, forms, self.warnings_as_errors).map_err(Report::new)
+        let (module, warnings) = self.parse_with_warnings(path, source)?;
+        if let Some(warnings) = warnings {
+            #[cfg(feature = "std")]
+            std::eprintln!("{warnings}");
+            #[cfg(not(feature = "std"))]
+            drop(warnings);
+        }
+        Ok(module)
+    }
+
+    /// Parse a [ast::Module] from `source`, and give it the provided `path`, like [Self::parse].
+    ///
+    /// Rather than emitting them, any warnings raised during semantic analysis are returned
+    /// alongside the module, as a single [Report] whose related diagnostics are the warnings.
+    pub fn parse_with_warnings(
+        &mut self,
+        path: LibraryPath,
+        source: Arc<SourceFile>,
+    ) -> Result<(Box<ast::Module>, Option<Report>), Report> {
+        let (forms, syntax_errors) =
+            parse_forms_with_recovery(source.clone(), &mut self.interned, &self.cfg).map_err(
+                |errors| {
+                    let errors = errors.into_iter().map(sema::SemanticAnalysisError::Syntax);
+                    syntax_error_report(source.clone(), errors.collect())
+                },
+            )?;
+        let (module, warnings) = sema::analyze(
+            source.clone(),
+            self.kind,
+            path,
+            forms,
+            syntax_errors,
+            self.warnings_as_errors,
+            self.lints.clone(),
+        )
+        .map_err(|err| syntax_error_report(source, err.errors))?;
+        Ok((module, warnings.map(Report::new)))
     }
 
     /// Parse a [ast::Module], `name`, from `path`.
@@ -147,23 +201,132 @@ impl ModuleParser {
 #[cfg(any(test, feature = "testing"))]
 pub fn parse_forms(source: Arc<SourceFile>) -> Result<Vec<ast::Form>, ParsingError> {
     let mut interned = BTreeSet::default();
-    parse_forms_internal(source, &mut interned)
+    parse_forms_internal(source, &mut interned, &CfgFlags::default())
 }
 
 /// Parse `source` as a set of [ast::Form]s
 ///
 /// Aside from catching syntax errors, this does little validation of the resulting forms, that is
-/// handled by semantic analysis, which the caller is expected to perform next.
-fn parse_forms_internal(
+/// handled by semantic analysis, which the caller is expected to perform next. Items whose `@cfg`
+/// attributes do not hold for `cfg` are removed from the resulting forms.
+///
+/// Returns the first syntax error found, if any, see [parse_forms_with_recovery] to obtain all of
+/// them.
+pub(crate) fn parse_forms_internal(
     source: Arc<SourceFile>,
     interned: &mut BTreeSet<Arc<str>>,
+    cfg: &CfgFlags,
 ) -> Result<Vec<ast::Form>, ParsingError> {
+    match parse_forms_with_recovery(source, interned, cfg) {
+        Ok((forms, errors)) => match errors.into_iter().next() {
+            None => Ok(forms),
+            Some(err) => Err(err),
+        },
+        Err(errors) => Err(errors.into_iter().next().expect("expected at least one error")),
+    }
+}
+
+/// Parse `source` as a set of [ast::Form]s, like [parse_forms_internal], but recovering from
+/// syntax errors where possible.
+///
+/// On a syntax error, the parser skips ahead to the next operation of the enclosing block, or to
+/// the next top-level form, and continues from there. The operation or form in which the error
+/// occurred is omitted from the resulting forms.
+///
+/// Returns the forms, along with the syntax errors that were recovered from, in the order in which
+/// they occur in `source`. If the parser cannot recover from an error, e.g. an invalid literal,
+/// all of the errors found up to and including that one are returned instead.
+///
+/// Only the first syntax error in each top-level form is returned, as the errors which follow it
+/// in the same form are usually a consequence of the parser skipping ahead to the wrong place,
+/// e.g. an `end` left over from a block whose opening was skipped.
+pub(crate) fn parse_forms_with_recovery(
+    source: Arc<SourceFile>,
+    interned: &mut BTreeSet<Arc<str>>,
+    cfg: &CfgFlags,
+) -> Result<(Vec<ast::Form>, Vec<ParsingError>), Vec<ParsingError>> {
     let source_id = source.id();
     let scanner = Scanner::new(source.as_str());
-    let lexer = Lexer::new(source_id, scanner);
-    grammar::FormsParser::new()
-        .parse(&source, interned, core::marker::PhantomData, lexer)
-        .map_err(|err| ParsingError::from_parse_error(source_id, err))
+    // The keywords which start top-level forms never occur within them, so their positions are
+    // used to tell which form an error occurs in, regardless of how far parsing got
+    let mut form_starts = Vec::new();
+    let lexer = Lexer::new(source_id, scanner).inspect(|token| {
+        if let Ok((
+            start,
+            Token::Proc
+            | Token::Internal
+            | Token::Export
+            | Token::Begin
+            | Token::Const
+            | Token::Use
+            | Token::Struct
+            | Token::AdvMap
+            | Token::Data,
+            _,
+        )) = token
+        {
+            form_starts.push(*start);
+        }
+    });
+    let mut errors = Vec::new();
+    let result = grammar::FormsParser::new().parse(
+        &source,
+        interned,
+        cfg,
+        &mut errors,
+        core::marker::PhantomData,
+        lexer,
+    );
+    let result = match result {
+        Ok(forms) => Ok(forms),
+        Err(err) => {
+            errors.push(ParsingError::from_parse_error(source_id, err));
+            Err(())
+        },
+    };
+
+    let mut last_form = None;
+    errors.retain(|error| {
+        let Some(offset) = syntax_error_offset(error) else {
+            return true;
+        };
+        // An unexpected keyword which starts a form is part of the form preceding it
+        let form = form_starts.partition_point(|&start| start < offset);
+        last_form.replace(form) != Some(form)
+    });
+    match result {
+        Ok(forms) => Ok((forms, errors)),
+        Err(()) => Err(errors),
+    }
+}
+
+/// Returns the offset in the source at which a syntax error detected by the parser, rather than
+/// by the actions of the grammar, occurs.
+fn syntax_error_offset(error: &ParsingError) -> Option<u32> {
+    match error {
+        ParsingError::InvalidToken { span }
+        | ParsingError::UnrecognizedToken { span, .. }
+        | ParsingError::ExtraToken { span, .. }
+        | ParsingError::UnrecognizedEof { span, .. } => Some(span.start().to_u32()),
+        _ => None,
+    }
+}
+
+/// Converts the errors found in `source` into a [Report].
+///
+/// A lone syntax error is reported on its own, while anything else is reported as the related
+/// diagnostics of a single [sema::SyntaxError].
+fn syntax_error_report(
+    source: Arc<SourceFile>,
+    mut errors: Vec<sema::SemanticAnalysisError>,
+) -> Report {
+    if let [sema::SemanticAnalysisError::Syntax(_)] = errors.as_slice() {
+        let Some(sema::SemanticAnalysisError::Syntax(err)) = errors.pop() else {
+            unreachable!()
+        };
+        return Report::new(err).with_source_code(source);
+    }
+    Report::new(sema::SyntaxError { source_file: source, errors })
 }
 
 // DIRECTORY PARSER
@@ -175,12 +338,15 @@ fn parse_forms_internal(
 /// Errors may also be returned if traversal discovers issues with the modules, such as
 /// invalid names, etc.
 ///
+/// The `@cfg` attributes of the modules are evaluated against `cfg`.
+///
 /// Returns an iterator over all parsed modules.
 #[cfg(feature = "std")]
 pub fn read_modules_from_dir(
     namespace: crate::LibraryNamespace,
     dir: impl AsRef<std::path::Path>,
     source_manager: &dyn SourceManager,
+    cfg: &CfgFlags,
 ) -> Result<impl Iterator<Item = Box<ast::Module>>, Report> {
     use std::collections::{BTreeMap, btree_map::Entry};
 
@@ -214,6 +380,7 @@ pub fn read_modules_from_dir(
 
         // Parse module at the given path
         let mut parser = ModuleParser::new(ast::ModuleKind::Library);
+        parser.set_cfg_flags(cfg.clone());
         let ast = parser.parse_file(name.clone(), &source_path, source_manager)?;
         match modules.entry(name) {
             Entry::Occupied(ref entry) => {
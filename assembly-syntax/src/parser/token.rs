@@ -218,13 +218,16 @@ pub enum Token<'input> {
     Breakpoint,
     Caller,
     Call,
+    Case,
     Cdrop,
     Cdropw,
     Clk,
     Const,
     Cswap,
     Cswapw,
+    Data,
     Debug,
+    Default,
     Div,
     Drop,
     Dropw,
@@ -249,6 +252,7 @@ pub enum Token<'input> {
     Exp,
     ExpU,
     False,
+    For,
     FriExt2Fold4,
     Gt,
     Gte,
@@ -259,6 +263,7 @@ pub enum Token<'input> {
     Hmerge,
     If,
     ILog2,
+    Internal,
     Inv,
     IsOdd,
     Local,
@@ -269,6 +274,7 @@ pub enum Token<'input> {
     LocStorew,
     Lt,
     Lte,
+    Match,
     Mem,
     MemLoad,
     MemLoadw,
@@ -288,6 +294,7 @@ pub enum Token<'input> {
     Neq,
     Not,
     Nop,
+    Offsetof,
     Or,
     Padw,
     Pow2,
@@ -296,7 +303,9 @@ pub enum Token<'input> {
     Push,
     Repeat,
     Sdepth,
+    Sizeof,
     Stack,
+    Struct,
     Sub,
     Swap,
     Swapw,
@@ -346,22 +355,38 @@ pub enum Token<'input> {
     U32Xor,
     While,
     Xor,
+    Ampersand,
     At,
     Bang,
+    BangEqual,
+    Caret,
+    Colon,
     ColonColon,
     Dot,
     Comma,
     Equal,
+    EqualEqual,
+    Langle,
+    LangleEqual,
     Lparen,
     Lbracket,
+    Lshift,
     Minus,
+    Percent,
+    Pipe,
     Plus,
+    Question,
     SlashSlash,
     Slash,
     Star,
+    StarStar,
+    Rangle,
+    RangleEqual,
     Rparen,
     Rbracket,
+    Rshift,
     Rstab,
+    Semicolon,
     DocComment(DocumentationType),
     HexValue(IntValue),
     BinValue(BinEncodedValue),
@@ -406,13 +431,16 @@ impl fmt::Display for Token<'_> {
             Token::Breakpoint => write!(f, "breakpoint"),
             Token::Caller => write!(f, "caller"),
             Token::Call => write!(f, "call"),
+            Token::Case => write!(f, "case"),
             Token::Cdrop => write!(f, "cdrop"),
             Token::Cdropw => write!(f, "cdropw"),
             Token::Clk => write!(f, "clk"),
             Token::Const => write!(f, "const"),
             Token::Cswap => write!(f, "cswap"),
             Token::Cswapw => write!(f, "cswapw"),
+            Token::Data => write!(f, "data"),
             Token::Debug => write!(f, "debug"),
+            Token::Default => write!(f, "default"),
             Token::Div => write!(f, "div"),
             Token::Drop => write!(f, "drop"),
             Token::Dropw => write!(f, "dropw"),
@@ -437,6 +465,7 @@ impl fmt::Display for Token<'_> {
             Token::ExpU => write!(f, "exp.u"),
             Token::Export => write!(f, "export"),
             Token::False => write!(f, "false"),
+            Token::For => write!(f, "for"),
             Token::FriExt2Fold4 => write!(f, "fri_ext2fold4"),
             Token::Gt => write!(f, "gt"),
             Token::Gte => write!(f, "gte"),
@@ -445,6 +474,7 @@ impl fmt::Display for Token<'_> {
             Token::Hmerge => write!(f, "hmerge"),
             Token::If => write!(f, "if"),
             Token::ILog2 => write!(f, "ilog2"),
+            Token::Internal => write!(f, "internal"),
             Token::Inv => write!(f, "inv"),
             Token::IsOdd => write!(f, "is_odd"),
             Token::Local => write!(f, "local"),
@@ -455,6 +485,7 @@ impl fmt::Display for Token<'_> {
             Token::LocStorew => write!(f, "loc_storew"),
             Token::Lt => write!(f, "lt"),
             Token::Lte => write!(f, "lte"),
+            Token::Match => write!(f, "match"),
             Token::Mem => write!(f, "mem"),
             Token::MemLoad => write!(f, "mem_load"),
             Token::MemLoadw => write!(f, "mem_loadw"),
@@ -474,6 +505,7 @@ impl fmt::Display for Token<'_> {
             Token::Neq => write!(f, "neq"),
             Token::Not => write!(f, "not"),
             Token::Nop => write!(f, "nop"),
+            Token::Offsetof => write!(f, "offsetof"),
             Token::Or => write!(f, "or"),
             Token::Padw => write!(f, "padw"),
             Token::Pow2 => write!(f, "pow2"),
@@ -484,7 +516,9 @@ impl fmt::Display for Token<'_> {
             Token::HornerExt => write!(f, "horner_eval_ext"),
             Token::Repeat => write!(f, "repeat"),
             Token::Sdepth => write!(f, "sdepth"),
+            Token::Sizeof => write!(f, "sizeof"),
             Token::Stack => write!(f, "stack"),
+            Token::Struct => write!(f, "struct"),
             Token::Sub => write!(f, "sub"),
             Token::Swap => write!(f, "swap"),
             Token::Swapw => write!(f, "swapw"),
@@ -534,22 +568,38 @@ impl fmt::Display for Token<'_> {
             Token::U32Xor => write!(f, "u32xor"),
             Token::While => write!(f, "while"),
             Token::Xor => write!(f, "xor"),
+            Token::Ampersand => write!(f, "&"),
             Token::At => write!(f, "@"),
             Token::Bang => write!(f, "!"),
+            Token::BangEqual => write!(f, "!="),
+            Token::Caret => write!(f, "^"),
+            Token::Colon => write!(f, ":"),
             Token::ColonColon => write!(f, "::"),
             Token::Dot => write!(f, "."),
             Token::Comma => write!(f, ","),
             Token::Equal => write!(f, "="),
+            Token::EqualEqual => write!(f, "=="),
+            Token::Langle => write!(f, "<"),
+            Token::LangleEqual => write!(f, "<="),
             Token::Lparen => write!(f, "("),
             Token::Lbracket => write!(f, "["),
+            Token::Lshift => write!(f, "<<"),
             Token::Minus => write!(f, "-"),
+            Token::Percent => write!(f, "%"),
+            Token::Pipe => write!(f, "|"),
             Token::Plus => write!(f, "+"),
+            Token::Question => write!(f, "?"),
             Token::SlashSlash => write!(f, "//"),
             Token::Slash => write!(f, "/"),
             Token::Star => write!(f, "*"),
+            Token::StarStar => write!(f, "**"),
+            Token::Rangle => write!(f, ">"),
+            Token::RangleEqual => write!(f, ">="),
             Token::Rparen => write!(f, ")"),
             Token::Rbracket => write!(f, "]"),
+            Token::Rshift => write!(f, ">>"),
             Token::Rstab => write!(f, "->"),
+            Token::Semicolon => write!(f, ";"),
             Token::DocComment(DocumentationType::Module(_)) => f.write_str("module doc"),
             Token::DocComment(DocumentationType::Form(_)) => f.write_str("doc comment"),
             Token::HexValue(_) => f.write_str("hex-encoded value"),
@@ -750,13 +800,16 @@ impl<'input> Token<'input> {
         ("breakpoint", Token::Breakpoint),
         ("caller", Token::Caller),
         ("call", Token::Call),
+        ("case", Token::Case),
         ("cdrop", Token::Cdrop),
         ("cdropw", Token::Cdropw),
         ("clk", Token::Clk),
         ("const", Token::Const),
         ("cswap", Token::Cswap),
         ("cswapw", Token::Cswapw),
+        ("data", Token::Data),
         ("debug", Token::Debug),
+        ("default", Token::Default),
         ("div", Token::Div),
         ("drop", Token::Drop),
         ("dropw", Token::Dropw),
@@ -781,6 +834,7 @@ impl<'input> Token<'input> {
         ("exp.u", Token::ExpU),
         ("export", Token::Export),
         ("false", Token::False),
+        ("for", Token::For),
         ("fri_ext2fold4", Token::FriExt2Fold4),
         ("gt", Token::Gt),
         ("gte", Token::Gte),
@@ -789,6 +843,7 @@ impl<'input> Token<'input> {
         ("hmerge", Token::Hmerge),
         ("if", Token::If),
         ("ilog2", Token::ILog2),
+        ("internal", Token::Internal),
         ("inv", Token::Inv),
         ("is_odd", Token::IsOdd),
         ("local", Token::Local),
@@ -799,6 +854,7 @@ impl<'input> Token<'input> {
         ("loc_storew", Token::LocStorew),
         ("lt", Token::Lt),
         ("lte", Token::Lte),
+        ("match", Token::Match),
         ("mem", Token::Mem),
         ("mem_load", Token::MemLoad),
         ("mem_loadw", Token::MemLoadw),
@@ -818,6 +874,7 @@ impl<'input> Token<'input> {
         ("neq", Token::Neq),
         ("not", Token::Not),
         ("nop", Token::Nop),
+        ("offsetof", Token::Offsetof),
         ("or", Token::Or),
         ("padw", Token::Padw),
         ("pow2", Token::Pow2),
@@ -828,7 +885,9 @@ impl<'input> Token<'input> {
         ("horner_eval_ext", Token::HornerExt),
         ("repeat", Token::Repeat),
         ("sdepth", Token::Sdepth),
+        ("sizeof", Token::Sizeof),
         ("stack", Token::Stack),
+        ("struct", Token::Struct),
         ("sub", Token::Sub),
         ("swap", Token::Swap),
         ("swapw", Token::Swapw),
@@ -880,6 +939,17 @@ impl<'input> Token<'input> {
         ("xor", Token::Xor),
     ];
 
+    /// Returns an iterator over the keywords which name instructions, e.g. `add` or `u32split`.
+    ///
+    /// Note that some instructions are spelled using several keywords, e.g. `adv.push_mapval`,
+    /// in which case each of those keywords is returned separately.
+    pub fn instruction_keywords() -> impl Iterator<Item = &'static str> {
+        Self::KEYWORDS
+            .iter()
+            .filter(|(_, token)| token.is_instruction())
+            .map(|(keyword, _)| *keyword)
+    }
+
     /// Constructs a DFA capable of recognizing Miden Assembly keywords.
     ///
     /// Constructing the state machine is expensive, so it should not be done in hot code. Instead,
@@ -947,22 +1017,38 @@ impl<'input> Token<'input> {
             Token::Ident(_) => {
                 // Nope, try again
                 match s {
+                    "&" => Some(Token::Ampersand),
                     "@" => Some(Token::At),
                     "!" => Some(Token::Bang),
+                    "!=" => Some(Token::BangEqual),
+                    "^" => Some(Token::Caret),
+                    ":" => Some(Token::Colon),
                     "::" => Some(Token::ColonColon),
                     "." => Some(Token::Dot),
                     "," => Some(Token::Comma),
                     "=" => Some(Token::Equal),
+                    "==" => Some(Token::EqualEqual),
+                    "<" => Some(Token::Langle),
+                    "<=" => Some(Token::LangleEqual),
                     "(" => Some(Token::Lparen),
                     "[" => Some(Token::Lbracket),
+                    "<<" => Some(Token::Lshift),
                     "-" => Some(Token::Minus),
+                    "%" => Some(Token::Percent),
+                    "|" => Some(Token::Pipe),
                     "+" => Some(Token::Plus),
+                    "?" => Some(Token::Question),
                     "//" => Some(Token::SlashSlash),
                     "/" => Some(Token::Slash),
                     "*" => Some(Token::Star),
+                    "**" => Some(Token::StarStar),
+                    ">" => Some(Token::Rangle),
+                    ">=" => Some(Token::RangleEqual),
                     ")" => Some(Token::Rparen),
                     "]" => Some(Token::Rbracket),
+                    ">>" => Some(Token::Rshift),
                     "->" => Some(Token::Rstab),
+                    ";" => Some(Token::Semicolon),
                     "end of file" => Some(Token::Eof),
                     "module doc" => {
                         Some(Token::DocComment(DocumentationType::Module(String::new())))
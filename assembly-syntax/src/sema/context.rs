@@ -1,34 +1,78 @@
 use alloc::{
     collections::{BTreeMap, BTreeSet},
+    string::String,
     sync::Arc,
     vec::Vec,
 };
+use core::cell::RefCell;
 
-use super::{SemanticAnalysisError, SyntaxError};
+use miden_core::FieldElement;
+
+use super::{Lint, LintConfig, LintLevel, SemanticAnalysisError, SyntaxError, SyntaxWarning};
 use crate::{
-    Felt, SourceFile, Span, Spanned,
+    Felt, SourceFile, SourceSpan, Span, Spanned,
     ast::*,
     diagnostics::{Diagnostic, Severity},
+    parser::WordValue,
 };
 
 /// This maintains the state for semantic analysis of a single [Module].
 pub struct AnalysisContext {
     /// A map of constants to the value of that constant
     constants: BTreeMap<Ident, Constant>,
+    /// The constants which have been referenced so far
+    used_constants: RefCell<BTreeSet<Ident>>,
+    /// The structs defined so far, with their layouts
+    structs: BTreeMap<Ident, Struct>,
     procedures: BTreeSet<ProcedureName>,
     errors: Vec<SemanticAnalysisError>,
     source_file: Arc<SourceFile>,
     warnings_as_errors: bool,
+    /// The lint levels in effect for the module, i.e. those configured globally, updated with any
+    /// module-level lint attributes
+    lints: LintConfig,
 }
 
 impl AnalysisContext {
     pub fn new(source_file: Arc<SourceFile>) -> Self {
         Self {
             constants: Default::default(),
+            used_constants: Default::default(),
+            structs: Default::default(),
             procedures: Default::default(),
             errors: Default::default(),
             source_file,
             warnings_as_errors: false,
+            lints: Default::default(),
+        }
+    }
+
+    pub fn set_lints(&mut self, lints: LintConfig) {
+        self.lints = lints;
+    }
+
+    #[inline(always)]
+    pub fn lints(&self) -> &LintConfig {
+        &self.lints
+    }
+
+    /// Reports a violation of `lint` at `span`, according to its level in `config`.
+    pub fn lint(
+        &mut self,
+        config: &LintConfig,
+        lint: Lint,
+        span: SourceSpan,
+        message: impl Into<String>,
+    ) {
+        let message = message.into();
+        match config.level(lint) {
+            LintLevel::Allow => (),
+            LintLevel::Warn => {
+                self.error(SemanticAnalysisError::LintWarning { span, lint, message })
+            },
+            LintLevel::Deny => {
+                self.error(SemanticAnalysisError::LintDenied { span, lint, message })
+            },
         }
     }
 
@@ -65,42 +109,245 @@ impl AnalysisContext {
                 Ok(())
             },
             Err(err) => {
-                self.errors.push(err);
-                let errors = core::mem::take(&mut self.errors);
-                Err(SyntaxError {
-                    source_file: self.source_file.clone(),
-                    errors,
-                })
+                self.error(err);
+                self.has_failed()
+            },
+        }
+    }
+
+    /// Define a new struct, computing the offsets of its fields and its size from the types of its
+    /// fields.
+    ///
+    /// Returns the struct with its layout, or `None` if it is invalid, in which case the errors
+    /// found were reported.
+    pub fn define_struct(&mut self, mut def: Struct) -> Option<Struct> {
+        if let Some(prev) = self.structs.get(&def.name) {
+            self.errors.push(SemanticAnalysisError::SymbolConflict {
+                span: def.name.span(),
+                prev_span: prev.name.span(),
+            });
+            return None;
+        }
+
+        let mut valid = true;
+        let mut offset = 0u32;
+        let mut align = 1u32;
+        for index in 0..def.fields.len() {
+            let field = &def.fields[index];
+            if let Some(prev) = def.fields[..index].iter().find(|prev| prev.name == field.name) {
+                self.errors.push(SemanticAnalysisError::SymbolConflict {
+                    span: field.name.span(),
+                    prev_span: prev.name.span(),
+                });
+                valid = false;
+                continue;
+            }
+            let Some((size, field_align)) = self.layout_of(&field.ty) else {
+                valid = false;
+                continue;
+            };
+            let Some(field_offset) = offset.checked_next_multiple_of(field_align) else {
+                self.errors.push(SemanticAnalysisError::StructTooLarge { span: def.span });
+                return None;
+            };
+            def.fields[index].offset = field_offset;
+            offset = match field_offset.checked_add(size) {
+                Some(offset) => offset,
+                None => {
+                    self.errors.push(SemanticAnalysisError::StructTooLarge { span: def.span });
+                    return None;
+                },
+            };
+            align = align.max(field_align);
+        }
+        if !valid {
+            return None;
+        }
+        let Some(size) = offset.checked_next_multiple_of(align) else {
+            self.errors.push(SemanticAnalysisError::StructTooLarge { span: def.span });
+            return None;
+        };
+        def.size = size;
+        def.align = align;
+
+        self.structs.insert(def.name.clone(), def.clone());
+        Some(def)
+    }
+
+    /// Returns the size and alignment of a value of type `ty`, in field elements.
+    ///
+    /// Returns `None` if `ty` is invalid, in which case the error found was reported.
+    fn layout_of(&mut self, ty: &FieldType) -> Option<(u32, u32)> {
+        match ty {
+            FieldType::Value(ty) => {
+                let align = if matches!(ty.inner(), StackType::Word) { 4 } else { 1 };
+                Some((ty.size_in_felts() as u32, align))
+            },
+            FieldType::Struct(name) => match self.structs.get(name) {
+                Some(def) => Some((def.size, def.align)),
+                None => {
+                    self.error(SemanticAnalysisError::SymbolUndefined { span: name.span() });
+                    None
+                },
+            },
+            FieldType::Array { span, element, len } => {
+                let (size, align) = self.layout_of(element)?;
+                let len = match len {
+                    Immediate::Value(len) => len.into_inner(),
+                    Immediate::Constant(name) => match self.get_constant(name) {
+                        Ok(ConstantExpr::Literal(value)) => match u32::try_from(value.as_int()) {
+                            Ok(len) => len,
+                            Err(_) => {
+                                self.errors.push(SemanticAnalysisError::ImmediateOverflow {
+                                    span: name.span(),
+                                });
+                                return None;
+                            },
+                        },
+                        Ok(_) => {
+                            self.errors
+                                .push(SemanticAnalysisError::InvalidConstant { span: name.span() });
+                            return None;
+                        },
+                        Err(err) => {
+                            self.errors.push(err);
+                            return None;
+                        },
+                    },
+                };
+                match size.checked_mul(len) {
+                    Some(size) => Some((size, align)),
+                    None => {
+                        self.errors.push(SemanticAnalysisError::StructTooLarge { span: *span });
+                        None
+                    },
+                }
             },
         }
     }
 
-    fn const_eval(&self, value: &ConstantExpr) -> Result<ConstantExpr, SemanticAnalysisError> {
+    /// Get the struct named `name`
+    ///
+    /// Returns `Err` if the struct is undefined
+    pub fn get_struct(&self, name: &Ident) -> Result<&Struct, SemanticAnalysisError> {
+        self.structs
+            .get(name)
+            .ok_or(SemanticAnalysisError::SymbolUndefined { span: name.span() })
+    }
+
+    /// Evaluates `value` using the constants and structs defined so far
+    ///
+    /// Expressions which refer to the layout of structs defined in other modules are evaluated as
+    /// far as possible, and the remainder is left to be evaluated when the module is linked.
+    ///
+    /// Returns `Err` if `value` references an undefined constant, struct, or field
+    pub(super) fn const_eval(
+        &self,
+        value: &ConstantExpr,
+    ) -> Result<ConstantExpr, SemanticAnalysisError> {
         match value {
             ConstantExpr::Literal(_) | ConstantExpr::String(_) => Ok((*value).clone()),
             ConstantExpr::Word(_) => Ok((*value).clone()),
             ConstantExpr::Var(name) => self.get_constant(name).cloned(),
-            ConstantExpr::BinaryOp { op, lhs, rhs, .. } => {
-                let rhs = self.const_eval(rhs)?.expect_literal();
-                let lhs = self.const_eval(lhs)?.expect_literal();
-                let felt = match op {
-                    ConstantOp::Add => lhs + rhs,
-                    ConstantOp::Sub => lhs - rhs,
-                    ConstantOp::Mul => lhs * rhs,
-                    ConstantOp::Div => lhs / rhs,
-                    ConstantOp::IntDiv => Felt::new(lhs.as_int() / rhs.as_int()),
+            ConstantExpr::SizeOf { ty, .. } | ConstantExpr::OffsetOf { ty, .. }
+                if ty.is_imported() =>
+            {
+                Ok((*value).clone())
+            },
+            ConstantExpr::SizeOf { span, ty } => {
+                let def = self.get_struct(&ty.name)?;
+                Ok(ConstantExpr::Literal(Span::new(*span, Felt::from(def.size))))
+            },
+            ConstantExpr::OffsetOf { span, ty, field } => {
+                let def = self.get_struct(&ty.name)?;
+                let field = def
+                    .field(field)
+                    .ok_or(SemanticAnalysisError::UndefinedField { span: field.span() })?;
+                Ok(ConstantExpr::Literal(Span::new(*span, Felt::from(field.offset))))
+            },
+            ConstantExpr::BinaryOp { span, op, lhs, rhs } => {
+                let rhs = self.const_eval(rhs)?;
+                let lhs = self.const_eval(lhs)?;
+                let (ConstantExpr::Literal(lhs), ConstantExpr::Literal(rhs)) = (&lhs, &rhs) else {
+                    return Ok(ConstantExpr::BinaryOp {
+                        span: *span,
+                        op: *op,
+                        lhs: lhs.into(),
+                        rhs: rhs.into(),
+                    });
                 };
-                Ok(ConstantExpr::Literal(Span::unknown(felt)))
+                match op.eval(lhs.into_inner(), rhs.into_inner()) {
+                    Ok(value) => Ok(ConstantExpr::Literal(Span::new(*span, value))),
+                    Err(kind) => {
+                        Err(SemanticAnalysisError::InvalidConstantExpression { span: *span, kind })
+                    },
+                }
+            },
+            ConstantExpr::Ternary { span, cond, then_expr, else_expr } => {
+                match self.const_eval(cond)? {
+                    ConstantExpr::Literal(cond) if cond.into_inner() == Felt::ZERO => {
+                        self.const_eval(else_expr)
+                    },
+                    ConstantExpr::Literal(_) => self.const_eval(then_expr),
+                    cond if cond.is_deferred() => Ok(ConstantExpr::Ternary {
+                        span: *span,
+                        cond: cond.into(),
+                        then_expr: self.const_eval(then_expr)?.into(),
+                        else_expr: self.const_eval(else_expr)?.into(),
+                    }),
+                    cond => Err(SemanticAnalysisError::InvalidConstant { span: cond.span() }),
+                }
+            },
+            ConstantExpr::Hash { span, args } => {
+                let args =
+                    args.iter().map(|arg| self.const_eval(arg)).collect::<Result<Vec<_>, _>>()?;
+                match ConstantExpr::eval_hash(&args) {
+                    Ok(Some(digest)) => {
+                        Ok(ConstantExpr::Word(Span::new(*span, WordValue(*digest))))
+                    },
+                    Ok(None) => Ok(ConstantExpr::Hash { span: *span, args }),
+                    Err(kind) => {
+                        Err(SemanticAnalysisError::InvalidConstantExpression { span: *span, kind })
+                    },
+                }
+            },
+            ConstantExpr::Event { span, name } => match self.const_eval(name)? {
+                ConstantExpr::String(name) => {
+                    Ok(ConstantExpr::Literal(Span::new(*span, ConstantExpr::eval_event(&name))))
+                },
+                name => Err(SemanticAnalysisError::InvalidConstant { span: name.span() }),
             },
         }
     }
 
+    /// Returns the constants whose values refer to the layout of structs defined in other modules,
+    /// and so can only be evaluated when the module is linked.
+    pub fn deferred_constants(&self) -> impl Iterator<Item = &Constant> {
+        self.constants.values().filter(|constant| constant.value.is_deferred())
+    }
+
+    /// Records that the constant `name` is used, e.g. because it is the key of an advice map
+    /// entry.
+    pub fn mark_constant_used(&self, name: &Ident) {
+        self.used_constants.borrow_mut().insert(name.clone());
+    }
+
+    /// Returns the constants which have been defined, but never referenced.
+    pub fn unused_constants(&self) -> Vec<&Constant> {
+        let used = self.used_constants.borrow();
+        self.constants
+            .values()
+            .filter(|constant| !used.contains(&constant.name))
+            .collect()
+    }
+
     /// Get the constant value bound to `name`
     ///
     /// Returns `Err` if the symbol is undefined
     pub fn get_constant(&self, name: &Ident) -> Result<&ConstantExpr, SemanticAnalysisError> {
         let span = name.span();
         if let Some(expr) = self.constants.get(name) {
+            self.mark_constant_used(name);
             Ok(&expr.value)
         } else {
             Err(SemanticAnalysisError::SymbolUndefined { span })
@@ -113,6 +360,7 @@ impl AnalysisContext {
     pub fn get_error(&self, name: &Ident) -> Result<Arc<str>, SemanticAnalysisError> {
         let span = name.span();
         if let Some(expr) = self.constants.get(name) {
+            self.mark_constant_used(name);
             Ok(expr.value.expect_string())
         } else {
             Err(SemanticAnalysisError::SymbolUndefined { span })
@@ -120,6 +368,18 @@ impl AnalysisContext {
     }
 
     pub fn error(&mut self, diagnostic: SemanticAnalysisError) {
+        // The definitions of symbols, imports and the entrypoint may be in the parts of the module
+        // which could not be parsed, so failing to find them is only reported for modules without
+        // syntax errors
+        let unresolved = matches!(
+            diagnostic,
+            SemanticAnalysisError::SymbolUndefined { .. }
+                | SemanticAnalysisError::MissingImport { .. }
+                | SemanticAnalysisError::MissingEntrypoint
+        );
+        if unresolved && self.has_syntax_errors() {
+            return;
+        }
         self.errors.push(diagnostic);
     }
 
@@ -132,8 +392,22 @@ impl AnalysisContext {
             .any(|err| matches!(err.severity().unwrap_or(Severity::Error), Severity::Error))
     }
 
+    /// Returns true if any syntax errors were found while parsing the module, in which case parts
+    /// of the module may be missing from the forms that were analyzed.
+    pub fn has_syntax_errors(&self) -> bool {
+        self.errors.iter().any(|err| matches!(err, SemanticAnalysisError::Syntax(_)))
+    }
+
+    /// Returns `Err` if any errors were raised, other than syntax errors which the parser recovered
+    /// from, as those do not prevent analysis of the remainder of the module.
     pub fn has_failed(&mut self) -> Result<(), SyntaxError> {
-        if self.has_errors() {
+        let warnings_as_errors = self.warnings_as_errors();
+        let failed = self.errors.iter().any(|err| {
+            !matches!(err, SemanticAnalysisError::Syntax(_))
+                && (warnings_as_errors
+                    || matches!(err.severity().unwrap_or(Severity::Error), Severity::Error))
+        });
+        if failed {
             Err(SyntaxError {
                 source_file: self.source_file.clone(),
                 errors: core::mem::take(&mut self.errors),
@@ -143,32 +417,20 @@ impl AnalysisContext {
         }
     }
 
-    pub fn into_result(self) -> Result<(), SyntaxError> {
+    /// Returns `Err` if analysis failed, or the warnings raised during analysis, if any.
+    pub fn into_result(self) -> Result<Option<SyntaxWarning>, SyntaxError> {
         if self.has_errors() {
             Err(SyntaxError {
                 source_file: self.source_file.clone(),
                 errors: self.errors,
             })
+        } else if self.errors.is_empty() {
+            Ok(None)
         } else {
-            self.emit_warnings();
-            Ok(())
-        }
-    }
-
-    #[cfg(feature = "std")]
-    fn emit_warnings(self) {
-        use crate::diagnostics::Report;
-
-        if !self.errors.is_empty() {
-            // Emit warnings to stderr
-            let warning = Report::from(super::errors::SyntaxWarning {
+            Ok(Some(SyntaxWarning {
                 source_file: self.source_file,
                 errors: self.errors,
-            });
-            std::eprintln!("{warning}");
+            }))
         }
     }
-
-    #[cfg(not(feature = "std"))]
-    fn emit_warnings(self) {}
 }
@@ -1,7 +1,13 @@
-use alloc::{sync::Arc, vec::Vec};
+use alloc::{string::String, sync::Arc, vec::Vec};
 use core::fmt;
 
-use crate::{SourceFile, SourceSpan, diagnostics::Diagnostic};
+use super::{ConstEvalError, Lint};
+use crate::{
+    SourceFile, SourceSpan,
+    ast::{ProcedureSignature, StackEffect},
+    diagnostics::Diagnostic,
+    parser::ParsingError,
+};
 
 /// The high-level error type for all semantic analysis errors.
 ///
@@ -27,7 +33,8 @@ pub struct SyntaxError {
 ///
 /// Like [SyntaxError], this rolls up all such notices into a single batch, and emits them all
 /// at once. The difference is that we never return this as an error from any API, it simply
-/// exists to leverage the diagnostic infrastructure of `miette`.
+/// exists to leverage the diagnostic infrastructure of `miette`, e.g. so that the warnings can be
+/// printed, or handed to an editor via [crate::ModuleParser::parse_with_warnings].
 #[derive(Debug, thiserror::Error, Diagnostic)]
 #[error("one or more warnings were emitted")]
 #[diagnostic(help("see below for details"))]
@@ -41,6 +48,10 @@ pub struct SyntaxWarning {
 /// Represents an error that occurs during semantic analysis
 #[derive(Debug, thiserror::Error, Diagnostic)]
 pub enum SemanticAnalysisError {
+    /// A syntax error which the parser recovered from
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Syntax(ParsingError),
     #[error("invalid program: no entrypoint defined")]
     #[diagnostic(help(
         "ensure you define an entrypoint somewhere in the body with `begin`..`end`"
@@ -193,11 +204,263 @@ pub enum SemanticAnalysisError {
         #[label]
         span: SourceSpan,
     },
+    #[error("invalid constant expression: {kind}")]
+    #[diagnostic()]
+    InvalidConstantExpression {
+        #[label]
+        span: SourceSpan,
+        kind: ConstEvalError,
+    },
+    #[error("undefined field: the struct has no field with this name")]
+    #[diagnostic()]
+    UndefinedField {
+        #[label]
+        span: SourceSpan,
+    },
+    #[error("invalid struct: the size of this struct does not fit in 32 bits")]
+    #[diagnostic()]
+    StructTooLarge {
+        #[label]
+        span: SourceSpan,
+    },
+    #[error("invalid repeat count: loops must repeat at least once")]
+    #[diagnostic()]
+    InvalidRepeatCount {
+        #[label]
+        span: SourceSpan,
+    },
+    #[error("invalid loop range: {start}..{end} is empty, but loops must iterate at least once")]
+    #[diagnostic()]
+    EmptyLoopRange {
+        #[label]
+        span: SourceSpan,
+        start: u32,
+        end: u32,
+    },
+    #[error("duplicate case in match: {value} is already handled by a previous case")]
+    #[diagnostic()]
+    DuplicateMatchCase {
+        #[label("duplicate case occurs here")]
+        span: SourceSpan,
+        #[label("previously handled here")]
+        prev_span: SourceSpan,
+        value: u32,
+    },
+    #[error("invalid generic procedure: generic procedures cannot be exported")]
+    #[diagnostic(help(
+        "generic procedures can only be instantiated by procedures of the same module, consider \
+        exporting a procedure which executes the instantiation you need instead"
+    ))]
+    ExportedGenericProcedure {
+        #[label]
+        span: SourceSpan,
+    },
+    #[error("invalid instantiation: callee is not a generic procedure")]
+    #[diagnostic(help(
+        "only procedures of the current module declared with parameters, e.g. `proc.foo(N)`, can \
+        be instantiated"
+    ))]
+    NotGeneric {
+        #[label]
+        span: SourceSpan,
+    },
+    #[error("invalid instantiation: expected {expected} arguments, but got {actual}")]
+    #[diagnostic()]
+    InstantiationArityMismatch {
+        #[label]
+        span: SourceSpan,
+        expected: usize,
+        actual: usize,
+    },
+    #[error("invalid invocation: generic procedures must be instantiated")]
+    #[diagnostic(help(
+        "provide a value for each parameter of this procedure, e.g. `exec.foo(4)` for a \
+        procedure declared as `proc.foo(N)`"
+    ))]
+    UninstantiatedGeneric {
+        #[label]
+        span: SourceSpan,
+    },
+    #[error("invalid recursive instantiation")]
+    #[diagnostic(help(
+        "this generic procedure is instantiated from one of its own instances, and so would be \
+        expanded forever"
+    ))]
+    RecursiveInstantiation {
+        #[label]
+        span: SourceSpan,
+    },
     #[error("advmap key already defined")]
     AdvMapKeyAlreadyDefined {
         #[label]
         span: SourceSpan,
     },
+    #[error("invalid data segment: data segments can only be declared in executable modules")]
+    #[diagnostic(help(
+        "data segments are copied into memory at program start, consider moving this one to the \
+        program which uses it"
+    ))]
+    UnexpectedDataSegment {
+        #[label]
+        span: SourceSpan,
+    },
+    #[error("invalid data segment: address {address} is not word-aligned")]
+    #[diagnostic(help("data segments must start at an address which is a multiple of 4"))]
+    MisalignedDataSegment {
+        #[label]
+        span: SourceSpan,
+        address: u32,
+    },
+    #[error("invalid data segment: expected a non-empty list of words, but got {len} elements")]
+    #[diagnostic(help("the number of elements in a data segment must be a multiple of 4"))]
+    InvalidDataSegmentLength {
+        #[label]
+        span: SourceSpan,
+        len: usize,
+    },
+    #[error("invalid data segment: the segment does not fit in memory")]
+    #[diagnostic(help("the last address of a data segment must be at most 2^32 - 1"))]
+    DataSegmentOutOfBounds {
+        #[label]
+        span: SourceSpan,
+    },
+    #[error("invalid data segment: overlaps a previously declared segment")]
+    #[diagnostic()]
+    OverlappingDataSegments {
+        #[label("this segment")]
+        span: SourceSpan,
+        #[label("overlaps this one")]
+        prev_span: SourceSpan,
+    },
+    #[error("invalid procedure signature")]
+    #[diagnostic(help(
+        "signature attributes must list the types of the values on the stack, starting from the \
+        top, e.g. `@inputs(word, felt)`; valid types are `felt`, `word`, `u32` and `u64`"
+    ))]
+    InvalidSignature {
+        #[label]
+        span: SourceSpan,
+    },
+    #[error("procedure signature mismatch: expected stack effect {expected}, but got {actual}")]
+    #[diagnostic(help(
+        "the body of this procedure accesses {} and leaves {} elements on the stack in their \
+        place, which is inconsistent with its signature `{signature}`",
+        actual.inputs, actual.outputs
+    ))]
+    SignatureMismatch {
+        #[label("the signature of this procedure is declared here")]
+        span: SourceSpan,
+        signature: ProcedureSignature,
+        expected: StackEffect,
+        actual: StackEffect,
+    },
+    #[error("invalid call: callee expects more stack elements than are available")]
+    #[diagnostic(help(
+        "the callee consumes {expected} elements, but only {available} elements are available \
+        according to the signature of the caller"
+    ))]
+    CallSignatureMismatch {
+        #[label("callee has signature `{signature}`")]
+        span: SourceSpan,
+        signature: ProcedureSignature,
+        expected: usize,
+        available: usize,
+    },
+    #[error("unchecked u32 operand")]
+    #[diagnostic(
+        severity(Warning),
+        help(
+            "an operand of this instruction is the result of field arithmetic, or was read from \
+            the advice provider, and may not be a valid u32 value: consider checking it with \
+            `u32assert` first"
+        )
+    )]
+    UncheckedU32Operand {
+        #[label("this instruction expects u32 operands")]
+        span: SourceSpan,
+    },
+    #[error("unbalanced branches")]
+    #[diagnostic(
+        severity(Warning),
+        help(
+            "the `then` branch changes the stack depth by {then_delta}, but the `else` branch \
+            changes it by {else_delta}"
+        )
+    )]
+    UnbalancedBranches {
+        #[label("the branches of this conditional leave the stack at different depths")]
+        span: SourceSpan,
+        then_delta: isize,
+        else_delta: isize,
+    },
+    #[error("loop changes stack depth")]
+    #[diagnostic(
+        severity(Warning),
+        help(
+            "each iteration of this loop changes the stack depth by {delta}, excluding the loop \
+            condition"
+        )
+    )]
+    StackDepthChangingLoop {
+        #[label("the body of this loop does not preserve the stack depth")]
+        span: SourceSpan,
+        delta: isize,
+    },
+    #[error("invalid lint attribute")]
+    #[diagnostic(help(
+        "lint attributes take a list of lint names, e.g. `@allow(unused_constant, trivial_exec)`"
+    ))]
+    InvalidLintAttribute {
+        #[label]
+        span: SourceSpan,
+    },
+    #[error("invalid inline attribute")]
+    #[diagnostic(help("`@inline` and `@noinline` do not take any arguments"))]
+    InvalidInlineAttribute {
+        #[label]
+        span: SourceSpan,
+    },
+    #[error("conflicting inline attributes")]
+    #[diagnostic(help("a procedure cannot be annotated with both `@inline` and `@noinline`"))]
+    ConflictingInlineAttributes {
+        #[label("this conflicts with `@inline`")]
+        span: SourceSpan,
+        #[label("procedure is annotated with `@inline` here")]
+        prev_span: SourceSpan,
+    },
+    #[error("unknown lint")]
+    #[diagnostic(severity(Warning), help("this lint does not exist, and will be ignored"))]
+    UnknownLint {
+        #[label]
+        span: SourceSpan,
+    },
+    #[error("invalid module attribute")]
+    #[diagnostic(help("only `@!allow`, `@!warn` and `@!deny` are supported as module attributes"))]
+    UnexpectedModuleAttribute {
+        #[label]
+        span: SourceSpan,
+    },
+    #[error("{message}")]
+    #[diagnostic(
+        severity(Warning),
+        help(
+            "this warning is emitted by the `{lint}` lint, and can be disabled with `@allow({lint})`"
+        )
+    )]
+    LintWarning {
+        #[label]
+        span: SourceSpan,
+        lint: Lint,
+        message: String,
+    },
+    #[error("{message}")]
+    #[diagnostic(help("this error is emitted by the `{lint}` lint, which is set to `deny`"))]
+    LintDenied {
+        #[label]
+        span: SourceSpan,
+        lint: Lint,
+        message: String,
+    },
 }
 
 /// Represents a system limit that was exceeded
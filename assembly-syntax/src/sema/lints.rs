@@ -0,0 +1,191 @@
+use alloc::collections::BTreeMap;
+use core::{fmt, str::FromStr};
+
+use super::{AnalysisContext, SemanticAnalysisError};
+use crate::{
+    Spanned,
+    ast::{Attribute, AttributeSet, MetaExpr},
+};
+
+// LINT
+// ================================================================================================
+
+/// A named check for code which is valid, but likely to be a mistake or to be improvable.
+///
+/// The level of each lint can be configured globally, e.g. on the assembler via [LintConfig], and
+/// overridden in Miden Assembly using the `@allow`, `@warn` and `@deny` attributes, either for a
+/// whole module (e.g. `@!allow(unused_constant)`), or for a single procedure (e.g.
+/// `@allow(trivial_exec)`).
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Lint {
+    /// A constant is defined, but never used.
+    UnusedConstant,
+    /// A private procedure is defined, but never referenced in its module.
+    UnusedProcedure,
+    /// A value is pushed onto the stack, and then immediately dropped, e.g. `push.1 drop`.
+    RedundantPushDrop,
+    /// A local procedure whose body consists of a single instruction is invoked with `exec`.
+    TrivialExec,
+    /// An exported procedure has no docstring.
+    MissingDocs,
+    /// An event is emitted using a literal event id, rather than a named constant.
+    MagicEmitEvent,
+}
+
+impl Lint {
+    /// All of the known lints.
+    pub const ALL: [Lint; 6] = [
+        Self::UnusedConstant,
+        Self::UnusedProcedure,
+        Self::RedundantPushDrop,
+        Self::TrivialExec,
+        Self::MissingDocs,
+        Self::MagicEmitEvent,
+    ];
+
+    /// Returns the name of this lint, as used in `@allow`, `@warn` and `@deny` attributes.
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::UnusedConstant => "unused_constant",
+            Self::UnusedProcedure => "unused_procedure",
+            Self::RedundantPushDrop => "redundant_push_drop",
+            Self::TrivialExec => "trivial_exec",
+            Self::MissingDocs => "missing_docs",
+            Self::MagicEmitEvent => "magic_emit_event",
+        }
+    }
+
+    /// Returns the level of this lint, unless configured otherwise.
+    pub const fn default_level(&self) -> LintLevel {
+        match self {
+            Self::UnusedConstant | Self::UnusedProcedure | Self::RedundantPushDrop => {
+                LintLevel::Warn
+            },
+            Self::TrivialExec | Self::MissingDocs | Self::MagicEmitEvent => LintLevel::Allow,
+        }
+    }
+}
+
+impl fmt::Display for Lint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Lint {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL.into_iter().find(|lint| lint.name() == s).ok_or(())
+    }
+}
+
+// LINT LEVEL
+// ================================================================================================
+
+/// How violations of a [Lint] are reported.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LintLevel {
+    /// Violations are not reported.
+    Allow,
+    /// Violations are reported as warnings.
+    Warn,
+    /// Violations are reported as errors.
+    Deny,
+}
+
+impl LintLevel {
+    /// Returns the name of the attribute used to set a lint to this level.
+    pub const fn attribute_name(&self) -> &'static str {
+        match self {
+            Self::Allow => "allow",
+            Self::Warn => "warn",
+            Self::Deny => "deny",
+        }
+    }
+
+    fn from_attribute_name(name: &str) -> Option<Self> {
+        [Self::Allow, Self::Warn, Self::Deny]
+            .into_iter()
+            .find(|level| level.attribute_name() == name)
+    }
+}
+
+// LINT CONFIG
+// ================================================================================================
+
+/// The levels of the lints run during semantic analysis.
+///
+/// Lints whose level has not been set explicitly use their [Lint::default_level].
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct LintConfig {
+    levels: BTreeMap<Lint, LintLevel>,
+}
+
+impl LintConfig {
+    /// Sets the level of `lint` to `level`.
+    pub fn set_level(&mut self, lint: Lint, level: LintLevel) {
+        self.levels.insert(lint, level);
+    }
+
+    /// Returns this configuration with the level of `lint` set to `level`.
+    pub fn with_level(mut self, lint: Lint, level: LintLevel) -> Self {
+        self.set_level(lint, level);
+        self
+    }
+
+    /// Returns the level of `lint`.
+    pub fn level(&self, lint: Lint) -> LintLevel {
+        self.levels.get(&lint).copied().unwrap_or_else(|| lint.default_level())
+    }
+
+    /// Returns true if `attribute` sets the level of one or more lints.
+    pub fn is_lint_attribute(attribute: &Attribute) -> bool {
+        LintLevel::from_attribute_name(attribute.name()).is_some()
+    }
+
+    /// Updates this configuration with the lint levels set by `attribute`, if it is an `@allow`,
+    /// `@warn` or `@deny` attribute, reporting any malformed attributes or unknown lints to
+    /// `analyzer`.
+    pub(crate) fn apply_attribute(
+        &mut self,
+        attribute: &Attribute,
+        analyzer: &mut AnalysisContext,
+    ) {
+        let Some(level) = LintLevel::from_attribute_name(attribute.name()) else {
+            return;
+        };
+        let Attribute::List(list) = attribute else {
+            analyzer.error(SemanticAnalysisError::InvalidLintAttribute { span: attribute.span() });
+            return;
+        };
+        for item in list.as_slice() {
+            match item {
+                MetaExpr::Ident(id) => match id.as_str().parse::<Lint>() {
+                    Ok(lint) => self.set_level(lint, level),
+                    Err(()) => {
+                        analyzer.error(SemanticAnalysisError::UnknownLint { span: id.span() })
+                    },
+                },
+                MetaExpr::String(id) => {
+                    analyzer.error(SemanticAnalysisError::InvalidLintAttribute { span: id.span() })
+                },
+                MetaExpr::Int(value) => analyzer
+                    .error(SemanticAnalysisError::InvalidLintAttribute { span: value.span() }),
+            }
+        }
+    }
+
+    /// Returns a copy of this configuration, updated with the lint levels set by `attributes`.
+    pub(crate) fn with_attributes(
+        &self,
+        attributes: &AttributeSet,
+        analyzer: &mut AnalysisContext,
+    ) -> Self {
+        let mut config = self.clone();
+        for attribute in attributes.iter() {
+            config.apply_attribute(attribute, analyzer);
+        }
+        config
+    }
+}
@@ -1,22 +1,34 @@
 mod context;
 mod errors;
+mod lints;
 mod passes;
 
 use alloc::{
     boxed::Box,
-    collections::{BTreeSet, VecDeque},
+    collections::{BTreeMap, BTreeSet, VecDeque},
+    format,
     sync::Arc,
     vec::Vec,
 };
 
-use miden_core::{Word, crypto::hash::Rpo256};
+use miden_core::{WORD_SIZE, Word, crypto::hash::Rpo256};
 
-use self::passes::{ConstEvalVisitor, VerifyInvokeTargets};
+use self::passes::{
+    AnalyzeStack, ConstEvalVisitor, InstantiateGenerics, LintProcedure, VerifyInvokeTargets,
+    VerifyStackEffects, parse_signature,
+};
 pub use self::{
     context::AnalysisContext,
-    errors::{SemanticAnalysisError, SyntaxError},
+    errors::{SemanticAnalysisError, SyntaxError, SyntaxWarning},
+    lints::{Lint, LintConfig, LintLevel},
+    passes::ConstEvalError,
+};
+use crate::{
+    LibraryPath, Span, Spanned,
+    ast::*,
+    diagnostics::SourceFile,
+    parser::{ParsingError, WordValue},
 };
-use crate::{LibraryPath, Span, Spanned, ast::*, diagnostics::SourceFile, parser::WordValue};
 
 /// Constructs and validates a [Module], given the forms constituting the module body.
 ///
@@ -24,24 +36,41 @@ use crate::{LibraryPath, Span, Spanned, ast::*, diagnostics::SourceFile, parser:
 ///
 /// * Documentation comments are attached to items they decorate
 /// * Import table is constructed
+/// * Generic procedures are instantiated for each set of arguments they are instantiated with
 /// * Symbol resolution is performed:
 ///   * Constants referenced by name are replaced with the value of that constant.
 ///   * Calls to imported procedures are resolved concretely
 /// * Semantic analysis is performed on the module to validate it
+/// * Lints are run, at the levels given by `lints`, and any lint attributes in the module
+///
+/// Any warnings raised during analysis are returned alongside the module.
+///
+/// `syntax_errors` are the syntax errors which the parser recovered from while parsing `forms`.
+/// These are reported along with any errors found during analysis, which proceeds as far as it
+/// can, but checks which can be thrown off by the parts of the module that are missing from
+/// `forms`, i.e. stack effect checks and lints, are skipped.
 pub fn analyze(
     source: Arc<SourceFile>,
     kind: ModuleKind,
     path: LibraryPath,
     forms: Vec<Form>,
+    syntax_errors: Vec<ParsingError>,
     warnings_as_errors: bool,
-) -> Result<Box<Module>, SyntaxError> {
+    lints: LintConfig,
+) -> Result<(Box<Module>, Option<SyntaxWarning>), SyntaxError> {
     let mut analyzer = AnalysisContext::new(source.clone());
     analyzer.set_warnings_as_errors(warnings_as_errors);
+    analyzer.set_lints(lints);
+    for error in syntax_errors {
+        analyzer.error(SemanticAnalysisError::Syntax(error));
+    }
 
     let mut module = Box::new(Module::new(kind, path).with_span(source.source_span()));
 
     let mut forms = VecDeque::from(forms);
     let mut docs = None;
+    // The structs defined in other modules which are referred to by constants
+    let mut imported_structs = Vec::new();
     while let Some(form) = forms.pop_front() {
         match form {
             Form::ModuleDoc(docstring) => {
@@ -54,8 +83,14 @@ pub fn analyze(
                 }
             },
             Form::Constant(constant) => {
+                collect_imported_structs(&constant.value, &mut imported_structs);
                 analyzer.define_constant(constant.with_docs(docs.take()))?;
             },
+            Form::Struct(def) => {
+                if let Some(def) = analyzer.define_struct(def.with_docs(docs.take())) {
+                    module.structs.push(def);
+                }
+            },
             Form::Import(import) => {
                 if let Some(docs) = docs.take() {
                     analyzer.error(SemanticAnalysisError::ImportDocstring { span: docs.span() });
@@ -101,6 +136,27 @@ pub fn analyze(
             Form::AdviceMapEntry(entry) => {
                 add_advice_map_entry(&mut module, entry.with_docs(docs.take()), &mut analyzer)?;
             },
+            Form::DataSegment(segment) if matches!(kind, ModuleKind::Executable) => {
+                add_data_segment(&mut module, segment.with_docs(docs.take()), &mut analyzer);
+            },
+            Form::DataSegment(segment) => {
+                docs.take();
+                analyzer.error(SemanticAnalysisError::UnexpectedDataSegment { span: segment.span });
+            },
+            Form::Attribute(attribute) => {
+                if let Some(docs) = docs.take() {
+                    analyzer.error(SemanticAnalysisError::UnusedDocstring { span: docs.span() });
+                }
+                if LintConfig::is_lint_attribute(&attribute) {
+                    let mut lints = analyzer.lints().clone();
+                    lints.apply_attribute(&attribute, &mut analyzer);
+                    analyzer.set_lints(lints);
+                } else {
+                    analyzer.error(SemanticAnalysisError::UnexpectedModuleAttribute {
+                        span: attribute.span(),
+                    });
+                }
+            },
         }
     }
 
@@ -112,11 +168,39 @@ pub fn analyze(
         analyzer.error(SemanticAnalysisError::MissingEntrypoint);
     }
 
+    // Constants referring to structs defined in other modules are evaluated when linking, so we
+    // only check that those modules are imported here
+    for path in imported_structs {
+        let module_name = path.module.as_ref().expect("expected imported struct");
+        match module.resolve_import_mut(module_name) {
+            Some(import) => import.uses += 1,
+            None => analyzer.error(SemanticAnalysisError::MissingImport { span: path.span() }),
+        }
+    }
+    module.deferred_constants = analyzer.deferred_constants().cloned().collect();
+
     analyzer.has_failed()?;
 
     // Run procedure checks
     visit_procedures(&mut module, &mut analyzer)?;
 
+    // The uses of constants and imports may have been in the parts of the module which could not
+    // be parsed, so only check for unused ones in complete modules
+    if analyzer.has_syntax_errors() {
+        return analyzer.into_result().map(move |warnings| (module, warnings));
+    }
+
+    // Check unused constants
+    let lints = analyzer.lints().clone();
+    let unused = analyzer
+        .unused_constants()
+        .into_iter()
+        .map(|constant| (constant.span(), constant.name.clone()))
+        .collect::<Vec<_>>();
+    for (span, name) in unused {
+        analyzer.lint(&lints, Lint::UnusedConstant, span, format!("unused constant `{name}`"));
+    }
+
     // Check unused imports
     for import in module.imports() {
         if !import.is_used() {
@@ -124,7 +208,25 @@ pub fn analyze(
         }
     }
 
-    analyzer.into_result().map(move |_| module)
+    analyzer.into_result().map(move |warnings| (module, warnings))
+}
+
+/// Validates the `@inline` and `@noinline` attributes of `procedure`, which must not take any
+/// arguments, and are mutually exclusive.
+fn validate_inline_attributes(procedure: &Procedure, analyzer: &mut AnalysisContext) {
+    let inline = procedure.get_attribute(InlineHint::INLINE_ATTRIBUTE);
+    let noinline = procedure.get_attribute(InlineHint::NOINLINE_ATTRIBUTE);
+    for attr in inline.into_iter().chain(noinline) {
+        if !matches!(attr, Attribute::Marker(_)) {
+            analyzer.error(SemanticAnalysisError::InvalidInlineAttribute { span: attr.span() });
+        }
+    }
+    if let (Some(inline), Some(noinline)) = (inline, noinline) {
+        analyzer.error(SemanticAnalysisError::ConflictingInlineAttributes {
+            span: noinline.span(),
+            prev_span: inline.span(),
+        });
+    }
 }
 
 /// Visit all of the procedures of the current analysis context,
@@ -137,8 +239,45 @@ fn visit_procedures(
     module: &mut Module,
     analyzer: &mut AnalysisContext,
 ) -> Result<(), SyntaxError> {
+    // Replace generic procedures with their instances first, so that instances are analyzed like
+    // any other procedure
+    expand_generic_procedures(module, analyzer)?;
+
     let is_kernel = module.is_kernel();
+    // The bodies of procedures in which syntax errors were found are incomplete, so only the
+    // checks which apply to individual operations are run on them
+    let complete = !analyzer.has_syntax_errors();
     let locals = BTreeSet::from_iter(module.procedures().map(|p| p.name().clone()));
+
+    // Derive procedure signatures from their attributes, so that they are known at call sites,
+    // and the lint levels in effect for each procedure
+    let mut signatures = BTreeMap::new();
+    let mut lints = BTreeMap::new();
+    let mut trivial = BTreeSet::new();
+    for export in module.procedures.iter_mut() {
+        if let Export::Procedure(procedure) = export {
+            let signature = parse_signature(procedure, analyzer);
+            if let Some(signature) = signature.as_ref() {
+                signatures.insert(procedure.name().clone(), signature.clone());
+            }
+            procedure.set_signature(signature);
+            validate_inline_attributes(procedure, analyzer);
+
+            let module_lints = analyzer.lints().clone();
+            let procedure_lints = module_lints.with_attributes(procedure.attributes(), analyzer);
+            lints.insert(procedure.name().clone(), procedure_lints);
+
+            if procedure.num_locals() == 0
+                && procedure.body().len() == 1
+                && matches!(procedure.body().iter().next(), Some(Op::Inst(_)))
+            {
+                trivial.insert(procedure.name().clone());
+            }
+        }
+    }
+
+    let mut referenced = BTreeSet::new();
+
     let mut procedures = VecDeque::from(core::mem::take(&mut module.procedures));
     while let Some(procedure) = procedures.pop_front() {
         match procedure {
@@ -148,6 +287,18 @@ fn visit_procedures(
                     procedure.set_visibility(Visibility::Syscall);
                 }
 
+                // Run the lints which apply to the procedure body, before named immediates are
+                // replaced with their values
+                if complete {
+                    LintProcedure::new(
+                        analyzer,
+                        &lints[procedure.name()],
+                        &trivial,
+                        &mut referenced,
+                    )
+                    .lint_procedure(&procedure);
+                }
+
                 // Evaluate all named immediates to their concrete values
                 {
                     let mut visitor = ConstEvalVisitor::new(analyzer);
@@ -169,6 +320,16 @@ fn visit_procedures(
                     );
                     let _ = visitor.visit_mut_procedure(&mut procedure);
                 }
+
+                if complete {
+                    // Next, check the stack effect of the procedure against its signature, and
+                    // the signatures of the local procedures it invokes
+                    VerifyStackEffects::new(analyzer, &signatures).verify_procedure(&procedure);
+
+                    // Finally, warn about unchecked u32 operands and code which leaves the stack
+                    // at inconsistent depths
+                    AnalyzeStack::new(analyzer, &signatures).analyze_procedure(&procedure);
+                }
                 module.procedures.push(Export::Procedure(procedure));
             },
             Export::Alias(alias) => {
@@ -189,9 +350,118 @@ fn visit_procedures(
         }
     }
 
+    // Warn about private procedures which are never referenced
+    if !complete {
+        return Ok(());
+    }
+    for procedure in module.procedures() {
+        if let Export::Procedure(procedure) = procedure {
+            if matches!(procedure.visibility(), Visibility::Private)
+                && !referenced.contains(procedure.name())
+            {
+                analyzer.lint(
+                    &lints[procedure.name()],
+                    Lint::UnusedProcedure,
+                    procedure.name().span(),
+                    format!("unused procedure `{}`", procedure.name()),
+                );
+            }
+        }
+    }
+
     Ok(())
 }
 
+/// Replaces the generic procedures of `module` with their instances, i.e. a procedure for each
+/// distinct set of arguments a generic procedure is instantiated with, and rewrites instantiations
+/// to invoke the corresponding instance.
+fn expand_generic_procedures(
+    module: &mut Module,
+    analyzer: &mut AnalysisContext,
+) -> Result<(), SyntaxError> {
+    let mut templates = BTreeMap::new();
+    let mut procedures = Vec::with_capacity(module.procedures.len());
+    for export in core::mem::take(&mut module.procedures) {
+        match export {
+            Export::Procedure(procedure) if procedure.is_generic() => {
+                if procedure.visibility().is_exported() {
+                    analyzer.error(SemanticAnalysisError::ExportedGenericProcedure {
+                        span: procedure.name().span(),
+                    });
+                }
+                let params = procedure.params();
+                for (index, param) in params.iter().enumerate() {
+                    if let Some(prev) = params[..index].iter().find(|prev| *prev == param) {
+                        analyzer.error(SemanticAnalysisError::SymbolConflict {
+                            span: param.span(),
+                            prev_span: prev.span(),
+                        });
+                    }
+                }
+                templates.insert(procedure.name().clone(), procedure);
+            },
+            export => procedures.push(export),
+        }
+    }
+
+    let names = BTreeSet::from_iter(procedures.iter().map(|p| p.name().clone()));
+    let mut visitor = InstantiateGenerics::new(analyzer, &templates, &names);
+    for export in procedures.iter_mut() {
+        if let Export::Procedure(procedure) = export {
+            visitor.expand_procedure(procedure);
+        }
+    }
+    let (instances, instantiated) = visitor.finish();
+    module.procedures = procedures;
+    module.procedures.extend(instances.into_iter().map(Export::Procedure));
+
+    // Warn about generic procedures which are never instantiated
+    for template in templates.values() {
+        if !template.visibility().is_exported() && !instantiated.contains(template.name()) {
+            let lints = analyzer.lints().clone().with_attributes(template.attributes(), analyzer);
+            analyzer.lint(
+                &lints,
+                Lint::UnusedProcedure,
+                template.name().span(),
+                format!("unused procedure `{}`", template.name()),
+            );
+        }
+    }
+
+    analyzer.has_failed()
+}
+
+/// Collects the references to structs defined in other modules in `expr`, e.g. `note::NOTE` in
+/// `sizeof(note::NOTE)`.
+fn collect_imported_structs(expr: &ConstantExpr, paths: &mut Vec<StructPath>) {
+    match expr {
+        ConstantExpr::SizeOf { ty, .. } | ConstantExpr::OffsetOf { ty, .. } => {
+            if ty.is_imported() {
+                paths.push(ty.clone());
+            }
+        },
+        ConstantExpr::BinaryOp { lhs, rhs, .. } => {
+            collect_imported_structs(lhs, paths);
+            collect_imported_structs(rhs, paths);
+        },
+        ConstantExpr::Ternary { cond, then_expr, else_expr, .. } => {
+            collect_imported_structs(cond, paths);
+            collect_imported_structs(then_expr, paths);
+            collect_imported_structs(else_expr, paths);
+        },
+        ConstantExpr::Hash { args, .. } => {
+            for arg in args {
+                collect_imported_structs(arg, paths);
+            }
+        },
+        ConstantExpr::Event { name, .. } => collect_imported_structs(name, paths),
+        ConstantExpr::Literal(_)
+        | ConstantExpr::Var(_)
+        | ConstantExpr::String(_)
+        | ConstantExpr::Word(_) => (),
+    }
+}
+
 fn define_import(
     import: Import,
     module: &mut Module,
@@ -239,6 +509,39 @@ fn define_procedure(
     Ok(())
 }
 
+/// Adds a static data segment to an executable module, after checking that it is a valid
+/// segment which does not overlap any of the segments declared before it.
+fn add_data_segment(module: &mut Module, segment: DataSegment, context: &mut AnalysisContext) {
+    let address = *segment.address.inner();
+    if address % WORD_SIZE as u32 != 0 {
+        context.error(SemanticAnalysisError::MisalignedDataSegment {
+            span: segment.address.span(),
+            address,
+        });
+    }
+    if segment.data.is_empty() || segment.data.len() % WORD_SIZE != 0 {
+        context.error(SemanticAnalysisError::InvalidDataSegmentLength {
+            span: segment.span,
+            len: segment.data.len(),
+        });
+    }
+    let addresses = segment.addresses();
+    if addresses.end > 1u64 << 32 {
+        context.error(SemanticAnalysisError::DataSegmentOutOfBounds { span: segment.span });
+    }
+    let overlapping = module.data_segments.iter().find(|prev| {
+        let prev = prev.addresses();
+        prev.start < addresses.end && addresses.start < prev.end
+    });
+    if let Some(prev) = overlapping {
+        context.error(SemanticAnalysisError::OverlappingDataSegments {
+            span: segment.span,
+            prev_span: prev.span,
+        });
+    }
+    module.data_segments.push(segment);
+}
+
 /// Inserts a new entry in the Advice Map and defines a constant corresposnding to the entry's
 /// key.
 ///
@@ -258,6 +561,7 @@ fn add_advice_map_entry(
         ConstantExpr::Word(Span::new(entry.span, WordValue(*key))),
     );
     context.define_constant(cst)?;
+    context.mark_constant_used(&entry.name);
     match module.advice_map.get(&key) {
         Some(_) => {
             context.error(SemanticAnalysisError::AdvMapKeyAlreadyDefined { span: entry.span });
@@ -0,0 +1,563 @@
+use alloc::{
+    collections::{BTreeMap, BTreeSet},
+    vec::Vec,
+};
+
+use crate::{
+    Felt, SourceSpan,
+    ast::*,
+    parser::IntValue,
+    sema::{AnalysisContext, SemanticAnalysisError},
+};
+
+/// The maximum number of times the body of a loop is analyzed before giving up on the loop.
+const MAX_LOOP_ITERATIONS: usize = 256;
+
+/// This pass abstractly interprets the body of a procedure, tracking the depth of the operand
+/// stack and what is known about the values on it, and emits warnings for:
+///
+/// * `u32` instructions whose operands are the result of field arithmetic, or are read from the
+///   advice provider, without having been range-checked (e.g. via `u32assert`).
+/// * `if.true` blocks whose branches leave the stack at different depths.
+/// * `while.true` loops whose body changes the depth of the stack, i.e. does not leave exactly one
+///   element (the next loop condition) in addition to what it started with.
+///
+/// Values whose origin is unknown (e.g. the inputs of a procedure without a signature, or values
+/// loaded from memory) are never reported, so as to avoid false positives. Analysis of the rest
+/// of a block continues after invocations of procedures whose signatures are unknown, but with
+/// nothing known about the stack.
+pub struct AnalyzeStack<'a> {
+    analyzer: &'a mut AnalysisContext,
+    signatures: &'a BTreeMap<ProcedureName, ProcedureSignature>,
+    /// The spans for which a warning has already been emitted, since loop bodies may be analyzed
+    /// more than once
+    reported: BTreeSet<SourceSpan>,
+}
+
+impl<'a> AnalyzeStack<'a> {
+    pub fn new(
+        analyzer: &'a mut AnalysisContext,
+        signatures: &'a BTreeMap<ProcedureName, ProcedureSignature>,
+    ) -> Self {
+        Self {
+            analyzer,
+            signatures,
+            reported: Default::default(),
+        }
+    }
+
+    pub fn analyze_procedure(&mut self, procedure: &Procedure) {
+        let mut stack = AbstractStack::default();
+        if let Some(signature) = procedure.signature() {
+            for ty in signature.inputs.iter().rev() {
+                stack.push_type(*ty);
+            }
+            stack.depth = Some(0);
+        }
+        self.analyze_block(procedure.body(), &mut stack);
+    }
+
+    fn analyze_block(&mut self, block: &Block, stack: &mut AbstractStack) {
+        for op in block.iter() {
+            match op {
+                Op::Inst(inst) => self.analyze_instruction(inst.inner(), inst.span(), stack),
+                Op::If { span, then_blk, else_blk } => {
+                    stack.pop();
+                    let mut then_stack = stack.enter();
+                    self.analyze_block(then_blk, &mut then_stack);
+                    let mut else_stack = stack.enter();
+                    self.analyze_block(else_blk, &mut else_stack);
+
+                    if let (Some(then_delta), Some(else_delta)) =
+                        (then_stack.depth, else_stack.depth)
+                    {
+                        if then_delta != else_delta {
+                            self.warn(
+                                *span,
+                                SemanticAnalysisError::UnbalancedBranches {
+                                    span: *span,
+                                    then_delta,
+                                    else_delta,
+                                },
+                            );
+                        }
+                    }
+                    stack.exit(then_stack.join(&else_stack));
+                },
+                Op::Match { cases, default, .. } => {
+                    stack.pop();
+                    let mut default_stack = stack.enter();
+                    self.analyze_block(default, &mut default_stack);
+                    let joined = cases.iter().fold(default_stack, |joined, case| {
+                        let mut case_stack = stack.enter();
+                        self.analyze_block(&case.body, &mut case_stack);
+                        joined.join(&case_stack)
+                    });
+                    stack.exit(joined);
+                },
+                Op::While { span, body } => {
+                    stack.pop();
+                    self.analyze_loop(*span, body, stack, None, false);
+                },
+                Op::Repeat {
+                    span,
+                    count: Immediate::Value(count),
+                    body,
+                } => {
+                    self.analyze_loop(*span, body, stack, Some(count.into_inner() as usize), false);
+                },
+                Op::For {
+                    span,
+                    start,
+                    end: Immediate::Value(end),
+                    body,
+                } => {
+                    let start = match start {
+                        Some(Immediate::Value(start)) => start.into_inner(),
+                        Some(Immediate::Constant(_)) => {
+                            stack.clear();
+                            continue;
+                        },
+                        None => 0,
+                    };
+                    let count = end.into_inner().saturating_sub(start) as usize;
+                    self.analyze_loop(*span, body, stack, Some(count), true);
+                },
+                // The count is unresolved if it depends on a constant that could not be evaluated
+                // yet, e.g. one referring to the size or layout of a type, so nothing is known
+                // about the stack after the loop
+                Op::Repeat { .. } | Op::For { .. } => stack.clear(),
+            }
+        }
+    }
+
+    /// Analyzes a `while.true` loop if `count` is `None`, or a `repeat` or `for` loop otherwise,
+    /// where `counter` is whether a loop counter is pushed on the stack before each iteration.
+    ///
+    /// The body of a `repeat` loop is analyzed once per iteration, until the state of the stack no
+    /// longer changes between iterations. The body of a `while.true` loop is analyzed until the
+    /// state of the stack at the start of an iteration, joined over all iterations so far, no
+    /// longer changes. Loops for which this does not happen within [MAX_LOOP_ITERATIONS] are
+    /// assumed to leave nothing known about the stack.
+    fn analyze_loop(
+        &mut self,
+        span: SourceSpan,
+        body: &Block,
+        stack: &mut AbstractStack,
+        count: Option<usize>,
+        counter: bool,
+    ) {
+        let num_iterations = count.unwrap_or(MAX_LOOP_ITERATIONS).min(MAX_LOOP_ITERATIONS);
+        let mut head = stack.enter();
+        let mut converged = false;
+        for _ in 0..num_iterations {
+            let mut next = head.enter();
+            if counter {
+                next.push(Domain::U32);
+            }
+            self.analyze_block(body, &mut next);
+
+            let Some(delta) = next.depth else {
+                stack.clear();
+                return;
+            };
+            let next = match count {
+                Some(_) => AbstractStack {
+                    depth: head.depth.map(|depth| depth + delta),
+                    values: next.values,
+                },
+                None if delta == 1 => {
+                    next.pop();
+                    let joined = head.join(&next);
+                    AbstractStack { depth: head.depth, values: joined.values }
+                },
+                None => {
+                    self.warn(
+                        span,
+                        SemanticAnalysisError::StackDepthChangingLoop { span, delta: delta - 1 },
+                    );
+                    stack.clear();
+                    return;
+                },
+            };
+
+            if next == head {
+                converged = true;
+                break;
+            }
+            head = next;
+        }
+
+        // all iterations of a `repeat` loop may have been analyzed without reaching a fixed point
+        if converged || count.is_some_and(|count| count <= MAX_LOOP_ITERATIONS) {
+            stack.exit(head);
+        } else {
+            stack.clear();
+        }
+    }
+
+    fn analyze_instruction(
+        &mut self,
+        inst: &Instruction,
+        span: SourceSpan,
+        stack: &mut AbstractStack,
+    ) {
+        use Instruction::*;
+
+        if let Some(num_operands) = u32_operands(inst) {
+            if (0..num_operands).any(|i| stack.get(i) == Domain::Felt) {
+                self.warn(span, SemanticAnalysisError::UncheckedU32Operand { span });
+            }
+        }
+
+        match inst {
+            Drop => stack.drop_n(1),
+            DropW => stack.drop_n(4),
+            PadW => (0..4).for_each(|_| stack.push(Domain::Bool)),
+            Dup0 | Dup1 | Dup2 | Dup3 | Dup4 | Dup5 | Dup6 | Dup7 | Dup8 | Dup9 | Dup10 | Dup11
+            | Dup12 | Dup13 | Dup14 | Dup15 => {
+                let n = stack_index(inst);
+                stack.push(stack.get(n));
+            },
+            DupW0 | DupW1 | DupW2 | DupW3 => {
+                let n = 4 * stack_index(inst) + 3;
+                (0..4).for_each(|_| stack.push(stack.get(n)));
+            },
+            Swap1 | Swap2 | Swap3 | Swap4 | Swap5 | Swap6 | Swap7 | Swap8 | Swap9 | Swap10
+            | Swap11 | Swap12 | Swap13 | Swap14 | Swap15 => {
+                let n = stack_index(inst);
+                stack.permute(n + 1, |values| values.swap(0, n));
+            },
+            SwapW1 | SwapW2 | SwapW3 => {
+                let n = 4 * stack_index(inst);
+                stack.permute(n + 4, |values| (0..4).for_each(|i| values.swap(i, n + i)));
+            },
+            SwapDw => stack.permute(16, |values| (0..8).for_each(|i| values.swap(i, 8 + i))),
+            MovUp2 | MovUp3 | MovUp4 | MovUp5 | MovUp6 | MovUp7 | MovUp8 | MovUp9 | MovUp10
+            | MovUp11 | MovUp12 | MovUp13 | MovUp14 | MovUp15 => {
+                let n = stack_index(inst);
+                stack.permute(n + 1, |values| values[..=n].rotate_right(1));
+            },
+            MovDn2 | MovDn3 | MovDn4 | MovDn5 | MovDn6 | MovDn7 | MovDn8 | MovDn9 | MovDn10
+            | MovDn11 | MovDn12 | MovDn13 | MovDn14 | MovDn15 => {
+                let n = stack_index(inst);
+                stack.permute(n + 1, |values| values[..=n].rotate_left(1));
+            },
+            MovUpW2 | MovUpW3 => {
+                let n = 4 * stack_index(inst);
+                stack.permute(n + 4, |values| values[..n + 4].rotate_right(4));
+            },
+            MovDnW2 | MovDnW3 => {
+                let n = 4 * stack_index(inst);
+                stack.permute(n + 4, |values| values[..n + 4].rotate_left(4));
+            },
+            CSwap | CSwapW | CDrop | CDropW => {
+                let n = if matches!(inst, CSwap | CDrop) { 1 } else { 4 };
+                stack.pop();
+                stack.permute(2 * n, |values| {
+                    for i in 0..n {
+                        let joined = values[i].join(values[n + i]);
+                        values[i] = joined;
+                        values[n + i] = joined;
+                    }
+                });
+                if matches!(inst, CDrop | CDropW) {
+                    stack.drop_n(n);
+                }
+            },
+
+            U32Assert | U32AssertWithError(_) => stack.assert_u32(1),
+            U32Assert2 | U32Assert2WithError(_) => stack.assert_u32(2),
+            U32AssertW | U32AssertWWithError(_) => stack.assert_u32(4),
+            U32Test | U32TestW | Eqw => stack.push(Domain::Bool),
+            MemStoreW => stack.drop_n(1),
+            SysEvent(_)
+            | LocStoreW(_)
+            | MemStoreWImm(_)
+            | MTreeVerify
+            | MTreeVerifyWithError(_) => {},
+
+            Push(Immediate::Value(value)) => match value.inner() {
+                IntValue::U8(v) => stack.push(Domain::of_u64(*v as u64)),
+                IntValue::U16(v) => stack.push(Domain::of_u64(*v as u64)),
+                IntValue::U32(v) => stack.push(Domain::of_u64(*v as u64)),
+                IntValue::Felt(v) => stack.push(Domain::of_felt(*v)),
+                IntValue::Word(word) => {
+                    word.0.iter().for_each(|v| stack.push(Domain::of_felt(*v)));
+                },
+            },
+            PushU8(v) => stack.push(Domain::of_u64(*v as u64)),
+            PushU16(v) => stack.push(Domain::of_u64(*v as u64)),
+            PushU32(v) => stack.push(Domain::of_u64(*v as u64)),
+            PushFelt(v) => stack.push(Domain::of_felt(*v)),
+            PushWord(word) => word.0.iter().for_each(|v| stack.push(Domain::of_felt(*v))),
+            PushU8List(values) => values.iter().for_each(|v| stack.push(Domain::of_u64(*v as u64))),
+            PushU16List(values) => {
+                values.iter().for_each(|v| stack.push(Domain::of_u64(*v as u64)))
+            },
+            PushU32List(values) => {
+                values.iter().for_each(|v| stack.push(Domain::of_u64(*v as u64)))
+            },
+            PushFeltList(values) => values.iter().for_each(|v| stack.push(Domain::of_felt(*v))),
+
+            Exec(target) | Call(target) | SysCall(target) => {
+                let signature = match target {
+                    InvocationTarget::ProcedureName(name) => self.signatures.get(name),
+                    _ => None,
+                };
+                match signature {
+                    Some(signature) => {
+                        stack.drop_n(signature.num_input_felts());
+                        for ty in signature.outputs.iter().rev() {
+                            stack.push_type(*ty);
+                        }
+                    },
+                    None => stack.clear(),
+                }
+            },
+
+            inst => match inst.stack_effect() {
+                Some(effect) => {
+                    stack.drop_n(effect.inputs);
+                    let domain = output_domain(inst);
+                    (0..effect.outputs).for_each(|_| stack.push(domain));
+                },
+                None => stack.clear(),
+            },
+        }
+    }
+
+    fn warn(&mut self, span: SourceSpan, warning: SemanticAnalysisError) {
+        if self.reported.insert(span) {
+            self.analyzer.error(warning);
+        }
+    }
+}
+
+// DOMAIN
+// ================================================================================================
+
+/// What is known about a value on the operand stack.
+///
+/// Domains are ordered from most to least specific, such that joining two domains yields the
+/// greater of the two.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum Domain {
+    /// The value is known to be either 0 or 1.
+    Bool,
+    /// The value is known to be a valid u32 value.
+    U32,
+    /// The value is the result of field arithmetic, or was read from the advice provider, and has
+    /// not been range-checked.
+    Felt,
+    /// Nothing is known about the value.
+    Unknown,
+}
+
+impl Domain {
+    fn of_u64(value: u64) -> Self {
+        match value {
+            0 | 1 => Self::Bool,
+            v if v <= u32::MAX as u64 => Self::U32,
+            _ => Self::Felt,
+        }
+    }
+
+    fn of_felt(value: Felt) -> Self {
+        Self::of_u64(value.as_int())
+    }
+
+    fn join(self, other: Self) -> Self {
+        self.max(other)
+    }
+}
+
+// ABSTRACT STACK
+// ================================================================================================
+
+/// An abstraction of the operand stack.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+struct AbstractStack {
+    /// The domains of the values at the top of the stack, with the top of the stack last. Values
+    /// below these are unknown.
+    values: Vec<Domain>,
+    /// The depth of the stack relative to the start of the innermost enclosing block whose effect
+    /// on the stack is being computed, if known.
+    depth: Option<isize>,
+}
+
+impl AbstractStack {
+    /// Returns a copy of this stack for analyzing a nested block, with depth relative to the
+    /// start of that block.
+    fn enter(&self) -> Self {
+        Self {
+            values: self.values.clone(),
+            depth: Some(0),
+        }
+    }
+
+    /// Updates this stack with the state at the end of a nested block started via [Self::enter].
+    fn exit(&mut self, nested: Self) {
+        self.values = nested.values;
+        self.depth = match (self.depth, nested.depth) {
+            (Some(depth), Some(delta)) => Some(depth + delta),
+            _ => None,
+        };
+    }
+
+    /// Forgets everything known about this stack.
+    fn clear(&mut self) {
+        self.values.clear();
+        self.depth = None;
+    }
+
+    /// Returns a stack describing the values on either this stack or `other`.
+    fn join(&self, other: &Self) -> Self {
+        let len = self.values.len().max(other.values.len());
+        let values = (0..len)
+            .rev()
+            .map(|i| {
+                if self.depth == other.depth {
+                    self.get(i).join(other.get(i))
+                } else {
+                    Domain::Unknown
+                }
+            })
+            .collect();
+        let depth = if self.depth == other.depth { self.depth } else { None };
+        Self { values, depth }
+    }
+
+    /// Returns the domain of the `n`-th value from the top of the stack.
+    fn get(&self, n: usize) -> Domain {
+        self.values.iter().rev().nth(n).copied().unwrap_or(Domain::Unknown)
+    }
+
+    fn push(&mut self, domain: Domain) {
+        self.values.push(domain);
+        self.depth = self.depth.map(|depth| depth + 1);
+    }
+
+    fn push_type(&mut self, ty: StackType) {
+        match ty {
+            StackType::Felt | StackType::Word => {
+                (0..ty.size_in_felts()).for_each(|_| self.push(Domain::Felt))
+            },
+            StackType::U32 | StackType::U64 => {
+                (0..ty.size_in_felts()).for_each(|_| self.push(Domain::U32))
+            },
+        }
+    }
+
+    fn pop(&mut self) -> Domain {
+        self.depth = self.depth.map(|depth| depth - 1);
+        self.values.pop().unwrap_or(Domain::Unknown)
+    }
+
+    fn drop_n(&mut self, n: usize) {
+        (0..n).for_each(|_| {
+            self.pop();
+        });
+    }
+
+    /// Marks the top `n` values as valid u32 values.
+    fn assert_u32(&mut self, n: usize) {
+        self.permute(n, |values| {
+            values.iter_mut().for_each(|domain| *domain = (*domain).min(Domain::U32))
+        });
+    }
+
+    /// Applies `f` to the top `n` values of the stack, given with the top of the stack first.
+    fn permute(&mut self, n: usize, f: impl FnOnce(&mut [Domain])) {
+        if self.values.len() < n {
+            let missing = n - self.values.len();
+            self.values.splice(0..0, (0..missing).map(|_| Domain::Unknown));
+        }
+        let start = self.values.len() - n;
+        let top = &mut self.values[start..];
+        top.reverse();
+        f(top);
+        top.reverse();
+    }
+}
+
+// HELPERS
+// ================================================================================================
+
+/// Returns the stack index (or word index) encoded in the name of a stack manipulation
+/// instruction, e.g. 3 for `dup.3` or `movupw.3`.
+fn stack_index(inst: &Instruction) -> usize {
+    use Instruction::*;
+
+    match inst {
+        Dup0 | DupW0 => 0,
+        Dup1 | Swap1 | DupW1 | SwapW1 => 1,
+        Dup2 | Swap2 | MovUp2 | MovDn2 | DupW2 | SwapW2 | MovUpW2 | MovDnW2 => 2,
+        Dup3 | Swap3 | MovUp3 | MovDn3 | DupW3 | SwapW3 | MovUpW3 | MovDnW3 => 3,
+        Dup4 | Swap4 | MovUp4 | MovDn4 => 4,
+        Dup5 | Swap5 | MovUp5 | MovDn5 => 5,
+        Dup6 | Swap6 | MovUp6 | MovDn6 => 6,
+        Dup7 | Swap7 | MovUp7 | MovDn7 => 7,
+        Dup8 | Swap8 | MovUp8 | MovDn8 => 8,
+        Dup9 | Swap9 | MovUp9 | MovDn9 => 9,
+        Dup10 | Swap10 | MovUp10 | MovDn10 => 10,
+        Dup11 | Swap11 | MovUp11 | MovDn11 => 11,
+        Dup12 | Swap12 | MovUp12 | MovDn12 => 12,
+        Dup13 | Swap13 | MovUp13 | MovDn13 => 13,
+        Dup14 | Swap14 | MovUp14 | MovDn14 => 14,
+        Dup15 | Swap15 | MovUp15 | MovDn15 => 15,
+        _ => unreachable!("not an indexed stack manipulation instruction: {inst}"),
+    }
+}
+
+/// Returns the number of operands of `inst` which must be valid u32 values, if `inst` is a `u32`
+/// instruction whose behavior is undefined for other values.
+fn u32_operands(inst: &Instruction) -> Option<usize> {
+    use Instruction::*;
+
+    match inst {
+        U32WrappingAdd | U32OverflowingAdd | U32WrappingSub | U32OverflowingSub
+        | U32WrappingMul | U32OverflowingMul | U32Div | U32Mod | U32DivMod | U32And | U32Or
+        | U32Xor | U32Shr | U32Shl | U32Rotr | U32Rotl | U32Lt | U32Lte | U32Gt | U32Gte
+        | U32Min | U32Max => Some(2),
+        U32WrappingAddImm(_)
+        | U32OverflowingAddImm(_)
+        | U32WrappingSubImm(_)
+        | U32OverflowingSubImm(_)
+        | U32WrappingMulImm(_)
+        | U32OverflowingMulImm(_)
+        | U32DivImm(_)
+        | U32ModImm(_)
+        | U32DivModImm(_)
+        | U32ShrImm(_)
+        | U32ShlImm(_)
+        | U32RotrImm(_)
+        | U32RotlImm(_)
+        | U32Not
+        | U32Popcnt
+        | U32Ctz
+        | U32Clz
+        | U32Clo
+        | U32Cto => Some(1),
+        U32OverflowingAdd3 | U32WrappingAdd3 | U32OverflowingMadd | U32WrappingMadd => Some(3),
+        _ => None,
+    }
+}
+
+/// Returns the domain of the values produced by `inst`, for instructions which are not handled
+/// specially by [AnalyzeStack].
+fn output_domain(inst: &Instruction) -> Domain {
+    use Instruction::*;
+
+    match inst {
+        Eq | EqImm(_) | Neq | NeqImm(_) | Lt | Lte | Gt | Gte | IsOdd | Not | And | Or | Xor
+        | U32Lt | U32Lte | U32Gt | U32Gte => Domain::Bool,
+        inst if u32_operands(inst).is_some() => Domain::U32,
+        U32Split | U32Cast | ILog2 | Sdepth | Clk | Locaddr(_) => Domain::U32,
+        Add | AddImm(_) | Sub | SubImm(_) | Mul | MulImm(_) | Div | DivImm(_) | Neg | Inv
+        | Incr | Pow2 | Exp | ExpImm(_) | ExpBitLength(_) | Ext2Add | Ext2Sub | Ext2Mul
+        | Ext2Div | Ext2Neg | Ext2Inv | AdvPush(_) | AdvLoadW | Hash | HMerge | HPerm
+        | MTreeGet | MTreeSet | MTreeMerge | ProcRef(_) | Caller => Domain::Felt,
+        _ => Domain::Unknown,
+    }
+}
@@ -1,13 +1,111 @@
-use alloc::sync::Arc;
-use core::ops::ControlFlow;
+use alloc::{collections::BTreeMap, sync::Arc, vec::Vec};
+use core::{fmt, ops::ControlFlow};
+
+use miden_core::{FieldElement, StarkField, Word, crypto::hash::Rpo256, sys_events};
 
 use crate::{
-    Felt, Span, Spanned,
+    Felt, SourceSpan, Span, Spanned,
     ast::*,
     parser::IntValue,
     sema::{AnalysisContext, SemanticAnalysisError},
 };
 
+// CONSTANT EVALUATION
+// ================================================================================================
+
+/// Represents the ways in which evaluating a constant expression can fail.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ConstEvalError {
+    /// The divisor of a division or remainder is zero
+    DivisionByZero,
+    /// The result of an integer operation does not fit in a field element
+    Overflow,
+    /// A string is hashed together with other data
+    InvalidHashInput,
+}
+
+impl fmt::Display for ConstEvalError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::DivisionByZero => f.write_str("division by zero"),
+            Self::Overflow => f.write_str("value overflowed the field modulus"),
+            Self::InvalidHashInput => f.write_str("strings can only be hashed on their own"),
+        }
+    }
+}
+
+impl ConstantOp {
+    /// Applies this operator to `lhs` and `rhs`.
+    ///
+    /// `+`, `-`, `*` and `/` are field operations, and so wrap around the field modulus. All other
+    /// operators treat their operands as unsigned integers, and fail rather than wrap if the result
+    /// does not fit in a field element. Comparisons produce `1` if they hold, and `0` otherwise.
+    pub fn eval(self, lhs: Felt, rhs: Felt) -> Result<Felt, ConstEvalError> {
+        let (a, b) = (lhs.as_int(), rhs.as_int());
+        let int = |value: Option<u64>| match value {
+            Some(value) if value < Felt::MODULUS => Ok(Felt::new(value)),
+            _ => Err(ConstEvalError::Overflow),
+        };
+        let bool = |value: bool| Ok(if value { Felt::ONE } else { Felt::ZERO });
+        match self {
+            Self::Add => Ok(lhs + rhs),
+            Self::Sub => Ok(lhs - rhs),
+            Self::Mul => Ok(lhs * rhs),
+            Self::Div if rhs == Felt::ZERO => Err(ConstEvalError::DivisionByZero),
+            Self::Div => Ok(lhs / rhs),
+            Self::IntDiv | Self::Mod if b == 0 => Err(ConstEvalError::DivisionByZero),
+            Self::IntDiv => Ok(Felt::new(a / b)),
+            Self::Mod => Ok(Felt::new(a % b)),
+            Self::Pow => int(u32::try_from(b).ok().and_then(|b| a.checked_pow(b))),
+            Self::And => Ok(Felt::new(a & b)),
+            Self::Or => int(Some(a | b)),
+            Self::Xor => int(Some(a ^ b)),
+            Self::Shl => int(u32::try_from(b)
+                .ok()
+                .and_then(|b| a.checked_shl(b))
+                .filter(|value| value >> b == a)),
+            Self::Shr => {
+                Ok(Felt::new(u32::try_from(b).ok().and_then(|b| a.checked_shr(b)).unwrap_or(0)))
+            },
+            Self::Eq => bool(a == b),
+            Self::Neq => bool(a != b),
+            Self::Lt => bool(a < b),
+            Self::Lte => bool(a <= b),
+            Self::Gt => bool(a > b),
+            Self::Gte => bool(a >= b),
+        }
+    }
+}
+
+impl ConstantExpr {
+    /// Computes `hash(args)`, the RPO digest of the constant data `args`.
+    ///
+    /// Returns `Ok(None)` if some argument has yet to be evaluated to a value.
+    pub fn eval_hash(args: &[ConstantExpr]) -> Result<Option<Word>, ConstEvalError> {
+        if let [ConstantExpr::String(data)] = args {
+            return Ok(Some(Rpo256::hash(data.as_bytes())));
+        }
+        let mut elements = Vec::with_capacity(args.len());
+        for arg in args {
+            match arg {
+                ConstantExpr::Literal(value) => elements.push(value.into_inner()),
+                ConstantExpr::Word(value) => elements.extend_from_slice(&value.inner().0),
+                ConstantExpr::String(_) => return Err(ConstEvalError::InvalidHashInput),
+                _ => return Ok(None),
+            }
+        }
+        Ok(Some(Rpo256::hash_elements(&elements)))
+    }
+
+    /// Computes `event(name)`, the ID of the event called `name`.
+    pub fn eval_event(name: &str) -> Felt {
+        Felt::from(sys_events::event_id_from_name(name))
+    }
+}
+
+// CONSTANT EVALUATION VISITOR
+// ================================================================================================
+
 /// This visitor evaluates all constant expressions and folds them to literals.
 pub struct ConstEvalVisitor<'analyzer> {
     analyzer: &'analyzer mut AnalysisContext,
@@ -37,6 +135,8 @@ impl ConstEvalVisitor<'_> {
                             self.analyzer.error(SemanticAnalysisError::ImmediateOverflow { span });
                         },
                     },
+                    // Resolved when the module is linked
+                    Ok(value) if value.is_deferred() => (),
                     Err(error) => {
                         self.analyzer.error(error);
                     },
@@ -49,6 +149,58 @@ impl ConstEvalVisitor<'_> {
 }
 
 impl VisitMut for ConstEvalVisitor<'_> {
+    fn visit_mut_op(&mut self, op: &mut Op) -> ControlFlow<()> {
+        match op {
+            Op::Repeat { count, .. } => {
+                self.eval_const(count)?;
+                if let Immediate::Value(value) = count {
+                    if value.into_inner() == 0 {
+                        self.analyzer.error(SemanticAnalysisError::InvalidRepeatCount {
+                            span: value.span(),
+                        });
+                    }
+                }
+            },
+            Op::For { span, start, end, .. } => {
+                if let Some(start) = start {
+                    self.eval_const(start)?;
+                }
+                self.eval_const(end)?;
+                let start = match start {
+                    Some(Immediate::Value(start)) => Some(start.into_inner()),
+                    Some(Immediate::Constant(_)) => None,
+                    None => Some(0),
+                };
+                if let (Some(start), Immediate::Value(end)) = (start, &*end) {
+                    let end = end.into_inner();
+                    if start >= end {
+                        self.analyzer.error(SemanticAnalysisError::EmptyLoopRange {
+                            span: *span,
+                            start,
+                            end,
+                        });
+                    }
+                }
+            },
+            Op::Match { cases, .. } => {
+                let mut seen = BTreeMap::<u32, SourceSpan>::default();
+                for case in cases.iter_mut() {
+                    self.eval_const(&mut case.value)?;
+                    if let Immediate::Value(value) = &case.value {
+                        if let Some(prev_span) = seen.insert(value.into_inner(), value.span()) {
+                            self.analyzer.error(SemanticAnalysisError::DuplicateMatchCase {
+                                span: value.span(),
+                                prev_span,
+                                value: value.into_inner(),
+                            });
+                        }
+                    }
+                }
+            },
+            _ => (),
+        }
+        visit::visit_mut_op(self, op)
+    }
     fn visit_mut_immediate_u8(&mut self, imm: &mut Immediate<u8>) -> ControlFlow<()> {
         self.eval_const(imm)
     }
@@ -87,6 +239,8 @@ impl VisitMut for ConstEvalVisitor<'_> {
                     Ok(ConstantExpr::Literal(value)) => {
                         *imm = Immediate::Value(Span::new(span, *value.inner()));
                     },
+                    // Resolved when the module is linked
+                    Ok(value) if value.is_deferred() => (),
                     Err(error) => {
                         self.analyzer.error(error);
                     },
@@ -109,6 +263,8 @@ impl VisitMut for ConstEvalVisitor<'_> {
                     Ok(ConstantExpr::Word(value)) => {
                         *imm = Immediate::Value(Span::new(span, IntValue::Word(*value.inner())));
                     },
+                    // Resolved when the module is linked
+                    Ok(value) if value.is_deferred() => (),
                     Err(error) => {
                         self.analyzer.error(error);
                     },
@@ -0,0 +1,259 @@
+use alloc::{
+    collections::{BTreeMap, BTreeSet, VecDeque},
+    format,
+    string::ToString,
+    sync::Arc,
+    vec::Vec,
+};
+use core::ops::ControlFlow;
+
+use crate::{
+    Felt, Span, Spanned,
+    ast::*,
+    parser::IntValue,
+    sema::{AnalysisContext, SemanticAnalysisError},
+};
+
+/// This pass expands the instantiations of generic procedures, e.g. `exec.foo(4, N)`.
+///
+/// Each distinct combination of a generic procedure and argument values is instantiated as a
+/// separate private procedure, in which the parameters of the generic procedure are replaced with
+/// the values of the arguments, and each instantiation is rewritten to invoke that procedure.
+/// Instances are named after the generic procedure and the values of their arguments, e.g.
+/// `"foo<4,8>"`, so that instantiating a procedure twice with the same values yields the same
+/// procedure.
+///
+/// Instantiations within generic procedures are expanded as their instances are created, with
+/// their arguments evaluated using the values bound to the parameters of the instance.
+pub struct InstantiateGenerics<'a> {
+    analyzer: &'a mut AnalysisContext,
+    /// The generic procedures of the current module
+    templates: &'a BTreeMap<ProcedureName, Procedure>,
+    /// The names of the non-generic procedures of the current module
+    procedures: &'a BTreeSet<ProcedureName>,
+    /// The instances created so far, by generic procedure and argument values
+    instances: BTreeMap<(ProcedureName, Vec<u64>), ProcedureName>,
+    /// The instances whose bodies remain to be expanded, along with the values bound to their
+    /// parameters, and the generic procedures they were transitively instantiated from
+    pending: VecDeque<(Procedure, BTreeMap<Ident, Felt>, Vec<ProcedureName>)>,
+    /// The values bound to the parameters of the procedure being expanded
+    bindings: BTreeMap<Ident, Felt>,
+    /// The generic procedures from which the procedure being expanded was instantiated
+    chain: Vec<ProcedureName>,
+}
+
+impl<'a> InstantiateGenerics<'a> {
+    pub fn new(
+        analyzer: &'a mut AnalysisContext,
+        templates: &'a BTreeMap<ProcedureName, Procedure>,
+        procedures: &'a BTreeSet<ProcedureName>,
+    ) -> Self {
+        Self {
+            analyzer,
+            templates,
+            procedures,
+            instances: Default::default(),
+            pending: Default::default(),
+            bindings: Default::default(),
+            chain: Default::default(),
+        }
+    }
+
+    /// Expands the instantiations in the body of `procedure`, a non-generic procedure.
+    pub fn expand_procedure(&mut self, procedure: &mut Procedure) {
+        let _ = self.visit_mut_procedure(procedure);
+    }
+
+    /// Expands the bodies of all instances created so far, and of the instances they create in
+    /// turn.
+    ///
+    /// Returns the instances, along with the names of the generic procedures which were
+    /// instantiated at least once.
+    pub fn finish(mut self) -> (Vec<Procedure>, BTreeSet<ProcedureName>) {
+        let mut instances = Vec::with_capacity(self.pending.len());
+        while let Some((mut instance, bindings, chain)) = self.pending.pop_front() {
+            self.bindings = bindings;
+            self.chain = chain;
+            let _ = self.visit_mut_procedure(&mut instance);
+            instances.push(instance);
+        }
+        let instantiated = self.instances.into_keys().map(|(name, _)| name).collect();
+        (instances, instantiated)
+    }
+
+    /// Returns the name of the instance of the generic procedure `name` for `args`, creating it if
+    /// this is the first instantiation with those values.
+    ///
+    /// Returns `None` if the instantiation is invalid, in which case an error was reported.
+    fn instantiate(
+        &mut self,
+        name: &ProcedureName,
+        args: &[ConstantExpr],
+    ) -> Option<ProcedureName> {
+        let span = name.span();
+        let Some(template) = self.templates.get(name) else {
+            if self.procedures.contains(name) {
+                self.analyzer.error(SemanticAnalysisError::NotGeneric { span });
+            } else {
+                self.analyzer.error(SemanticAnalysisError::SymbolUndefined { span });
+            }
+            return None;
+        };
+        if args.len() != template.params().len() {
+            self.analyzer.error(SemanticAnalysisError::InstantiationArityMismatch {
+                span,
+                expected: template.params().len(),
+                actual: args.len(),
+            });
+            return None;
+        }
+        if self.chain.contains(name) {
+            self.analyzer.error(SemanticAnalysisError::RecursiveInstantiation { span });
+            return None;
+        }
+
+        let values = args.iter().map(|arg| self.eval_arg(arg)).collect::<Vec<_>>();
+        let values = values.into_iter().collect::<Option<Vec<_>>>()?;
+        let key = (name.clone(), values.iter().map(Felt::as_int).collect::<Vec<_>>());
+        if let Some(instance) = self.instances.get(&key) {
+            return Some(instance.clone());
+        }
+
+        let args = key.1.iter().map(u64::to_string).collect::<Vec<_>>().join(",");
+        let instance_name = Arc::<str>::from(format!("{name}<{args}>").into_boxed_str());
+        let instance_name = ProcedureName::from_raw_parts(Ident::from_raw_parts(Span::new(
+            template.name().span(),
+            instance_name,
+        )));
+        let bindings = template.params().iter().cloned().zip(values).collect();
+        let mut chain = self.chain.clone();
+        chain.push(name.clone());
+        self.pending
+            .push_back((template.instantiate(instance_name.clone()), bindings, chain));
+        self.instances.insert(key, instance_name.clone());
+        Some(instance_name)
+    }
+
+    /// Evaluates `arg`, an argument of an instantiation, to a field element.
+    fn eval_arg(&mut self, arg: &ConstantExpr) -> Option<Felt> {
+        let span = arg.span();
+        match self.analyzer.const_eval(&self.bind(arg)) {
+            Ok(ConstantExpr::Literal(value)) => Some(value.into_inner()),
+            Ok(_) => {
+                self.analyzer.error(SemanticAnalysisError::InvalidConstant { span });
+                None
+            },
+            Err(error) => {
+                self.analyzer.error(error);
+                None
+            },
+        }
+    }
+
+    /// Replaces the references to parameters of the procedure being expanded in `expr` with the
+    /// values bound to them.
+    fn bind(&self, expr: &ConstantExpr) -> ConstantExpr {
+        match expr {
+            ConstantExpr::Var(name) => match self.bindings.get(name) {
+                Some(value) => ConstantExpr::Literal(Span::new(name.span(), *value)),
+                None => expr.clone(),
+            },
+            ConstantExpr::BinaryOp { span, op, lhs, rhs } => ConstantExpr::BinaryOp {
+                span: *span,
+                op: *op,
+                lhs: self.bind(lhs).into(),
+                rhs: self.bind(rhs).into(),
+            },
+            ConstantExpr::Ternary { span, cond, then_expr, else_expr } => ConstantExpr::Ternary {
+                span: *span,
+                cond: self.bind(cond).into(),
+                then_expr: self.bind(then_expr).into(),
+                else_expr: self.bind(else_expr).into(),
+            },
+            ConstantExpr::Hash { span, args } => ConstantExpr::Hash {
+                span: *span,
+                args: args.iter().map(|arg| self.bind(arg)).collect(),
+            },
+            ConstantExpr::Event { span, name } => ConstantExpr::Event {
+                span: *span,
+                name: self.bind(name).into(),
+            },
+            ConstantExpr::Literal(_)
+            | ConstantExpr::String(_)
+            | ConstantExpr::Word(_)
+            | ConstantExpr::SizeOf { .. }
+            | ConstantExpr::OffsetOf { .. } => expr.clone(),
+        }
+    }
+
+    /// Replaces `imm` with the value bound to it, if it refers to a parameter of the procedure
+    /// being expanded.
+    fn bind_immediate<T>(
+        &mut self,
+        imm: &mut Immediate<T>,
+        convert: impl FnOnce(Felt) -> Option<T>,
+    ) {
+        let Immediate::Constant(name) = imm else {
+            return;
+        };
+        let Some(value) = self.bindings.get(name).copied() else {
+            return;
+        };
+        let span = name.span();
+        match convert(value) {
+            Some(value) => *imm = Immediate::Value(Span::new(span, value)),
+            None => self.analyzer.error(SemanticAnalysisError::ImmediateOverflow { span }),
+        }
+    }
+}
+
+impl VisitMut for InstantiateGenerics<'_> {
+    fn visit_mut_invoke_target(&mut self, target: &mut InvocationTarget) -> ControlFlow<()> {
+        match target {
+            InvocationTarget::ProcedureName(name) if self.templates.contains_key(name) => {
+                self.analyzer
+                    .error(SemanticAnalysisError::UninstantiatedGeneric { span: name.span() });
+            },
+            InvocationTarget::Instantiation { name, args } => {
+                if let Some(instance) = self.instantiate(name, args) {
+                    *target = InvocationTarget::ProcedureName(instance.with_span(name.span()));
+                }
+            },
+            _ => (),
+        }
+        ControlFlow::Continue(())
+    }
+    fn visit_mut_immediate_u8(&mut self, imm: &mut Immediate<u8>) -> ControlFlow<()> {
+        self.bind_immediate(imm, |value| u8::try_from(value.as_int()).ok());
+        ControlFlow::Continue(())
+    }
+    fn visit_mut_immediate_u16(&mut self, imm: &mut Immediate<u16>) -> ControlFlow<()> {
+        self.bind_immediate(imm, |value| u16::try_from(value.as_int()).ok());
+        ControlFlow::Continue(())
+    }
+    fn visit_mut_immediate_u32(&mut self, imm: &mut Immediate<u32>) -> ControlFlow<()> {
+        self.bind_immediate(imm, |value| u32::try_from(value.as_int()).ok());
+        ControlFlow::Continue(())
+    }
+    fn visit_mut_immediate_felt(&mut self, imm: &mut Immediate<Felt>) -> ControlFlow<()> {
+        self.bind_immediate(imm, Some);
+        ControlFlow::Continue(())
+    }
+    fn visit_mut_immediate_hex(&mut self, imm: &mut Immediate<IntValue>) -> ControlFlow<()> {
+        self.bind_immediate(imm, |value| Some(IntValue::Felt(value)));
+        ControlFlow::Continue(())
+    }
+    fn visit_mut_immediate_error_message(
+        &mut self,
+        imm: &mut Immediate<Arc<str>>,
+    ) -> ControlFlow<()> {
+        // Parameters are bound to field elements, so they cannot be used as error messages
+        if let Immediate::Constant(name) = imm {
+            if self.bindings.contains_key(name) {
+                self.analyzer
+                    .error(SemanticAnalysisError::InvalidConstant { span: name.span() });
+            }
+        }
+        ControlFlow::Continue(())
+    }
+}
@@ -0,0 +1,164 @@
+use alloc::{collections::BTreeSet, format};
+
+use crate::{
+    SourceSpan, Span, Spanned,
+    ast::*,
+    parser::IntValue,
+    sema::{AnalysisContext, Lint, LintConfig},
+};
+
+/// This pass runs the lints which apply to the body of a single procedure, and records which
+/// local procedures the procedure refers to, so that unused procedures can be detected once all
+/// procedures of the module have been visited.
+///
+/// It must run before constant evaluation, so that literal immediates can be distinguished from
+/// references to named constants.
+pub struct LintProcedure<'a> {
+    analyzer: &'a mut AnalysisContext,
+    /// The lint levels in effect for the procedure
+    config: &'a LintConfig,
+    /// The local procedures whose body consists of a single instruction
+    trivial: &'a BTreeSet<ProcedureName>,
+    /// The local procedures referenced so far
+    referenced: &'a mut BTreeSet<ProcedureName>,
+}
+
+impl<'a> LintProcedure<'a> {
+    pub fn new(
+        analyzer: &'a mut AnalysisContext,
+        config: &'a LintConfig,
+        trivial: &'a BTreeSet<ProcedureName>,
+        referenced: &'a mut BTreeSet<ProcedureName>,
+    ) -> Self {
+        Self { analyzer, config, trivial, referenced }
+    }
+
+    pub fn lint_procedure(&mut self, procedure: &Procedure) {
+        if procedure.visibility().is_exported()
+            && !procedure.is_entrypoint()
+            && procedure.docs().is_none()
+        {
+            self.analyzer.lint(
+                self.config,
+                Lint::MissingDocs,
+                procedure.name().span(),
+                format!("missing docstring for exported procedure `{}`", procedure.name()),
+            );
+        }
+
+        self.lint_block(procedure.body());
+    }
+
+    fn lint_block(&mut self, block: &Block) {
+        let mut prev: Option<&Span<Instruction>> = None;
+        for op in block.iter() {
+            let Op::Inst(inst) = op else {
+                prev = None;
+                match op {
+                    Op::If { then_blk, else_blk, .. } => {
+                        self.lint_block(then_blk);
+                        self.lint_block(else_blk);
+                    },
+                    Op::While { body, .. } | Op::Repeat { body, .. } | Op::For { body, .. } => {
+                        self.lint_block(body)
+                    },
+                    Op::Match { cases, default, .. } => {
+                        for case in cases.iter() {
+                            self.lint_block(&case.body);
+                        }
+                        self.lint_block(default);
+                    },
+                    Op::Inst(_) => unreachable!(),
+                }
+                continue;
+            };
+
+            if let Some(prev) = prev {
+                if is_redundant_pair(prev.inner(), inst.inner()) {
+                    let span = prev.span();
+                    let span = SourceSpan::new(span.source_id(), span.start()..inst.span().end());
+                    self.analyzer.lint(
+                        self.config,
+                        Lint::RedundantPushDrop,
+                        span,
+                        "value is pushed onto the stack and then immediately dropped",
+                    );
+                }
+            }
+            self.lint_instruction(inst);
+            prev = Some(inst);
+        }
+    }
+
+    fn lint_instruction(&mut self, inst: &Span<Instruction>) {
+        match inst.inner() {
+            Instruction::Exec(target)
+            | Instruction::Call(target)
+            | Instruction::SysCall(target)
+            | Instruction::ProcRef(target) => {
+                let InvocationTarget::ProcedureName(name) = target else {
+                    return;
+                };
+                self.referenced.insert(name.clone());
+                if matches!(inst.inner(), Instruction::Exec(_)) && self.trivial.contains(name) {
+                    self.analyzer.lint(
+                        self.config,
+                        Lint::TrivialExec,
+                        inst.span(),
+                        format!(
+                            "`{name}` consists of a single instruction, which could be used \
+                             directly instead"
+                        ),
+                    );
+                }
+            },
+            Instruction::Emit(Immediate::Value(id)) => {
+                self.analyzer.lint(
+                    self.config,
+                    Lint::MagicEmitEvent,
+                    inst.span(),
+                    format!("event id {id} should be given a name using a constant"),
+                );
+            },
+            _ => (),
+        }
+    }
+}
+
+/// Returns true if `first` only pushes values onto the stack which `second` then drops.
+fn is_redundant_pair(first: &Instruction, second: &Instruction) -> bool {
+    use Instruction::*;
+
+    match second {
+        Drop => {
+            matches!(
+                first,
+                PushU8(_)
+                    | PushU16(_)
+                    | PushU32(_)
+                    | PushFelt(_)
+                    | Dup0
+                    | Dup1
+                    | Dup2
+                    | Dup3
+                    | Dup4
+                    | Dup5
+                    | Dup6
+                    | Dup7
+                    | Dup8
+                    | Dup9
+                    | Dup10
+                    | Dup11
+                    | Dup12
+                    | Dup13
+                    | Dup14
+                    | Dup15
+            ) || matches!(first, Push(Immediate::Value(value)) if !matches!(value.inner(), IntValue::Word(_)))
+        },
+        DropW => {
+            matches!(first, PushWord(_) | PadW | DupW0 | DupW1 | DupW2 | DupW3)
+                || matches!(first, Push(Immediate::Value(value)) if matches!(value.inner(), IntValue::Word(_)))
+        },
+        _ => false,
+    }
+}
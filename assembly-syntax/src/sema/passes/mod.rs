@@ -1,4 +1,15 @@
+mod analyze_stack;
 mod const_eval;
+mod instantiate;
+mod lints;
 mod verify_invoke;
+mod verify_stack_effects;
 
-pub use self::{const_eval::ConstEvalVisitor, verify_invoke::VerifyInvokeTargets};
+pub use self::{
+    analyze_stack::AnalyzeStack,
+    const_eval::{ConstEvalError, ConstEvalVisitor},
+    instantiate::InstantiateGenerics,
+    lints::LintProcedure,
+    verify_invoke::VerifyInvokeTargets,
+    verify_stack_effects::{VerifyStackEffects, parse_signature},
+};
@@ -119,6 +119,9 @@ impl VisitMut for VerifyInvokeTargets<'_> {
             // We assume that a syscall specifying a MAST root knows what it is doing, but this
             // will be validated by the assembler
             InvocationTarget::MastRoot(_) => (),
+            InvocationTarget::Instantiation { .. } => {
+                unreachable!("instantiations of generic procedures are expanded before this pass")
+            },
         }
         self.invoked.insert(Invoke::new(InvokeKind::SysCall, target.clone()));
         ControlFlow::Continue(())
@@ -161,6 +164,9 @@ impl VisitMut for VerifyInvokeTargets<'_> {
                     *target = new_target;
                 }
             },
+            InvocationTarget::Instantiation { .. } => {
+                unreachable!("instantiations of generic procedures are expanded before this pass")
+            },
         }
         ControlFlow::Continue(())
     }
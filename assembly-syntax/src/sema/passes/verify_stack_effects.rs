@@ -0,0 +1,251 @@
+use alloc::{collections::BTreeMap, vec::Vec};
+
+use crate::{
+    SourceSpan, Spanned,
+    ast::*,
+    sema::{AnalysisContext, SemanticAnalysisError},
+};
+
+/// Derives the signature of `procedure` from its `@inputs` and `@outputs` attributes, if present.
+///
+/// Any malformed signature attributes are reported to `analyzer`, in which case `None` is
+/// returned.
+pub fn parse_signature(
+    procedure: &Procedure,
+    analyzer: &mut AnalysisContext,
+) -> Option<ProcedureSignature> {
+    let inputs = procedure.get_attribute(ProcedureSignature::INPUTS_ATTRIBUTE);
+    let outputs = procedure.get_attribute(ProcedureSignature::OUTPUTS_ATTRIBUTE);
+    if inputs.is_none() && outputs.is_none() {
+        return None;
+    }
+
+    let inputs = inputs.map(|attr| parse_stack_types(attr, analyzer)).unwrap_or(Some(Vec::new()));
+    let outputs = outputs
+        .map(|attr| parse_stack_types(attr, analyzer))
+        .unwrap_or(Some(Vec::new()));
+    Some(ProcedureSignature::new(inputs?, outputs?))
+}
+
+fn parse_stack_types(attr: &Attribute, analyzer: &mut AnalysisContext) -> Option<Vec<StackType>> {
+    let Attribute::List(list) = attr else {
+        analyzer.error(SemanticAnalysisError::InvalidSignature { span: attr.span() });
+        return None;
+    };
+
+    let mut types = Vec::with_capacity(list.len());
+    let mut valid = true;
+    for item in list.as_slice() {
+        match item {
+            MetaExpr::Ident(id) => match id.as_str().parse::<StackType>() {
+                Ok(ty) => types.push(ty),
+                Err(()) => {
+                    analyzer.error(SemanticAnalysisError::InvalidSignature { span: id.span() });
+                    valid = false;
+                },
+            },
+            MetaExpr::String(id) => {
+                analyzer.error(SemanticAnalysisError::InvalidSignature { span: id.span() });
+                valid = false;
+            },
+            MetaExpr::Int(value) => {
+                analyzer.error(SemanticAnalysisError::InvalidSignature { span: value.span() });
+                valid = false;
+            },
+        }
+    }
+
+    valid.then_some(types)
+}
+
+/// This pass infers the effect of the body of a procedure on the operand stack, and checks it
+/// against the signatures of the procedure and of the local procedures it invokes.
+///
+/// Specifically:
+///
+/// * If the procedure has a signature and the stack effect of its body could be inferred, the two
+///   must agree. Inputs which are not accessed by the body are considered to be passed through, so
+///   a procedure with signature `(felt, felt) -> (felt)` may consist of just `drop`.
+/// * If the procedure has a signature, every invocation of a local procedure which also has a
+///   signature must not consume more stack elements than are available at the call site.
+///
+/// Inference is limited to straight-line code, conditionals whose branches have the same effect,
+/// and `repeat` loops. Effects of `while` loops, dynamic calls, and invocations of procedures
+/// without a known signature are unknown, in which case no further checking is done.
+pub struct VerifyStackEffects<'a> {
+    analyzer: &'a mut AnalysisContext,
+    signatures: &'a BTreeMap<ProcedureName, ProcedureSignature>,
+    /// The number of elements available on entry to the current procedure, if known
+    available: Option<usize>,
+}
+
+impl<'a> VerifyStackEffects<'a> {
+    pub fn new(
+        analyzer: &'a mut AnalysisContext,
+        signatures: &'a BTreeMap<ProcedureName, ProcedureSignature>,
+    ) -> Self {
+        Self { analyzer, signatures, available: None }
+    }
+
+    pub fn verify_procedure(&mut self, procedure: &Procedure) {
+        let signature = procedure.signature();
+        self.available = signature.map(ProcedureSignature::num_input_felts);
+
+        let Some(actual) = self.block_effect(procedure.body(), Some(StackEffect::default())) else {
+            return;
+        };
+        let Some(signature) = signature else {
+            return;
+        };
+
+        let expected = signature.stack_effect();
+        let actual = if actual.inputs <= expected.inputs {
+            actual.widen(expected.inputs)
+        } else {
+            actual
+        };
+        if actual != expected {
+            let span = procedure
+                .get_attribute(ProcedureSignature::INPUTS_ATTRIBUTE)
+                .or_else(|| procedure.get_attribute(ProcedureSignature::OUTPUTS_ATTRIBUTE))
+                .map(|attr| attr.span())
+                .unwrap_or_else(|| procedure.name().span());
+            self.analyzer.error(SemanticAnalysisError::SignatureMismatch {
+                span,
+                signature: signature.clone(),
+                expected,
+                actual,
+            });
+        }
+    }
+
+    /// Returns the effect of `block`, composed with `prefix` if given.
+    ///
+    /// Call sites are only checked when `prefix` is given, i.e. it is the effect of all the code
+    /// executed since the start of the procedure.
+    fn block_effect(&mut self, block: &Block, prefix: Option<StackEffect>) -> Option<StackEffect> {
+        let check_calls = prefix.is_some();
+        let mut effect = prefix.unwrap_or_default();
+        for op in block.iter() {
+            let prefix = check_calls.then_some(effect);
+            effect = match op {
+                Op::Inst(inst) => {
+                    effect.then(self.instruction_effect(inst.inner(), inst.span(), prefix)?)
+                },
+                Op::If { then_blk, else_blk, .. } => {
+                    let cond = effect.then(StackEffect::new(1, 0));
+                    let prefix = check_calls.then_some(cond);
+                    let then_effect = self.block_effect(then_blk, prefix);
+                    let else_effect = self.block_effect(else_blk, prefix);
+                    let branch = then_effect?.join(else_effect?)?;
+                    if check_calls { branch } else { cond.then(branch) }
+                },
+                Op::Repeat { count, body, .. } => {
+                    // the count is only unresolved if evaluating it failed, which was reported
+                    let Immediate::Value(count) = count else {
+                        return None;
+                    };
+                    self.loop_effect(body, count.into_inner(), None, effect, check_calls)?
+                },
+                Op::For { start, end, body, .. } => {
+                    // the bounds are only unresolved if evaluating them failed, which was reported
+                    let start = match start {
+                        Some(Immediate::Value(start)) => start.into_inner(),
+                        Some(Immediate::Constant(_)) => return None,
+                        None => 0,
+                    };
+                    let Immediate::Value(end) = end else {
+                        return None;
+                    };
+                    let count = end.into_inner().checked_sub(start)?;
+                    // the loop counter is pushed on the stack before each iteration
+                    let counter = StackEffect::new(0, 1);
+                    self.loop_effect(body, count, Some(counter), effect, check_calls)?
+                },
+                Op::Match { cases, default, .. } => {
+                    let value = effect.then(StackEffect::new(1, 0));
+                    let prefix = check_calls.then_some(value);
+                    let case_effects = cases
+                        .iter()
+                        .map(|case| self.block_effect(&case.body, prefix))
+                        .collect::<Vec<_>>();
+                    let mut branch = self.block_effect(default, prefix);
+                    for case_effect in case_effects {
+                        branch = branch?.join(case_effect?);
+                    }
+                    let branch = branch?;
+                    if check_calls { branch } else { value.then(branch) }
+                },
+                Op::While { .. } => return None,
+            };
+        }
+
+        Some(effect)
+    }
+
+    /// Returns the effect of `count` iterations of the loop `body`, each preceded by `counter` if
+    /// given, composed with `effect`.
+    fn loop_effect(
+        &mut self,
+        body: &Block,
+        count: u32,
+        counter: Option<StackEffect>,
+        effect: StackEffect,
+        check_calls: bool,
+    ) -> Option<StackEffect> {
+        let counter = counter.unwrap_or_default();
+        let body_effect = counter.then(self.block_effect(body, None)?);
+        // the stack cannot hold more than 2^16 elements, so such loops would fail
+        if body_effect.net() != 0 && count > u16::MAX as u32 {
+            return None;
+        }
+        let mut effect = if check_calls {
+            self.block_effect(body, Some(effect.then(counter)))?
+        } else {
+            effect.then(body_effect)
+        };
+        for _ in 1..count {
+            let next = effect.then(body_effect);
+            if next == effect {
+                break;
+            }
+            effect = next;
+        }
+        Some(effect)
+    }
+
+    fn instruction_effect(
+        &mut self,
+        inst: &Instruction,
+        span: SourceSpan,
+        prefix: Option<StackEffect>,
+    ) -> Option<StackEffect> {
+        let target = match inst {
+            Instruction::Exec(target)
+            | Instruction::Call(target)
+            | Instruction::SysCall(target) => target,
+            inst => return inst.stack_effect(),
+        };
+        let InvocationTarget::ProcedureName(name) = target else {
+            return None;
+        };
+        let signature = self.signatures.get(name)?;
+        let callee = signature.stack_effect();
+
+        if let (Some(available), Some(prefix)) = (self.available, prefix) {
+            if prefix.inputs <= available {
+                let available = available - prefix.inputs + prefix.outputs;
+                if callee.inputs > available {
+                    self.analyzer.error(SemanticAnalysisError::CallSignatureMismatch {
+                        span,
+                        signature: signature.clone(),
+                        expected: callee.inputs,
+                        available,
+                    });
+                }
+            }
+        }
+
+        Some(callee)
+    }
+}
@@ -3,7 +3,7 @@ use alloc::{boxed::Box, sync::Arc, vec::Vec};
 #[cfg(feature = "std")]
 use crate::diagnostics::reporting::set_panic_hook;
 use crate::{
-    LibraryPath, Parse, ParseOptions,
+    CfgFlags, LibraryPath, Lint, LintConfig, LintLevel, Parse, ParseOptions,
     ast::{Form, Module, ModuleKind},
     diagnostics::{
         DefaultSourceManager, Report, SourceFile, SourceManager,
@@ -22,6 +22,8 @@ use crate::{
 pub struct SyntaxTestContext {
     source_manager: Arc<dyn SourceManager + Send + Sync>,
     warnings_as_errors: bool,
+    lints: LintConfig,
+    cfg: CfgFlags,
 }
 
 impl Default for SyntaxTestContext {
@@ -55,6 +57,8 @@ impl SyntaxTestContext {
         Self {
             source_manager,
             warnings_as_errors: false,
+            lints: LintConfig::default(),
+            cfg: CfgFlags::default(),
         }
     }
 
@@ -63,6 +67,16 @@ impl SyntaxTestContext {
         self
     }
 
+    pub fn with_lint_level(mut self, lint: Lint, level: LintLevel) -> Self {
+        self.lints.set_level(lint, level);
+        self
+    }
+
+    pub fn with_cfg_flag(mut self, flag: &str) -> Self {
+        self.cfg.set(flag);
+        self
+    }
+
     #[inline(always)]
     pub fn source_manager(&self) -> Arc<dyn SourceManager + Send + Sync> {
         self.source_manager.clone()
@@ -88,6 +102,8 @@ impl SyntaxTestContext {
             self.source_manager.as_ref(),
             ParseOptions {
                 warnings_as_errors: self.warnings_as_errors,
+                lints: self.lints.clone(),
+                cfg: self.cfg.clone(),
                 ..Default::default()
             },
         )
@@ -104,6 +120,8 @@ impl SyntaxTestContext {
             self.source_manager.as_ref(),
             ParseOptions {
                 warnings_as_errors: self.warnings_as_errors,
+                lints: self.lints.clone(),
+                cfg: self.cfg.clone(),
                 ..ParseOptions::for_kernel()
             },
         )
@@ -119,6 +137,8 @@ impl SyntaxTestContext {
             self.source_manager.as_ref(),
             ParseOptions {
                 warnings_as_errors: self.warnings_as_errors,
+                lints: self.lints.clone(),
+                cfg: self.cfg.clone(),
                 ..ParseOptions::for_library()
             },
         )
@@ -135,6 +155,8 @@ impl SyntaxTestContext {
             self.source_manager.as_ref(),
             ParseOptions {
                 warnings_as_errors: self.warnings_as_errors,
+                lints: self.lints.clone(),
+                cfg: self.cfg.clone(),
                 ..ParseOptions::new(ModuleKind::Library, path).unwrap()
             },
         )
@@ -1,25 +1,36 @@
-use alloc::{collections::BTreeMap, string::ToString, sync::Arc, vec::Vec};
+use alloc::{
+    collections::BTreeMap,
+    format,
+    string::{String, ToString},
+    sync::Arc,
+    vec::Vec,
+};
 
 use miden_assembly_syntax::{
-    self as syntax, DefaultSourceManager, KernelLibrary, Library, LibraryNamespace, LibraryPath,
-    Parse, ParseOptions, SemanticAnalysisError, SourceManager, Spanned,
+    self as syntax, CfgFlags, DefaultSourceManager, KernelLibrary, Library, LibraryNamespace,
+    LibraryPath, Lint, LintConfig, LintLevel, Parse, ParseOptions, SemanticAnalysisError,
+    SourceManager, Spanned,
     ast::{self, Export, InvocationTarget, InvokeKind, ModuleKind, QualifiedProcedureName},
     diagnostics::{RelatedLabel, Report},
 };
 use miden_core::{
-    AssemblyOp, Decorator, Felt, Kernel, Operation, Program, WORD_SIZE, Word,
-    debuginfo::SourceSpan,
-    mast::{DecoratorId, MastNodeId},
+    AssemblyOp, Decorator, Felt, Kernel, KernelVersion, Operation, Program, WORD_SIZE, Word,
+    debuginfo::{SourceMap, SourceSpan},
+    mast::{DecoratorId, MastForest, MastNode, MastNodeId},
 };
 
 use crate::{
-    GlobalProcedureIndex, ModuleIndex, Procedure, ProcedureContext,
+    CompilationCache, GlobalProcedureIndex, InliningReport, ModuleIndex, Procedure,
+    ProcedureContext,
     basic_block_builder::{BasicBlockBuilder, BasicBlockOrDecorators},
+    cache::CacheSession,
+    data_segments::initialize_data_segments,
     linker::{
         CallerInfo, LinkLibrary, LinkLibraryKind, Linker, LinkerError, ModuleLink, ProcedureLink,
         ResolvedTarget,
     },
     mast_forest_builder::MastForestBuilder,
+    match_lowering::store_jump_tables,
 };
 
 // ASSEMBLER
@@ -78,8 +89,25 @@ pub struct Assembler {
     linker: Linker,
     /// Whether to treat warning diagnostics as errors
     warnings_as_errors: bool,
+    /// The levels of the lints run on modules parsed by the assembler
+    lints: LintConfig,
+    /// The flags against which `@cfg` attributes are evaluated in modules parsed by the assembler
+    cfg: CfgFlags,
     /// Whether the assembler enables extra debugging information.
     in_debug_mode: bool,
+    /// The directory against which the source maps of libraries assembled in debug mode are
+    /// computed, if any
+    source_root: Option<Arc<str>>,
+    /// The version of the kernel of this assembler, if it has a versioned kernel
+    kernel_version: Option<KernelVersion>,
+    /// Whether the assembler runs the peephole optimizer over the operations of basic blocks
+    optimize: bool,
+    /// The cache of previously compiled procedures to reuse, if any
+    cache: Option<CompilationCache>,
+    /// The report in which to record the inlining decisions made during assembly, if any
+    inlining_report: Option<InliningReport>,
+    /// The number of iterations from which `repeat` loops are rolled into a `LOOP` node, if any
+    repeat_rolling_threshold: Option<u32>,
 }
 
 impl Default for Assembler {
@@ -90,7 +118,15 @@ impl Default for Assembler {
             source_manager,
             linker,
             warnings_as_errors: false,
+            lints: LintConfig::default(),
+            cfg: CfgFlags::default(),
             in_debug_mode: false,
+            source_root: None,
+            kernel_version: None,
+            optimize: true,
+            cache: None,
+            inlining_report: None,
+            repeat_rolling_threshold: None,
         }
     }
 }
@@ -105,7 +141,15 @@ impl Assembler {
             source_manager,
             linker,
             warnings_as_errors: false,
+            lints: LintConfig::default(),
+            cfg: CfgFlags::default(),
             in_debug_mode: false,
+            source_root: None,
+            kernel_version: None,
+            optimize: true,
+            cache: None,
+            inlining_report: None,
+            repeat_rolling_threshold: None,
         }
     }
 
@@ -114,11 +158,13 @@ impl Assembler {
         source_manager: Arc<dyn SourceManager + Send + Sync>,
         kernel_lib: KernelLibrary,
     ) -> Self {
+        let kernel_version = kernel_lib.version().cloned();
         let (kernel, kernel_module, _) = kernel_lib.into_parts();
         let linker = Linker::with_kernel(source_manager.clone(), kernel, kernel_module);
         Self {
             source_manager,
             linker,
+            kernel_version,
             ..Default::default()
         }
     }
@@ -131,6 +177,57 @@ impl Assembler {
         self
     }
 
+    /// Sets the level of `lint` for all modules parsed by this assembler.
+    ///
+    /// This can be overridden in individual modules and procedures using the `@allow`, `@warn` and
+    /// `@deny` attributes.
+    pub fn with_lint_level(mut self, lint: Lint, level: LintLevel) -> Self {
+        self.lints.set_level(lint, level);
+        self
+    }
+
+    /// Sets the levels of the lints run on all modules parsed by this assembler.
+    pub fn with_lints(mut self, lints: LintConfig) -> Self {
+        self.lints = lints;
+        self
+    }
+
+    /// Sets the `@cfg` flag `flag` for all modules parsed by this assembler.
+    ///
+    /// Items annotated with `@cfg(feature = "flag")` are only assembled when `flag` is set, and
+    /// items annotated with `@cfg(not_feature = "flag")` only when it is not.
+    pub fn with_cfg_flag(mut self, flag: impl Into<Arc<str>>) -> Self {
+        self.cfg.set(flag);
+        self
+    }
+
+    /// Sets the flags against which `@cfg` attributes are evaluated in all modules parsed by this
+    /// assembler.
+    pub fn with_cfg_flags(mut self, cfg: CfgFlags) -> Self {
+        self.cfg = cfg;
+        self
+    }
+
+    /// Sets the cache of compiled procedures used by this assembler.
+    ///
+    /// Procedures whose fingerprint matches the one stored in the cache are reused instead of
+    /// being compiled, and the cache is updated with the procedures compiled by the assembler. See
+    /// [CompilationCache] for details.
+    pub fn with_compilation_cache(mut self, cache: CompilationCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Sets the report in which the assembler records its inlining decisions.
+    ///
+    /// The report is updated once assembly completes, and lists, for each compiled procedure, the
+    /// number of invocation sites at which it was inlined or kept out-of-line. See
+    /// [InliningReport] for details.
+    pub fn with_inlining_report(mut self, report: InliningReport) -> Self {
+        self.inlining_report = Some(report);
+        self
+    }
+
     /// Puts the assembler into the debug mode.
     pub fn with_debug_mode(mut self, yes: bool) -> Self {
         self.in_debug_mode = yes;
@@ -141,6 +238,54 @@ impl Assembler {
     pub fn set_debug_mode(&mut self, yes: bool) {
         self.in_debug_mode = yes;
     }
+
+    /// Sets the directory against which the source maps of libraries are computed.
+    ///
+    /// When the assembler is in debug mode, libraries it assembles ship a [SourceMap] recording
+    /// the paths, relative to `root`, of the source files referenced by their decorators, so that
+    /// the sources can be found under a different root when the library is debugged. Source files
+    /// which are not under `root` are omitted from the source map.
+    pub fn with_source_root(mut self, root: impl Into<Arc<str>>) -> Self {
+        self.source_root = Some(root.into());
+        self
+    }
+
+    /// Sets whether the assembler runs the peephole optimizer, which is enabled by default.
+    ///
+    /// The peephole optimizer rewrites short sequences of operations within a basic block into
+    /// shorter, equivalent sequences, e.g. `Swap Swap` is removed, and `Push(1) Add` becomes
+    /// `Incr`. Operations are never moved across `debug` or `trace` decorators.
+    pub fn with_peephole_optimizer(mut self, yes: bool) -> Self {
+        self.optimize = yes;
+        self
+    }
+
+    /// Sets whether the assembler runs the peephole optimizer.
+    pub fn set_peephole_optimizer(&mut self, yes: bool) {
+        self.optimize = yes;
+    }
+
+    /// Sets whether warning diagnostics emitted by the assembler are promoted to errors
+    pub fn set_warnings_as_errors(&mut self, yes: bool) {
+        self.warnings_as_errors = yes;
+    }
+
+    /// Sets the number of iterations from which `repeat` loops are rolled into a `LOOP` node
+    /// rather than unrolled, which is disabled by default.
+    ///
+    /// Rolling a `repeat` loop reduces the size of the MAST, at the cost of a few cycles per
+    /// iteration to maintain the loop counter, which is stored in a local allocated by the
+    /// assembler.
+    pub fn with_repeat_rolling_threshold(mut self, threshold: u32) -> Self {
+        self.repeat_rolling_threshold = Some(threshold);
+        self
+    }
+
+    /// Sets the number of iterations from which `repeat` loops are rolled into a `LOOP` node, or
+    /// disables rolling if `None`, see [Self::with_repeat_rolling_threshold].
+    pub fn set_repeat_rolling_threshold(&mut self, threshold: Option<u32>) {
+        self.repeat_rolling_threshold = threshold;
+    }
 }
 
 // ------------------------------------------------------------------------------------------------
@@ -169,6 +314,8 @@ impl Assembler {
                     &self.source_manager,
                     ParseOptions {
                         warnings_as_errors: self.warnings_as_errors,
+                        lints: self.lints.clone(),
+                        cfg: self.cfg.clone(),
                         ..ParseOptions::for_library()
                     },
                 )
@@ -204,7 +351,8 @@ impl Assembler {
         namespace: crate::LibraryNamespace,
         dir: impl AsRef<std::path::Path>,
     ) -> Result<(), Report> {
-        let modules = syntax::parser::read_modules_from_dir(namespace, dir, &self.source_manager)?;
+        let modules =
+            syntax::parser::read_modules_from_dir(namespace, dir, &self.source_manager, &self.cfg)?;
         self.linker.link_modules(modules)?;
         Ok(())
     }
@@ -227,11 +375,11 @@ impl Assembler {
         library: impl AsRef<Library>,
         kind: LinkLibraryKind,
     ) -> Result<(), Report> {
+        let library = library.as_ref();
+        self.check_library_kernel(library)?;
+        self.check_library_versions(library)?;
         self.linker
-            .link_library(LinkLibrary {
-                library: Arc::new(library.as_ref().clone()),
-                kind,
-            })
+            .link_library(LinkLibrary { library: Arc::new(library.clone()), kind })
             .map_err(Report::from)
     }
 
@@ -261,9 +409,7 @@ impl Assembler {
     /// example, unexpected procedure paths or source locations in diagnostics - it could be due
     /// to this edge case.
     pub fn link_dynamic_library(&mut self, library: impl AsRef<Library>) -> Result<(), Report> {
-        self.linker
-            .link_library(LinkLibrary::dynamic(Arc::new(library.as_ref().clone())))
-            .map_err(Report::from)
+        self.link_library(library, LinkLibraryKind::Dynamic)
     }
 
     /// Dynamically link against `library` during assembly.
@@ -283,9 +429,7 @@ impl Assembler {
     /// Static linking produces larger binaries, but allows you to produce self-contained artifacts
     /// that avoid the requirement that you provide `library` to the VM at runtime.
     pub fn link_static_library(&mut self, library: impl AsRef<Library>) -> Result<(), Report> {
-        self.linker
-            .link_library(LinkLibrary::r#static(Arc::new(library.as_ref().clone())))
-            .map_err(Report::from)
+        self.link_library(library, LinkLibraryKind::Static)
     }
 
     /// Statically link against `library` during assembly.
@@ -305,11 +449,48 @@ impl Assembler {
         self.warnings_as_errors
     }
 
+    /// Returns the levels of the lints run on modules parsed by this assembler.
+    pub fn lints(&self) -> &LintConfig {
+        &self.lints
+    }
+
+    /// Returns the flags against which `@cfg` attributes are evaluated in modules parsed by this
+    /// assembler.
+    pub fn cfg_flags(&self) -> &CfgFlags {
+        &self.cfg
+    }
+
     /// Returns true if this assembler was instantiated in debug mode.
     pub fn in_debug_mode(&self) -> bool {
         self.in_debug_mode
     }
 
+    /// Returns the directory against which the source maps of libraries are computed, if any.
+    pub fn source_root(&self) -> Option<&str> {
+        self.source_root.as_deref()
+    }
+
+    /// Returns true if the assembler runs the peephole optimizer.
+    pub fn peephole_optimizer_enabled(&self) -> bool {
+        self.optimize
+    }
+
+    /// Returns the number of iterations from which `repeat` loops are rolled into a `LOOP` node,
+    /// if rolling is enabled.
+    pub fn repeat_rolling_threshold(&self) -> Option<u32> {
+        self.repeat_rolling_threshold
+    }
+
+    /// Returns the cache of compiled procedures used by this assembler, if any.
+    pub fn compilation_cache(&self) -> Option<&CompilationCache> {
+        self.cache.as_ref()
+    }
+
+    /// Returns the report in which this assembler records its inlining decisions, if any.
+    pub fn inlining_report(&self) -> Option<&InliningReport> {
+        self.inlining_report.as_ref()
+    }
+
     /// Returns a reference to the kernel for this assembler.
     ///
     /// If the assembler was instantiated without a kernel, the internal kernel will be empty.
@@ -348,6 +529,8 @@ impl Assembler {
                     &self.source_manager,
                     ParseOptions {
                         warnings_as_errors: self.warnings_as_errors,
+                        lints: self.lints.clone(),
+                        cfg: self.cfg.clone(),
                         ..ParseOptions::for_library()
                     },
                 )
@@ -394,7 +577,9 @@ impl Assembler {
         let path = path.as_ref();
 
         let source_manager = self.source_manager.clone();
-        let modules = syntax::parser::read_modules_from_dir(namespace, path, &source_manager)?;
+        let cfg = self.cfg.clone();
+        let modules =
+            syntax::parser::read_modules_from_dir(namespace, path, &source_manager, &cfg)?;
         self.assemble_library(modules)
     }
 
@@ -409,6 +594,8 @@ impl Assembler {
             ParseOptions {
                 path: Some(LibraryPath::new_from_components(LibraryNamespace::Kernel, [])),
                 warnings_as_errors: self.warnings_as_errors,
+                lints: self.lints.clone(),
+                cfg: self.cfg.clone(),
                 ..ParseOptions::for_kernel()
             },
         )?;
@@ -456,6 +643,8 @@ impl Assembler {
             }
         });
         let mut mast_forest_builder = MastForestBuilder::new(staticlibs)?;
+        let mut cache = self.start_cache_session();
+        let mut signatures = BTreeMap::new();
         let mut exports = {
             let mut exports = BTreeMap::new();
 
@@ -468,12 +657,15 @@ impl Assembler {
 
                 for (proc_idx, fqn) in ast_module.exported_procedures() {
                     let gid = module_idx + proc_idx;
-                    self.compile_subgraph(gid, &mut mast_forest_builder)?;
+                    self.compile_subgraph(gid, &mut mast_forest_builder, cache.as_mut())?;
 
                     let proc_root_node_id = mast_forest_builder
                         .get_procedure(gid)
                         .expect("compilation succeeded but root not found in cache")
                         .body_node_id();
+                    if let Some(signature) = ast_module[proc_idx].signature() {
+                        signatures.insert(fqn.clone(), signature.clone());
+                    }
                     exports.insert(fqn, proc_root_node_id);
                 }
             }
@@ -481,14 +673,37 @@ impl Assembler {
             exports
         };
 
+        if let Some(report) = self.inlining_report.as_ref() {
+            report.set(mast_forest_builder.inlining().clone());
+        }
         let (mast_forest, id_remappings) = mast_forest_builder.build();
+        if let Some(cache) = cache {
+            cache.finish(&self.linker, &mast_forest, &id_remappings);
+        }
         for (_proc_name, node_id) in exports.iter_mut() {
             if let Some(&new_node_id) = id_remappings.get(node_id) {
                 *node_id = new_node_id;
             }
         }
 
-        Ok(Library::new(mast_forest.into(), exports)?)
+        let source_map = match self.source_root.as_deref() {
+            Some(root) if self.in_debug_mode => {
+                SourceMap::new(&mast_forest, &*self.source_manager, root)
+            },
+            _ => SourceMap::default(),
+        };
+
+        let kernel_version =
+            self.kernel_version.filter(|_| syscall_targets(&mast_forest).next().is_some());
+
+        let library = Library::new(mast_forest.into(), exports)?
+            .with_signatures(signatures)
+            .with_cfg_flags(self.cfg)
+            .with_source_map(source_map);
+        Ok(match kernel_version {
+            Some(kernel_version) => library.with_kernel_version(kernel_version),
+            None => library,
+        })
     }
 
     /// Compiles the provided module into a [`Program`]. The resulting program can be executed on
@@ -502,6 +717,8 @@ impl Assembler {
         let options = ParseOptions {
             kind: ModuleKind::Executable,
             warnings_as_errors: self.warnings_as_errors,
+            lints: self.lints.clone(),
+            cfg: self.cfg.clone(),
             path: Some(LibraryPath::from(LibraryNamespace::Exec)),
         };
 
@@ -528,35 +745,88 @@ impl Assembler {
             }
         });
         let mut mast_forest_builder = MastForestBuilder::new(staticlibs)?;
+        let mut cache = self.start_cache_session();
 
-        mast_forest_builder
-            .merge_advice_map(self.linker[module_index].unwrap_ast().advice_map())?;
+        let program = self.linker[module_index].unwrap_ast();
+        mast_forest_builder.merge_advice_map(program.advice_map())?;
+        let data_segments = program.data_segments().cloned().collect::<Vec<_>>();
 
-        self.compile_subgraph(entrypoint, &mut mast_forest_builder)?;
+        self.compile_subgraph(entrypoint, &mut mast_forest_builder, cache.as_mut())?;
         let entry_node_id = mast_forest_builder
             .get_procedure(entrypoint)
             .expect("compilation succeeded but root not found in cache")
             .body_node_id();
+        let entry_node_id =
+            initialize_data_segments(&data_segments, entry_node_id, &mut mast_forest_builder)?;
 
         // in case the node IDs changed, update the entrypoint ID to the new value
+        if let Some(report) = self.inlining_report.as_ref() {
+            report.set(mast_forest_builder.inlining().clone());
+        }
         let (mast_forest, id_remappings) = mast_forest_builder.build();
+        if let Some(cache) = cache {
+            cache.finish(&self.linker, &mast_forest, &id_remappings);
+        }
         let entry_node_id = *id_remappings.get(&entry_node_id).unwrap_or(&entry_node_id);
 
-        Ok(Program::with_kernel(
-            mast_forest.into(),
-            entry_node_id,
-            self.linker.kernel().clone(),
-        ))
+        let program =
+            Program::with_kernel(mast_forest.into(), entry_node_id, self.linker.kernel().clone());
+        Ok(match self.kernel_version {
+            Some(kernel_version) => program.with_kernel_version(kernel_version),
+            None => program,
+        })
+    }
+
+    /// Returns an error if `library` invokes via syscall a procedure which is not in the kernel of
+    /// this assembler, i.e. if it was assembled against a different kernel.
+    fn check_library_kernel(&self, library: &Library) -> Result<(), LinkerError> {
+        let kernel = self.linker.kernel();
+        match syscall_targets(library.mast_forest()).find(|target| !kernel.contains_proc(*target)) {
+            Some(proc_root) => Err(LinkerError::KernelMismatch {
+                expected: library.kernel_version().cloned(),
+                found: self.kernel_version.clone(),
+                proc_root,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns an error if `library` and a library already linked by this assembler are
+    /// incompatible, i.e. if either of them requires a version of the other which it does not have.
+    fn check_library_versions(&self, library: &Library) -> Result<(), LinkerError> {
+        for linked in self.linker.libraries() {
+            check_library_dependencies(library, &linked.library)?;
+            check_library_dependencies(&linked.library, library)?;
+        }
+        Ok(())
+    }
+
+    /// Starts a session of the compilation cache of this assembler, if any, once all inputs have
+    /// been linked.
+    fn start_cache_session(&self) -> Option<CacheSession> {
+        self.cache.clone().map(|cache| {
+            CacheSession::new(
+                cache,
+                &self.linker,
+                self.in_debug_mode,
+                self.optimize,
+                self.repeat_rolling_threshold,
+                &self.cfg,
+            )
+        })
     }
 
     /// Compile the uncompiled procedure in the linked module graph which are members of the
     /// subgraph rooted at `root`, placing them in the MAST forest builder once compiled.
     ///
+    /// Procedures found in the compilation `cache`, if provided, are reused rather than compiled.
+    ///
     /// Returns an error if any of the provided Miden Assembly is invalid.
     fn compile_subgraph(
         &mut self,
         root: GlobalProcedureIndex,
         mast_forest_builder: &mut MastForestBuilder,
+        cache: Option<&mut CacheSession>,
     ) -> Result<(), Report> {
         let mut worklist: Vec<GlobalProcedureIndex> = self
             .linker
@@ -577,7 +847,7 @@ impl Assembler {
 
         assert!(!worklist.is_empty());
 
-        self.process_graph_worklist(&mut worklist, mast_forest_builder)
+        self.process_graph_worklist(&mut worklist, mast_forest_builder, cache)
     }
 
     /// Compiles all procedures in the `worklist`.
@@ -585,6 +855,7 @@ impl Assembler {
         &mut self,
         worklist: &mut Vec<GlobalProcedureIndex>,
         mast_forest_builder: &mut MastForestBuilder,
+        mut cache: Option<&mut CacheSession>,
     ) -> Result<(), Report> {
         // Process the topological ordering in reverse order (bottom-up), so that
         // each procedure is compiled with all of its dependencies fully compiled
@@ -602,6 +873,12 @@ impl Assembler {
                 ModuleLink::Info(_) => continue,
             };
 
+            // Fingerprint the procedure, so that it can be looked up in the cache, and so that its
+            // dependents can be fingerprinted in turn
+            if let Some(cache) = cache.as_deref_mut() {
+                cache.fingerprint(procedure_gid, &self.linker, &self.source_manager);
+            }
+
             let export = &module[procedure_gid.index];
             match export {
                 Export::Procedure(proc) => {
@@ -619,10 +896,27 @@ impl Assembler {
                         self.source_manager.clone(),
                     )
                     .with_num_locals(num_locals)
-                    .with_span(proc.span());
-
-                    // Compile this procedure
-                    let procedure = self.compile_procedure(pctx, mast_forest_builder)?;
+                    .with_span(proc.span())
+                    .with_inline_hint(proc.inline_hint());
+
+                    // Compile this procedure, unless it can be reused from the cache
+                    let cached = match cache.as_deref_mut() {
+                        Some(cache) => {
+                            cache.reuse(procedure_gid, module.path(), mast_forest_builder)?
+                        },
+                        None => None,
+                    };
+                    let procedure = match cached {
+                        Some((mast_root, body_node_id)) => {
+                            pctx.into_procedure(mast_root, body_node_id)
+                        },
+                        None => self.compile_procedure(pctx, mast_forest_builder)?,
+                    };
+                    if let Some(cache) = cache.as_deref_mut() {
+                        let body_node_id = procedure.body_node_id();
+                        let targets = mast_forest_builder.jump_table_targets(body_node_id).to_vec();
+                        cache.record(procedure_gid, body_node_id, targets, cached.is_some());
+                    }
                     // TODO: if a re-exported procedure with the same MAST root had been previously
                     // added to the builder, this will result in unreachable nodes added to the
                     // MAST forest. This is because while we won't insert a duplicate node for the
@@ -698,13 +992,43 @@ impl Assembler {
             self.compile_body(proc.iter(), &mut proc_ctx, None, mast_forest_builder)?
         };
 
+        // The internal locals, i.e. the jump tables of `match` operations and the counters of
+        // rolled loops, are stored in a frame of their own, allocated before that of the locals
+        // declared by the procedure
+        let proc_body_id = if proc_ctx.num_internal_locals() == 0 {
+            proc_body_id
+        } else {
+            let internal_frame = Felt::from(proc_ctx.num_internal_locals());
+            let mut prologue = vec![Operation::Push(internal_frame), Operation::FmpUpdate];
+            prologue.extend(store_jump_tables(&proc_ctx, mast_forest_builder));
+            let prologue = mast_forest_builder.ensure_block(prologue, None)?;
+            let epilogue = mast_forest_builder
+                .ensure_block(vec![Operation::Push(-internal_frame), Operation::FmpUpdate], None)?;
+            let proc_body_id =
+                mast_forest_builder.join_nodes(vec![prologue, proc_body_id, epilogue])?;
+            if !proc_ctx.jump_tables().is_empty() {
+                let targets = proc_ctx
+                    .jump_tables()
+                    .iter()
+                    .flat_map(|table| table.targets().iter().copied())
+                    .collect();
+                mast_forest_builder.set_jump_table_targets(proc_body_id, targets);
+            }
+            proc_body_id
+        };
+
         let proc_body_node = mast_forest_builder
             .get_mast_node(proc_body_id)
             .expect("no MAST node for compiled procedure");
         Ok(proc_ctx.into_procedure(proc_body_node.digest(), proc_body_id))
     }
 
-    fn compile_body<'a, I>(
+    /// Returns true if a `repeat` loop iterating `count` times is rolled into a `LOOP` node.
+    fn rolls_repeat(&self, count: u32) -> bool {
+        self.repeat_rolling_threshold.is_some_and(|threshold| count >= threshold)
+    }
+
+    pub(super) fn compile_body<'a, I>(
         &self,
         body: I,
         proc_ctx: &mut ProcedureContext,
@@ -717,7 +1041,7 @@ impl Assembler {
         use ast::Op;
 
         let mut body_node_ids: Vec<MastNodeId> = Vec::new();
-        let mut block_builder = BasicBlockBuilder::new(wrapper, mast_forest_builder);
+        let mut block_builder = BasicBlockBuilder::new(wrapper, self.optimize, mast_forest_builder);
 
         for op in body {
             match op {
@@ -783,17 +1107,45 @@ impl Assembler {
                     body_node_ids.push(split_node_id);
                 },
 
+                Op::Repeat { count, body, span } if self.rolls_repeat(count.expect_value()) => {
+                    let count = count.expect_value();
+                    if let Some(basic_block_id) = block_builder.make_basic_block()? {
+                        body_node_ids.push(basic_block_id);
+                    }
+
+                    let loop_node_id = self.compile_counted_loop(
+                        *span,
+                        format!("repeat.{count}"),
+                        0..count,
+                        false,
+                        body,
+                        proc_ctx,
+                        block_builder.mast_forest_builder_mut(),
+                    )?;
+                    if let Some(decorator_ids) = block_builder.drain_decorators() {
+                        block_builder
+                            .mast_forest_builder_mut()
+                            .append_before_enter(loop_node_id, &decorator_ids)
+                    }
+
+                    body_node_ids.push(loop_node_id);
+                },
+
                 Op::Repeat { count, body, .. } => {
+                    let count = count.expect_value();
                     if let Some(basic_block_id) = block_builder.make_basic_block()? {
                         body_node_ids.push(basic_block_id);
                     }
 
+                    let repetitions = proc_ctx.repetitions();
+                    proc_ctx.set_repetitions(repetitions.saturating_mul(count));
                     let repeat_node_id = self.compile_body(
                         body.iter(),
                         proc_ctx,
                         None,
                         block_builder.mast_forest_builder_mut(),
                     )?;
+                    proc_ctx.set_repetitions(repetitions);
 
                     if let Some(decorator_ids) = block_builder.drain_decorators() {
                         // Attach the decorators before the first instance of the repeated node
@@ -805,11 +1157,11 @@ impl Assembler {
                             .ensure_node(first_repeat_node)?;
 
                         body_node_ids.push(first_repeat_node_id);
-                        for _ in 0..(*count - 1) {
+                        for _ in 1..count {
                             body_node_ids.push(repeat_node_id);
                         }
                     } else {
-                        for _ in 0..*count {
+                        for _ in 0..count {
                             body_node_ids.push(repeat_node_id);
                         }
                     }
@@ -854,6 +1206,52 @@ impl Assembler {
 
                     body_node_ids.push(loop_node_id);
                 },
+
+                Op::For { start, end, body, span } => {
+                    let start = start.as_ref().map(|start| start.expect_value()).unwrap_or(0);
+                    let end = end.expect_value();
+                    if let Some(basic_block_id) = block_builder.make_basic_block()? {
+                        body_node_ids.push(basic_block_id);
+                    }
+
+                    let loop_node_id = self.compile_counted_loop(
+                        *span,
+                        format!("for.{start}..{end}"),
+                        start..end,
+                        true,
+                        body,
+                        proc_ctx,
+                        block_builder.mast_forest_builder_mut(),
+                    )?;
+                    if let Some(decorator_ids) = block_builder.drain_decorators() {
+                        block_builder
+                            .mast_forest_builder_mut()
+                            .append_before_enter(loop_node_id, &decorator_ids)
+                    }
+
+                    body_node_ids.push(loop_node_id);
+                },
+
+                Op::Match { cases, default, span } => {
+                    if let Some(basic_block_id) = block_builder.make_basic_block()? {
+                        body_node_ids.push(basic_block_id);
+                    }
+
+                    let match_node_id = self.compile_match(
+                        *span,
+                        cases,
+                        default,
+                        proc_ctx,
+                        block_builder.mast_forest_builder_mut(),
+                    )?;
+                    if let Some(decorator_ids) = block_builder.drain_decorators() {
+                        block_builder
+                            .mast_forest_builder_mut()
+                            .append_before_enter(match_node_id, &decorator_ids)
+                    }
+
+                    body_node_ids.push(match_node_id);
+                },
             }
         }
 
@@ -1019,3 +1417,42 @@ pub(crate) struct BodyWrapper {
     pub prologue: Vec<Operation>,
     pub epilogue: Vec<Operation>,
 }
+
+/// Returns an iterator over the digests of the procedures invoked via syscall in `mast_forest`.
+fn syscall_targets(mast_forest: &MastForest) -> impl Iterator<Item = Word> + '_ {
+    mast_forest.nodes().iter().filter_map(|node| match node {
+        MastNode::Call(call) if call.is_syscall() => Some(mast_forest[call.callee()].digest()),
+        _ => None,
+    })
+}
+
+/// Returns an error if `dependency` exports procedures from a namespace on which `library` has a
+/// version requirement, which the version of `dependency` does not match.
+///
+/// Requirements on libraries without a version cannot be checked, and are ignored.
+fn check_library_dependencies(library: &Library, dependency: &Library) -> Result<(), LinkerError> {
+    let Some(version) = dependency.version() else {
+        return Ok(());
+    };
+    let namespaces = dependency.namespaces();
+    match library.dependencies().find(|(namespace, requirement)| {
+        namespaces.contains(namespace) && !requirement.matches(version)
+    }) {
+        Some((namespace, requirement)) => Err(LinkerError::IncompatibleLibraryVersion {
+            namespace: namespace.clone(),
+            requirement: requirement.clone(),
+            version: *version,
+            required_by: describe_library(library),
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Returns a description of `library` for error messages, e.g. `'std@0.16.0'`.
+fn describe_library(library: &Library) -> String {
+    let namespaces = library.namespaces().into_iter().map(ToString::to_string).collect::<Vec<_>>();
+    match library.version() {
+        Some(version) => format!("'{}@{version}'", namespaces.join(", ")),
+        None => format!("'{}'", namespaces.join(", ")),
+    }
+}
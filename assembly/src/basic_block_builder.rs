@@ -7,7 +7,9 @@ use miden_core::{
     sys_events::SystemEvent,
 };
 
-use crate::{ProcedureContext, assembler::BodyWrapper, mast_forest_builder::MastForestBuilder};
+use crate::{
+    ProcedureContext, assembler::BodyWrapper, mast_forest_builder::MastForestBuilder, peephole,
+};
 
 // BASIC BLOCK BUILDER
 // ================================================================================================
@@ -27,6 +29,7 @@ pub struct BasicBlockBuilder<'a> {
     decorators: DecoratorList,
     epilogue: Vec<Operation>,
     last_asmop_pos: usize,
+    optimize: bool,
     mast_forest_builder: &'a mut MastForestBuilder,
 }
 
@@ -37,8 +40,12 @@ impl<'a> BasicBlockBuilder<'a> {
     /// If the wrapper is provided, the prologue of the wrapper is immediately appended to the
     /// vector of span operations. The epilogue of the wrapper is appended to the list of operations
     /// upon consumption of the builder via the [`Self::try_into_basic_block`] method.
+    ///
+    /// If `optimize` is true, the operations of each basic block are run through the peephole
+    /// optimizer before the block is created.
     pub(super) fn new(
         wrapper: Option<BodyWrapper>,
+        optimize: bool,
         mast_forest_builder: &'a mut MastForestBuilder,
     ) -> Self {
         match wrapper {
@@ -47,6 +54,7 @@ impl<'a> BasicBlockBuilder<'a> {
                 decorators: Vec::new(),
                 epilogue: wrapper.epilogue,
                 last_asmop_pos: 0,
+                optimize,
                 mast_forest_builder,
             },
             None => Self {
@@ -54,6 +62,7 @@ impl<'a> BasicBlockBuilder<'a> {
                 decorators: Default::default(),
                 epilogue: Default::default(),
                 last_asmop_pos: 0,
+                optimize,
                 mast_forest_builder,
             },
         }
@@ -178,6 +187,10 @@ impl BasicBlockBuilder<'_> {
     /// epilogue of the builder.
     pub fn make_basic_block(&mut self) -> Result<Option<MastNodeId>, Report> {
         if !self.ops.is_empty() {
+            if self.optimize {
+                self.optimize_ops()?;
+            }
+
             let ops = self.ops.drain(..).collect();
             let decorators = if !self.decorators.is_empty() {
                 Some(self.decorators.drain(..).collect())
@@ -193,6 +206,65 @@ impl BasicBlockBuilder<'_> {
         }
     }
 
+    /// Runs the peephole optimizer over the operations currently in this builder.
+    ///
+    /// `Trace` decorators prevent rewrites across their position, so that they observe the same
+    /// stack as without optimization. `AsmOp` and `Debug` decorators are only emitted in debug
+    /// mode, and hence must not affect the optimized operations, as otherwise the digests of
+    /// procedures would depend on the mode they were assembled in.
+    ///
+    /// The positions of all decorators are updated to match the optimized operations, as are the
+    /// cycle counts of `AsmOp` decorators; `AsmOp` decorators of instructions whose operations
+    /// were all optimized away are removed.
+    fn optimize_ops(&mut self) -> Result<(), Report> {
+        let barriers: Vec<usize> = self
+            .decorators
+            .iter()
+            .filter(|(_, id)| matches!(self.mast_forest_builder[*id], Decorator::Trace(_)))
+            .map(|(pos, _)| *pos)
+            .collect();
+        let mut optimized = peephole::optimize(&self.ops, &barriers);
+        if optimized.len() == self.ops.len() {
+            // every rewrite removes operations, so nothing was rewritten
+            return Ok(());
+        }
+        // basic blocks can't be empty, so a block whose operations cancel out becomes a NOOP,
+        // which follows all the decorators of the block
+        if optimized.is_empty() {
+            optimized.push((self.ops.len(), Operation::Noop));
+        }
+
+        // the position of a decorator is the number of operations preceding it
+        let new_pos = |pos: usize| optimized.partition_point(|&(index, _)| index < pos);
+        let mut decorators = Vec::with_capacity(self.decorators.len());
+        for (pos, decorator_id) in self.decorators.drain(..) {
+            let decorator_id = match &self.mast_forest_builder[decorator_id] {
+                Decorator::AsmOp(assembly_op) => {
+                    let num_cycles = assembly_op.num_cycles() as usize;
+                    let new_num_cycles = new_pos(pos + num_cycles) - new_pos(pos);
+                    if new_num_cycles == 0 {
+                        continue;
+                    } else if new_num_cycles == num_cycles {
+                        decorator_id
+                    } else {
+                        // the decorator may be shared with other blocks, so it is not updated
+                        // in place
+                        let mut assembly_op = assembly_op.clone();
+                        assembly_op.set_num_cycles(new_num_cycles as u8);
+                        self.mast_forest_builder.ensure_decorator(Decorator::AsmOp(assembly_op))?
+                    }
+                },
+                _ => decorator_id,
+            };
+            decorators.push((new_pos(pos), decorator_id));
+        }
+
+        self.decorators = decorators;
+        self.ops = optimized.into_iter().map(|(_, op)| op).collect();
+
+        Ok(())
+    }
+
     /// Creates and returns a new basic block node from the operations and decorators currently in
     /// this builder. If there are no operations however, we return the decorators that were
     /// accumulated up until this point. If the builder is empty, then no node is created and
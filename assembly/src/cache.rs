@@ -0,0 +1,549 @@
+use alloc::{
+    collections::{BTreeMap, BTreeSet},
+    string::{String, ToString},
+    sync::Arc,
+    vec::Vec,
+};
+use core::fmt;
+
+use miden_assembly_syntax::{CfgFlags, LibraryPath, SourceManager, Spanned, diagnostics::Report};
+use miden_core::{
+    Word,
+    crypto::hash::{Blake3_256, Blake3Digest},
+    mast::{MastForest, MastNodeId, MastNodeStore},
+    utils::{
+        ByteReader, ByteWriter, Deserializable, DeserializationError, Serializable, sync::RwLock,
+    },
+};
+
+use crate::{
+    GlobalProcedureIndex, LinkLibraryKind, ModuleIndex,
+    linker::{Linker, ModuleLink, ProcedureLink},
+    mast_forest_builder::MastForestBuilder,
+};
+
+/// A fingerprint of a procedure, or of one of the inputs from which it is compiled.
+type Fingerprint = Blake3Digest<32>;
+
+/// The version of the assembler which wrote a serialized cache, which must match the version
+/// reading it, as the MAST produced for the same sources may differ between versions.
+const CACHE_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+// COMPILATION CACHE
+// ================================================================================================
+
+/// A cache of compiled procedures, which allows an [Assembler](crate::Assembler) to reuse the MAST
+/// of procedures whose sources and dependencies did not change since they were last assembled.
+///
+/// A cache is a shared handle: clones of a cache refer to the same underlying state, so that a
+/// cache attached to an assembler via
+/// [`Assembler::with_compilation_cache`](crate::Assembler::with_compilation_cache) can be
+/// inspected, and written to disk, once the assembler has been consumed.
+///
+/// # Fingerprints
+///
+/// Each compiled procedure is stored along with a fingerprint, which commits to:
+///
+/// * The path and source code of the module defining the procedure, and of all the modules it
+///   imports, directly or transitively.
+/// * The fingerprints of the procedures it invokes, or their MAST roots if they are provided by a
+///   library.
+/// * The options of the assembler which affect code generation, i.e. debug mode, the peephole
+///   optimizer, the rolling of `repeat` loops, `@cfg` flags, the kernel, and the set of linked
+///   libraries.
+///
+/// A procedure is reused when its fingerprint matches the one stored in the cache, and compiled
+/// otherwise. Procedures of modules which are not backed by a source file known to the source
+/// manager of the assembler are always compiled.
+///
+/// Modules are still parsed and linked, as the linker requires their AST to resolve invocations,
+/// so the cache only saves the time spent on code generation.
+#[derive(Clone, Default)]
+pub struct CompilationCache {
+    state: Arc<RwLock<CacheState>>,
+}
+
+#[derive(Default)]
+struct CacheState {
+    modules: BTreeMap<LibraryPath, CachedModule>,
+    /// What was reused during the most recent assembly using this cache
+    report: CacheReport,
+}
+
+/// The cached procedures of a module.
+struct CachedModule {
+    /// The MAST of the cached procedures
+    mast: MastForest,
+    /// The cached procedures, by their index in the module
+    procedures: BTreeMap<u16, CachedProcedure>,
+}
+
+struct CachedProcedure {
+    fingerprint: Fingerprint,
+    /// The root of the procedure body in the MAST of the module
+    body_node_id: MastNodeId,
+    /// The arms of the jump tables used by the procedure, which are executed via `dynexec`
+    jump_table_targets: Vec<MastNodeId>,
+}
+
+// ------------------------------------------------------------------------------------------------
+/// Constructors
+impl CompilationCache {
+    /// Creates a new, empty [CompilationCache].
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+/// Public accessors
+impl CompilationCache {
+    /// Returns the number of modules with procedures in this cache.
+    pub fn len(&self) -> usize {
+        self.state.read().modules.len()
+    }
+
+    /// Returns true if this cache contains no procedures.
+    pub fn is_empty(&self) -> bool {
+        self.state.read().modules.is_empty()
+    }
+
+    /// Returns true if this cache contains procedures of the module at `path`.
+    pub fn contains_module(&self, path: &LibraryPath) -> bool {
+        self.state.read().modules.contains_key(path)
+    }
+
+    /// Returns a report of the modules which were reused and compiled during the most recent
+    /// assembly using this cache.
+    pub fn report(&self) -> CacheReport {
+        self.state.read().report.clone()
+    }
+
+    /// Removes all procedures from this cache.
+    pub fn clear(&self) {
+        let mut state = self.state.write();
+        state.modules.clear();
+        state.report = CacheReport::default();
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+/// Serialization
+#[cfg(feature = "std")]
+impl CompilationCache {
+    /// The file extension conventionally used for serialized compilation caches.
+    pub const CACHE_EXTENSION: &'static str = "masc";
+
+    /// Writes this cache to the file at `path`, creating its parent directories if needed.
+    pub fn write_to_file(&self, path: impl AsRef<std::path::Path>) -> std::io::Result<()> {
+        let path = path.as_ref();
+
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+
+        std::fs::write(path, self.to_bytes())
+    }
+
+    /// Reads a cache previously written with [`Self::write_to_file`] from the file at `path`.
+    ///
+    /// Returns an error if the file cannot be read, or was written by a different version of the
+    /// assembler, in which case the cache should be discarded.
+    pub fn deserialize_from_file(
+        path: impl AsRef<std::path::Path>,
+    ) -> Result<Self, DeserializationError> {
+        let path = path.as_ref();
+        let bytes = std::fs::read(path).map_err(|err| {
+            DeserializationError::InvalidValue(format!(
+                "failed to read file at {}: {err}",
+                path.to_string_lossy()
+            ))
+        })?;
+
+        Self::read_from_bytes(&bytes)
+    }
+}
+
+impl Serializable for CompilationCache {
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        let state = self.state.read();
+        CACHE_VERSION.write_into(target);
+        target.write_usize(state.modules.len());
+        for (path, module) in state.modules.iter() {
+            path.write_into(target);
+            module.mast.write_into(target);
+            target.write_usize(module.procedures.len());
+            for (index, procedure) in module.procedures.iter() {
+                target.write_u16(*index);
+                procedure.fingerprint.write_into(target);
+                target.write_u32(procedure.body_node_id.as_u32());
+                target.write_usize(procedure.jump_table_targets.len());
+                for node_id in procedure.jump_table_targets.iter() {
+                    target.write_u32(node_id.as_u32());
+                }
+            }
+        }
+    }
+}
+
+impl Deserializable for CompilationCache {
+    fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
+        let version = String::read_from(source)?;
+        if version != CACHE_VERSION {
+            return Err(DeserializationError::InvalidValue(format!(
+                "compilation cache was written by version {version} of the assembler, but this is \
+                 version {CACHE_VERSION}"
+            )));
+        }
+
+        let mut modules = BTreeMap::new();
+        let num_modules = source.read_usize()?;
+        for _ in 0..num_modules {
+            let path = LibraryPath::read_from(source)?;
+            let mast = MastForest::read_from(source)?;
+            let mut procedures = BTreeMap::new();
+            let num_procedures = source.read_usize()?;
+            for _ in 0..num_procedures {
+                let index = source.read_u16()?;
+                let fingerprint = Fingerprint::read_from(source)?;
+                let body_node_id = MastNodeId::from_u32_safe(source.read_u32()?, &mast)?;
+                let num_targets = source.read_usize()?;
+                let jump_table_targets = (0..num_targets)
+                    .map(|_| MastNodeId::from_u32_safe(source.read_u32()?, &mast))
+                    .collect::<Result<_, _>>()?;
+                procedures.insert(
+                    index,
+                    CachedProcedure {
+                        fingerprint,
+                        body_node_id,
+                        jump_table_targets,
+                    },
+                );
+            }
+            modules.insert(path, CachedModule { mast, procedures });
+        }
+
+        let state = CacheState { modules, report: CacheReport::default() };
+        Ok(Self { state: Arc::new(RwLock::new(state)) })
+    }
+}
+
+// CACHE REPORT
+// ================================================================================================
+
+/// Describes which modules were reused from a [CompilationCache] during an assembly.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct CacheReport {
+    reused: BTreeSet<LibraryPath>,
+    compiled: BTreeSet<LibraryPath>,
+}
+
+impl CacheReport {
+    /// Returns the modules whose procedures were all reused from the cache.
+    pub fn reused(&self) -> impl Iterator<Item = &LibraryPath> {
+        self.reused.iter()
+    }
+
+    /// Returns the modules with at least one procedure which had to be compiled.
+    pub fn compiled(&self) -> impl Iterator<Item = &LibraryPath> {
+        self.compiled.iter()
+    }
+
+    /// Returns true if all procedures of the module at `path` were reused from the cache.
+    pub fn is_reused(&self, path: &LibraryPath) -> bool {
+        self.reused.contains(path)
+    }
+
+    /// Returns true if at least one procedure of the module at `path` had to be compiled.
+    pub fn is_compiled(&self, path: &LibraryPath) -> bool {
+        self.compiled.contains(path)
+    }
+}
+
+impl fmt::Display for CacheReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let plural = |n: usize| if n == 1 { "" } else { "s" };
+        let (reused, compiled) = (self.reused.len(), self.compiled.len());
+        write!(
+            f,
+            "reused {reused} module{}, compiled {compiled} module{}",
+            plural(reused),
+            plural(compiled)
+        )
+    }
+}
+
+// CACHE SESSION
+// ================================================================================================
+
+/// The state of a [CompilationCache] during a single assembly.
+pub(crate) struct CacheSession {
+    cache: CompilationCache,
+    /// The fingerprint of the assembler options which affect code generation
+    environment: Fingerprint,
+    /// The fingerprints of the modules seen so far, or `None` for modules which cannot be cached
+    module_fingerprints: BTreeMap<ModuleIndex, Option<Fingerprint>>,
+    /// The fingerprints of the procedures seen so far
+    procedure_fingerprints: BTreeMap<GlobalProcedureIndex, Fingerprint>,
+    /// The procedures reused or compiled so far, by module
+    modules: BTreeMap<ModuleIndex, SessionModule>,
+}
+
+#[derive(Default)]
+struct SessionModule {
+    /// The procedures of the module, along with the root of their body and the arms of their jump
+    /// tables in the forest being built
+    procedures: BTreeMap<u16, (Fingerprint, MastNodeId, Vec<MastNodeId>)>,
+    /// Whether any procedure of the module was compiled
+    compiled: bool,
+    /// Whether any procedure of the module could not be fingerprinted
+    uncacheable: bool,
+}
+
+impl CacheSession {
+    /// Starts a new session using `cache`, for an assembler with the given options.
+    pub fn new(
+        cache: CompilationCache,
+        linker: &Linker,
+        in_debug_mode: bool,
+        optimize: bool,
+        repeat_rolling_threshold: Option<u32>,
+        cfg: &CfgFlags,
+    ) -> Self {
+        let mut bytes = Vec::new();
+        CACHE_VERSION.write_into(&mut bytes);
+        bytes.write_bool(in_debug_mode);
+        bytes.write_bool(optimize);
+        bytes.write_bool(repeat_rolling_threshold.is_some());
+        bytes.write_u32(repeat_rolling_threshold.unwrap_or_default());
+        cfg.write_into(&mut bytes);
+        for proc_hash in linker.kernel().proc_hashes() {
+            proc_hash.write_into(&mut bytes);
+        }
+        for library in linker.libraries() {
+            library.library.digest().write_into(&mut bytes);
+            bytes.write_bool(matches!(library.kind, LinkLibraryKind::Static));
+        }
+
+        Self {
+            cache,
+            environment: Blake3_256::hash(&bytes),
+            module_fingerprints: BTreeMap::new(),
+            procedure_fingerprints: BTreeMap::new(),
+            modules: BTreeMap::new(),
+        }
+    }
+
+    /// Computes the fingerprint of the procedure `gid`, all of whose callees must have been
+    /// fingerprinted already.
+    ///
+    /// Returns `None` if the procedure cannot be cached.
+    pub fn fingerprint(
+        &mut self,
+        gid: GlobalProcedureIndex,
+        linker: &Linker,
+        source_manager: &dyn SourceManager,
+    ) -> Option<Fingerprint> {
+        let module_fingerprint = self.module_fingerprint(gid.module, linker, source_manager)?;
+
+        let mut bytes = Vec::new();
+        module_fingerprint.write_into(&mut bytes);
+        bytes.write_u16(gid.index.as_usize() as u16);
+        for callee in linker.callees(gid) {
+            match linker.get_procedure_unsafe(*callee) {
+                ProcedureLink::Ast(_) => {
+                    self.procedure_fingerprints.get(callee)?.write_into(&mut bytes)
+                },
+                ProcedureLink::Info(info) => info.digest.write_into(&mut bytes),
+            }
+        }
+
+        let fingerprint = Blake3_256::hash(&bytes);
+        self.procedure_fingerprints.insert(gid, fingerprint);
+        Some(fingerprint)
+    }
+
+    /// Copies the MAST of the procedure `gid` from the cache into `mast_forest_builder`, if its
+    /// fingerprint matches the one computed by [`Self::fingerprint`].
+    ///
+    /// Returns the MAST root of the procedure and the ID of its body, or `None` if the procedure
+    /// must be compiled.
+    pub fn reuse(
+        &mut self,
+        gid: GlobalProcedureIndex,
+        path: &LibraryPath,
+        mast_forest_builder: &mut MastForestBuilder,
+    ) -> Result<Option<(Word, MastNodeId)>, Report> {
+        let Some(fingerprint) = self.procedure_fingerprints.get(&gid).copied() else {
+            return Ok(None);
+        };
+
+        let state = self.cache.state.read();
+        let index = gid.index.as_usize() as u16;
+        let cached = state.modules.get(path).and_then(|module| {
+            module
+                .procedures
+                .get(&index)
+                .filter(|procedure| procedure.fingerprint == fingerprint)
+                .map(|procedure| (&module.mast, procedure))
+        });
+        let Some((mast, procedure)) = cached else {
+            return Ok(None);
+        };
+
+        let node_id = mast_forest_builder.import_subtree(mast, procedure.body_node_id)?;
+        if !procedure.jump_table_targets.is_empty() {
+            let mut targets = Vec::with_capacity(procedure.jump_table_targets.len());
+            for target in procedure.jump_table_targets.iter() {
+                let target = mast_forest_builder.import_subtree(mast, *target)?;
+                mast_forest_builder.ensure_root(target);
+                targets.push(target);
+            }
+            mast_forest_builder.set_jump_table_targets(node_id, targets);
+        }
+        Ok(Some((mast[procedure.body_node_id].digest(), node_id)))
+    }
+
+    /// Records that the procedure `gid`, whose body is `body_node_id` and whose jump tables
+    /// dispatch to `jump_table_targets`, was reused from the cache, or compiled.
+    pub fn record(
+        &mut self,
+        gid: GlobalProcedureIndex,
+        body_node_id: MastNodeId,
+        jump_table_targets: Vec<MastNodeId>,
+        reused: bool,
+    ) {
+        let module = self.modules.entry(gid.module).or_default();
+        module.compiled |= !reused;
+        match self.procedure_fingerprints.get(&gid) {
+            Some(fingerprint) => {
+                let index = gid.index.as_usize() as u16;
+                module
+                    .procedures
+                    .insert(index, (*fingerprint, body_node_id, jump_table_targets));
+            },
+            None => module.uncacheable = true,
+        }
+    }
+
+    /// Updates the cache with the procedures compiled during this session, given the assembled
+    /// `mast_forest`, and the remapping of node IDs which occurred while building it.
+    pub fn finish(
+        self,
+        linker: &Linker,
+        mast_forest: &MastForest,
+        id_remappings: &BTreeMap<MastNodeId, MastNodeId>,
+    ) {
+        let mut report = CacheReport::default();
+        let mut updated = Vec::new();
+        for (module_idx, module) in self.modules {
+            let path = linker[module_idx].path().clone();
+            if module.compiled {
+                report.compiled.insert(path.clone());
+                if !module.uncacheable {
+                    updated.push((path, module.procedures));
+                }
+            } else {
+                report.reused.insert(path);
+            }
+        }
+
+        let mut state = self.cache.state.write();
+        state.report = report;
+        if updated.is_empty() {
+            return;
+        }
+
+        let store = match MastNodeStore::from_forest(mast_forest.clone()) {
+            Ok(store) => store,
+            Err(err) => {
+                log::warn!(target: "assembler", "failed to update compilation cache: {err}");
+                return;
+            },
+        };
+        for (path, procedures) in updated {
+            let remap = |id: MastNodeId| *id_remappings.get(&id).unwrap_or(&id);
+            let roots = procedures.values().flat_map(|(_, body_node_id, targets)| {
+                core::iter::once(body_node_id).chain(targets).map(|node_id| remap(*node_id))
+            });
+            let (mast, remapping) = match store.extract(roots) {
+                Ok(extracted) => extracted,
+                Err(err) => {
+                    log::warn!(target: "assembler", "failed to cache module '{path}': {err}");
+                    state.modules.remove(&path);
+                    continue;
+                },
+            };
+            let procedures = procedures
+                .into_iter()
+                .map(|(index, (fingerprint, body_node_id, targets))| {
+                    let body_node_id = remapping[&remap(body_node_id)];
+                    let jump_table_targets =
+                        targets.into_iter().map(|node_id| remapping[&remap(node_id)]).collect();
+                    (
+                        index,
+                        CachedProcedure {
+                            fingerprint,
+                            body_node_id,
+                            jump_table_targets,
+                        },
+                    )
+                })
+                .collect();
+            state.modules.insert(path, CachedModule { mast, procedures });
+        }
+    }
+
+    /// Computes the fingerprint of the sources from which the module `module_idx` is compiled.
+    fn module_fingerprint(
+        &mut self,
+        module_idx: ModuleIndex,
+        linker: &Linker,
+        source_manager: &dyn SourceManager,
+    ) -> Option<Fingerprint> {
+        if let Some(fingerprint) = self.module_fingerprints.get(&module_idx) {
+            return *fingerprint;
+        }
+
+        // Visit the module along with all modules it transitively imports
+        let mut sources = BTreeMap::<String, Fingerprint>::new();
+        let mut visited = BTreeSet::new();
+        let mut worklist = vec![module_idx];
+        let mut cacheable = true;
+        while let Some(idx) = worklist.pop() {
+            if !visited.insert(idx) {
+                continue;
+            }
+            let ModuleLink::Ast(module) = &linker[idx] else {
+                // Modules of libraries are covered by the environment fingerprint
+                continue;
+            };
+            let Ok(source_file) = source_manager.get(module.span().source_id()) else {
+                cacheable = false;
+                break;
+            };
+            let mut bytes = Vec::new();
+            module.kind().to_string().write_into(&mut bytes);
+            source_file.uri().as_str().write_into(&mut bytes);
+            source_file.as_str().write_into(&mut bytes);
+            sources.insert(module.path().to_string(), Blake3_256::hash(&bytes));
+
+            worklist.extend(
+                module.imports().filter_map(|import| linker.find_module_index(&import.path)),
+            );
+        }
+
+        let fingerprint = cacheable.then(|| {
+            let mut bytes = Vec::new();
+            self.environment.write_into(&mut bytes);
+            linker[module_idx].path().to_string().write_into(&mut bytes);
+            for (path, source) in sources.iter() {
+                path.write_into(&mut bytes);
+                source.write_into(&mut bytes);
+            }
+            Blake3_256::hash(&bytes)
+        });
+        self.module_fingerprints.insert(module_idx, fingerprint);
+        fingerprint
+    }
+}
@@ -0,0 +1,124 @@
+use alloc::vec::Vec;
+
+use miden_assembly_syntax::{ast::DataSegment, diagnostics::Report};
+use miden_core::{
+    AdviceMap, Felt, ONE, Operation, WORD_SIZE, mast::MastNodeId, sys_events::SystemEvent,
+};
+
+use crate::mast_forest_builder::MastForestBuilder;
+
+// DATA SEGMENTS
+// ================================================================================================
+
+/// Returns the entrypoint of a program which copies the given static data `segments` into memory
+/// before executing the body of its `entrypoint` procedure.
+///
+/// The data of each segment is placed in the advice map of the program under its commitment, see
+/// [DataSegment::commitment]. At program start, it is moved to the advice stack, piped into
+/// memory with `adv_pipe`, hashing it along the way, and the resulting hash is asserted to be
+/// equal to the commitment, so that a malicious prover cannot start the program with different
+/// data. This leaves the operand stack unchanged.
+///
+/// Returns `entrypoint` itself if there are no segments.
+pub(crate) fn initialize_data_segments(
+    segments: &[DataSegment],
+    entrypoint: MastNodeId,
+    mast_forest_builder: &mut MastForestBuilder,
+) -> Result<MastNodeId, Report> {
+    if segments.is_empty() {
+        return Ok(entrypoint);
+    }
+
+    let advice_map = segments
+        .iter()
+        .map(|segment| (segment.commitment(), segment.data.clone()))
+        .collect::<AdviceMap>();
+    mast_forest_builder.merge_advice_map(&advice_map)?;
+
+    let err_code = mast_forest_builder.register_error("data segment commitment mismatch".into());
+    let mut node_ids = Vec::with_capacity(segments.len() * 3 + 1);
+    for segment in segments {
+        initialize_data_segment(segment, err_code, mast_forest_builder, &mut node_ids)?;
+    }
+    node_ids.push(entrypoint);
+
+    let node_id = mast_forest_builder.join_nodes(node_ids)?;
+    mast_forest_builder.ensure_root(node_id);
+    Ok(node_id)
+}
+
+/// Appends the nodes copying `segment` into memory to `node_ids`.
+///
+/// This is the same sequence of operations as `std::mem::pipe_preimage_to_memory`, specialized
+/// to the address and size of the segment, which are known statically.
+fn initialize_data_segment(
+    segment: &DataSegment,
+    err_code: Felt,
+    mast_forest_builder: &mut MastForestBuilder,
+    node_ids: &mut Vec<MastNodeId>,
+) -> Result<(), Report> {
+    use Operation::*;
+
+    let num_words = segment.data.len() / WORD_SIZE;
+    let num_word_pairs = num_words / 2;
+    let is_odd = num_words % 2 == 1;
+    let start = segment.addresses().start;
+    let end = start + (num_word_pairs * 2 * WORD_SIZE) as u64;
+
+    // push.COM adv.push_mapval
+    let mut ops = segment.commitment().iter().map(|elem| Push(*elem)).collect::<Vec<_>>();
+    ops.push(Emit(SystemEvent::MapValueToStack.into_event_id()));
+
+    // => [C, B, A, write_ptr, end_ptr, COM, ...], where A is the capacity word for a sequence of
+    // `4 * num_words` elements
+    ops.extend([Push(Felt::new(end)), Push(Felt::new(start))]);
+    ops.extend([Push(Felt::from(if is_odd { WORD_SIZE as u32 } else { 0 })), Pad, Pad, Pad]);
+    ops.extend([Pad; 2 * WORD_SIZE]);
+
+    if num_word_pairs > 0 {
+        ops.push(Push(ONE));
+        node_ids.push(mast_forest_builder.ensure_block(ops, None)?);
+
+        // while.true adv_pipe hperm dup.13 dup.13 neq end
+        let body =
+            mast_forest_builder.ensure_block(vec![Pipe, HPerm, Dup13, Dup13, Eq, Not], None)?;
+        node_ids.push(mast_forest_builder.ensure_loop(body)?);
+        ops = Vec::new();
+    }
+
+    // movup.13 drop => [C, B, A, write_ptr, COM, ...]
+    ops.extend([SwapDW, MovUp5, SwapDW, MovUp8, Drop]);
+
+    if is_odd {
+        // dropw adv_loadw movup.8 dup.0 add.4 movdn.5 mem_storew movup.4 movdn.8 padw hperm
+        ops.extend([Drop; WORD_SIZE]);
+        ops.extend([AdvPopW, MovUp8, Dup0, Push(Felt::from(WORD_SIZE as u32)), Add, MovDn5]);
+        ops.extend([MStoreW, MovUp4, MovDn8]);
+        ops.extend([Pad; WORD_SIZE]);
+        ops.push(HPerm);
+    }
+
+    // dropw swapw dropw movup.4 drop => [HASH, COM, ...]
+    ops.extend([Drop; WORD_SIZE]);
+    ops.push(SwapW);
+    ops.extend([Drop; WORD_SIZE]);
+    ops.extend([MovUp4, Drop]);
+
+    // assert_eqw
+    ops.extend([
+        MovUp4,
+        Eq,
+        Assert(err_code),
+        MovUp3,
+        Eq,
+        Assert(err_code),
+        MovUp2,
+        Eq,
+        Assert(err_code),
+        Eq,
+        Assert(err_code),
+    ]);
+    node_ids.push(mast_forest_builder.ensure_block(ops, None)?);
+
+    Ok(())
+}
@@ -0,0 +1,137 @@
+use alloc::{collections::BTreeMap, sync::Arc};
+use core::fmt;
+
+use miden_core::utils::sync::RwLock;
+
+use crate::ast::QualifiedProcedureName;
+
+// CONSTANTS
+// ================================================================================================
+
+/// The estimated number of cycles saved each time an inlined invocation site is executed.
+///
+/// An invocation which is not inlined executes the `SPAN` and `END` operations of the body of the
+/// procedure, as well as the `JOIN` and `END` operations of the node joining it with the rest of
+/// the caller.
+const CYCLES_SAVED_PER_INLINED_SITE: usize = 4;
+
+// INLINING REPORT
+// ================================================================================================
+
+/// A report of the inlining decisions made by an [Assembler](crate::Assembler).
+///
+/// A procedure is inlined into a caller by merging its body with the basic blocks of the caller
+/// which surround an `exec` of the procedure, and kept out-of-line by leaving its body as a
+/// separate node, which is shared by all of its callers. Procedures annotated with `@inline` are
+/// always inlined, procedures annotated with `@noinline` never are, and all other procedures are
+/// inlined when they are small enough: exported procedures are held to a lower threshold, as
+/// their body remains in the MAST forest regardless of whether they are inlined.
+///
+/// A report is a shared handle: clones of a report refer to the same underlying state, so that
+/// a report provided to [`Assembler::with_inlining_report`](crate::Assembler::with_inlining_report)
+/// can be inspected once the assembler has been consumed. Only procedures compiled by the most
+/// recent assembly are included, i.e. not those reused from a
+/// [CompilationCache](crate::CompilationCache).
+#[derive(Clone, Default)]
+pub struct InliningReport {
+    procedures: Arc<RwLock<BTreeMap<QualifiedProcedureName, ProcedureInlining>>>,
+}
+
+/// The inlining decisions made for the invocation sites of a single procedure.
+///
+/// Invocation sites which are not adjacent to any other basic block of the caller are counted as
+/// out-of-line, as there is nothing to merge the body of the procedure with.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct ProcedureInlining {
+    /// The number of operations in the inlined body of the procedure
+    pub num_operations: usize,
+    /// The number of invocation sites at which the procedure was inlined
+    pub inlined_sites: usize,
+    /// The number of invocation sites at which the procedure was kept out-of-line
+    pub out_of_line_sites: usize,
+}
+
+/// Constructors
+impl InliningReport {
+    /// Creates a new, empty [InliningReport].
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// Public accessors
+impl InliningReport {
+    /// Returns the inlining decisions made for each procedure during the most recent assembly.
+    pub fn procedures(&self) -> BTreeMap<QualifiedProcedureName, ProcedureInlining> {
+        self.procedures.read().clone()
+    }
+
+    /// Returns the inlining decisions made for the procedure `name`, if any.
+    pub fn get(&self, name: &QualifiedProcedureName) -> Option<ProcedureInlining> {
+        self.procedures.read().get(name).copied()
+    }
+
+    /// Returns the total number of invocation sites at which a procedure was inlined.
+    pub fn inlined_sites(&self) -> usize {
+        self.procedures.read().values().map(|procedure| procedure.inlined_sites).sum()
+    }
+
+    /// Returns the total number of invocation sites at which a procedure was kept out-of-line.
+    pub fn out_of_line_sites(&self) -> usize {
+        self.procedures
+            .read()
+            .values()
+            .map(|procedure| procedure.out_of_line_sites)
+            .sum()
+    }
+
+    /// Returns the number of operations which were added to the MAST forest by inlining, i.e. the
+    /// increase in code size due to copying the bodies of procedures into their callers.
+    pub fn added_operations(&self) -> usize {
+        self.procedures
+            .read()
+            .values()
+            .map(|procedure| procedure.num_operations * procedure.inlined_sites)
+            .sum()
+    }
+
+    /// Returns the estimated number of cycles saved by inlining, assuming each inlined invocation
+    /// site is executed once.
+    pub fn estimated_saved_cycles(&self) -> usize {
+        self.inlined_sites() * CYCLES_SAVED_PER_INLINED_SITE
+    }
+}
+
+/// Mutators
+impl InliningReport {
+    /// Replaces the contents of this report with the decisions made during an assembly.
+    pub(crate) fn set(&self, procedures: BTreeMap<QualifiedProcedureName, ProcedureInlining>) {
+        *self.procedures.write() = procedures;
+    }
+}
+
+impl fmt::Debug for InliningReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.procedures.read().iter()).finish()
+    }
+}
+
+impl fmt::Display for InliningReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (name, procedure) in self.procedures.read().iter() {
+            writeln!(
+                f,
+                "{name}: {} operations, inlined at {} sites, out-of-line at {} sites",
+                procedure.num_operations, procedure.inlined_sites, procedure.out_of_line_sites
+            )?;
+        }
+        write!(
+            f,
+            "inlined {} sites, kept {} sites out-of-line: {} operations added, ~{} cycles saved",
+            self.inlined_sites(),
+            self.out_of_line_sites(),
+            self.added_operations(),
+            self.estimated_saved_cycles()
+        )
+    }
+}
@@ -10,10 +10,16 @@ use miden_core::{ONE, ZERO};
 
 mod assembler;
 mod basic_block_builder;
+mod cache;
+mod data_segments;
 mod id;
+mod inlining;
 mod instruction;
 pub mod linker;
+mod loop_lowering;
 mod mast_forest_builder;
+mod match_lowering;
+mod peephole;
 mod procedure;
 
 #[cfg(test)]
@@ -25,9 +31,9 @@ mod tests;
 
 // Re-exported for downstream crates
 pub use miden_assembly_syntax::{
-    DefaultSourceManager, KernelLibrary, Library, LibraryNamespace, LibraryPath, ModuleParser,
-    Parse, ParseOptions, Report, SourceFile, SourceId, SourceManager, SourceSpan, Span, Spanned,
-    ast, diagnostics, library, report,
+    CfgFlags, DefaultSourceManager, KernelLibrary, Library, LibraryNamespace, LibraryPath, Lint,
+    LintConfig, LintLevel, ModuleParser, Parse, ParseOptions, Report, SourceFile, SourceId,
+    SourceManager, SourceSpan, Span, Spanned, ast, diagnostics, format, library, report,
 };
 /// Syntax components for the Miden Assembly AST
 /// Merkelized abstract syntax tree (MAST) components defining Miden VM programs.
@@ -37,7 +43,9 @@ pub use miden_core::{mast, utils};
 pub use self::linker::{LinkLibraryKind, LinkerError};
 pub use self::{
     assembler::Assembler,
+    cache::{CacheReport, CompilationCache},
     id::{GlobalProcedureIndex, ModuleIndex},
+    inlining::{InliningReport, ProcedureInlining},
     procedure::{Procedure, ProcedureContext},
 };
 
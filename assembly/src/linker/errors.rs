@@ -1,11 +1,11 @@
 use alloc::{boxed::Box, string::String, sync::Arc, vec::Vec};
 
 use miden_assembly_syntax::{
-    Felt, LibraryPath, SourceSpan,
-    ast::QualifiedProcedureName,
+    ConstEvalError, Felt, LibraryNamespace, LibraryPath, SourceSpan, Version, VersionReq,
+    ast::{Ident, QualifiedProcedureName},
     diagnostics::{Diagnostic, RelatedLabel, SourceFile, miette},
 };
-use miden_core::{FieldElement, utils::to_hex};
+use miden_core::{FieldElement, KernelVersion, Word, errors::describe_kernel, utils::to_hex};
 
 // LINKER ERROR
 // ================================================================================================
@@ -47,6 +47,117 @@ pub enum LinkerError {
         source_file: Option<Arc<SourceFile>>,
         callee: Box<QualifiedProcedureName>,
     },
+    #[error("procedure '{callee}' is not visible from module '{module}'")]
+    #[diagnostic(help(
+        "private procedures can only be referenced from their own module, or from the other modules \
+        of a kernel, and internal procedures from the modules of their own library namespace"
+    ))]
+    InaccessibleProcedure {
+        #[label("reference occurs here")]
+        span: SourceSpan,
+        #[source_code]
+        source_file: Option<Arc<SourceFile>>,
+        callee: Box<QualifiedProcedureName>,
+        module: LibraryPath,
+    },
+    #[error(
+        "library was assembled against {expected}, but the procedure with root {proc_root} which \
+        it invokes via syscall is not in the kernel of the assembler ({found})",
+        expected = describe_kernel(.expected.as_ref()),
+        found = describe_kernel(.found.as_ref())
+    )]
+    #[diagnostic(help("the library must be reassembled against the kernel of the assembler"))]
+    KernelMismatch {
+        expected: Option<KernelVersion>,
+        found: Option<KernelVersion>,
+        proc_root: Word,
+    },
+    #[error(
+        "library {required_by} requires version '{requirement}' of '{namespace}', but version \
+        {version} of '{namespace}' is linked"
+    )]
+    #[diagnostic(help(
+        "link a version of '{namespace}' matching '{requirement}', or reassemble {required_by} \
+        against version {version} of '{namespace}'"
+    ))]
+    IncompatibleLibraryVersion {
+        namespace: LibraryNamespace,
+        requirement: VersionReq,
+        version: Version,
+        required_by: String,
+    },
+    #[error("undefined struct '{name}' in module '{path}'")]
+    #[diagnostic(help(
+        "structs can only be referred to from other modules if the module defining them is assembled from source"
+    ))]
+    UndefinedStruct {
+        #[label]
+        span: SourceSpan,
+        #[source_code]
+        source_file: Option<Arc<SourceFile>>,
+        path: LibraryPath,
+        name: Ident,
+    },
+    #[error("undefined field: the struct has no field with this name")]
+    #[diagnostic()]
+    UndefinedField {
+        #[label]
+        span: SourceSpan,
+        #[source_code]
+        source_file: Option<Arc<SourceFile>>,
+    },
+    #[error("invalid constant")]
+    #[diagnostic(help("this constant does not resolve to a value of the right type"))]
+    InvalidConstant {
+        #[label]
+        span: SourceSpan,
+        #[source_code]
+        source_file: Option<Arc<SourceFile>>,
+    },
+    #[error("invalid constant expression: division by zero")]
+    #[diagnostic()]
+    DivisionByZero {
+        #[label]
+        span: SourceSpan,
+        #[source_code]
+        source_file: Option<Arc<SourceFile>>,
+    },
+    #[error("invalid constant expression: {kind}")]
+    #[diagnostic()]
+    InvalidConstantExpression {
+        #[label]
+        span: SourceSpan,
+        #[source_code]
+        source_file: Option<Arc<SourceFile>>,
+        kind: ConstEvalError,
+    },
+    #[error("invalid immediate: value is larger than expected range")]
+    #[diagnostic()]
+    ImmediateOverflow {
+        #[label]
+        span: SourceSpan,
+        #[source_code]
+        source_file: Option<Arc<SourceFile>>,
+    },
+    #[error("invalid repeat count: loops must repeat at least once")]
+    #[diagnostic()]
+    InvalidRepeatCount {
+        #[label]
+        span: SourceSpan,
+        #[source_code]
+        source_file: Option<Arc<SourceFile>>,
+    },
+    #[error("duplicate case in match: {value} is already handled by a previous case")]
+    #[diagnostic()]
+    DuplicateMatchCase {
+        #[label("duplicate case occurs here")]
+        span: SourceSpan,
+        #[label("previously handled here")]
+        prev_span: SourceSpan,
+        #[source_code]
+        source_file: Option<Arc<SourceFile>>,
+        value: u32,
+    },
     #[error("value for key {} already present in the advice map", to_hex(Felt::elements_as_bytes(.key)))]
     #[diagnostic(help(
         "previous values at key were '{prev_values:?}'. Operation would have replaced them with '{new_values:?}'",
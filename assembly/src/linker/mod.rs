@@ -11,7 +11,10 @@ use core::ops::Index;
 use miden_core::{Kernel, Word};
 use smallvec::{SmallVec, smallvec};
 
-use self::{analysis::MaybeRewriteCheck, rewrites::ModuleRewriter};
+use self::{
+    analysis::MaybeRewriteCheck,
+    rewrites::{DeferredConstantRewriter, ModuleRewriter},
+};
 pub use self::{
     callgraph::{CallGraph, CycleError},
     errors::LinkerError,
@@ -22,7 +25,7 @@ use crate::{
     Library, LibraryNamespace, LibraryPath, SourceManager, Spanned,
     ast::{
         Export, InvocationTarget, InvokeKind, Module, ProcedureIndex, ProcedureName,
-        ResolvedProcedure,
+        ResolvedProcedure, Struct,
     },
     library::{ModuleInfo, ProcedureInfo},
 };
@@ -518,6 +521,14 @@ impl Linker {
             }
         }
 
+        // Collect the structs defined in modules assembled from source, if any of the pending
+        // modules has constants referring to the layout of structs defined in other modules
+        let structs = if pending.iter().any(|m| !m.module.deferred_constants().is_empty()) {
+            self.structs_by_module(&pending)
+        } else {
+            BTreeMap::new()
+        };
+
         // Obtain a set of resolvers for the pending modules so that we can do name resolution
         // before they are added to the graph
         let mut resolver = NameResolver::new(self);
@@ -531,6 +542,8 @@ impl Linker {
         for PreLinkModule { mut module, module_index } in pending.into_iter() {
             log::debug!(target: "linker", "rewriting pending module {} (index {})", module.path(), module_index.as_usize());
 
+            DeferredConstantRewriter::new(&structs, &*self.source_manager).apply(&mut module)?;
+
             let mut rewriter = ModuleRewriter::new(&resolver);
             rewriter.apply(module_index, &mut module)?;
 
@@ -654,6 +667,20 @@ impl Linker {
         Ok(())
     }
 
+    /// Returns the structs defined in each of the modules for which we have the AST, including the
+    /// `pending` modules, keyed by module path.
+    fn structs_by_module(&self, pending: &[PreLinkModule]) -> BTreeMap<LibraryPath, Vec<Struct>> {
+        let linked = self.modules.iter().filter_map(|module| match module {
+            Some(ModuleLink::Ast(module)) => Some(&**module),
+            _ => None,
+        });
+        linked
+            .chain(pending.iter().map(|pending| &*pending.module))
+            .filter(|module| module.structs().next().is_some())
+            .map(|module| (module.path().clone(), module.structs().cloned().collect()))
+            .collect()
+    }
+
     fn reanalyze_module(
         &mut self,
         module_id: ModuleIndex,
@@ -691,6 +718,11 @@ impl Linker {
         self.callgraph.toposort_caller(caller)
     }
 
+    /// Returns the procedures invoked by `caller`, not counting invocations by MAST root
+    pub(crate) fn callees(&self, caller: GlobalProcedureIndex) -> &[GlobalProcedureIndex] {
+        self.callgraph.out_edges(caller)
+    }
+
     /// Fetch a [ProcedureLink] by its [GlobalProcedureIndex].
     ///
     /// # Panics
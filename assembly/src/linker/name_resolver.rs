@@ -4,7 +4,7 @@ use miden_assembly_syntax::{
     SourceSpan, Span, Spanned, Word,
     ast::{
         Ident, InvocationTarget, InvokeKind, ProcedureName, QualifiedProcedureName,
-        ResolvedProcedure,
+        ResolvedProcedure, Visibility,
     },
     diagnostics::RelatedLabel,
     library::{LibraryNamespace, LibraryPath},
@@ -25,6 +25,8 @@ struct ThinModule {
     index: ModuleIndex,
     path: LibraryPath,
     resolver: crate::ast::LocalNameResolver,
+    /// The visibility of each procedure in the module, by procedure index
+    visibility: Vec<Visibility>,
 }
 
 /// Represents the context in which names should be resolved.
@@ -113,6 +115,7 @@ impl<'a> NameResolver<'a> {
             index: module.module_index,
             path: module.module.path().clone(),
             resolver: module.module.resolver(),
+            visibility: module.module.procedures().map(|p| p.visibility()).collect(),
         });
     }
 
@@ -176,6 +179,11 @@ impl<'a> NameResolver<'a> {
                 let gid = self.find(caller, &fqn)?;
                 Ok(ResolvedTarget::Exact { gid })
             },
+            InvocationTarget::Instantiation { .. } => {
+                unreachable!(
+                    "instantiations of generic procedures are expanded by semantic analysis"
+                )
+            },
         }
     }
 
@@ -329,6 +337,23 @@ impl<'a> NameResolver<'a> {
                         module: module_index,
                         index: index.into_inner(),
                     };
+                    // The visibility of syscall targets is validated by the assembler
+                    if !matches!(caller.kind, InvokeKind::SysCall) {
+                        let visibility = self.procedure_visibility(id);
+                        let caller_path = self.module_path(current_caller.module);
+                        if !visibility.is_visible_from(&caller_path, &current_callee.module) {
+                            break Err(LinkerError::InaccessibleProcedure {
+                                span: current_caller.span,
+                                source_file: self
+                                    .graph
+                                    .source_manager
+                                    .get(current_caller.span.source_id())
+                                    .ok(),
+                                callee: current_callee.into_owned().into(),
+                                module: caller_path,
+                            });
+                        }
+                    }
                     if matches!(current_caller.kind, InvokeKind::SysCall if self.graph.kernel_index != Some(module_index))
                     {
                         break Err(LinkerError::InvalidSysCallTarget {
@@ -515,6 +540,19 @@ impl<'a> NameResolver<'a> {
         }
     }
 
+    /// Returns the visibility of the procedure `gid`, as declared in its module.
+    fn procedure_visibility(&self, gid: GlobalProcedureIndex) -> Visibility {
+        match self.graph.modules[gid.module.as_usize()].as_ref() {
+            Some(ModuleLink::Ast(module)) => module[gid.index].visibility(),
+            // Only the exported procedures of compiled modules can be referenced
+            Some(ModuleLink::Info(_)) => Visibility::Public,
+            None => {
+                let pending_index = self.pending_index(gid.module);
+                self.pending[pending_index].visibility[gid.index.as_usize()]
+            },
+        }
+    }
+
     fn pending_index(&self, index: ModuleIndex) -> usize {
         self.pending
             .iter()
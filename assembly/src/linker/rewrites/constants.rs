@@ -0,0 +1,218 @@
+use alloc::{collections::BTreeMap, sync::Arc, vec::Vec};
+use core::ops::ControlFlow;
+
+use miden_assembly_syntax::{
+    ConstEvalError, Felt, LibraryPath, SourceManager, SourceSpan, Span, Spanned,
+    ast::{
+        ConstantExpr, Ident, Immediate, Module, Op, Struct, StructPath,
+        visit::{self, VisitMut},
+    },
+    diagnostics::SourceFile,
+    parser::IntValue,
+};
+use miden_core::FieldElement;
+
+use crate::linker::LinkerError;
+
+// DEFERRED CONSTANT REWRITER
+// ================================================================================================
+
+/// A [DeferredConstantRewriter] evaluates the constants of a [Module] which refer to the layout of
+/// structs defined in other modules, e.g. `const.KEY = offsetof(note::NOTE.key)`, and replaces the
+/// immediates referring to those constants with their values.
+///
+/// These constants cannot be evaluated during semantic analysis, as each module is analyzed on its
+/// own, so they are evaluated once the modules defining those structs are known to the linker.
+pub struct DeferredConstantRewriter<'a> {
+    /// The structs defined in each module assembled from source, by module path
+    structs: &'a BTreeMap<LibraryPath, Vec<Struct>>,
+    source_manager: &'a dyn SourceManager,
+    /// The values of the deferred constants of the module being rewritten
+    values: BTreeMap<Ident, Felt>,
+}
+
+impl<'a> DeferredConstantRewriter<'a> {
+    /// Create a new [DeferredConstantRewriter] which resolves structs using `structs`
+    pub fn new(
+        structs: &'a BTreeMap<LibraryPath, Vec<Struct>>,
+        source_manager: &'a dyn SourceManager,
+    ) -> Self {
+        Self {
+            structs,
+            source_manager,
+            values: Default::default(),
+        }
+    }
+
+    /// Evaluate the deferred constants of `module`, and resolve the immediates referring to them
+    pub fn apply(&mut self, module: &mut Module) -> Result<(), LinkerError> {
+        let constants = module.take_deferred_constants();
+        if constants.is_empty() {
+            return Ok(());
+        }
+
+        self.values.clear();
+        for constant in constants {
+            let value = self.eval(module, &constant.value)?;
+            self.values.insert(constant.name, value);
+        }
+
+        if let ControlFlow::Break(err) = self.visit_mut_module(module) {
+            return Err(err);
+        }
+
+        Ok(())
+    }
+
+    fn eval(&self, module: &Module, expr: &ConstantExpr) -> Result<Felt, LinkerError> {
+        match expr {
+            ConstantExpr::Literal(value) => Ok(value.into_inner()),
+            ConstantExpr::SizeOf { ty, .. } => {
+                self.resolve_struct(module, ty).map(|def| Felt::from(def.size))
+            },
+            ConstantExpr::OffsetOf { ty, field, .. } => {
+                let def = self.resolve_struct(module, ty)?;
+                match def.field(field) {
+                    Some(field) => Ok(Felt::from(field.offset)),
+                    None => Err(LinkerError::UndefinedField {
+                        span: field.span(),
+                        source_file: self.source_file(field.span()),
+                    }),
+                }
+            },
+            ConstantExpr::BinaryOp { span, op, lhs, rhs } => {
+                let lhs = self.eval(module, lhs)?;
+                let rhs = self.eval(module, rhs)?;
+                op.eval(lhs, rhs).map_err(|kind| {
+                    let source_file = self.source_file(*span);
+                    match kind {
+                        ConstEvalError::DivisionByZero => {
+                            LinkerError::DivisionByZero { span: *span, source_file }
+                        },
+                        kind => LinkerError::InvalidConstantExpression {
+                            span: *span,
+                            source_file,
+                            kind,
+                        },
+                    }
+                })
+            },
+            ConstantExpr::Ternary { cond, then_expr, else_expr, .. } => {
+                if self.eval(module, cond)? == Felt::ZERO {
+                    self.eval(module, else_expr)
+                } else {
+                    self.eval(module, then_expr)
+                }
+            },
+            ConstantExpr::Var(_)
+            | ConstantExpr::String(_)
+            | ConstantExpr::Word(_)
+            | ConstantExpr::Hash { .. }
+            | ConstantExpr::Event { .. } => Err(LinkerError::InvalidConstant {
+                span: expr.span(),
+                source_file: self.source_file(expr.span()),
+            }),
+        }
+    }
+
+    fn resolve_struct(&self, module: &Module, ty: &StructPath) -> Result<&'a Struct, LinkerError> {
+        let path = match ty.module.as_ref() {
+            // Semantic analysis ensures that the module of an imported struct is imported
+            Some(name) => module
+                .resolve_import(name)
+                .expect("expected module of imported struct to be imported")
+                .path(),
+            None => module.path(),
+        };
+        self.structs
+            .get(path)
+            .and_then(|structs| structs.iter().find(|def| def.name == ty.name))
+            .ok_or_else(|| LinkerError::UndefinedStruct {
+                span: ty.span(),
+                source_file: self.source_file(ty.span()),
+                path: path.clone(),
+                name: ty.name.clone(),
+            })
+    }
+
+    /// Replaces `imm` with the value of the deferred constant it refers to, if any
+    fn rewrite_immediate<T>(
+        &self,
+        imm: &mut Immediate<T>,
+        convert: impl FnOnce(Felt) -> Option<T>,
+    ) -> ControlFlow<LinkerError> {
+        let Immediate::Constant(name) = imm else {
+            return ControlFlow::Continue(());
+        };
+        let Some(value) = self.values.get(name).copied() else {
+            return ControlFlow::Continue(());
+        };
+        let span = name.span();
+        match convert(value) {
+            Some(value) => {
+                *imm = Immediate::Value(Span::new(span, value));
+                ControlFlow::Continue(())
+            },
+            None => ControlFlow::Break(LinkerError::ImmediateOverflow {
+                span,
+                source_file: self.source_file(span),
+            }),
+        }
+    }
+
+    fn source_file(&self, span: SourceSpan) -> Option<Arc<SourceFile>> {
+        self.source_manager.get(span.source_id()).ok()
+    }
+}
+
+impl VisitMut<LinkerError> for DeferredConstantRewriter<'_> {
+    fn visit_mut_op(&mut self, op: &mut Op) -> ControlFlow<LinkerError> {
+        visit::visit_mut_op(self, op)?;
+
+        // the checks done during semantic analysis for constant operands which were not known then
+        match op {
+            Op::Repeat { count: Immediate::Value(count), .. } if count.into_inner() == 0 => {
+                ControlFlow::Break(LinkerError::InvalidRepeatCount {
+                    span: count.span(),
+                    source_file: self.source_file(count.span()),
+                })
+            },
+            Op::Match { cases, .. } => {
+                let mut seen = BTreeMap::<u32, SourceSpan>::default();
+                for case in cases.iter() {
+                    let Immediate::Value(value) = &case.value else {
+                        continue;
+                    };
+                    if let Some(prev_span) = seen.insert(value.into_inner(), value.span()) {
+                        return ControlFlow::Break(LinkerError::DuplicateMatchCase {
+                            span: value.span(),
+                            prev_span,
+                            source_file: self.source_file(value.span()),
+                            value: value.into_inner(),
+                        });
+                    }
+                }
+                ControlFlow::Continue(())
+            },
+            _ => ControlFlow::Continue(()),
+        }
+    }
+    fn visit_mut_immediate_u8(&mut self, imm: &mut Immediate<u8>) -> ControlFlow<LinkerError> {
+        self.rewrite_immediate(imm, |value| u8::try_from(value.as_int()).ok())
+    }
+    fn visit_mut_immediate_u16(&mut self, imm: &mut Immediate<u16>) -> ControlFlow<LinkerError> {
+        self.rewrite_immediate(imm, |value| u16::try_from(value.as_int()).ok())
+    }
+    fn visit_mut_immediate_u32(&mut self, imm: &mut Immediate<u32>) -> ControlFlow<LinkerError> {
+        self.rewrite_immediate(imm, |value| u32::try_from(value.as_int()).ok())
+    }
+    fn visit_mut_immediate_felt(&mut self, imm: &mut Immediate<Felt>) -> ControlFlow<LinkerError> {
+        self.rewrite_immediate(imm, Some)
+    }
+    fn visit_mut_immediate_hex(
+        &mut self,
+        imm: &mut Immediate<IntValue>,
+    ) -> ControlFlow<LinkerError> {
+        self.rewrite_immediate(imm, |value| Some(IntValue::Felt(value)))
+    }
+}
@@ -1,3 +1,4 @@
+mod constants;
 mod module;
 
-pub use self::module::ModuleRewriter;
+pub use self::{constants::DeferredConstantRewriter, module::ModuleRewriter};
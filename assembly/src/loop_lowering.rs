@@ -0,0 +1,107 @@
+use alloc::string::{String, ToString};
+use core::ops::Range;
+
+use miden_assembly_syntax::{
+    ast::Block,
+    diagnostics::{RelatedLabel, Report, Spanned},
+};
+use miden_core::{
+    AssemblyOp, Decorator, Felt, ONE, Operation, WORD_SIZE, debuginfo::SourceSpan, mast::MastNodeId,
+};
+
+use crate::{Assembler, ProcedureContext, mast_forest_builder::MastForestBuilder};
+
+// COUNTED LOOPS
+// ================================================================================================
+
+impl Assembler {
+    /// Compiles a loop executing `body` once for each value of a counter in `range`, in increasing
+    /// order, to a `LOOP` node, rather than unrolling it.
+    ///
+    /// The counter is kept in an internal local of the procedure, see
+    /// [ProcedureContext::num_internal_locals], and is pushed on top of the stack before each
+    /// iteration if `push_counter` is true, i.e. for `for` loops, but not for rolled `repeat`
+    /// loops. `op` is the name of the loop in the assembly operation decorator added in debug
+    /// mode.
+    #[allow(clippy::too_many_arguments)]
+    pub(super) fn compile_counted_loop(
+        &self,
+        span: SourceSpan,
+        op: String,
+        range: Range<u32>,
+        push_counter: bool,
+        body: &Block,
+        proc_ctx: &mut ProcedureContext,
+        mast_forest_builder: &mut MastForestBuilder,
+    ) -> Result<MastNodeId, Report> {
+        use Operation::*;
+
+        // the range is only empty here if it is given by constants resolved at link time
+        if range.is_empty() {
+            return Err(RelatedLabel::error("invalid loop range")
+                .with_help("loops must iterate at least once")
+                .with_labeled_span(span, format!("the range {range:?} is empty"))
+                .with_source_file(proc_ctx.source_manager().get(span.source_id()).ok())
+                .into());
+        }
+        let Some(offset) = proc_ctx.alloc_internal_locals(WORD_SIZE as u16) else {
+            return Err(RelatedLabel::error("too many locals")
+                .with_help("the loop counter does not fit in the locals of this procedure")
+                .with_labeled_span(proc_ctx.span(), "this procedure allocates too many locals")
+                .with_labeled_span(span, "but this loop requires a local for its counter")
+                .with_source_file(proc_ctx.source_manager().get(span.source_id()).ok())
+                .into());
+        };
+        let counter = Felt::from(offset);
+
+        let repetitions = proc_ctx.repetitions();
+        proc_ctx.set_repetitions(repetitions.saturating_mul(range.len() as u32));
+        let body = self.compile_body(body.iter(), proc_ctx, None, mast_forest_builder)?;
+        proc_ctx.set_repetitions(repetitions);
+
+        // push.START loc_store.COUNTER push.1
+        let setup = mast_forest_builder.ensure_block(
+            vec![Push(Felt::from(range.start)), Push(-counter), FmpAdd, MStore, Drop, Push(ONE)],
+            None,
+        )?;
+
+        // [loc_load.COUNTER] BODY loc_load.COUNTER add.1 dup loc_store.COUNTER neq.END
+        let step = mast_forest_builder.ensure_block(
+            vec![
+                Push(-counter),
+                FmpAdd,
+                MLoad,
+                Incr,
+                Dup0,
+                Push(-counter),
+                FmpAdd,
+                MStore,
+                Drop,
+                Push(Felt::from(range.end)),
+                Eq,
+                Not,
+            ],
+            None,
+        )?;
+        let mut iteration = vec![body, step];
+        if push_counter {
+            let load =
+                mast_forest_builder.ensure_block(vec![Push(-counter), FmpAdd, MLoad], None)?;
+            iteration.insert(0, load);
+        }
+        let iteration = mast_forest_builder.join_nodes(iteration)?;
+        let loop_node_id = mast_forest_builder.ensure_loop(iteration)?;
+        let node_id = mast_forest_builder.join_nodes(vec![setup, loop_node_id])?;
+
+        // Add an assembly operation decorator to the loop node in debug mode.
+        if self.in_debug_mode() {
+            let location = proc_ctx.source_manager().location(span).ok();
+            let context_name = proc_ctx.name().to_string();
+            let op = AssemblyOp::new(location, context_name, 0, op, false);
+            let decorator_id = mast_forest_builder.ensure_decorator(Decorator::AsmOp(op))?;
+            mast_forest_builder.append_before_enter(node_id, &[decorator_id]);
+        }
+
+        Ok(node_id)
+    }
+}
@@ -15,7 +15,8 @@ use miden_core::{
 
 use super::{GlobalProcedureIndex, LinkerError, Procedure};
 use crate::{
-    Library,
+    Library, ProcedureInlining,
+    ast::{InlineHint, QualifiedProcedureName},
     diagnostics::{IntoDiagnostic, Report, WrapErr},
     report,
 };
@@ -26,6 +27,10 @@ use crate::{
 /// Constant that decides how many operation batches disqualify a procedure from inlining.
 const PROCEDURE_INLINING_THRESHOLD: usize = 32;
 
+/// Constant that decides how many operation batches disqualify an exported procedure from
+/// inlining.
+const EXPORTED_PROCEDURE_INLINING_THRESHOLD: usize = 4;
+
 // MAST FOREST BUILDER
 // ================================================================================================
 
@@ -70,6 +75,15 @@ pub struct MastForestBuilder {
     /// Keeps track of the new ids assigned to nodes that are copied from the MAST of
     /// statically-linked libraries.
     statically_linked_mast_remapping: Remapping,
+    /// The inlining behavior requested by the procedures whose body is a given node. If multiple
+    /// procedures share the same body, `@noinline` takes precedence over `@inline`.
+    inline_hints: BTreeMap<MastNodeId, InlineHint>,
+    /// The nodes which are the body of at least one exported procedure.
+    exported_roots: BTreeSet<MastNodeId>,
+    /// The inlining decisions made for the invocation sites of each procedure.
+    inlining: BTreeMap<QualifiedProcedureName, ProcedureInlining>,
+    /// The arms of the jump tables used by the procedures whose body is a given node.
+    jump_table_targets: BTreeMap<MastNodeId, Vec<MastNodeId>>,
 }
 
 impl MastForestBuilder {
@@ -190,6 +204,22 @@ impl MastForestBuilder {
     pub fn get_mast_node(&self, id: MastNodeId) -> Option<&MastNode> {
         self.mast_forest.get_node_by_id(id)
     }
+
+    /// Returns the inlining decisions made for the invocation sites of each procedure so far.
+    pub fn inlining(&self) -> &BTreeMap<QualifiedProcedureName, ProcedureInlining> {
+        &self.inlining
+    }
+
+    /// Returns the arms of the jump tables used by the procedure whose body is `body_node_id`.
+    ///
+    /// The arms are executed via `dynexec`, so they must be roots of any MAST forest which the
+    /// body of the procedure is copied to.
+    pub fn jump_table_targets(&self, body_node_id: MastNodeId) -> &[MastNodeId] {
+        self.jump_table_targets
+            .get(&body_node_id)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
 }
 
 // ------------------------------------------------------------------------------------------------
@@ -245,7 +275,21 @@ impl MastForestBuilder {
             }
         }
 
-        self.mast_forest.make_root(procedure.body_node_id());
+        let body_node_id = procedure.body_node_id();
+        if procedure.visibility().is_exported() {
+            self.exported_roots.insert(body_node_id);
+        }
+        match procedure.inline_hint() {
+            Some(InlineHint::Never) => {
+                self.inline_hints.insert(body_node_id, InlineHint::Never);
+            },
+            Some(InlineHint::Always) => {
+                self.inline_hints.entry(body_node_id).or_insert(InlineHint::Always);
+            },
+            None => (),
+        }
+
+        self.mast_forest.make_root(body_node_id);
         self.proc_gid_by_mast_root.insert(procedure.mast_root(), gid);
         self.procedures.insert(gid, procedure);
 
@@ -294,7 +338,7 @@ impl MastForestBuilder {
         let mut merged_node_ids = Vec::with_capacity(node_ids.len());
         let mut contiguous_basic_block_ids: Vec<MastNodeId> = Vec::new();
 
-        for mast_node_id in node_ids {
+        for mast_node_id in self.expand_inlined_joins(node_ids) {
             if self.mast_forest[mast_node_id].is_basic_block() {
                 contiguous_basic_block_ids.push(mast_node_id);
             } else {
@@ -323,6 +367,10 @@ impl MastForestBuilder {
             return Ok(Vec::new());
         }
         if contiguous_basic_block_ids.len() == 1 {
+            // there is nothing to merge the block with, so it is kept as-is
+            if self.mast_forest.is_procedure_root(contiguous_basic_block_ids[0]) {
+                self.record_inlining(contiguous_basic_block_ids[0], false);
+            }
             return Ok(contiguous_basic_block_ids.to_vec());
         }
 
@@ -338,10 +386,11 @@ impl MastForestBuilder {
                 self.mast_forest[basic_block_id].get_basic_block().unwrap().clone();
 
             // check if the block should be merged with other blocks
-            if should_merge(
-                self.mast_forest.is_procedure_root(basic_block_id),
-                basic_block_node.num_op_batches(),
-            ) {
+            let merge = self.should_merge(basic_block_id, basic_block_node.num_op_batches());
+            if self.mast_forest.is_procedure_root(basic_block_id) {
+                self.record_inlining(basic_block_id, merge);
+            }
+            if merge {
                 for &(op_idx, decorator) in basic_block_node.decorators() {
                     decorators.push((op_idx + operations.len(), decorator));
                 }
@@ -373,6 +422,96 @@ impl MastForestBuilder {
 
         Ok(merged_basic_blocks)
     }
+
+    /// Replaces the nodes in `node_ids` which are `JOIN` nodes forming the body of a procedure
+    /// annotated with `@inline` by the sequence of nodes they join, so that the basic blocks of the
+    /// procedure can be merged with the surrounding basic blocks.
+    fn expand_inlined_joins(&mut self, node_ids: Vec<MastNodeId>) -> Vec<MastNodeId> {
+        let mut expanded = Vec::with_capacity(node_ids.len());
+        for node_id in node_ids {
+            if matches!(self.mast_forest[node_id], MastNode::Join(_))
+                && self.inline_hints.get(&node_id) == Some(&InlineHint::Always)
+            {
+                self.record_inlining(node_id, true);
+                self.flatten_join(node_id, &mut expanded);
+            } else {
+                expanded.push(node_id);
+            }
+        }
+
+        expanded
+    }
+
+    /// Appends the nodes joined by the `JOIN` node `node_id` to `node_ids`, recursively flattening
+    /// nested `JOIN` nodes, unless they form the body of a procedure which is not itself inlined.
+    ///
+    /// `JOIN` nodes with decorators are not flattened, as their decorators would be lost.
+    fn flatten_join(&self, node_id: MastNodeId, node_ids: &mut Vec<MastNodeId>) {
+        let inlined = !self.mast_forest.is_procedure_root(node_id)
+            || self.inline_hints.get(&node_id) == Some(&InlineHint::Always);
+        match &self.mast_forest[node_id] {
+            MastNode::Join(join)
+                if inlined && join.before_enter().is_empty() && join.after_exit().is_empty() =>
+            {
+                self.flatten_join(join.first(), node_ids);
+                self.flatten_join(join.second(), node_ids);
+            },
+            _ => node_ids.push(node_id),
+        }
+    }
+
+    /// Determines if we want to merge a block with other blocks. Currently, this works as follows:
+    /// - If the block is the body of a procedure annotated with `@inline` or `@noinline`, we always
+    ///   or never merge it, respectively.
+    /// - If the block is a procedure, we merge it only if the number of operation batches is
+    ///   smaller then the threshold (currently set at 32). The reasoning is based on an estimate of
+    ///   the the runtime penalty of not inlining the procedure. We assume that this penalty is
+    ///   roughly 3 extra nodes in the MAST and so would require 3 additional hashes at runtime.
+    ///   Since hashing each operation batch requires 1 hash, this basically implies that if the
+    ///   runtime penalty is more than 10%, we inline the block, but if it is less than 10% we
+    ///   accept the penalty to make deserialization faster.
+    /// - If the block is an exported procedure, its body remains in the MAST forest even if it is
+    ///   merged everywhere, so every merge adds a copy of it. Hence, we merge it only if it has
+    ///   fewer operation batches than the roughly 3 extra nodes required to keep it out-of-line,
+    ///   and otherwise reuse the existing node.
+    /// - If the block is not a procedure, we always merge it because: (1) if it is a large block,
+    ///   it is likely to be unique and, thus, the original block will be orphaned and removed
+    ///   later; (2) if it is a small block, there is a large run-time benefit for inlining it.
+    fn should_merge(&self, node_id: MastNodeId, num_op_batches: usize) -> bool {
+        if !self.mast_forest.is_procedure_root(node_id) {
+            return true;
+        }
+
+        match self.inline_hints.get(&node_id) {
+            Some(InlineHint::Always) => true,
+            Some(InlineHint::Never) => false,
+            None if self.exported_roots.contains(&node_id) => {
+                num_op_batches < EXPORTED_PROCEDURE_INLINING_THRESHOLD
+            },
+            None => num_op_batches < PROCEDURE_INLINING_THRESHOLD,
+        }
+    }
+
+    /// Records whether an invocation site of the procedure whose body is `node_id` was inlined.
+    fn record_inlining(&mut self, node_id: MastNodeId, inlined: bool) {
+        let digest = self.mast_forest[node_id].digest();
+        let Some(procedure) = self.find_procedure_by_mast_root(&digest) else {
+            return;
+        };
+        let name = procedure.fully_qualified_name().clone();
+        let num_operations = SubtreeIterator::new(&node_id, &self.mast_forest)
+            .filter_map(|node_id| self.mast_forest[node_id].get_basic_block())
+            .map(|block| block.num_operations() as usize)
+            .sum();
+
+        let entry = self.inlining.entry(name).or_default();
+        entry.num_operations = num_operations;
+        if inlined {
+            entry.inlined_sites += 1;
+        } else {
+            entry.out_of_line_sites += 1;
+        }
+    }
 }
 
 // ------------------------------------------------------------------------------------------------
@@ -491,6 +630,18 @@ impl MastForestBuilder {
         self.ensure_node(MastNode::new_dyncall())
     }
 
+    /// Marks the node with the given ID as a root of the MAST forest, so that it can be executed
+    /// via `dynexec`.
+    pub fn ensure_root(&mut self, node_id: MastNodeId) {
+        self.mast_forest.make_root(node_id);
+    }
+
+    /// Records the arms of the jump tables used by the procedure whose body is `body_node_id`,
+    /// see [Self::jump_table_targets].
+    pub fn set_jump_table_targets(&mut self, body_node_id: MastNodeId, targets: Vec<MastNodeId>) {
+        self.jump_table_targets.insert(body_node_id, targets);
+    }
+
     /// Adds a node corresponding to the given MAST root, according to how it is linked.
     ///
     /// * If statically-linked, then the entire subtree is copied, and the MastNodeId of the root of
@@ -510,6 +661,111 @@ impl MastForestBuilder {
         }
     }
 
+    /// Copies the subtree of `forest` rooted at `root` into this forest, along with its decorators
+    /// and the error messages referenced by its operations, and returns the [MastNodeId] of the
+    /// copied root.
+    ///
+    /// Unlike [`Self::ensure_external_link`], this does not require `forest` to be one of the
+    /// statically-linked forests, and is used to reuse the MAST of previously compiled procedures.
+    pub fn import_subtree(
+        &mut self,
+        forest: &MastForest,
+        root: MastNodeId,
+    ) -> Result<MastNodeId, Report> {
+        let mut node_ids = BTreeMap::<MastNodeId, MastNodeId>::new();
+        let mut decorator_ids = BTreeMap::<DecoratorId, DecoratorId>::new();
+        for old_id in SubtreeIterator::new(&root, forest) {
+            let old_node = &forest[old_id];
+            let mut node = match old_node {
+                MastNode::Block(block) => {
+                    let (op_indices, ids): (Vec<_>, Vec<_>) =
+                        block.decorators().iter().copied().unzip();
+                    let ids = self.import_decorators(forest, &ids, &mut decorator_ids)?;
+                    let decorators = op_indices.into_iter().zip(ids).collect();
+                    let mut block = block.clone();
+                    block.set_decorators(decorators);
+                    for op in block.operations() {
+                        if let Operation::U32assert2(code)
+                        | Operation::Assert(code)
+                        | Operation::MpVerify(code) = op
+                        {
+                            if let Some(msg) = forest.resolve_error_message(*code) {
+                                self.register_error(msg);
+                            }
+                        }
+                    }
+                    MastNode::Block(block)
+                },
+                MastNode::Join(join) => MastNode::new_join(
+                    node_ids[&join.first()],
+                    node_ids[&join.second()],
+                    &self.mast_forest,
+                )
+                .into_diagnostic()?,
+                MastNode::Split(split) => MastNode::new_split(
+                    node_ids[&split.on_true()],
+                    node_ids[&split.on_false()],
+                    &self.mast_forest,
+                )
+                .into_diagnostic()?,
+                MastNode::Loop(loop_node) => {
+                    MastNode::new_loop(node_ids[&loop_node.body()], &self.mast_forest)
+                        .into_diagnostic()?
+                },
+                MastNode::Call(call) if call.is_syscall() => {
+                    MastNode::new_syscall(node_ids[&call.callee()], &self.mast_forest)
+                        .into_diagnostic()?
+                },
+                MastNode::Call(call) => {
+                    MastNode::new_call(node_ids[&call.callee()], &self.mast_forest)
+                        .into_diagnostic()?
+                },
+                MastNode::Dyn(dyn_node) if dyn_node.is_dyncall() => MastNode::new_dyncall(),
+                MastNode::Dyn(_) => MastNode::new_dyn(),
+                MastNode::External(external) => MastNode::new_external(external.digest()),
+            };
+            // decorators of basic blocks were imported above
+            if !node.is_basic_block() {
+                let before_enter =
+                    self.import_decorators(forest, old_node.before_enter(), &mut decorator_ids)?;
+                let after_exit =
+                    self.import_decorators(forest, old_node.after_exit(), &mut decorator_ids)?;
+                node.append_before_enter(&before_enter);
+                node.append_after_exit(&after_exit);
+            }
+
+            let new_id = self.ensure_node(node)?;
+            node_ids.insert(old_id, new_id);
+        }
+
+        Ok(node_ids[&root])
+    }
+
+    /// Copies the decorators `ids` of `forest` into this forest, and returns their new IDs.
+    ///
+    /// `imported` maps the IDs of decorators of `forest` which were already copied to their new
+    /// IDs.
+    fn import_decorators(
+        &mut self,
+        forest: &MastForest,
+        ids: &[DecoratorId],
+        imported: &mut BTreeMap<DecoratorId, DecoratorId>,
+    ) -> Result<Vec<DecoratorId>, Report> {
+        ids.iter()
+            .map(|id| match imported.get(id) {
+                Some(new_id) => Ok(*new_id),
+                None => {
+                    let decorator = forest
+                        .get_decorator_by_id(*id)
+                        .ok_or_else(|| report!("invalid decorator id {id}"))?;
+                    let new_id = self.ensure_decorator(decorator.clone())?;
+                    imported.insert(*id, new_id);
+                    Ok(new_id)
+                },
+            })
+            .collect()
+    }
+
     /// Adds a list of decorators to the provided node to be executed before the node executes.
     ///
     /// If other decorators are already present, the new decorators are added to the end of the
@@ -589,25 +845,3 @@ impl MastForestBuilder {
             .into_diagnostic()
     }
 }
-
-// HELPER FUNCTIONS
-// ================================================================================================
-
-/// Determines if we want to merge a block with other blocks. Currently, this works as follows:
-/// - If the block is a procedure, we merge it only if the number of operation batches is smaller
-///   then the threshold (currently set at 32). The reasoning is based on an estimate of the the
-///   runtime penalty of not inlining the procedure. We assume that this penalty is roughly 3 extra
-///   nodes in the MAST and so would require 3 additional hashes at runtime. Since hashing each
-///   operation batch requires 1 hash, this basically implies that if the runtime penalty is more
-///   than 10%, we inline the block, but if it is less than 10% we accept the penalty to make
-///   deserialization faster.
-/// - If the block is not a procedure, we always merge it because: (1) if it is a large block, it is
-///   likely to be unique and, thus, the original block will be orphaned and removed later; (2) if
-///   it is a small block, there is a large run-time benefit for inlining it.
-fn should_merge(is_procedure: bool, num_op_batches: usize) -> bool {
-    if is_procedure {
-        num_op_batches < PROCEDURE_INLINING_THRESHOLD
-    } else {
-        true
-    }
-}
@@ -0,0 +1,275 @@
+use alloc::{string::ToString, vec::Vec};
+
+use miden_assembly_syntax::{
+    ast::{Block, MatchCase},
+    diagnostics::Report,
+};
+use miden_core::{
+    AssemblyOp, Decorator, Felt, Operation, WORD_SIZE, ZERO, debuginfo::SourceSpan,
+    mast::MastNodeId,
+};
+
+use crate::{Assembler, ProcedureContext, mast_forest_builder::MastForestBuilder, report};
+
+// CONSTANTS
+// ================================================================================================
+
+/// The number of cycles spent at each level of the `Split` tree to which a `match` is lowered,
+/// i.e. executing `dup push.PIVOT u32lt` in a basic block, the `SPLIT` node branching on the
+/// result, and the `JOIN` node sequencing the two.
+const TREE_LEVEL_CYCLES: u64 = 11;
+
+/// The number of cycles spent at a leaf of the `Split` tree to which a `match` is lowered, when
+/// the value still needs to be compared to the case of the leaf, i.e. executing `dup eq.K` in a
+/// basic block, the `SPLIT` node branching on the result, and the `JOIN` node sequencing the two.
+const TREE_LEAF_CYCLES: u64 = 9;
+
+/// The number of cycles spent dispatching through a jump table, i.e. executing the bounds check,
+/// the `SPLIT` node branching on it, computing the address of the entry and the `DYN` node.
+const TABLE_DISPATCH_CYCLES: u64 = 22;
+
+/// The number of cycles spent storing each entry of a jump table in the locals of the procedure,
+/// i.e. pushing the digest of the arm and its address, then `mem_storew dropw`.
+const TABLE_ENTRY_SETUP_CYCLES: u64 = 11;
+
+/// The maximum number of entries in a jump table.
+const MAX_TABLE_LEN: u32 = 256;
+
+// MATCH LOWERING
+// ================================================================================================
+
+/// A jump table through which a `match` dispatches to its arms.
+///
+/// The digests of the arms are stored in locals allocated for the procedure in addition to the
+/// ones it declares, when the procedure is entered. Dispatching computes the address of the entry
+/// from the value being matched, and executes the arm via `dynexec`.
+pub struct JumpTable {
+    /// The arm executed for each value, starting with the smallest case of the `match`, where the
+    /// values without a case map to the `default` arm. Each arm is a root of the MAST forest, as
+    /// required by `dynexec`.
+    targets: Vec<MastNodeId>,
+    /// The offset of the table below the frame pointer, as seen from the body of the procedure
+    offset: u16,
+}
+
+impl JumpTable {
+    /// Returns the arms of the table.
+    pub fn targets(&self) -> &[MastNodeId] {
+        &self.targets
+    }
+}
+
+/// The ways in which a `match` can be lowered to MAST.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum MatchLowering {
+    /// A balanced tree of `Split` nodes, doing a binary search over the cases.
+    Tree,
+    /// A jump table of the digests of the arms, see [JumpTable].
+    JumpTable,
+}
+
+impl MatchLowering {
+    /// Chooses the cheaper lowering of a `match` with the given (sorted) case values, which is
+    /// executed `repetitions` times per invocation of the enclosing procedure.
+    ///
+    /// Dispatching through a jump table costs about the same number of cycles as two levels of a
+    /// `Split` tree, but storing the table costs cycles for each entry on every invocation of the
+    /// procedure. Hence, a jump table is only cheaper for large matches executed many times, i.e.
+    /// in the body of `repeat` loops. The number of iterations of `while.true` loops is unknown,
+    /// so their bodies are assumed to be executed once.
+    fn choose(values: &[u32], repetitions: u32, available_locals: u16) -> Self {
+        let (Some(&min), Some(&max)) = (values.first(), values.last()) else {
+            return Self::Tree;
+        };
+        let len = max - min + 1;
+        if values.len() < 2
+            || len > MAX_TABLE_LEN
+            || len as usize * WORD_SIZE > available_locals as usize
+        {
+            return Self::Tree;
+        }
+
+        let tree_cycles =
+            values.len().next_power_of_two().ilog2() as u64 * TREE_LEVEL_CYCLES + TREE_LEAF_CYCLES;
+        let setup_cycles = len as u64 * TABLE_ENTRY_SETUP_CYCLES;
+        let repetitions = repetitions as u64;
+        if TABLE_DISPATCH_CYCLES * repetitions + setup_cycles < tree_cycles * repetitions {
+            Self::JumpTable
+        } else {
+            Self::Tree
+        }
+    }
+}
+
+impl Assembler {
+    /// Compiles a `match` over the u32 value on top of the stack, which is consumed.
+    ///
+    /// The `match` is lowered either to a balanced tree of `Split` nodes, or to a jump table, see
+    /// [MatchLowering::choose].
+    pub(super) fn compile_match(
+        &self,
+        span: SourceSpan,
+        cases: &[MatchCase],
+        default: &Block,
+        proc_ctx: &mut ProcedureContext,
+        mast_forest_builder: &mut MastForestBuilder,
+    ) -> Result<MastNodeId, Report> {
+        let mut arms = Vec::with_capacity(cases.len());
+        for case in cases {
+            let body = self.compile_body(case.body.iter(), proc_ctx, None, mast_forest_builder)?;
+            arms.push((case.value.expect_value(), body));
+        }
+        let default = self.compile_body(default.iter(), proc_ctx, None, mast_forest_builder)?;
+        arms.sort_by_key(|(value, _)| *value);
+
+        let values = arms.iter().map(|(value, _)| *value).collect::<Vec<_>>();
+        let available_locals = u16::MAX
+            - proc_ctx.num_locals().next_multiple_of(WORD_SIZE as u16)
+            - proc_ctx.num_internal_locals();
+        let lowered = match MatchLowering::choose(&values, proc_ctx.repetitions(), available_locals)
+        {
+            MatchLowering::Tree => lower_tree(&arms, 0, u32::MAX, default, mast_forest_builder)?,
+            MatchLowering::JumpTable => {
+                lower_jump_table(&arms, default, proc_ctx, mast_forest_builder)?
+            },
+        };
+
+        // u32assert
+        let check = mast_forest_builder.ensure_block(
+            vec![Operation::Pad, Operation::U32assert2(ZERO), Operation::Drop],
+            None,
+        )?;
+        let node_id = mast_forest_builder.join_nodes(vec![check, lowered])?;
+
+        // Add an assembly operation decorator to the match node in debug mode.
+        if self.in_debug_mode() {
+            let location = proc_ctx.source_manager().location(span).ok();
+            let context_name = proc_ctx.name().to_string();
+            let op = AssemblyOp::new(location, context_name, 0, "match".into(), false);
+            let decorator_id = mast_forest_builder.ensure_decorator(Decorator::AsmOp(op))?;
+            mast_forest_builder.append_before_enter(node_id, &[decorator_id]);
+        }
+
+        Ok(node_id)
+    }
+}
+
+/// Lowers the `arms` of a `match` to a balanced tree of `Split` nodes, given that the value being
+/// matched is known to be in the range `lo..=hi`.
+fn lower_tree(
+    arms: &[(u32, MastNodeId)],
+    lo: u32,
+    hi: u32,
+    default: MastNodeId,
+    mast_forest_builder: &mut MastForestBuilder,
+) -> Result<MastNodeId, Report> {
+    use Operation::*;
+
+    match arms {
+        [] => drop_then(default, mast_forest_builder),
+        [(_, arm)] if lo == hi => drop_then(*arm, mast_forest_builder),
+        [(value, arm)] => {
+            // dup eq.K if.true drop ARM else drop DEFAULT end
+            let check =
+                mast_forest_builder.ensure_block(vec![Dup0, Push(Felt::from(*value)), Eq], None)?;
+            let then_blk = drop_then(*arm, mast_forest_builder)?;
+            let else_blk = drop_then(default, mast_forest_builder)?;
+            let split = mast_forest_builder.ensure_split(then_blk, else_blk)?;
+            mast_forest_builder.join_nodes(vec![check, split])
+        },
+        _ => {
+            // dup push.PIVOT u32lt if.true LOWER else UPPER end
+            let mid = arms.len() / 2;
+            let pivot = arms[mid].0;
+            let check = mast_forest_builder
+                .ensure_block(vec![Dup0, Push(Felt::from(pivot)), U32sub, Swap, Drop], None)?;
+            // the cases are distinct, so the pivot is greater than the first case
+            let below_pivot = pivot
+                .checked_sub(1)
+                .ok_or_else(|| report!("internal error: duplicate case {pivot} in match"))?;
+            let then_blk = lower_tree(&arms[..mid], lo, below_pivot, default, mast_forest_builder)?;
+            let else_blk = lower_tree(&arms[mid..], pivot, hi, default, mast_forest_builder)?;
+            let split = mast_forest_builder.ensure_split(then_blk, else_blk)?;
+            mast_forest_builder.join_nodes(vec![check, split])
+        },
+    }
+}
+
+/// Lowers the `arms` of a `match` to a jump table, which is added to `proc_ctx`.
+fn lower_jump_table(
+    arms: &[(u32, MastNodeId)],
+    default: MastNodeId,
+    proc_ctx: &mut ProcedureContext,
+    mast_forest_builder: &mut MastForestBuilder,
+) -> Result<MastNodeId, Report> {
+    use Operation::*;
+
+    let min = arms[0].0;
+    let len = arms[arms.len() - 1].0 - min + 1;
+    let mut targets = vec![default; len as usize];
+    for (value, arm) in arms {
+        targets[(value - min) as usize] = *arm;
+    }
+    for target in targets.iter() {
+        mast_forest_builder.ensure_root(*target);
+    }
+
+    // The table is stored below the locals declared by the procedure, see
+    // [ProcedureContext::num_internal_locals]
+    let num_locals = (targets.len() * WORD_SIZE) as u16;
+    let offset = proc_ctx
+        .alloc_internal_locals(num_locals)
+        .expect("the table was checked to fit in the available locals");
+    proc_ctx.add_jump_table(JumpTable { targets, offset });
+
+    // [u32wrapping_sub.MIN] dup push.LEN u32lt
+    let mut ops = Vec::new();
+    if min != 0 {
+        ops.extend([Push(Felt::from(min)), U32sub, Drop]);
+    }
+    ops.extend([Dup0, Push(Felt::from(len)), U32sub, Swap, Drop]);
+    let check = mast_forest_builder.ensure_block(ops, None)?;
+
+    // if.true mul.4 locaddr.TABLE add dynexec else drop DEFAULT end
+    let address = mast_forest_builder.ensure_block(
+        vec![Push(Felt::from(WORD_SIZE as u32)), Mul, Push(-Felt::from(offset)), FmpAdd, Add],
+        None,
+    )?;
+    let dyn_node = mast_forest_builder.ensure_dyn()?;
+    let then_blk = mast_forest_builder.join_nodes(vec![address, dyn_node])?;
+    let else_blk = drop_then(default, mast_forest_builder)?;
+    let split = mast_forest_builder.ensure_split(then_blk, else_blk)?;
+    mast_forest_builder.join_nodes(vec![check, split])
+}
+
+/// Returns the operations storing the jump tables of a procedure in its locals, to be executed
+/// once the frame pointer has been advanced past its internal locals, but not yet past the locals
+/// it declares.
+pub fn store_jump_tables(
+    proc_ctx: &ProcedureContext,
+    mast_forest_builder: &MastForestBuilder,
+) -> Vec<Operation> {
+    use Operation::*;
+
+    let locals_frame = proc_ctx.num_locals().next_multiple_of(WORD_SIZE as u16);
+    let mut ops = Vec::new();
+    for table in proc_ctx.jump_tables() {
+        for (i, target) in table.targets.iter().enumerate() {
+            // procref.ARM loc_storew.ENTRY dropw
+            let digest = mast_forest_builder[*target].digest();
+            ops.extend(digest.iter().map(|elem| Push(*elem)));
+            let offset = table.offset - locals_frame - (i * WORD_SIZE) as u16;
+            ops.extend([Push(-Felt::from(offset)), FmpAdd, MStoreW, Drop, Drop, Drop, Drop]);
+        }
+    }
+    ops
+}
+
+/// Returns a node which drops the top of the stack, then executes `node_id`.
+fn drop_then(
+    node_id: MastNodeId,
+    mast_forest_builder: &mut MastForestBuilder,
+) -> Result<MastNodeId, Report> {
+    let drop = mast_forest_builder.ensure_block(vec![Operation::Drop], None)?;
+    mast_forest_builder.join_nodes(vec![drop, node_id])
+}
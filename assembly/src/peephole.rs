@@ -0,0 +1,311 @@
+use alloc::vec::Vec;
+
+use miden_core::{ONE, Operation, ZERO};
+
+// PEEPHOLE OPTIMIZER
+// ================================================================================================
+
+/// The maximum number of operations matched by a single rewrite rule.
+const MAX_WINDOW_LEN: usize = 4;
+
+/// Runs the peephole optimizer over the operations of a basic block.
+///
+/// Each rewrite rule replaces a short window of adjacent operations with a strictly shorter
+/// sequence of operations which has the same effect on the stack. Rules are applied until none of
+/// them matches anymore.
+///
+/// `barriers` are the positions in `ops`, in increasing order, of the decorators which must
+/// observe the stack exactly as the unoptimized operations leave it, i.e. `trace` decorators. A
+/// window of operations is never rewritten if a barrier falls strictly inside of it.
+///
+/// Returns the optimized operations, each paired with the index in `ops` of the operation it
+/// originates from. An operation produced by a rewrite takes the index of the operation at the
+/// same offset in the window it replaces, so the returned indices are strictly increasing.
+pub fn optimize(ops: &[Operation], barriers: &[usize]) -> Vec<(usize, Operation)> {
+    let mut ops: Vec<(usize, Operation)> = ops.iter().copied().enumerate().collect();
+
+    let mut i = 0;
+    while i < ops.len() {
+        // the window starting at `i` must end before the first barrier following its first
+        // operation
+        let start = ops[i].0;
+        let limit = barriers.iter().copied().find(|&pos| pos > start).unwrap_or(usize::MAX);
+        let mut window = [Operation::Noop; MAX_WINDOW_LEN];
+        let mut window_len = 0;
+        for &(index, op) in ops[i..].iter().take(MAX_WINDOW_LEN) {
+            if index >= limit {
+                break;
+            }
+            window[window_len] = op;
+            window_len += 1;
+        }
+
+        match rewrite(&window[..window_len]) {
+            Some((len, replacement)) => {
+                let indices = ops[i..i + len].iter().map(|&(index, _)| index);
+                let replacement: Vec<_> = indices.zip(replacement).collect();
+                ops.splice(i..i + len, replacement);
+                // the rewrite may have enabled a rule matching a window which starts before `i`
+                i = i.saturating_sub(MAX_WINDOW_LEN - 1);
+            },
+            None => i += 1,
+        }
+    }
+
+    ops
+}
+
+/// Returns the number of operations at the start of `window` matched by a rewrite rule, along
+/// with the operations they should be replaced with, or `None` if no rule matches.
+fn rewrite(window: &[Operation]) -> Option<(usize, Vec<Operation>)> {
+    use Operation::*;
+
+    let rewrite = match *window {
+        // pairs of operations which undo each other
+        [a, b, ..] if cancel_out(a, b) => (2, vec![]),
+        // values which are pushed onto the stack only to be dropped right away
+        [
+            Pad | Push(_) | Dup0 | Dup1 | Dup2 | Dup3 | Dup4 | Dup5 | Dup6 | Dup7 | Dup9 | Dup11
+            | Dup13 | Dup15,
+            Drop,
+            ..,
+        ] => (2, vec![]),
+        // unary operations whose result is dropped; note that `Inv` is not included as it fails
+        // when the top of the stack is zero
+        [Incr | Neg, Drop, ..] => (2, vec![Drop]),
+        // consecutive additions or multiplications of constants
+        [Push(a), Add, Push(b), Add, ..] => (4, vec![Push(a + b), Add]),
+        [Push(a), Mul, Push(b), Mul, ..] => (4, vec![Push(a * b), Mul]),
+        // additions of zero and one, and multiplications by one
+        [Pad, Add, ..] => (2, vec![]),
+        [Push(a), Add, ..] if a == ZERO => (2, vec![]),
+        [Pad, Incr, Add, ..] => (3, vec![Incr]),
+        [Push(a), Add, ..] if a == ONE => (2, vec![Incr]),
+        [Pad, Incr, Mul, ..] => (3, vec![]),
+        [Push(a), Mul, ..] if a == ONE => (2, vec![]),
+        // swapping an element with its own copy
+        [Dup0, Swap, ..] => (2, vec![Dup0]),
+        _ => return None,
+    };
+
+    Some(rewrite)
+}
+
+/// Returns true if executing `a` followed by `b` leaves the stack unchanged.
+fn cancel_out(a: Operation, b: Operation) -> bool {
+    use Operation::*;
+
+    matches!(
+        (a, b),
+        (Swap, Swap)
+            | (SwapW, SwapW)
+            | (SwapW2, SwapW2)
+            | (SwapW3, SwapW3)
+            | (SwapDW, SwapDW)
+            | (Neg, Neg)
+            | (MovUp2, MovDn2)
+            | (MovDn2, MovUp2)
+            | (MovUp3, MovDn3)
+            | (MovDn3, MovUp3)
+            | (MovUp4, MovDn4)
+            | (MovDn4, MovUp4)
+            | (MovUp5, MovDn5)
+            | (MovDn5, MovUp5)
+            | (MovUp6, MovDn6)
+            | (MovDn6, MovUp6)
+            | (MovUp7, MovDn7)
+            | (MovDn7, MovUp7)
+            | (MovUp8, MovDn8)
+            | (MovDn8, MovUp8)
+    )
+}
+
+// TESTS
+// ================================================================================================
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use miden_core::{Felt, ONE, Operation, ZERO};
+    use proptest::prelude::*;
+
+    use super::optimize;
+
+    /// The number of elements on the stack of the model the operations are executed against.
+    const STACK_DEPTH: usize = 64;
+
+    /// Executes `ops` against `stack`, where the top of the stack is the first element.
+    fn execute(stack: &mut Vec<Felt>, ops: impl IntoIterator<Item = Operation>) {
+        use Operation::*;
+
+        for op in ops {
+            match op {
+                Noop => (),
+                Pad => stack.insert(0, ZERO),
+                Push(value) => stack.insert(0, value),
+                Drop => {
+                    stack.remove(0);
+                },
+                Dup0 => stack.insert(0, stack[0]),
+                Dup1 => stack.insert(0, stack[1]),
+                Dup5 => stack.insert(0, stack[5]),
+                Dup15 => stack.insert(0, stack[15]),
+                Swap => stack.swap(0, 1),
+                SwapW => swap_ranges(stack, 0, 4, 4),
+                SwapW2 => swap_ranges(stack, 0, 8, 4),
+                SwapW3 => swap_ranges(stack, 0, 12, 4),
+                SwapDW => swap_ranges(stack, 0, 8, 8),
+                MovUp2 | MovUp3 | MovUp8 => {
+                    let n = match op {
+                        MovUp2 => 2,
+                        MovUp3 => 3,
+                        _ => 8,
+                    };
+                    let value = stack.remove(n);
+                    stack.insert(0, value);
+                },
+                MovDn2 | MovDn3 | MovDn8 => {
+                    let n = match op {
+                        MovDn2 => 2,
+                        MovDn3 => 3,
+                        _ => 8,
+                    };
+                    let value = stack.remove(0);
+                    stack.insert(n, value);
+                },
+                Add => {
+                    let b = stack.remove(0);
+                    stack[0] += b;
+                },
+                Mul => {
+                    let b = stack.remove(0);
+                    stack[0] *= b;
+                },
+                Neg => stack[0] = -stack[0],
+                Incr => stack[0] += ONE,
+                op => panic!("operation {op} is not supported by the stack model"),
+            }
+        }
+    }
+
+    fn swap_ranges(stack: &mut [Felt], a: usize, b: usize, len: usize) {
+        for i in 0..len {
+            stack.swap(a + i, b + i);
+        }
+    }
+
+    fn op_strategy() -> impl Strategy<Value = Operation> {
+        use Operation::*;
+
+        prop_oneof![
+            Just(Noop),
+            Just(Pad),
+            Just(Drop),
+            Just(Dup0),
+            Just(Dup1),
+            Just(Dup5),
+            Just(Dup15),
+            Just(Swap),
+            Just(SwapW),
+            Just(SwapW2),
+            Just(SwapW3),
+            Just(SwapDW),
+            Just(MovUp2),
+            Just(MovUp3),
+            Just(MovUp8),
+            Just(MovDn2),
+            Just(MovDn3),
+            Just(MovDn8),
+            Just(Add),
+            Just(Mul),
+            Just(Neg),
+            Just(Incr),
+            Just(Push(ZERO)),
+            Just(Push(ONE)),
+            any::<u64>().prop_map(|value| Push(Felt::new(value))),
+        ]
+    }
+
+    proptest! {
+        /// Checks that the optimized operations leave the stack in the same state as the original
+        /// operations, both at the end of the block and at every barrier.
+        #[test]
+        fn optimized_ops_are_equivalent(
+            ops in prop::collection::vec(op_strategy(), 0..24),
+            stack in prop::collection::vec(any::<u64>(), STACK_DEPTH),
+            barriers in prop::collection::btree_set(0usize..24, 0..4),
+        ) {
+            let stack: Vec<Felt> = stack.into_iter().map(Felt::new).collect();
+            let barriers: Vec<usize> = barriers.into_iter().filter(|&pos| pos <= ops.len()).collect();
+            let optimized = optimize(&ops, &barriers);
+
+            prop_assert!(optimized.len() <= ops.len());
+            prop_assert!(optimized.windows(2).all(|pair| pair[0].0 < pair[1].0));
+
+            for pos in barriers.iter().copied().chain([ops.len()]) {
+                let mut expected = stack.clone();
+                execute(&mut expected, ops[..pos].iter().copied());
+
+                let mut actual = stack.clone();
+                let optimized_ops =
+                    optimized.iter().take_while(|(index, _)| *index < pos).map(|(_, op)| *op);
+                execute(&mut actual, optimized_ops);
+
+                prop_assert_eq!(expected, actual);
+            }
+        }
+    }
+
+    #[test]
+    fn optimize_rewrites() {
+        use Operation::*;
+
+        let two = Felt::new(2);
+        let three = Felt::new(3);
+        let cases: &[(&[Operation], &[Operation])] = &[
+            (&[Swap, Swap], &[]),
+            (&[MovUp2, MovDn2], &[]),
+            (&[SwapDW, Add, SwapDW], &[SwapDW, Add, SwapDW]),
+            (&[Pad, Drop], &[]),
+            (&[Push(two), Drop], &[]),
+            (&[Dup5, Drop], &[]),
+            (&[Pad, Add], &[]),
+            (&[Pad, Incr, Add], &[Incr]),
+            (&[Pad, Incr, Mul], &[]),
+            (&[Push(two), Add, Push(three), Add], &[Push(Felt::new(5)), Add]),
+            (&[Push(two), Mul, Push(three), Mul], &[Push(Felt::new(6)), Mul]),
+            (&[Push(two), Add, Push(-two), Add], &[]),
+            (&[Dup0, Swap], &[Dup0]),
+            // rewrites which enable further rewrites
+            (&[Swap, MovUp2, MovDn2, Swap], &[]),
+            (&[Pad, Incr, Neg, Drop], &[]),
+            // `Inv` fails on zero, so its result can't be dropped
+            (&[Inv, Drop], &[Inv, Drop]),
+        ];
+
+        for (ops, expected) in cases {
+            let optimized: Vec<Operation> =
+                optimize(ops, &[]).into_iter().map(|(_, op)| op).collect();
+            assert_eq!(&optimized, expected, "unexpected optimization of {ops:?}");
+        }
+    }
+
+    #[test]
+    fn optimize_respects_barriers() {
+        use Operation::*;
+
+        // a barrier between the two operations of a rule prevents the rewrite
+        let optimized = optimize(&[Swap, Swap], &[1]);
+        assert_eq!(optimized, [(0, Swap), (1, Swap)]);
+
+        // barriers before or after a window don't
+        let optimized = optimize(&[Add, Swap, Swap, Add], &[1, 3]);
+        assert_eq!(optimized, [(0, Add), (3, Add)]);
+
+        // a barrier inside a longer rule only prevents that rule
+        let two = Felt::new(2);
+        let optimized = optimize(&[Push(two), Add, Push(ZERO), Add], &[2]);
+        assert_eq!(optimized, [(0, Push(two)), (1, Add)]);
+    }
+}
@@ -1,12 +1,13 @@
-use alloc::sync::Arc;
+use alloc::{sync::Arc, vec::Vec};
 
-use miden_core::{Word, mast::MastNodeId};
+use miden_core::{WORD_SIZE, Word, mast::MastNodeId};
 
 use super::GlobalProcedureIndex;
 use crate::{
     LibraryPath,
-    ast::{ProcedureName, QualifiedProcedureName, Visibility},
+    ast::{InlineHint, ProcedureName, QualifiedProcedureName, Visibility},
     diagnostics::{SourceManager, SourceSpan, Spanned},
+    match_lowering::JumpTable,
 };
 
 // PROCEDURE CONTEXT
@@ -21,6 +22,15 @@ pub struct ProcedureContext {
     visibility: Visibility,
     is_kernel: bool,
     num_locals: u16,
+    inline_hint: Option<InlineHint>,
+    /// The number of times the code being compiled is executed per invocation of the procedure,
+    /// as far as is known from the enclosing `repeat` loops.
+    repetitions: u32,
+    /// The jump tables of the `match` operations compiled so far.
+    jump_tables: Vec<JumpTable>,
+    /// The number of locals allocated for the internal use of the assembler so far, see
+    /// [Self::num_internal_locals].
+    num_internal_locals: u16,
 }
 
 // ------------------------------------------------------------------------------------------------
@@ -41,6 +51,10 @@ impl ProcedureContext {
             visibility,
             is_kernel,
             num_locals: 0,
+            inline_hint: None,
+            repetitions: 1,
+            jump_tables: Vec::new(),
+            num_internal_locals: 0,
         }
     }
 
@@ -54,6 +68,12 @@ impl ProcedureContext {
         self.span = span;
         self
     }
+
+    /// Sets the inlining behavior requested for the procedure via its attributes.
+    pub fn with_inline_hint(mut self, inline_hint: Option<InlineHint>) -> Self {
+        self.inline_hint = inline_hint;
+        self
+    }
 }
 
 // ------------------------------------------------------------------------------------------------
@@ -85,11 +105,59 @@ impl ProcedureContext {
     pub fn source_manager(&self) -> &dyn SourceManager {
         self.source_manager.as_ref()
     }
+
+    /// Returns the number of times the code being compiled is executed per invocation of the
+    /// procedure, as far as is known from the enclosing `repeat` loops.
+    pub(crate) fn repetitions(&self) -> u32 {
+        self.repetitions
+    }
+
+    /// Returns the jump tables of the `match` operations compiled so far.
+    pub(crate) fn jump_tables(&self) -> &[JumpTable] {
+        &self.jump_tables
+    }
+
+    /// Returns the number of locals allocated for the internal use of the assembler, i.e. for the
+    /// jump tables of `match` operations and the counters of rolled loops.
+    ///
+    /// These are allocated below the locals declared by the procedure, in a frame of their own,
+    /// so that the addresses of the declared locals are unaffected by them.
+    pub(crate) fn num_internal_locals(&self) -> u16 {
+        self.num_internal_locals
+    }
 }
 
 // ------------------------------------------------------------------------------------------------
 /// State mutators
 impl ProcedureContext {
+    /// Sets the number of times the code being compiled is executed per invocation of the
+    /// procedure, see [Self::repetitions].
+    pub(crate) fn set_repetitions(&mut self, repetitions: u32) {
+        self.repetitions = repetitions;
+    }
+
+    /// Adds the jump table of a `match` operation, whose locals have been allocated via
+    /// [Self::alloc_internal_locals].
+    pub(crate) fn add_jump_table(&mut self, table: JumpTable) {
+        self.jump_tables.push(table);
+    }
+
+    /// Allocates `num_locals` internal locals, see [Self::num_internal_locals], which must be a
+    /// multiple of the word size so that all internal locals are word-aligned.
+    ///
+    /// Returns the offset of the first of them below the frame pointer, as seen from the body of
+    /// the procedure, or `None` if the procedure would exceed the maximum number of locals.
+    pub(crate) fn alloc_internal_locals(&mut self, num_locals: u16) -> Option<u16> {
+        debug_assert_eq!(num_locals % WORD_SIZE as u16, 0);
+        let num_internal_locals = self.num_internal_locals.checked_add(num_locals)?;
+        let offset = self
+            .num_locals
+            .next_multiple_of(WORD_SIZE as u16)
+            .checked_add(num_internal_locals)?;
+        self.num_internal_locals = num_internal_locals;
+        Some(offset)
+    }
+
     /// Transforms this procedure context into a [Procedure].
     ///
     /// The passed-in `mast_root` defines the MAST root of the procedure's body while
@@ -105,6 +173,7 @@ impl ProcedureContext {
     pub fn into_procedure(self, mast_root: Word, mast_node_id: MastNodeId) -> Procedure {
         Procedure::new(self.name, self.visibility, self.num_locals as u32, mast_root, mast_node_id)
             .with_span(self.span)
+            .with_inline_hint(self.inline_hint)
     }
 }
 
@@ -132,6 +201,8 @@ pub struct Procedure {
     path: QualifiedProcedureName,
     visibility: Visibility,
     num_locals: u32,
+    /// The inlining behavior requested via the attributes of the procedure, if any.
+    inline_hint: Option<InlineHint>,
     /// The MAST root of the procedure.
     mast_root: Word,
     /// The MAST node id which resolves to the above MAST root.
@@ -153,6 +224,7 @@ impl Procedure {
             path,
             visibility,
             num_locals,
+            inline_hint: None,
             mast_root,
             body_node_id,
         }
@@ -162,6 +234,11 @@ impl Procedure {
         self.span = span;
         self
     }
+
+    pub(crate) fn with_inline_hint(mut self, inline_hint: Option<InlineHint>) -> Self {
+        self.inline_hint = inline_hint;
+        self
+    }
 }
 
 // ------------------------------------------------------------------------------------------------
@@ -183,6 +260,12 @@ impl Procedure {
         self.visibility
     }
 
+    /// Returns the inlining behavior requested via the `@inline` or `@noinline` attributes of this
+    /// procedure, if any.
+    pub fn inline_hint(&self) -> Option<InlineHint> {
+        self.inline_hint
+    }
+
     /// Returns a reference to the fully-qualified module path of this procedure
     pub fn path(&self) -> &LibraryPath {
         &self.path.module
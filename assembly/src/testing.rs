@@ -71,6 +71,11 @@ impl TestContext {
         self
     }
 
+    pub fn with_warnings_as_errors(mut self, yes: bool) -> Self {
+        self.assembler.set_warnings_as_errors(yes);
+        self
+    }
+
     #[inline(always)]
     pub fn source_manager(&self) -> Arc<dyn SourceManager + Send + Sync> {
         self.source_manager.clone()
@@ -95,6 +100,8 @@ impl TestContext {
             self.source_manager.as_ref(),
             ParseOptions {
                 warnings_as_errors: self.assembler.warnings_as_errors(),
+                lints: self.assembler.lints().clone(),
+                cfg: self.assembler.cfg_flags().clone(),
                 ..Default::default()
             },
         )
@@ -111,6 +118,8 @@ impl TestContext {
             self.source_manager.as_ref(),
             ParseOptions {
                 warnings_as_errors: self.assembler.warnings_as_errors(),
+                lints: self.assembler.lints().clone(),
+                cfg: self.assembler.cfg_flags().clone(),
                 ..ParseOptions::for_kernel()
             },
         )
@@ -126,6 +135,8 @@ impl TestContext {
             self.source_manager.as_ref(),
             ParseOptions {
                 warnings_as_errors: self.assembler.warnings_as_errors(),
+                lints: self.assembler.lints().clone(),
+                cfg: self.assembler.cfg_flags().clone(),
                 ..ParseOptions::for_library()
             },
         )
@@ -142,6 +153,8 @@ impl TestContext {
             self.source_manager.as_ref(),
             ParseOptions {
                 warnings_as_errors: self.assembler.warnings_as_errors(),
+                lints: self.assembler.lints().clone(),
+                cfg: self.assembler.cfg_flags().clone(),
                 ..ParseOptions::new(ModuleKind::Library, path).unwrap()
             },
         )
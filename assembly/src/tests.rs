@@ -3,11 +3,11 @@ use core::str::FromStr;
 use std::sync::{Arc, LazyLock};
 
 use miden_core::{
-    Operation, Program, Word, assert_matches,
+    Decorator, Operation, Program, Word, assert_matches,
     mast::{MastNode, MastNodeId, error_code_from_msg},
     utils::{Deserializable, Serializable},
 };
-use miden_mast_package::{MastArtifact, MastForest, Package, PackageManifest};
+use miden_mast_package::{MastArtifact, MastForest, Package, PackageManifest, Version};
 use pretty_assertions::{assert_eq, assert_str_eq};
 use proptest::{
     prelude::*,
@@ -15,12 +15,15 @@ use proptest::{
 };
 
 use crate::{
-    Assembler, Library, LibraryNamespace, LibraryPath, ModuleParser,
+    Assembler, InliningReport, Library, LibraryNamespace, LibraryPath, Lint, LintLevel,
+    ModuleParser, ProcedureInlining,
     ast::{Ident, Module, ModuleKind, ProcedureName, QualifiedProcedureName},
     diagnostics::Report,
     mast_forest_builder::MastForestBuilder,
     report,
-    testing::{TestContext, assert_diagnostic_lines, parse_module, regex, source_file},
+    testing::{
+        TestContext, assert_diagnostic, assert_diagnostic_lines, parse_module, regex, source_file,
+    },
 };
 
 type TestResult = Result<(), Report>;
@@ -98,7 +101,7 @@ fn empty_if() -> TestResult {
         "  :               ^|^",
         "  :                `-- found a end here",
         "  `----",
-        " help: expected primitive opcode (e.g. \"add\"), or \"else\", or control flow opcode (e.g. \"if.true\")"
+        " help: expected \"@\", or primitive opcode (e.g. \"add\"), or \"else\", or control flow opcode (e.g. \"if.true\")"
     );
     Ok(())
 }
@@ -185,7 +188,8 @@ end";
 
 #[test]
 fn basic_block_and_simple_if_true() -> TestResult {
-    let context = TestContext::default();
+    // the branches of some of the conditionals below are deliberately unbalanced
+    let context = TestContext::default().with_warnings_as_errors(false);
 
     // if with else
     let source = source_file!(&context, "begin push.2 push.3 if.true add else mul end end");
@@ -223,7 +227,8 @@ end";
 
 #[test]
 fn basic_block_and_simple_if_false() -> TestResult {
-    let context = TestContext::default();
+    // the branches of some of the conditionals below are deliberately unbalanced
+    let context = TestContext::default().with_warnings_as_errors(false);
 
     // if with else
     let source = source_file!(&context, "begin push.2 push.3 if.false add else mul end end");
@@ -357,6 +362,173 @@ fn library_exports() -> Result<(), Report> {
     Ok(())
 }
 
+#[test]
+fn library_internal_procedures() -> Result<(), Report> {
+    use miden_assembly_syntax::diagnostics::NamedSource;
+
+    let context = TestContext::new();
+    let helpers = r#"
+        #! Adds one to the top stack element
+        internal.add_one
+            push.1 add
+        end
+
+        proc.double
+            push.2 mul
+        end
+
+        export.add_two
+            exec.add_one exec.add_one
+        end
+    "#;
+    let api = r#"
+        use.lib::helpers
+
+        export.increment
+            exec.helpers::add_one
+        end
+    "#;
+
+    // internal procedures can be referenced by the other modules of the library, but they are not
+    // exported from it
+    let library = Assembler::new(context.source_manager()).assemble_library([
+        NamedSource::new("lib::helpers", helpers),
+        NamedSource::new("lib::api", api),
+    ])?;
+    let exports = library.exports().map(|name| name.to_string()).collect::<Vec<_>>();
+    assert_eq!(exports, ["lib::api::increment", "lib::helpers::add_two"]);
+    let manifest = PackageManifest::from_library(&library);
+    let exports = manifest
+        .exports
+        .iter()
+        .map(|export| export.name.to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(exports, ["lib::api::increment", "lib::helpers::add_two"]);
+
+    // private procedures are only visible within their own module
+    let error = Assembler::new(context.source_manager())
+        .assemble_library([
+            NamedSource::new("lib::helpers", helpers),
+            NamedSource::new("lib::api", "use.lib::helpers\nexport.foo exec.helpers::double end"),
+        ])
+        .unwrap_err();
+    assert_diagnostic!(
+        error,
+        "procedure 'lib::helpers::double' is not visible from module 'lib::api'"
+    );
+
+    // internal procedures are not visible from the modules of other namespaces
+    let error = Assembler::new(context.source_manager())
+        .assemble_library([
+            NamedSource::new("lib::helpers", helpers),
+            NamedSource::new(
+                "other::api",
+                "use.lib::helpers\nexport.foo exec.helpers::add_one end",
+            ),
+        ])
+        .unwrap_err();
+    assert_diagnostic!(
+        error,
+        "procedure 'lib::helpers::add_one' is not visible from module 'other::api'"
+    );
+
+    // nor from programs linked against the library
+    let error = Assembler::new(context.source_manager())
+        .with_dynamic_library(library)?
+        .assemble_program("use.lib::helpers\nbegin exec.helpers::add_one end")
+        .unwrap_err();
+    assert_diagnostic!(error, "undefined procedure");
+
+    // the modules of a kernel can reference each other's private procedures
+    let mut assembler = Assembler::new(context.source_manager());
+    assembler.compile_and_statically_link(NamedSource::new("$kernel::helpers", helpers))?;
+    assembler.assemble_kernel("export.foo exec.::$kernel::helpers::double end")?;
+
+    Ok(())
+}
+
+#[test]
+fn library_source_map() -> Result<(), Report> {
+    use miden_core::{
+        crypto::hash::Blake3_256,
+        debuginfo::{DefaultSourceManager, SourceManager, SourceManagerExt},
+    };
+
+    let dir = tempfile::tempdir().unwrap();
+    let build_dir = dir.path().join("build");
+    let debug_dir = dir.path().join("debug");
+    std::fs::create_dir_all(build_dir.join("lib/math")).unwrap();
+    std::fs::create_dir_all(debug_dir.join("lib/math")).unwrap();
+
+    let api = "use.lib::math::ops\nexport.foo\n    exec.ops::bar\nend\n";
+    let ops = "export.bar\n    push.1\n    add\nend\n";
+    for root in [&build_dir, &debug_dir] {
+        std::fs::write(root.join("lib/api.masm"), api).unwrap();
+        std::fs::write(root.join("lib/math/ops.masm"), ops).unwrap();
+    }
+
+    // libraries assembled in debug mode with a source root record the paths of their sources
+    // relative to the root
+    let library = Assembler::default()
+        .with_debug_mode(true)
+        .with_source_root(build_dir.to_string_lossy())
+        .assemble_library_from_dir(build_dir.join("lib"), LibraryNamespace::new("lib")?)?;
+    let files = library
+        .source_map()
+        .files()
+        .map(|(_, file)| (file.path.as_str(), file.content_hash))
+        .collect::<BTreeSet<_>>();
+    assert_eq!(
+        files,
+        BTreeSet::from_iter([
+            ("lib/api.masm", Blake3_256::hash(api.as_bytes())),
+            ("lib/math/ops.masm", Blake3_256::hash(ops.as_bytes())),
+        ])
+    );
+
+    // the source map survives serialization
+    let library = Library::read_from_bytes(&library.to_bytes()).unwrap();
+    assert_eq!(library.source_map().len(), 2);
+
+    // the sources can be loaded from another root, under the URIs referenced by the decorators
+    let source_manager = DefaultSourceManager::default();
+    source_manager.load_source_map(library.source_map(), &debug_dir).unwrap();
+    let locations = library
+        .mast_forest()
+        .decorators()
+        .iter()
+        .filter_map(|decorator| match decorator {
+            Decorator::AsmOp(assembly_op) => assembly_op.location().cloned(),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert!(!locations.is_empty());
+    for location in locations {
+        let span = source_manager.location_to_span(location).expect("source should be loaded");
+        assert!(source_manager.get(span.source_id()).is_ok());
+    }
+
+    // libraries assembled without debug mode or a source root do not have a source map
+    let library = Assembler::default()
+        .with_source_root(build_dir.to_string_lossy())
+        .assemble_library_from_dir(build_dir.join("lib"), LibraryNamespace::new("lib")?)?;
+    assert!(library.source_map().is_empty());
+
+    // sources which were modified after being assembled are rejected
+    let library = Assembler::default()
+        .with_debug_mode(true)
+        .with_source_root(build_dir.to_string_lossy())
+        .assemble_library_from_dir(build_dir.join("lib"), LibraryNamespace::new("lib")?)?;
+    std::fs::write(debug_dir.join("lib/math/ops.masm"), "export.bar\n    push.2 add\nend\n")
+        .unwrap();
+    let error = DefaultSourceManager::default()
+        .load_source_map(library.source_map(), &debug_dir)
+        .unwrap_err();
+    assert!(error.to_string().contains("does not match the source map"), "{error}");
+
+    Ok(())
+}
+
 #[test]
 fn library_procedure_collision() -> Result<(), Report> {
     let context = TestContext::new();
@@ -449,6 +621,557 @@ fn library_serialization() -> Result<(), Report> {
     Ok(())
 }
 
+#[test]
+fn library_diff() -> Result<(), Report> {
+    use miden_core::mast::{NodeDiffKind, NodePathStep};
+
+    let context = TestContext::new();
+
+    let old_foo = r#"
+        export.foo
+            push.1
+            if.true
+                push.1 push.2 add
+            else
+                push.1 push.2 mul
+            end
+        end
+        export.bar
+            mul
+        end
+        export.baz
+            add
+        end
+    "#;
+    let new_foo = r#"
+        export.foo
+            push.1
+            if.true
+                push.1 push.3 add
+            else
+                push.1 push.2 mul
+            end
+        end
+        export.baz
+            add
+        end
+        export.qux
+            sub
+        end
+    "#;
+    let old_lib = Assembler::new(context.source_manager()).assemble_library([parse_module!(
+        &context,
+        "test::foo",
+        old_foo
+    )])?;
+    let new_lib = Assembler::new(context.source_manager()).assemble_library([parse_module!(
+        &context,
+        "test::foo",
+        new_foo
+    )])?;
+
+    let diff = old_lib.diff(&new_lib).unwrap();
+
+    let removed: Vec<_> = diff.removed().iter().map(|(name, _)| name.to_string()).collect();
+    assert_eq!(removed, ["test::foo::bar"]);
+    let added: Vec<_> = diff.added().iter().map(|(name, _)| name.to_string()).collect();
+    assert_eq!(added, ["test::foo::qux"]);
+
+    assert_eq!(diff.changed().len(), 1);
+    let change = &diff.changed()[0];
+    assert_eq!(change.key().to_string(), "test::foo::foo");
+    assert_eq!(change.node_diff().path(), &[NodePathStep::JoinSecond, NodePathStep::SplitTrue]);
+    // `push.1` is lowered to `pad incr`, so `push.2` is the third operation of the block
+    assert_eq!(
+        change.node_diff().kind(),
+        &NodeDiffKind::Operation {
+            index: 2,
+            old: Some(Operation::Push(2_u32.into())),
+            new: Some(Operation::Push(3_u32.into())),
+        }
+    );
+
+    assert!(old_lib.diff(&old_lib).unwrap().is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn library_archive_shares_subtrees() -> Result<(), Report> {
+    use crate::library::LibraryArchive;
+
+    let context = TestContext::new();
+
+    // both libraries inline the same helper into their exported procedures
+    let lib_source = |name: &str, unique_op: &str| {
+        format!(
+            r#"
+            proc.helper
+                repeat.4
+                    push.1 push.2 add mul
+                end
+                if.true
+                    swap
+                else
+                    drop
+                end
+            end
+            export.{name}
+                exec.helper
+                {unique_op}
+            end
+            "#
+        )
+    };
+    let lib_a = Assembler::new(context.source_manager()).assemble_library([parse_module!(
+        &context,
+        "test::a",
+        lib_source("foo", "add")
+    )])?;
+    let lib_b = Assembler::new(context.source_manager()).assemble_library([parse_module!(
+        &context,
+        "test::b",
+        lib_source("bar", "sub")
+    )])?;
+
+    let mut archive = LibraryArchive::new();
+    assert_eq!(archive.add(&lib_a).unwrap(), 0);
+    assert_eq!(archive.add(&lib_b).unwrap(), 1);
+    assert_eq!(archive.len(), 2);
+    assert!(
+        archive.store().num_nodes()
+            < lib_a.mast_forest().num_nodes() + lib_b.mast_forest().num_nodes()
+    );
+
+    let archive = LibraryArchive::read_from_bytes(&archive.to_bytes()).unwrap();
+    let libraries = archive.libraries().collect::<Result<Vec<_>, _>>().unwrap();
+    for (original, extracted) in [&lib_a, &lib_b].into_iter().zip(libraries.iter()) {
+        assert_eq!(original.digest(), extracted.digest());
+        assert!(original.diff(extracted).unwrap().is_empty());
+    }
+    assert!(archive.get(2).is_none());
+
+    Ok(())
+}
+
+#[test]
+fn library_carries_procedure_signatures() -> Result<(), Report> {
+    use crate::{
+        ast::{ProcedureSignature, StackType},
+        library::LibraryArchive,
+    };
+
+    let context = TestContext::new();
+    let source = r#"
+        @inputs(word, felt)
+        @outputs(felt)
+        export.foo
+            drop dropw push.1
+        end
+
+        export.bar
+            exec.foo
+        end
+    "#;
+    let library = Assembler::new(context.source_manager()).assemble_library([parse_module!(
+        &context,
+        "test::sig",
+        source
+    )])?;
+
+    let foo = QualifiedProcedureName::from_str("test::sig::foo").unwrap();
+    let bar = QualifiedProcedureName::from_str("test::sig::bar").unwrap();
+    let foo_signature =
+        ProcedureSignature::new([StackType::Word, StackType::Felt], [StackType::Felt]);
+    assert_eq!(library.get_procedure_signature(&foo), Some(&foo_signature));
+    assert_eq!(library.get_procedure_signature(&bar), None);
+
+    // signatures survive serialization of libraries, archives and packages
+    let deserialized = Library::read_from_bytes(&library.to_bytes()).unwrap();
+    assert_eq!(deserialized, library);
+
+    let mut archive = LibraryArchive::new();
+    archive.add(&library).unwrap();
+    let archive = LibraryArchive::read_from_bytes(&archive.to_bytes()).unwrap();
+    let extracted = archive.get(0).unwrap().unwrap();
+    assert_eq!(extracted.get_procedure_signature(&foo), Some(&foo_signature));
+
+    let manifest = PackageManifest::from_library(&library);
+    let package = Package {
+        name: "sig".to_string(),
+        version: Version::default(),
+        mast: MastArtifact::Library(Arc::new(library)),
+        manifest,
+        account_component_metadata_bytes: None,
+    };
+    let package = Package::read_from_bytes(&package.to_bytes()).unwrap();
+    let signatures = package
+        .manifest
+        .exports
+        .iter()
+        .map(|export| (export.name.to_string(), export.signature.clone()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        signatures,
+        [
+            ("test::sig::bar".to_string(), None),
+            ("test::sig::foo".to_string(), Some(foo_signature))
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn library_serialization_is_versioned() -> Result<(), Report> {
+    let context = TestContext::new();
+    let library = Assembler::new(context.source_manager()).assemble_library([parse_module!(
+        &context,
+        "test::versioned",
+        "export.foo add end"
+    )])?;
+
+    let bytes = library.to_bytes();
+    assert_eq!(&bytes[..5], b"MASL\0");
+    assert_eq!(Library::read_from_bytes(&bytes).unwrap(), library);
+
+    // libraries serialized before the format was versioned start with the MAST forest
+    let err = Library::read_from_bytes(&bytes[8..]).unwrap_err();
+    assert!(err.to_string().contains("unversioned legacy format"), "unexpected error: {err}");
+
+    let mut bytes = bytes;
+    bytes[7] += 1;
+    let err = Library::read_from_bytes(&bytes).unwrap_err();
+    assert!(err.to_string().contains("unsupported version"), "unexpected error: {err}");
+
+    Ok(())
+}
+
+#[test]
+fn library_deserialize_from_file() -> Result<(), Report> {
+    let context = TestContext::new();
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("lib.masl");
+
+    // the contents of files of different sizes end at different offsets within the buffer of a
+    // buffered reader, which must not affect deserialization
+    for num_ops in 1..=16 {
+        let source = format!("export.foo {}end", "add ".repeat(num_ops));
+        let library = Assembler::new(context.source_manager())
+            .with_debug_mode(true)
+            .assemble_library([parse_module!(&context, "dep::math", source)])?;
+        library.write_to_file(&path).unwrap();
+        assert_eq!(Library::deserialize_from_file(&path).unwrap(), library);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn library_archive_serialization_is_versioned() -> Result<(), Report> {
+    use crate::library::LibraryArchive;
+
+    let context = TestContext::new();
+    let library = Assembler::new(context.source_manager()).assemble_library([parse_module!(
+        &context,
+        "test::archived",
+        "export.foo add end"
+    )])?;
+    let mut archive = LibraryArchive::new();
+    archive.add(&library).unwrap();
+
+    let bytes = archive.to_bytes();
+    assert_eq!(&bytes[..5], b"MASA\0");
+    assert_eq!(LibraryArchive::read_from_bytes(&bytes).unwrap(), archive);
+
+    // a serialized library is not an archive
+    let err = LibraryArchive::read_from_bytes(&library.to_bytes()).unwrap_err();
+    assert!(err.to_string().contains("invalid magic bytes"), "unexpected error: {err}");
+
+    let mut bytes = bytes;
+    bytes[7] += 1;
+    let err = LibraryArchive::read_from_bytes(&bytes).unwrap_err();
+    assert!(err.to_string().contains("unsupported version"), "unexpected error: {err}");
+
+    Ok(())
+}
+
+#[test]
+fn library_version_requirements() -> Result<(), Report> {
+    use crate::library::LibraryArchive;
+
+    let context = TestContext::new();
+    let dependency = |version: Option<&str>| -> Result<Library, Report> {
+        let library = Assembler::new(context.source_manager())
+            .assemble_library([parse_module!(&context, "dep::math", "export.foo add end")])?;
+        Ok(match version {
+            Some(version) => library.with_version(version.parse().unwrap()),
+            None => library,
+        })
+    };
+    let app = Assembler::new(context.source_manager())
+        .assemble_library([parse_module!(&context, "app::api", "export.bar mul end")])?
+        .with_version("0.1.0".parse().unwrap())
+        .with_dependency(LibraryNamespace::new("dep")?, "^1.2".parse().unwrap());
+
+    // the version and the requirements of a library survive serialization
+    let deserialized = Library::read_from_bytes(&app.to_bytes()).unwrap();
+    assert_eq!(deserialized.version(), Some(&Version { major: 0, minor: 1, patch: 0 }));
+    let dependencies = deserialized
+        .dependencies()
+        .map(|(namespace, requirement)| format!("{namespace}: {requirement}"))
+        .collect::<Vec<_>>();
+    assert_eq!(dependencies, ["dep: ^1.2"]);
+
+    let mut archive = LibraryArchive::new();
+    archive.add(&app).unwrap();
+    let archive = LibraryArchive::read_from_bytes(&archive.to_bytes()).unwrap();
+    let extracted = archive.get(0).unwrap().unwrap();
+    assert_eq!(extracted.version(), app.version());
+    assert!(extracted.dependencies().eq(app.dependencies()));
+
+    // compatible and unversioned dependencies can be linked in any order
+    for dependency in [dependency(Some("1.3.0"))?, dependency(None)?] {
+        Assembler::new(context.source_manager())
+            .with_dynamic_library(&app)?
+            .with_dynamic_library(&dependency)?;
+        Assembler::new(context.source_manager())
+            .with_dynamic_library(&dependency)?
+            .with_dynamic_library(&app)?;
+    }
+
+    // incompatible dependencies are rejected, whichever library is linked first
+    let mut assembler = Assembler::new(context.source_manager()).with_dynamic_library(&app)?;
+    let error = assembler.link_dynamic_library(dependency(Some("2.0.0"))?).unwrap_err();
+    assert_diagnostic!(
+        error,
+        "library 'app@0.1.0' requires version '^1.2' of 'dep', but version 2.0.0 of 'dep' is linked"
+    );
+    let mut assembler =
+        Assembler::new(context.source_manager()).with_static_library(dependency(Some("1.1.0"))?)?;
+    let error = assembler.link_static_library(&app).unwrap_err();
+    assert_diagnostic!(
+        error,
+        "library 'app@0.1.0' requires version '^1.2' of 'dep', but version 1.1.0 of 'dep' is linked"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn library_records_cfg_flags() -> Result<(), Report> {
+    use miden_assembly_syntax::diagnostics::NamedSource;
+
+    let context = TestContext::new();
+    let source = r#"
+        @cfg(feature = "debug")
+        export.foo
+            debug.stack push.1
+        end
+
+        @cfg(not_feature = "debug")
+        export.foo
+            push.1
+        end
+
+        @cfg(feature = "debug")
+        export.only_in_debug
+            push.2
+        end
+    "#;
+    let only_in_debug = QualifiedProcedureName::from_str("test::cfg::only_in_debug").unwrap();
+
+    let library = Assembler::new(context.source_manager())
+        .assemble_library([NamedSource::new("test::cfg", source)])?;
+    assert!(library.cfg_flags().is_empty());
+    assert_eq!(library.exports().count(), 1);
+    assert!(!library.exports().any(|export| export == &only_in_debug));
+
+    let library = Assembler::new(context.source_manager())
+        .with_cfg_flag("debug")
+        .with_cfg_flag("trace")
+        .assemble_library([NamedSource::new("test::cfg", source)])?;
+    assert_eq!(library.cfg_flags().iter().collect::<Vec<_>>(), ["debug", "trace"]);
+    assert_eq!(library.exports().count(), 2);
+    assert!(library.exports().any(|export| export == &only_in_debug));
+
+    // the active flags survive serialization
+    let deserialized = Library::read_from_bytes(&library.to_bytes()).unwrap();
+    assert_eq!(deserialized.cfg_flags(), library.cfg_flags());
+
+    Ok(())
+}
+
+#[test]
+fn library_reuses_cached_procedures() -> Result<(), Report> {
+    use miden_assembly_syntax::diagnostics::NamedSource;
+
+    use crate::CompilationCache;
+
+    let context = TestContext::new();
+    let a = r#"
+        use.test::b
+
+        export.foo
+            exec.b::bar push.1 assert.err="foo failed"
+        end
+    "#;
+    let b = r#"
+        export.bar
+            push.2 mul
+        end
+    "#;
+    let b_changed = r#"
+        export.bar
+            push.3 mul
+        end
+    "#;
+    let c = r#"
+        export.baz.1
+            loc_store.0 push.4
+        end
+    "#;
+    let path = |path: &str| LibraryPath::new(path).unwrap();
+    let assemble = |cache: &CompilationCache, b: &'static str| {
+        Assembler::new(context.source_manager())
+            .with_debug_mode(true)
+            .with_compilation_cache(cache.clone())
+            .assemble_library([
+                NamedSource::new("test::a", a),
+                NamedSource::new("test::b", b),
+                NamedSource::new("test::c", c),
+            ])
+    };
+
+    // nothing is cached on the first assembly
+    let cache = CompilationCache::new();
+    let library = assemble(&cache, b)?;
+    let report = cache.report();
+    assert_eq!(report.reused().count(), 0);
+    assert_eq!(report.compiled().count(), 3);
+    assert_eq!(cache.len(), 3);
+
+    // all procedures are reused when nothing changed, resulting in the same library
+    let reassembled = assemble(&cache, b)?;
+    let report = cache.report();
+    assert_eq!(report.reused().count(), 3);
+    assert_eq!(report.compiled().count(), 0);
+    assert_eq!(report.to_string(), "reused 3 modules, compiled 0 modules");
+    // the reused MAST only differs from the original in the decorators which are no longer used
+    assert_eq!(reassembled.digest(), library.digest());
+    assert!(reassembled.diff(&library).unwrap().is_empty());
+    let err_code = error_code_from_msg("foo failed");
+    assert_eq!(
+        reassembled.mast_forest().resolve_error_message(err_code).as_deref(),
+        Some("foo failed")
+    );
+
+    // the cache survives serialization
+    let cache = CompilationCache::read_from_bytes(&cache.to_bytes()).unwrap();
+    assert!(assemble(&cache, b)?.diff(&library).unwrap().is_empty());
+    assert_eq!(cache.report().reused().count(), 3);
+
+    // changing a module recompiles it and the modules which import it
+    let changed = assemble(&cache, b_changed)?;
+    let report = cache.report();
+    assert!(report.is_compiled(&path("test::a")));
+    assert!(report.is_compiled(&path("test::b")));
+    assert!(report.is_reused(&path("test::c")));
+    assert_ne!(changed.digest(), library.digest());
+
+    let recompiled = Assembler::new(context.source_manager())
+        .with_debug_mode(true)
+        .assemble_library([
+            NamedSource::new("test::a", a),
+            NamedSource::new("test::b", b_changed),
+            NamedSource::new("test::c", c),
+        ])?;
+    assert_eq!(changed.digest(), recompiled.digest());
+    assert!(changed.diff(&recompiled).unwrap().is_empty());
+
+    // changing assembler options invalidates the cache
+    Assembler::new(context.source_manager())
+        .with_compilation_cache(cache.clone())
+        .assemble_library([
+            NamedSource::new("test::a", a),
+            NamedSource::new("test::b", b_changed),
+            NamedSource::new("test::c", c),
+        ])?;
+    assert_eq!(cache.report().compiled().count(), 3);
+
+    Ok(())
+}
+
+#[test]
+fn program_reuses_cached_procedures() -> Result<(), Report> {
+    use crate::CompilationCache;
+
+    let context = TestContext::new();
+    let source = source_file!(
+        &context,
+        "\
+proc.foo
+    push.1 push.2 add
+end
+
+begin
+    exec.foo
+    trace.1
+end"
+    );
+
+    let cache = CompilationCache::new();
+    let assemble = || {
+        Assembler::new(context.source_manager())
+            .with_compilation_cache(cache.clone())
+            .assemble_program(source.clone())
+    };
+    let program = assemble()?;
+    assert_eq!(cache.report().compiled().count(), 1);
+
+    let reassembled = assemble()?;
+    assert_eq!(cache.report().reused().count(), 1);
+    assert_eq!(reassembled.hash(), program.hash());
+
+    Ok(())
+}
+
+#[test]
+fn repeat_rolling_threshold_is_part_of_fingerprint() -> Result<(), Report> {
+    use crate::CompilationCache;
+
+    let context = TestContext::new();
+    let source = source_file!(
+        &context,
+        "\
+proc.foo
+    repeat.10 push.1 add end
+end
+
+begin
+    exec.foo
+end"
+    );
+
+    let cache = CompilationCache::new();
+    let assemble = |threshold: Option<u32>| {
+        let mut assembler =
+            Assembler::new(context.source_manager()).with_compilation_cache(cache.clone());
+        assembler.set_repeat_rolling_threshold(threshold);
+        assembler.assemble_program(source.clone())
+    };
+    let unrolled = assemble(None)?;
+    let rolled = assemble(Some(10))?;
+    assert_eq!(cache.report().compiled().count(), 1);
+    assert_ne!(rolled.hash(), unrolled.hash());
+
+    let is_loop = |node: &MastNode| matches!(node, MastNode::Loop(_));
+    assert!(!unrolled.mast_forest().nodes().iter().any(is_loop));
+    assert!(rolled.mast_forest().nodes().iter().any(is_loop));
+
+    Ok(())
+}
+
 #[test]
 fn get_module_by_path() -> Result<(), Report> {
     let context = TestContext::new();
@@ -873,6 +1596,112 @@ fn constant_err_div_by_zero() -> TestResult {
     Ok(())
 }
 
+#[test]
+fn constant_bitwise_and_comparison_expressions() -> TestResult {
+    let context = TestContext::default();
+    let source = source_file!(
+        &context,
+        "\
+const.A=(1<<4)|3
+const.B=A^0xFF&0x0F
+const.C=2**3**2%100
+const.D=A>B ? A : B
+const.E=(A<=B)+(A!=B)+(C==12)+(A>>1)
+const.F=B==0 ? 0 : 100//B
+begin
+    push.A push.B push.C push.D push.E push.F
+end"
+    );
+    let expected = "\
+begin
+    basic_block push(19) push(28) push(12) push(28) push(12) push(3) end
+end";
+    let program = context.assemble(source)?;
+    assert_str_eq!(format!("{program}"), expected);
+    Ok(())
+}
+
+#[test]
+fn constant_hash_and_event_expressions() -> TestResult {
+    use miden_core::{Felt, crypto::hash::Rpo256, sys_events::event_id_from_name};
+
+    let context = TestContext::default();
+    let source = source_file!(
+        &context,
+        "\
+const.DATA=0x0200000000000000030000000000000004000000000000000500000000000000
+const.KEY=hash(1, DATA)
+const.NAME=\"miden::transfer\"
+const.TAG=hash(NAME)
+const.TRANSFER=event(NAME)
+begin
+    push.KEY push.TAG emit.TRANSFER
+end"
+    );
+    let key = Rpo256::hash_elements(&[1u32, 2, 3, 4, 5].map(Felt::from));
+    let tag = Rpo256::hash(b"miden::transfer");
+    let ops = key
+        .iter()
+        .chain(tag.iter())
+        .map(|felt| format!("push({felt})"))
+        .chain([format!("emit({})", event_id_from_name("miden::transfer"))])
+        .collect::<Vec<_>>();
+    let expected = format!(
+        "\
+begin
+    basic_block
+        {}
+    end
+end",
+        ops.join("\n        ")
+    );
+    let program = context.assemble(source)?;
+    assert_str_eq!(format!("{program}"), expected);
+    Ok(())
+}
+
+#[test]
+fn constant_err_overflow() -> TestResult {
+    let context = TestContext::default();
+    let source = source_file!(
+        &context,
+        "const.TEST_CONSTANT=1<<64 \
+    begin \
+    push.TEST_CONSTANT \
+    end"
+    );
+    assert_assembler_diagnostic!(
+        context,
+        source,
+        "invalid constant expression: value overflowed the field modulus",
+        regex!(r#",-\[test[\d]+:1:21\]"#),
+        "1 | const.TEST_CONSTANT=1<<64 begin push.TEST_CONSTANT end",
+        "  :                     ^^^^^",
+        "  `----"
+    );
+
+    let source = source_file!(
+        &context,
+        "const.BASE=2 \
+    const.TEST_CONSTANT=BASE**64 \
+    begin \
+    push.TEST_CONSTANT \
+    end"
+    );
+    assert_assembler_diagnostic!(
+        context,
+        source,
+        "syntax error",
+        "help: see emitted diagnostics for details",
+        "invalid constant expression: value overflowed the field modulus",
+        regex!(r#",-\[test[\d]+:1:34\]"#),
+        "1 | const.BASE=2 const.TEST_CONSTANT=BASE**64 begin push.TEST_CONSTANT end",
+        "  :                                  ^^^^^^^^",
+        "  `----"
+    );
+    Ok(())
+}
+
 #[test]
 fn constants_must_be_uppercase() -> TestResult {
     let context = TestContext::default();
@@ -887,13 +1716,22 @@ fn constants_must_be_uppercase() -> TestResult {
     assert_assembler_diagnostic!(
         context,
         source,
+        "syntax error",
+        "help: see emitted diagnostics for details",
         "invalid syntax",
         regex!(r#",-\[test[\d]+:1:7\]"#),
         "1 | const.constant_1=12 begin push.constant_1 end",
         "  :       ^^^^^|^^^^",
         "  :            `-- found a identifier here",
         "  `----",
-        "        help: expected constant identifier"
+        "        help: expected constant identifier",
+        "invalid syntax",
+        regex!(r#",-\[test[\d]+:1:32\]"#),
+        "1 | const.constant_1=12 begin push.constant_1 end",
+        "  :                                ^^^^^|^^^^",
+        "  :                                     `-- found a identifier here",
+        "  `----",
+        r#"        help: expected "[", or constant identifier, or hex-encoded literal, or integer literal"#
     );
 
     Ok(())
@@ -947,8 +1785,11 @@ fn constant_must_be_valid_felt() -> TestResult {
         "  :                    ^^^|^^^",
         "  :                       `-- found a constant identifier here",
         "  `----",
-        " help: expected \"*\", or \"+\", or \"-\", or \"/\", or \"//\", or \"@\", or \"adv_map\", or \"begin\", or \"const\", \
-or \"export\", or \"proc\", or \"use\", or end of file, or doc comment"
+        " help: expected \"!=\", or \"%\", or \"&\", or \"*\", or \"**\", or \"+\", or \"-\", or \"/\", or \"//\", \
+or \"<\", or \"<<\", or \"<=\", or \"==\", or \">\", or \">=\", or \">>\", or \"?\", or \"@\", or \"^\", or \"|\", \
+or \"adv_map\",",
+        "       or \"begin\", or \"const\", or \"data\", or \"export\", or \"internal\", or \"proc\", \
+or \"struct\", or \"use\", or end of file, or doc comment"
     );
     Ok(())
 }
@@ -1040,7 +1881,7 @@ fn constants_defined_in_global_scope() -> TestResult {
         "  :             `-- found a const here",
         "3 |     push.CONSTANT end",
         "  `----",
-        r#" help: expected primitive opcode (e.g. "add"), or control flow opcode (e.g. "if.true")"#
+        r#" help: expected "@", or primitive opcode (e.g. "add"), or control flow opcode (e.g. "if.true")"#
     );
     Ok(())
 }
@@ -1965,7 +2806,8 @@ end";
 
 #[test]
 fn nested_control_blocks() -> TestResult {
-    let context = TestContext::default();
+    // the branches of some of the conditionals below are deliberately unbalanced
+    let context = TestContext::default().with_warnings_as_errors(false);
 
     // if with else
     let source = source_file!(
@@ -2116,6 +2958,188 @@ end";
     Ok(())
 }
 
+#[test]
+fn program_with_generic_procedure() -> TestResult {
+    let context = TestContext::default();
+    let source = source_file!(
+        &context,
+        "\
+        proc.scale(N) push.N mul end \
+        begin push.2 exec.scale(3) exec.scale(3) exec.scale(5) end"
+    );
+    let program = context.assemble(source)?;
+    let expected = "\
+begin
+    basic_block push(2) push(3) mul push(3) mul push(5) mul end
+end";
+    assert_str_eq!(format!("{program}"), expected);
+    Ok(())
+}
+
+#[test]
+fn generic_procedure_instances_have_distinct_digests() -> TestResult {
+    let context = TestContext::default();
+    let source = source_file!(
+        &context,
+        "\
+        proc.scale(N) push.N mul end \
+        begin push.2 call.scale(3) call.scale(1 + 2) call.scale(5) end"
+    );
+    let program = context.assemble(source)?;
+
+    // `scale(3)` and `scale(1 + 2)` are the same instance, while `scale(5)` is a distinct one
+    let forest = program.mast_forest();
+    let callees = forest
+        .nodes()
+        .iter()
+        .filter_map(|node| match node {
+            MastNode::Call(call) => Some(forest[call.callee()].digest()),
+            _ => None,
+        })
+        .collect::<BTreeSet<_>>();
+    assert_eq!(callees.len(), 2);
+    Ok(())
+}
+
+#[test]
+fn program_with_struct_layout_from_other_module() -> TestResult {
+    let context = TestContext::default();
+    let note = parse_module!(
+        &context,
+        "lib::note",
+        "\
+        struct.NOTE
+            id: felt
+            key: word
+            amount: u64
+        end"
+    );
+    let source = source_file!(
+        &context,
+        "\
+        use.lib::note
+        const.KEY=offsetof(note::NOTE.key)
+        const.NOTES=sizeof(note::NOTE) * 2
+        begin push.NOTES mem_loadw.KEY end"
+    );
+
+    let mut assembler = Assembler::new(context.source_manager());
+    assembler.compile_and_statically_link(note)?;
+    let program = assembler.assemble_program(source)?;
+    let expected = "\
+begin
+    basic_block push(24) push(4) mloadw end
+end";
+    assert_str_eq!(format!("{program}"), expected);
+    Ok(())
+}
+
+#[test]
+fn program_with_undefined_struct_from_other_module() -> TestResult {
+    let context = TestContext::default();
+    let note = parse_module!(
+        &context,
+        "lib::note",
+        "\
+        struct.NOTE
+            key: word
+        end"
+    );
+    let source = source_file!(
+        &context,
+        "\
+        use.lib::note
+        const.SIZE=sizeof(note::ASSET)
+        begin push.SIZE end"
+    );
+
+    let mut assembler = Assembler::new(context.source_manager());
+    assembler.compile_and_statically_link(note)?;
+    let error = assembler.assemble_program(source).expect_err("expected undefined struct error");
+    assert_diagnostic_lines!(
+        error,
+        "undefined struct 'ASSET' in module 'lib::note'",
+        regex!(r#",-\[test[\d]+:2:27\]"#),
+        "1 | use.lib::note",
+        "2 |         const.SIZE=sizeof(note::ASSET)",
+        "  :                           ^^^^^^^^^^^",
+        "3 |         begin push.SIZE end",
+        "  `----",
+        "help: structs can only be referred to from other modules if the module defining them is assembled from source"
+    );
+    Ok(())
+}
+
+#[test]
+fn program_with_zero_repeat_count_from_other_module() -> TestResult {
+    let context = TestContext::default();
+    let note = parse_module!(
+        &context,
+        "lib::note",
+        "\
+        struct.NOTE
+            id: felt
+        end"
+    );
+    let source = source_file!(
+        &context,
+        "\
+        use.lib::note
+        const.N=offsetof(note::NOTE.id)
+        begin repeat.N add end end"
+    );
+
+    let mut assembler = Assembler::new(context.source_manager());
+    assembler.compile_and_statically_link(note)?;
+    let error = assembler.assemble_program(source).expect_err("expected invalid repeat count");
+    assert_diagnostic_lines!(
+        error,
+        "invalid repeat count: loops must repeat at least once",
+        regex!(r#",-\[test[\d]+:3:22\]"#),
+        "2 |         const.N=offsetof(note::NOTE.id)",
+        "3 |         begin repeat.N add end end",
+        "  :                      ^",
+        "  `----"
+    );
+    Ok(())
+}
+
+#[test]
+fn program_with_duplicate_match_case_from_other_module() -> TestResult {
+    let context = TestContext::default();
+    let note = parse_module!(
+        &context,
+        "lib::note",
+        "\
+        struct.NOTE
+            id: felt
+        end"
+    );
+    let source = source_file!(
+        &context,
+        "\
+        use.lib::note
+        const.ID=offsetof(note::NOTE.id)
+        begin match case.0 push.1 case.ID push.2 default push.3 end end"
+    );
+
+    let mut assembler = Assembler::new(context.source_manager());
+    assembler.compile_and_statically_link(note)?;
+    let error = assembler.assemble_program(source).expect_err("expected duplicate match case");
+    assert_diagnostic_lines!(
+        error,
+        "duplicate case in match: 0 is already handled by a previous case",
+        regex!(r#",-\[test[\d]+:3:\d+\]"#),
+        "2 |         const.ID=offsetof(note::NOTE.id)",
+        "3 |         begin match case.0 push.1 case.ID push.2 default push.3 end end",
+        "  :                          |             ^|",
+        "  :                          |              `-- duplicate case occurs here",
+        "  :                          `-- previously handled here",
+        "  `----"
+    );
+    Ok(())
+}
+
 #[test]
 fn program_with_proc_locals() -> TestResult {
     let context = TestContext::default();
@@ -2751,6 +3775,8 @@ end";
     assert_assembler_diagnostic!(
         context,
         source,
+        "syntax error",
+        "help: see emitted diagnostics for details",
         "invalid syntax",
         regex!(r#",-\[test[\d]+:2:37\]"#),
         "1 |",
@@ -2759,7 +3785,16 @@ end";
         "  :                                      `-- found a -> here",
         "3 |",
         "  `----",
-        r#" help: expected "@", or "adv_map", or "begin", or "const", or "export", or "proc", or "use", or end of file, or doc comment"#
+        r#" help: expected "@", or "adv_map", or "begin", or "const", or "data", or "export", or "internal", or "proc", or "struct", or "use", or end of file, or doc comment"#,
+        "invalid syntax",
+        regex!(r#",-\[test[\d]+:7:24\]"#),
+        "6 |             push.2.0",
+        "7 |             exec.bigint->invalidname::checked_add",
+        "  :                        ^|",
+        "  :                         `-- found a -> here",
+        "8 |         end",
+        "  `----",
+        r#" help: expected "(", or "@", or primitive opcode (e.g. "add"), or "end", or control flow opcode (e.g. "if.true")"#
     );
 
     // --- duplicate module import --------------------------------------------
@@ -2880,7 +3915,8 @@ end";
 
 #[test]
 fn comment_in_nested_control_blocks() -> TestResult {
-    let context = TestContext::default();
+    // the branches of some of the conditionals below are deliberately unbalanced
+    let context = TestContext::default().with_warnings_as_errors(false);
 
     // if with else
     let source = source_file!(
@@ -3031,7 +4067,7 @@ fn invalid_empty_program() {
         "unexpected end of file",
         regex!(r#",-\[test[\d]+:1:1\]"#),
         "`----",
-        r#" help: expected "@", or "adv_map", or "begin", or "const", or "export", or "proc", or "use", or doc comment"#
+        r#" help: expected "@", or "adv_map", or "begin", or "const", or "data", or "export", or "internal", or "proc", or "struct", or "use", or doc comment"#
     );
 
     assert_assembler_diagnostic!(
@@ -3040,7 +4076,7 @@ fn invalid_empty_program() {
         "unexpected end of file",
         regex!(r#",-\[test[\d]+:1:1\]"#),
         "  `----",
-        r#" help: expected "@", or "adv_map", or "begin", or "const", or "export", or "proc", or "use", or doc comment"#
+        r#" help: expected "@", or "adv_map", or "begin", or "const", or "data", or "export", or "internal", or "proc", or "struct", or "use", or doc comment"#
     );
 }
 
@@ -3056,7 +4092,7 @@ fn invalid_program_unrecognized_token() {
         "  : ^^|^",
         "  :   `-- found a identifier here",
         "  `----",
-        r#" help: expected "@", or "adv_map", or "begin", or "const", or "export", or "proc", or "use", or doc comment"#
+        r#" help: expected "@", or "adv_map", or "begin", or "const", or "data", or "export", or "internal", or "proc", or "struct", or "use", or doc comment"#
     );
 }
 
@@ -3070,7 +4106,7 @@ fn invalid_program_unmatched_begin() {
         regex!(r#",-\[test[\d]+:1:10\]"#),
         "1 | begin add",
         "  `----",
-        r#" help: expected ".", or primitive opcode (e.g. "add"), or "end", or control flow opcode (e.g. "if.true")"#
+        r#" help: expected ".", or "@", or primitive opcode (e.g. "add"), or "end", or control flow opcode (e.g. "if.true")"#
     );
 }
 
@@ -3086,7 +4122,7 @@ fn invalid_program_invalid_top_level_token() {
         "  :               ^|^",
         "  :                `-- found a mul here",
         "  `----",
-        r#" help: expected "@", or "adv_map", or "begin", or "const", or "export", or "proc", or "use", or end of file, or doc comment"#
+        r#" help: expected "@", or "adv_map", or "begin", or "const", or "data", or "export", or "internal", or "proc", or "struct", or "use", or end of file, or doc comment"#
     );
 }
 
@@ -3103,7 +4139,7 @@ fn invalid_proc_missing_end_unexpected_begin() {
         "  :                  ^^|^^",
         "  :                    `-- found a begin here",
         "  `----",
-        r#" help: expected ".", or primitive opcode (e.g. "add"), or "end", or control flow opcode (e.g. "if.true")"#
+        r#" help: expected ".", or "@", or primitive opcode (e.g. "add"), or "end", or control flow opcode (e.g. "if.true")"#
     );
 }
 
@@ -3120,7 +4156,7 @@ fn invalid_proc_missing_end_unexpected_proc() {
         "  :                  ^^|^",
         "  :                    `-- found a proc here",
         "  `----",
-        r#" help: expected ".", or primitive opcode (e.g. "add"), or "end", or control flow opcode (e.g. "if.true")"#
+        r#" help: expected ".", or "@", or primitive opcode (e.g. "add"), or "end", or control flow opcode (e.g. "if.true")"#
     );
 }
 
@@ -3138,7 +4174,15 @@ fn invalid_proc_undefined_local() {
         "1 | proc.foo add mul end begin push.1 exec.bar end",
         "  :                                        ^^^",
         "  `----",
-        " help: are you missing an import?"
+        " help: are you missing an import?",
+        "Warning:   ! unused procedure `foo`",
+        regex!(r#",-\[test[\d]+:1:6\]"#),
+        "1 | proc.foo add mul end begin push.1 exec.bar end",
+        "  :      ^^^",
+        "  `----",
+        regex!(
+            r#"help: this warning is emitted by the `unused_procedure` lint, and can\s+be disabled with `@allow\(unused_procedure\)`"#
+        )
     );
 }
 
@@ -3191,7 +4235,7 @@ fn invalid_if_missing_end_no_else() {
         regex!(r#",-\[test[\d]+:1:29\]"#),
         "1 | begin push.1 add if.true mul",
         "  `----",
-        r#" help: expected ".", or primitive opcode (e.g. "add"), or "else", or "end", or control flow opcode (e.g. "if.true")"#
+        r#" help: expected ".", or "@", or primitive opcode (e.g. "add"), or "else", or "end", or control flow opcode (e.g. "if.true")"#
     );
 }
 
@@ -3208,7 +4252,7 @@ fn invalid_else_with_no_if() {
         "  :                  ^^|^",
         "  :                    `-- found a else here",
         "  `----",
-        r#" help: expected primitive opcode (e.g. "add"), or "end", or control flow opcode (e.g. "if.true")"#
+        r#" help: expected "@", or primitive opcode (e.g. "add"), or "end", or control flow opcode (e.g. "if.true")"#
     );
 
     let source = source_file!(&context, "begin push.1 while.true add else mul end end");
@@ -3256,7 +4300,7 @@ fn invalid_if_else_no_matching_end() {
         regex!(r#",-\[test[\d]+:1:38\]"#),
         "1 | begin push.1 add if.true mul else add",
         "  `----",
-        r#" help: expected ".", or primitive opcode (e.g. "add"), or "end", or control flow opcode (e.g. "if.true")"#
+        r#" help: expected ".", or "@", or primitive opcode (e.g. "add"), or "end", or control flow opcode (e.g. "if.true")"#
     );
 }
 
@@ -3273,7 +4317,7 @@ fn invalid_repeat() -> TestResult {
         regex!(r#",-\[test[\d]+:1:31\]"#),
         "1 | begin push.1 add repeat.10 mul",
         "  `----",
-        r#" help: expected ".", or primitive opcode (e.g. "add"), or "end", or control flow opcode (e.g. "if.true")"#
+        r#" help: expected ".", or "@", or primitive opcode (e.g. "add"), or "end", or control flow opcode (e.g. "if.true")"#
     );
 
     // invalid iter count
@@ -3287,7 +4331,7 @@ fn invalid_repeat() -> TestResult {
         "  :                           ^|",
         "  :                            `-- found a identifier here",
         "  `----",
-        r#" help: expected primitive opcode (e.g. "add"), or control flow opcode (e.g. "if.true")"#
+        r#" help: expected "@", or primitive opcode (e.g. "add"), or control flow opcode (e.g. "if.true")"#
     );
 
     Ok(())
@@ -3331,8 +4375,44 @@ fn invalid_while() -> TestResult {
         regex!(r#",-\[test[\d]+:1:32\]"#),
         "1 | begin push.1 add while.true mul",
         "  `----",
-        r#" help: expected ".", or primitive opcode (e.g. "add"), or "end", or control flow opcode (e.g. "if.true")"#
+        r#" help: expected ".", or "@", or primitive opcode (e.g. "add"), or "end", or control flow opcode (e.g. "if.true")"#
+    );
+    Ok(())
+}
+
+#[test]
+fn lint_levels_configured_on_assembler() -> TestResult {
+    let context = TestContext::default();
+    let source = "begin emit.42 end";
+
+    // `magic_emit_event` is allowed by default
+    Assembler::new(context.source_manager())
+        .with_warnings_as_errors(true)
+        .assemble_program(source)?;
+
+    let error = Assembler::new(context.source_manager())
+        .with_lint_level(Lint::MagicEmitEvent, LintLevel::Deny)
+        .assemble_program(source_file!(&context, source))
+        .expect_err("expected diagnostic to be raised, but assembly succeeded");
+    assert_diagnostic_lines!(
+        error,
+        "syntax error",
+        "help: see emitted diagnostics for details",
+        "event id 42 should be given a name using a constant",
+        regex!(r#",-\[test[\d]+:1:7\]"#),
+        "1 | begin emit.42 end",
+        "  :       ^^^^^^^",
+        "  `----",
+        regex!(
+            r#"help: this error is emitted by the `magic_emit_event` lint, which is set\s+to `deny`"#
+        )
     );
+
+    // module attributes override the assembler configuration
+    Assembler::new(context.source_manager())
+        .with_lint_level(Lint::MagicEmitEvent, LintLevel::Deny)
+        .assemble_program("@!allow(magic_emit_event) begin emit.42 end")?;
+
     Ok(())
 }
 
@@ -3492,6 +4572,223 @@ fn test_program_serde_simple() {
     assert_eq!(original_program, deserialized_program);
 }
 
+#[test]
+fn peephole_optimizer() -> TestResult {
+    let context = TestContext::default();
+    let source = "\
+    begin
+        swap swap
+        push.1 add
+        push.3 mul push.5 mul
+        movup.3 movdn.3
+        neg neg
+        dup.0 swap
+    end";
+    let program = context.assemble(source_file!(&context, source))?;
+    let expected = "\
+begin
+    basic_block incr push(15) mul dup0 end
+end";
+    assert_str_eq!(format!("{program}"), expected);
+
+    // the optimizer can be disabled
+    let program = Assembler::new(context.source_manager())
+        .with_peephole_optimizer(false)
+        .assemble_program(source_file!(&context, source))?;
+    let expected = "\
+begin
+    basic_block
+        swap
+        swap
+        pad
+        incr
+        add
+        push(3)
+        mul
+        push(5)
+        mul
+        movup3
+        movdn3
+        neg
+        neg
+        dup0
+        swap
+    end
+end";
+    assert_str_eq!(format!("{program}"), expected);
+
+    // blocks whose operations all cancel out are replaced with a single noop
+    let program = context.assemble(source_file!(&context, "begin swap swap end"))?;
+    let expected = "\
+begin
+    basic_block noop end
+end";
+    assert_str_eq!(format!("{program}"), expected);
+
+    Ok(())
+}
+
+#[test]
+fn peephole_optimizer_respects_decorators() -> TestResult {
+    let context = TestContext::default();
+    let source = source_file!(&context, "begin push.1 add swap trace.1 swap movup.2 movdn.2 end");
+    let program = Assembler::new(context.source_manager())
+        .with_debug_mode(true)
+        .assemble_program(source)?;
+
+    let MastNode::Block(block) = &program.mast_forest()[program.entrypoint()] else {
+        panic!("expected the program body to be a basic block");
+    };
+    assert_eq!(
+        block.operations().copied().collect::<Vec<_>>(),
+        [Operation::Incr, Operation::Swap, Operation::Swap]
+    );
+
+    // the `trace` decorator stays between the two `swap`s, and the cycle counts of the
+    // instructions are updated, or their `AsmOp` removed if all of their operations were removed
+    let decorators: Vec<_> = block
+        .decorators()
+        .iter()
+        .map(|&(pos, id)| match &program.mast_forest()[id] {
+            Decorator::AsmOp(asm_op) => (pos, asm_op.op().to_string(), asm_op.num_cycles()),
+            decorator => (pos, decorator.to_string(), 0),
+        })
+        .collect();
+    assert_eq!(
+        decorators,
+        [
+            (0, "push.1".to_string(), 1),
+            (1, "swap.1".to_string(), 1),
+            (2, "trace(1)".to_string(), 0),
+            (2, "swap.1".to_string(), 1),
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn peephole_optimizer_is_independent_of_debug_mode() -> TestResult {
+    let context = TestContext::default();
+    let source = "begin push.1 add swap debug.stack swap movup.2 movdn.2 end";
+
+    let release = Assembler::new(context.source_manager())
+        .assemble_program(source_file!(&context, source))?;
+    let debug = Assembler::new(context.source_manager())
+        .with_debug_mode(true)
+        .assemble_program(source_file!(&context, source))?;
+    assert_eq!(debug.hash(), release.hash());
+
+    // the `debug` decorator is moved to the position of the rewritten operations
+    let MastNode::Block(block) = &debug.mast_forest()[debug.entrypoint()] else {
+        panic!("expected the program body to be a basic block");
+    };
+    assert_eq!(block.operations().copied().collect::<Vec<_>>(), [Operation::Incr]);
+    let debug_positions: Vec<_> = block
+        .decorators()
+        .iter()
+        .filter(|&&(_, id)| matches!(debug.mast_forest()[id], Decorator::Debug(_)))
+        .map(|&(pos, _)| pos)
+        .collect();
+    assert_eq!(debug_positions, [1]);
+
+    // decorators of a block whose operations cancel out precede the NOOP replacing them
+    let debug = Assembler::new(context.source_manager())
+        .with_debug_mode(true)
+        .assemble_program(source_file!(&context, "begin push.12 debug.adv_stack drop end"))?;
+    let MastNode::Block(block) = &debug.mast_forest()[debug.entrypoint()] else {
+        panic!("expected the program body to be a basic block");
+    };
+    assert_eq!(block.operations().copied().collect::<Vec<_>>(), [Operation::Noop]);
+    let decorators: Vec<_> = block
+        .decorators()
+        .iter()
+        .map(|&(pos, id)| (pos, debug.mast_forest()[id].to_string()))
+        .collect();
+    assert_eq!(decorators, [(0, "debug(adv_stack.0)".to_string())]);
+
+    Ok(())
+}
+
+#[test]
+fn inline_attributes() -> TestResult {
+    let context = TestContext::default();
+    let source = source_file!(
+        &context,
+        "\
+        @noinline
+        proc.foo push.3 push.7 mul end
+        @inline
+        proc.bar push.5 if.true push.1 else push.2 end push.6 end
+        begin push.2 exec.foo push.4 exec.bar push.8 end"
+    );
+    let report = InliningReport::new();
+    let program = Assembler::new(context.source_manager())
+        .with_inlining_report(report.clone())
+        .assemble_program(source)?;
+    let expected = "\
+begin
+    join
+        join
+            join
+                basic_block push(2) end
+                basic_block push(3) push(7) mul end
+            end
+            join
+                basic_block push(4) push(5) end
+                if.true
+                    basic_block pad incr end
+                else
+                    basic_block push(2) end
+                end
+            end
+        end
+        basic_block push(6) push(8) end
+    end
+end";
+    assert_str_eq!(format!("{program}"), expected);
+
+    let name = |name: &str| QualifiedProcedureName::from_str(&format!("$exec::{name}")).unwrap();
+    assert_eq!(
+        report.get(&name("foo")),
+        Some(ProcedureInlining {
+            num_operations: 3,
+            inlined_sites: 0,
+            out_of_line_sites: 1
+        })
+    );
+    assert_eq!(report.get(&name("bar")).map(|bar| bar.inlined_sites), Some(1));
+    assert_eq!(report.inlined_sites(), 1);
+    assert_eq!(report.out_of_line_sites(), 1);
+    Ok(())
+}
+
+#[test]
+fn inline_heuristic_keeps_large_exported_procedures_out_of_line() -> TestResult {
+    let context = TestContext::default();
+    let source = "
+        export.large repeat.40 push.1000 add end end
+        proc.private repeat.40 push.1000 mul end end
+        export.small push.1000 add end
+        export.main push.1 exec.large push.2 exec.private push.3 exec.small push.4 end";
+    let module = parse_module!(&context, "test::inlining", source);
+    let report = InliningReport::new();
+    Assembler::new(context.source_manager())
+        .with_inlining_report(report.clone())
+        .assemble_library([module])?;
+
+    let name =
+        |name: &str| QualifiedProcedureName::from_str(&format!("test::inlining::{name}")).unwrap();
+    let large = report.get(&name("large")).unwrap();
+    assert_eq!((large.inlined_sites, large.out_of_line_sites), (0, 1));
+    let private = report.get(&name("private")).unwrap();
+    assert_eq!((private.inlined_sites, private.out_of_line_sites), (1, 0));
+    let small = report.get(&name("small")).unwrap();
+    assert_eq!((small.inlined_sites, small.out_of_line_sites), (1, 0));
+    assert_eq!(report.added_operations(), private.num_operations + small.num_operations);
+    Ok(())
+}
+
 #[test]
 fn test_program_serde_with_decorators() {
     let source = "
@@ -3563,8 +4860,10 @@ fn test_assert_diagnostic_lines() {
 // ================================================================================================
 
 prop_compose! {
-    fn any_package()(name in ".*", mast in any::<ArbitraryMastArtifact>(), manifest in any::<PackageManifest>()) -> Package {
-        Package { name, mast: mast.0, manifest, account_component_metadata_bytes: None }
+    fn any_package()(name in ".*", version in any::<(u16, u16, u16)>(), mast in any::<ArbitraryMastArtifact>(), manifest in any::<PackageManifest>()) -> Package {
+        let (major, minor, patch) = version;
+        let version = Version { major, minor, patch };
+        Package { name, version, mast: mast.0, manifest, account_component_metadata_bytes: None }
     }
 }
 
@@ -3855,7 +5154,8 @@ fn duplicate_procedure() {
 
 #[test]
 fn distinguish_grandchildren_correctly() {
-    let context = TestContext::new();
+    // the branches of some of the conditionals below are deliberately unbalanced
+    let context = TestContext::new().with_warnings_as_errors(false);
 
     let program_source = r#"
     begin
@@ -3888,7 +5188,8 @@ fn distinguish_grandchildren_correctly() {
 /// Ensures that equal MAST nodes don't get added twice to a MAST forest
 #[test]
 fn duplicate_nodes() {
-    let context = TestContext::new().with_debug_info(false);
+    // the branches of some of the conditionals below are deliberately unbalanced
+    let context = TestContext::new().with_debug_info(false).with_warnings_as_errors(false);
 
     let program_source = r#"
     begin
@@ -4063,3 +5364,111 @@ fn can_assemble_a_multi_module_kernel() -> Result<(), Report> {
 
     Ok(())
 }
+
+#[test]
+fn programs_record_the_version_of_their_kernel() -> Result<(), Report> {
+    use miden_core::{KernelVersion, ProgramInfo};
+
+    use crate::{
+        KernelLibrary,
+        ast::{ProcedureSignature, StackType},
+    };
+
+    const KERNEL: &str = r#"
+        @inputs(felt)
+        @outputs(felt)
+        export.foo
+            add.1
+        end
+
+        export.bar
+            push.2
+        end"#;
+
+    let context = TestContext::new();
+    let kernel_lib = Assembler::new(context.source_manager())
+        .assemble_kernel(KERNEL)?
+        .with_version(KernelVersion::new("kernel", "1.0.0"));
+
+    // the kernel metadata survives serialization
+    let deserialized = KernelLibrary::read_from_bytes(&kernel_lib.to_bytes()).unwrap();
+    assert_eq!(deserialized, kernel_lib);
+    assert_eq!(deserialized.version().map(ToString::to_string).as_deref(), Some("kernel@1.0.0"));
+
+    let procedures = kernel_lib
+        .procedures()
+        .map(|(name, _, signature)| (name.to_string(), signature.cloned()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        procedures,
+        [
+            ("bar".to_string(), None),
+            (
+                "foo".to_string(),
+                Some(ProcedureSignature::new([StackType::Felt], [StackType::Felt]))
+            ),
+        ]
+    );
+
+    let program = Assembler::with_kernel(context.source_manager(), kernel_lib.clone())
+        .assemble_program("begin syscall.foo end")?;
+    assert_eq!(program.kernel_version(), kernel_lib.version());
+
+    let deserialized = Program::read_from_bytes(&program.to_bytes()).unwrap();
+    assert_eq!(deserialized.kernel_version(), kernel_lib.version());
+
+    let program_info = ProgramInfo::from(program);
+    assert_eq!(program_info.kernel_version(), kernel_lib.version());
+    assert!(program_info.check_kernel(kernel_lib.kernel()).is_ok());
+
+    // a kernel which no longer provides `foo` is reported as a mismatch
+    let other_kernel_lib = Assembler::new(context.source_manager())
+        .assemble_kernel("export.foo add.2 end")?
+        .with_version(KernelVersion::new("kernel", "2.0.0"));
+    let err = program_info.check_kernel(other_kernel_lib.kernel()).unwrap_err();
+    assert!(
+        err.to_string().starts_with(
+            "kernel mismatch: the program was assembled against kernel 'kernel@1.0.0'"
+        )
+    );
+
+    Ok(())
+}
+
+#[test]
+fn linking_library_against_different_kernel_fails() -> Result<(), Report> {
+    use miden_core::KernelVersion;
+
+    let context = TestContext::new();
+    let kernel_v1 = Assembler::new(context.source_manager())
+        .assemble_kernel("export.foo add.1 end")?
+        .with_version(KernelVersion::new("kernel", "1.0.0"));
+    let kernel_v2 = Assembler::new(context.source_manager())
+        .assemble_kernel("export.foo add.2 end")?
+        .with_version(KernelVersion::new("kernel", "2.0.0"));
+
+    let library = Assembler::with_kernel(context.source_manager(), kernel_v1.clone())
+        .assemble_library([parse_module!(&context, "lib::sys", "export.bar syscall.foo end")])?;
+    assert_eq!(library.kernel_version(), kernel_v1.version());
+
+    // a library which does not invoke the kernel does not record its version
+    let plain = Assembler::with_kernel(context.source_manager(), kernel_v1.clone())
+        .assemble_library([parse_module!(&context, "lib::plain", "export.baz push.1 end")])?;
+    assert_eq!(plain.kernel_version(), None);
+
+    // the library can be linked against the kernel it was assembled with
+    let mut assembler = Assembler::with_kernel(context.source_manager(), kernel_v1);
+    assembler.link_dynamic_library(&library)?;
+
+    let mut assembler = Assembler::with_kernel(context.source_manager(), kernel_v2);
+    assembler.link_dynamic_library(&plain)?;
+    let err = assembler.link_dynamic_library(&library).unwrap_err();
+    assert_diagnostic!(
+        err,
+        regex!(
+            r#"library was assembled against kernel 'kernel@1\.0\.0', but the procedure with root 0x[0-9a-f]+ which it invokes via syscall is not in the[\s|]+kernel of the assembler \(kernel 'kernel@2\.0\.0'\)"#
+        )
+    );
+
+    Ok(())
+}
@@ -2,6 +2,7 @@ mod location;
 mod selection;
 mod source_file;
 mod source_manager;
+mod source_map;
 mod span;
 
 use alloc::{string::String, sync::Arc};
@@ -19,6 +20,7 @@ pub use self::{
         SourceContentUpdateError, SourceFile, SourceFileRef, SourceLanguage,
     },
     source_manager::{DefaultSourceManager, SourceId, SourceManager},
+    source_map::{SourceMap, SourceMapFile},
     span::{SourceSpan, Span, Spanned},
 };
 
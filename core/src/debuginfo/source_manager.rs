@@ -229,12 +229,7 @@ pub trait SourceManagerExt: SourceManager {
             return Ok(existing);
         }
 
-        let lang = match path.extension().and_then(|ext| ext.to_str()) {
-            Some("masm") => "masm",
-            Some("rs") => "rust",
-            Some(ext) => ext,
-            None => "unknown",
-        };
+        let lang = language_of(path);
 
         let content = std::fs::read_to_string(path)
             .map(|s| SourceContent::new(lang, uri.clone(), s))
@@ -247,11 +242,65 @@ pub trait SourceManagerExt: SourceManager {
 
         Ok(self.load_from_raw_parts(uri, content))
     }
+
+    /// Load the source files recorded in `source_map` from the directory `root` into this
+    /// [SourceManager], under the URIs they had when they were assembled, so that the locations of
+    /// the decorators referring to them can be resolved.
+    ///
+    /// Files which are already known to this [SourceManager] are not reloaded.
+    ///
+    /// Returns an error if a file cannot be read, or if its content differs from the content it
+    /// had when it was assembled.
+    fn load_source_map(
+        &self,
+        source_map: &SourceMap,
+        root: &std::path::Path,
+    ) -> Result<Vec<Arc<SourceFile>>, SourceManagerError> {
+        use crate::crypto::hash::Blake3_256;
+
+        let mut files = Vec::with_capacity(source_map.len());
+        for (uri, file) in source_map.files() {
+            if let Some(existing) = self.get_by_uri(uri) {
+                files.push(existing);
+                continue;
+            }
+
+            let path = root.join(&file.path);
+            let content = std::fs::read_to_string(&path).map_err(|source| {
+                SourceManagerError::custom_with_source(
+                    format!("failed to load source file at `{}`", path.display()),
+                    source,
+                )
+            })?;
+            if Blake3_256::hash(content.as_bytes()) != file.content_hash {
+                return Err(SourceManagerError::custom(format!(
+                    "source file at `{}` does not match the source map, it was modified after \
+                    being assembled",
+                    path.display()
+                )));
+            }
+
+            let content = SourceContent::new(language_of(&path), uri.clone(), content);
+            files.push(self.load_from_raw_parts(uri.clone(), content));
+        }
+        Ok(files)
+    }
 }
 
 #[cfg(feature = "std")]
 impl<T: ?Sized + SourceManager> SourceManagerExt for T {}
 
+/// Returns the language of the source file at `path`, based on its extension.
+#[cfg(feature = "std")]
+fn language_of(path: &std::path::Path) -> &str {
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("masm") => "masm",
+        Some("rs") => "rust",
+        Some(ext) => ext,
+        None => "unknown",
+    }
+}
+
 // DEFAULT SOURCE MANAGER
 // ================================================================================================
 
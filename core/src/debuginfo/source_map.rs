@@ -0,0 +1,161 @@
+use alloc::{collections::BTreeMap, format, string::String, vec::Vec};
+
+use super::{SourceManager, Uri};
+use crate::{
+    Decorator,
+    crypto::hash::{Blake3_256, Blake3Digest},
+    mast::MastForest,
+    utils::{ByteReader, ByteWriter, Deserializable, DeserializationError, Serializable},
+};
+
+// SOURCE MAP
+// ================================================================================================
+
+/// A portable mapping from the source files referenced by the [crate::AssemblyOp] decorators of a
+/// [MastForest] to their path relative to a source root, e.g. the root directory of a project.
+///
+/// The locations of decorators refer to source files by the URI they had when they were
+/// assembled, which is usually a path on the machine which assembled them. A source map records,
+/// for each such URI, the path of the file relative to the source root, and a hash of its content,
+/// so that the files can be found under a different source root later, e.g. when debugging on
+/// another machine, and checked to be the ones the decorators were assembled from. See
+/// `SourceManagerExt::load_source_map`.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SourceMap {
+    files: BTreeMap<Uri, SourceMapFile>,
+}
+
+/// A source file recorded in a [SourceMap].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceMapFile {
+    /// The path of the file relative to the source root, with `/` as separator.
+    pub path: String,
+    /// The hash of the content of the file when it was assembled.
+    pub content_hash: Blake3Digest<32>,
+}
+
+impl SourceMap {
+    /// Creates a source map for the source files referenced by the decorators of `mast_forest`,
+    /// relative to the directory `root`, whose content is found in `source_manager`.
+    ///
+    /// Files which are not under `root`, or which are not known to `source_manager`, are omitted.
+    pub fn new(mast_forest: &MastForest, source_manager: &dyn SourceManager, root: &str) -> Self {
+        let mut files = BTreeMap::new();
+        for decorator in mast_forest.decorators() {
+            let Decorator::AsmOp(assembly_op) = decorator else {
+                continue;
+            };
+            let Some(location) = assembly_op.location() else {
+                continue;
+            };
+            if files.contains_key(&location.uri) {
+                continue;
+            }
+            let Some(path) = relative_path(location.uri.path(), root) else {
+                continue;
+            };
+            let Some(file) = source_manager.get_by_uri(&location.uri) else {
+                continue;
+            };
+            let content_hash = Blake3_256::hash(file.as_bytes());
+            files.insert(location.uri.clone(), SourceMapFile { path, content_hash });
+        }
+        Self { files }
+    }
+
+    /// Returns the source file recorded for `uri`, if any.
+    pub fn get(&self, uri: &Uri) -> Option<&SourceMapFile> {
+        self.files.get(uri)
+    }
+
+    /// Returns an iterator over the URIs of the source files in this map, and the corresponding
+    /// files.
+    pub fn files(&self) -> impl Iterator<Item = (&Uri, &SourceMapFile)> + '_ {
+        self.files.iter()
+    }
+
+    /// Returns the number of source files in this map.
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    /// Returns true if this map does not contain any source file.
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// Adds the source files of `other` to this map.
+    pub fn extend(&mut self, other: SourceMap) {
+        self.files.extend(other.files);
+    }
+}
+
+/// Returns `path` relative to `root`, with `/` as separator, if it is under `root`.
+fn relative_path(path: &str, root: &str) -> Option<String> {
+    fn components(path: &str) -> impl Iterator<Item = &str> {
+        path.split(['/', '\\'])
+            .filter(|component| !component.is_empty() && *component != ".")
+    }
+
+    let is_absolute = |path: &str| path.starts_with(['/', '\\']);
+    if is_absolute(path) != is_absolute(root) {
+        return None;
+    }
+    let mut path = components(path);
+    for root_component in components(root) {
+        if path.next()? != root_component {
+            return None;
+        }
+    }
+    let relative = path.collect::<Vec<_>>();
+    if relative.is_empty() || relative.contains(&"..") {
+        return None;
+    }
+    Some(relative.join("/"))
+}
+
+impl Serializable for SourceMap {
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        target.write_usize(self.files.len());
+        for (uri, file) in self.files.iter() {
+            uri.as_str().write_into(target);
+            file.path.write_into(target);
+            file.content_hash.write_into(target);
+        }
+    }
+}
+
+impl Deserializable for SourceMap {
+    fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
+        let len = source.read_usize()?;
+        let mut files = BTreeMap::new();
+        for _ in 0..len {
+            let uri = Uri::from(String::read_from(source)?);
+            let path = String::read_from(source)?;
+            if relative_path(&path, "").is_none_or(|normalized| normalized != path) {
+                return Err(DeserializationError::InvalidValue(format!(
+                    "invalid source map: '{path}' is not a normalized relative path"
+                )));
+            }
+            let content_hash = Blake3Digest::read_from(source)?;
+            files.insert(uri, SourceMapFile { path, content_hash });
+        }
+        Ok(Self { files })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn source_map_relative_paths() {
+        assert_eq!(relative_path("/src/lib/foo.masm", "/src").as_deref(), Some("lib/foo.masm"));
+        assert_eq!(relative_path("./lib/foo.masm", "lib").as_deref(), Some("foo.masm"));
+        assert_eq!(relative_path("lib/foo.masm", "").as_deref(), Some("lib/foo.masm"));
+        assert_eq!(relative_path("/src/foo.masm", "/lib"), None);
+        assert_eq!(relative_path("/src/foo.masm", "src"), None);
+        assert_eq!(relative_path("/src/../foo.masm", "/src"), None);
+        assert_eq!(relative_path("/src", "/src"), None);
+    }
+}
@@ -1,7 +1,10 @@
-use alloc::string::String;
+use alloc::{format, string::String};
 
+use miden_crypto::Word;
 use miden_formatting::hex::DisplayHex;
 
+use crate::KernelVersion;
+
 // INPUT ERROR
 // ================================================================================================
 
@@ -39,4 +42,21 @@ pub enum KernelError {
     DuplicatedProcedures,
     #[error("kernel can have at most {0} procedures, received {1}")]
     TooManyProcedures(usize, usize),
+    #[error(
+        "kernel mismatch: the program was assembled against {expected}, but the procedure with \
+        root {proc_root} is not in the kernel it is executed against",
+        expected = describe_kernel(.expected.as_ref())
+    )]
+    Mismatch {
+        expected: Option<KernelVersion>,
+        proc_root: Word,
+    },
+}
+
+/// Returns a description of the kernel identified by `version`, for use in error messages.
+pub fn describe_kernel(version: Option<&KernelVersion>) -> String {
+    match version {
+        Some(version) => format!("kernel '{version}'"),
+        None => String::from("an unversioned kernel"),
+    }
 }
@@ -1,4 +1,9 @@
-use alloc::vec::Vec;
+use alloc::{
+    string::{String, ToString},
+    sync::Arc,
+    vec::Vec,
+};
+use core::fmt;
 
 use miden_crypto::Word;
 
@@ -55,6 +60,21 @@ impl Kernel {
     pub fn proc_hashes(&self) -> &[Word] {
         &self.0
     }
+
+    /// Returns an error if this kernel does not contain all the procedures of `expected`, the
+    /// kernel which a program was assembled against, identified by `version` if it is known.
+    pub fn check_compatible(
+        &self,
+        expected: &Kernel,
+        version: Option<&KernelVersion>,
+    ) -> Result<(), KernelError> {
+        match expected.0.iter().find(|proc_hash| !self.contains_proc(**proc_hash)) {
+            Some(&proc_root) => {
+                Err(KernelError::Mismatch { expected: version.cloned(), proc_root })
+            },
+            None => Ok(()),
+        }
+    }
 }
 
 // this is required by AIR as public inputs will be serialized with the proof
@@ -73,3 +93,58 @@ impl Deserializable for Kernel {
         Ok(Self(kernel))
     }
 }
+
+// KERNEL VERSION
+// ================================================================================================
+
+/// The name and version of a kernel, e.g. `tx@0.10.0`.
+///
+/// A [Kernel] only consists of the hashes of its procedures, so the programs assembled against a
+/// kernel record its version, which allows reporting which kernel they expect when they are
+/// executed against a different one.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct KernelVersion {
+    name: Arc<str>,
+    version: Arc<str>,
+}
+
+impl KernelVersion {
+    /// Returns a new [KernelVersion] for version `version` of the kernel named `name`.
+    pub fn new(name: impl Into<Arc<str>>, version: impl ToString) -> Self {
+        Self {
+            name: name.into(),
+            version: version.to_string().into(),
+        }
+    }
+
+    /// Returns the name of the kernel.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the version of the kernel.
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+}
+
+impl fmt::Display for KernelVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}@{}", self.name, self.version)
+    }
+}
+
+impl Serializable for KernelVersion {
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        self.name.as_ref().write_into(target);
+        self.version.as_ref().write_into(target);
+    }
+}
+
+impl Deserializable for KernelVersion {
+    fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
+        let name = String::read_from(source)?;
+        let version = String::read_from(source)?;
+        Ok(Self::new(name, version))
+    }
+}
@@ -57,7 +57,7 @@ mod program;
 pub use program::{Program, ProgramInfo};
 
 mod kernel;
-pub use kernel::Kernel;
+pub use kernel::{Kernel, KernelVersion};
 pub use miden_crypto::{EMPTY_WORD, ONE, WORD_SIZE, Word, ZERO};
 pub mod crypto {
     pub mod merkle {
@@ -0,0 +1,408 @@
+use alloc::{
+    collections::{BTreeMap, BTreeSet},
+    string::String,
+    vec::Vec,
+};
+use core::fmt::{self, Write};
+
+use crate::{
+    Word,
+    mast::{MastForest, MastNode, MastNodeId},
+};
+
+#[cfg(test)]
+mod tests;
+
+// MAST CALL GRAPH
+// ================================================================================================
+
+/// The call graph of the procedures in a [`MastForest`].
+///
+/// The vertices of the graph are the procedure roots of the forest, the procedures they reference
+/// via `call` and `syscall`, and the procedures of other forests which they reference via
+/// [`MastNode::External`] nodes. Procedures can optionally be given names (e.g. the names under
+/// which a library exports them), which are used when rendering the graph.
+///
+/// Since `exec`'d procedures are inlined into their caller during compilation, an `exec` edge is
+/// recorded whenever the body of a procedure contains the root of another procedure of the forest.
+/// `dyn` and `dyncall` sites are recorded as edges to an unknown callee.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MastCallGraph {
+    procedures: Vec<CallGraphProcedure>,
+    edges: Vec<CallGraphEdge>,
+}
+
+// ------------------------------------------------------------------------------------------------
+/// Constructors
+impl MastCallGraph {
+    /// Builds the call graph of all procedures in `forest`, attaching the given names to the
+    /// procedures rooted at the associated nodes.
+    ///
+    /// # Panics
+    /// Panics if any of the named nodes is not a node of `forest`.
+    pub fn build(
+        forest: &MastForest,
+        names: impl IntoIterator<Item = (String, MastNodeId)>,
+    ) -> Self {
+        let mut builder = CallGraphBuilder {
+            forest,
+            roots: forest.procedure_roots().iter().copied().collect(),
+            procedures: Vec::new(),
+            local_procedures: BTreeMap::new(),
+            external_procedures: BTreeMap::new(),
+            edges: BTreeSet::new(),
+        };
+
+        for &root in forest.procedure_roots() {
+            builder.procedure_index(root);
+        }
+        for (name, node_id) in names {
+            let index = builder.procedure_index(node_id);
+            builder.procedures[index].names.push(name);
+        }
+
+        // callees which are not procedure roots are appended to the list of procedures while
+        // walking the bodies, and hence are visited as well
+        let mut caller = 0;
+        while caller < builder.procedures.len() {
+            if let Some(node_id) = builder.procedures[caller].node_id {
+                builder.visit_body(caller, node_id);
+            }
+            caller += 1;
+        }
+
+        Self {
+            procedures: builder.procedures,
+            edges: builder.edges.into_iter().collect(),
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+/// Public accessors
+impl MastCallGraph {
+    /// Returns the procedures of this call graph.
+    ///
+    /// The callers and callees of [`CallGraphEdge`]s are indices into this slice.
+    pub fn procedures(&self) -> &[CallGraphProcedure] {
+        &self.procedures
+    }
+
+    /// Returns the edges of this call graph, sorted by caller.
+    pub fn edges(&self) -> &[CallGraphEdge] {
+        &self.edges
+    }
+
+    /// Returns the edges of this call graph whose caller is the procedure at `caller`.
+    pub fn callees(&self, caller: usize) -> impl Iterator<Item = &CallGraphEdge> {
+        self.edges.iter().filter(move |edge| edge.caller == caller)
+    }
+
+    /// Returns the index of the first procedure with the given name, if any.
+    pub fn find_procedure_by_name(&self, name: &str) -> Option<usize> {
+        self.procedures
+            .iter()
+            .position(|procedure| procedure.names.iter().any(|n| n == name))
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+/// Export
+impl MastCallGraph {
+    /// Renders this call graph in the Graphviz DOT format.
+    ///
+    /// Procedures of other forests are drawn with dashed outlines, and all dynamic call sites point
+    /// to a single `<dynamic>` vertex.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::new();
+        self.write_dot(&mut dot).expect("writing to a string should not fail");
+        dot
+    }
+
+    /// Renders this call graph as a JSON object of the form:
+    ///
+    /// ```json
+    /// {
+    ///   "procedures": [{ "digest": "0x..", "names": ["..."], "external": false }],
+    ///   "edges": [{ "caller": 0, "callee": 1, "kind": "exec" }]
+    /// }
+    /// ```
+    ///
+    /// where `caller` and `callee` are indices into `procedures`, and `callee` is `null` for
+    /// dynamic call sites.
+    pub fn to_json(&self) -> String {
+        let mut json = String::new();
+        self.write_json(&mut json).expect("writing to a string should not fail");
+        json
+    }
+
+    fn write_dot(&self, f: &mut impl Write) -> fmt::Result {
+        writeln!(f, "digraph callgraph {{")?;
+        writeln!(f, "    node [shape=box];")?;
+        for (index, procedure) in self.procedures.iter().enumerate() {
+            write!(f, "    p{index} [label=\"")?;
+            if procedure.names.is_empty() {
+                write!(f, "{}", procedure.digest.to_hex())?;
+            }
+            for (i, name) in procedure.names.iter().enumerate() {
+                if i > 0 {
+                    f.write_str("\\n")?;
+                }
+                write_escaped(f, name)?;
+            }
+            f.write_char('"')?;
+            if procedure.is_external() {
+                f.write_str(", style=dashed")?;
+            }
+            writeln!(f, "];")?;
+        }
+        if self.edges.iter().any(|edge| edge.callee.is_none()) {
+            writeln!(f, "    dynamic [label=\"<dynamic>\", shape=diamond];")?;
+        }
+        for edge in self.edges.iter() {
+            write!(f, "    p{} -> ", edge.caller)?;
+            match edge.callee {
+                Some(callee) => write!(f, "p{callee}")?,
+                None => f.write_str("dynamic")?,
+            }
+            writeln!(f, " [label=\"{}\"];", edge.kind)?;
+        }
+        writeln!(f, "}}")
+    }
+
+    fn write_json(&self, f: &mut impl Write) -> fmt::Result {
+        f.write_str("{\"procedures\":[")?;
+        for (index, procedure) in self.procedures.iter().enumerate() {
+            if index > 0 {
+                f.write_char(',')?;
+            }
+            write!(f, "{{\"digest\":\"{}\",\"names\":[", procedure.digest.to_hex())?;
+            for (i, name) in procedure.names.iter().enumerate() {
+                if i > 0 {
+                    f.write_char(',')?;
+                }
+                f.write_char('"')?;
+                write_escaped(f, name)?;
+                f.write_char('"')?;
+            }
+            write!(f, "],\"external\":{}}}", procedure.is_external())?;
+        }
+        f.write_str("],\"edges\":[")?;
+        for (index, edge) in self.edges.iter().enumerate() {
+            if index > 0 {
+                f.write_char(',')?;
+            }
+            write!(f, "{{\"caller\":{},\"callee\":", edge.caller)?;
+            match edge.callee {
+                Some(callee) => write!(f, "{callee}")?,
+                None => f.write_str("null")?,
+            }
+            write!(f, ",\"kind\":\"{}\"}}", edge.kind)?;
+        }
+        f.write_str("]}")
+    }
+}
+
+// CALL GRAPH PROCEDURE
+// ================================================================================================
+
+/// A procedure in a [`MastCallGraph`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallGraphProcedure {
+    digest: Word,
+    node_id: Option<MastNodeId>,
+    names: Vec<String>,
+}
+
+impl CallGraphProcedure {
+    /// Returns the MAST root of this procedure.
+    pub fn digest(&self) -> Word {
+        self.digest
+    }
+
+    /// Returns the ID of the root of this procedure in the forest, or `None` if the procedure is
+    /// defined in another forest.
+    pub fn node_id(&self) -> Option<MastNodeId> {
+        self.node_id
+    }
+
+    /// Returns the names given to this procedure when building the call graph.
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    /// Returns true if this procedure is defined in another forest.
+    pub fn is_external(&self) -> bool {
+        self.node_id.is_none()
+    }
+}
+
+// CALL GRAPH EDGE
+// ================================================================================================
+
+/// A reference from one procedure of a [`MastCallGraph`] to another.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct CallGraphEdge {
+    caller: usize,
+    callee: Option<usize>,
+    kind: CallKind,
+}
+
+impl CallGraphEdge {
+    /// Returns the index of the calling procedure.
+    pub fn caller(&self) -> usize {
+        self.caller
+    }
+
+    /// Returns the index of the called procedure, or `None` if the callee is only known at
+    /// runtime (i.e. for `dyn` and `dyncall`).
+    pub fn callee(&self) -> Option<usize> {
+        self.callee
+    }
+
+    /// Returns the way in which the callee is invoked.
+    pub fn kind(&self) -> CallKind {
+        self.kind
+    }
+}
+
+/// The way in which a procedure invokes another one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CallKind {
+    /// The callee is inlined into the caller.
+    Exec,
+    /// The callee is invoked via `call`.
+    Call,
+    /// The callee is a kernel procedure invoked via `syscall`.
+    SysCall,
+    /// The callee is determined at runtime, and executed in the caller's context.
+    Dyn,
+    /// The callee is determined at runtime, and executed in a new context.
+    DynCall,
+}
+
+impl CallKind {
+    /// Returns the name of the instruction corresponding to this kind of call.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Exec => "exec",
+            Self::Call => "call",
+            Self::SysCall => "syscall",
+            Self::Dyn => "dyn",
+            Self::DynCall => "dyncall",
+        }
+    }
+}
+
+impl fmt::Display for CallKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+// HELPERS
+// ================================================================================================
+
+struct CallGraphBuilder<'a> {
+    forest: &'a MastForest,
+    roots: BTreeSet<MastNodeId>,
+    procedures: Vec<CallGraphProcedure>,
+    local_procedures: BTreeMap<MastNodeId, usize>,
+    external_procedures: BTreeMap<Word, usize>,
+    edges: BTreeSet<CallGraphEdge>,
+}
+
+impl CallGraphBuilder<'_> {
+    /// Returns the index of the procedure rooted at `node_id`, adding it if necessary.
+    ///
+    /// External nodes are resolved to the procedure of the forest with the same digest if there
+    /// is one, and to a procedure of another forest otherwise.
+    fn procedure_index(&mut self, node_id: MastNodeId) -> usize {
+        let node = &self.forest[node_id];
+        if let MastNode::External(external) = node {
+            let digest = external.digest();
+            match self.forest.find_procedure_root(digest) {
+                Some(local_root) if local_root != node_id => {
+                    return self.procedure_index(local_root);
+                },
+                _ => (),
+            }
+
+            return *self.external_procedures.entry(digest).or_insert_with(|| {
+                self.procedures.push(CallGraphProcedure {
+                    digest,
+                    node_id: None,
+                    names: Vec::new(),
+                });
+                self.procedures.len() - 1
+            });
+        }
+
+        *self.local_procedures.entry(node_id).or_insert_with(|| {
+            self.procedures.push(CallGraphProcedure {
+                digest: node.digest(),
+                node_id: Some(node_id),
+                names: Vec::new(),
+            });
+            self.procedures.len() - 1
+        })
+    }
+
+    /// Records the edges of the procedure at index `caller`, whose root is `root`.
+    fn visit_body(&mut self, caller: usize, root: MastNodeId) {
+        let mut visited = BTreeSet::new();
+        let mut unvisited = vec![root];
+        while let Some(node_id) = unvisited.pop() {
+            if !visited.insert(node_id) {
+                continue;
+            }
+
+            let node = &self.forest[node_id];
+            if node_id != root && (self.roots.contains(&node_id) || node.is_external()) {
+                let callee = self.procedure_index(node_id);
+                self.add_edge(caller, Some(callee), CallKind::Exec);
+                continue;
+            }
+
+            match node {
+                MastNode::Call(call) => {
+                    let kind = if call.is_syscall() {
+                        CallKind::SysCall
+                    } else {
+                        CallKind::Call
+                    };
+                    let callee = self.procedure_index(call.callee());
+                    self.add_edge(caller, Some(callee), kind);
+                },
+                MastNode::Dyn(dyn_node) => {
+                    let kind = if dyn_node.is_dyncall() {
+                        CallKind::DynCall
+                    } else {
+                        CallKind::Dyn
+                    };
+                    self.add_edge(caller, None, kind);
+                },
+                _ => node.append_children_to(&mut unvisited),
+            }
+        }
+    }
+
+    fn add_edge(&mut self, caller: usize, callee: Option<usize>, kind: CallKind) {
+        self.edges.insert(CallGraphEdge { caller, callee, kind });
+    }
+}
+
+/// Writes `s` with backslashes, double quotes and control characters escaped, so that it can be
+/// embedded in a quoted DOT or JSON string.
+fn write_escaped(f: &mut impl Write, s: &str) -> fmt::Result {
+    for c in s.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            c if c.is_control() => write!(f, "\\u{:04x}", c as u32)?,
+            c => f.write_char(c)?,
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,118 @@
+use alloc::string::ToString;
+
+use super::*;
+use crate::Operation;
+
+/// Builds a forest with the procedures:
+/// - `helper`: a single basic block,
+/// - `main`: `join(join(helper, call(callee)), join(external, dyncall))`,
+/// - `callee`: a basic block which is only reachable via `call` (i.e. not a procedure root).
+fn forest_with_all_call_kinds() -> (MastForest, MastNodeId, MastNodeId, Word) {
+    let mut forest = MastForest::new();
+    let helper = forest.add_block(vec![Operation::Add, Operation::Mul], None).unwrap();
+    let callee = forest.add_block(vec![Operation::Swap], None).unwrap();
+    let call = forest.add_call(callee).unwrap();
+    let external_digest = forest[callee].digest().iter().rev().copied().collect::<Vec<_>>();
+    let external_digest = Word::try_from(external_digest.as_slice()).unwrap();
+    let external = forest.add_external(external_digest).unwrap();
+    let dyncall = forest.add_dyncall().unwrap();
+    let first = forest.add_join(helper, call).unwrap();
+    let second = forest.add_join(external, dyncall).unwrap();
+    let main = forest.add_join(first, second).unwrap();
+    forest.make_root(helper);
+    forest.make_root(main);
+
+    (forest, helper, main, external_digest)
+}
+
+#[test]
+fn call_graph_records_all_call_kinds() {
+    let (forest, helper, main, external_digest) = forest_with_all_call_kinds();
+    let graph = MastCallGraph::build(&forest, [("main".to_string(), main)]);
+
+    let main_index = graph.find_procedure_by_name("main").unwrap();
+    assert_eq!(graph.procedures()[main_index].node_id(), Some(main));
+    assert_eq!(graph.procedures().len(), 4);
+
+    let callees: Vec<_> = graph
+        .callees(main_index)
+        .map(|edge| (edge.callee().map(|callee| &graph.procedures()[callee]), edge.kind()))
+        .collect();
+    assert_eq!(callees.len(), 4);
+
+    assert!(
+        callees
+            .iter()
+            .any(|(callee, kind)| *kind == CallKind::Exec
+                && callee.unwrap().node_id() == Some(helper))
+    );
+    assert!(callees.iter().any(|(callee, kind)| *kind == CallKind::Exec
+        && callee.unwrap().is_external()
+        && callee.unwrap().digest() == external_digest));
+    assert!(callees.iter().any(|(callee, kind)| *kind == CallKind::Call
+        && callee.unwrap().node_id().is_some_and(|id| !forest.is_procedure_root(id))));
+    assert!(callees.contains(&(None, CallKind::DynCall)));
+
+    // the helper and the callee don't invoke any other procedures
+    let helper_index = graph.procedures().iter().position(|p| p.node_id() == Some(helper)).unwrap();
+    assert_eq!(graph.callees(helper_index).count(), 0);
+}
+
+#[test]
+fn call_graph_resolves_external_nodes_to_local_procedures() {
+    let mut forest = MastForest::new();
+    let helper = forest.add_block(vec![Operation::Add], None).unwrap();
+    let external = forest.add_external(forest[helper].digest()).unwrap();
+    let call = forest.add_call(external).unwrap();
+    forest.make_root(helper);
+    forest.make_root(call);
+
+    let graph = MastCallGraph::build(&forest, []);
+    assert_eq!(graph.procedures().len(), 2);
+    assert_eq!(graph.edges().len(), 1);
+
+    let edge = graph.edges()[0];
+    assert_eq!(graph.procedures()[edge.caller()].node_id(), Some(call));
+    assert_eq!(graph.procedures()[edge.callee().unwrap()].node_id(), Some(helper));
+    assert_eq!(edge.kind(), CallKind::Call);
+}
+
+#[test]
+fn call_graph_exports_dot_and_json() {
+    let mut forest = MastForest::new();
+    let helper = forest.add_block(vec![Operation::Add], None).unwrap();
+    let dyn_node = forest.add_dyn().unwrap();
+    let main = forest.add_join(helper, dyn_node).unwrap();
+    forest.make_root(helper);
+    forest.make_root(main);
+
+    let graph = MastCallGraph::build(
+        &forest,
+        [("helper".to_string(), helper), ("\"quoted\"::main".to_string(), main)],
+    );
+
+    let expected_dot = "\
+digraph callgraph {
+    node [shape=box];
+    p0 [label=\"helper\"];
+    p1 [label=\"\\\"quoted\\\"::main\"];
+    dynamic [label=\"<dynamic>\", shape=diamond];
+    p1 -> dynamic [label=\"dyn\"];
+    p1 -> p0 [label=\"exec\"];
+}
+";
+    assert_eq!(graph.to_dot(), expected_dot);
+
+    let helper_digest = forest[helper].digest().to_hex();
+    let main_digest = forest[main].digest().to_hex();
+    let expected_json = format!(
+        "{{\"procedures\":[\
+            {{\"digest\":\"{helper_digest}\",\"names\":[\"helper\"],\"external\":false}},\
+            {{\"digest\":\"{main_digest}\",\"names\":[\"\\\"quoted\\\"::main\"],\"external\":false}}\
+        ],\"edges\":[\
+            {{\"caller\":1,\"callee\":null,\"kind\":\"dyn\"}},\
+            {{\"caller\":1,\"callee\":0,\"kind\":\"exec\"}}\
+        ]}}"
+    );
+    assert_eq!(graph.to_json(), expected_json);
+}
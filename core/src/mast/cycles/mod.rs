@@ -0,0 +1,348 @@
+use alloc::{collections::BTreeMap, vec::Vec};
+use core::{fmt, ops::Add};
+
+use crate::{
+    Word,
+    mast::{BasicBlockNode, MastForest, MastNode, MastNodeId, OpBatch},
+};
+
+#[cfg(test)]
+mod tests;
+
+// CYCLE ESTIMATE
+// ================================================================================================
+
+/// Static bounds on the number of VM cycles needed to execute a MAST node.
+///
+/// For nodes without control flow (i.e. basic blocks and joins, calls and syscalls of such nodes),
+/// the estimate is exact. `split` nodes are bounded by their cheapest and most expensive branch.
+/// The upper bound of nodes containing loops is symbolic: it is a polynomial in `n`, the maximum
+/// number of iterations of each loop, which can be evaluated with [`Self::with_loop_iterations`].
+/// Dynamic calls and references to procedures of other forests have no upper bound.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CycleEstimate {
+    min: u64,
+    /// The coefficients of the upper bound as a polynomial in `n`, lowest degree first and without
+    /// trailing zeros, or `None` if there is no upper bound.
+    max: Option<Vec<u64>>,
+}
+
+impl CycleEstimate {
+    /// Returns an estimate for a node which always executes in exactly `cycles` cycles.
+    pub fn exact(cycles: u64) -> Self {
+        Self { min: cycles, max: Some(vec![cycles]) }
+    }
+
+    /// Returns an estimate for a node which executes in at least `min` cycles, and for which no
+    /// upper bound is known.
+    pub const fn unbounded(min: u64) -> Self {
+        Self { min, max: None }
+    }
+
+    /// Returns the estimate for a loop whose body is estimated by `body`.
+    ///
+    /// A loop which is not entered takes 2 cycles (`LOOP` and `END`), while a loop iterating `k`
+    /// times takes `1 + k * (body + 1)` cycles, as each iteration after the first one starts with
+    /// a `REPEAT`. The upper bound `2 + n * (body + 1)` covers both cases, so that it holds for
+    /// loops which are not entered even when they are nested in other nodes.
+    pub fn repeated(body: &Self) -> Self {
+        let max = body.max.as_ref().map(|body_max| {
+            // 2 + n * (body + 1)
+            let mut per_iteration = body_max.clone();
+            per_iteration[0] = per_iteration[0].saturating_add(1);
+            let mut max = vec![2];
+            max.extend(per_iteration);
+            max
+        });
+        Self { min: 2, max }
+    }
+
+    /// Returns the minimum number of cycles.
+    pub fn min(&self) -> u64 {
+        self.min
+    }
+
+    /// Returns the maximum number of cycles, or `None` if there is no upper bound or if it depends
+    /// on the number of loop iterations.
+    pub fn max(&self) -> Option<u64> {
+        match self.max.as_deref() {
+            Some([max]) => Some(*max),
+            _ => None,
+        }
+    }
+
+    /// Returns true if the upper bound of this estimate depends on the number of loop iterations.
+    pub fn depends_on_loop_iterations(&self) -> bool {
+        self.max.as_ref().is_some_and(|max| max.len() > 1)
+    }
+
+    /// Returns the estimate obtained by assuming that each loop iterates at most `iterations`
+    /// times.
+    pub fn with_loop_iterations(&self, iterations: u64) -> Self {
+        let max = self.max.as_ref().map(|max| {
+            let max = max.iter().rev().fold(0u64, |acc, &coefficient| {
+                acc.saturating_mul(iterations).saturating_add(coefficient)
+            });
+            vec![max.max(self.min)]
+        });
+        Self { min: self.min, max }
+    }
+
+    /// Returns the number of cycles if the estimate is exact.
+    pub fn as_exact(&self) -> Option<u64> {
+        self.max().filter(|&max| max == self.min)
+    }
+
+    /// Returns true if executing the node can take `cycles` cycles according to this estimate.
+    ///
+    /// If the upper bound depends on the number of loop iterations, only the lower bound is
+    /// checked; use [`Self::with_loop_iterations`] to check the upper bound as well.
+    pub fn contains(&self, cycles: u64) -> bool {
+        self.min <= cycles
+            && (self.depends_on_loop_iterations() || self.max().is_none_or(|max| cycles <= max))
+    }
+
+    /// Returns an estimate covering both `self` and `other`, i.e. the estimate for executing one
+    /// of two nodes.
+    pub fn union(self, other: Self) -> Self {
+        Self {
+            min: self.min.min(other.min),
+            max: self.max.zip(other.max).map(|(a, b)| combine(a, b, u64::max)),
+        }
+    }
+}
+
+impl Add for CycleEstimate {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self {
+            min: self.min.saturating_add(rhs.min),
+            max: self.max.zip(rhs.max).map(|(a, b)| combine(a, b, u64::saturating_add)),
+        }
+    }
+}
+
+impl fmt::Display for CycleEstimate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Some(max) = &self.max else {
+            return write!(f, "{}..", self.min);
+        };
+        if let Some(exact) = self.as_exact() {
+            return write!(f, "{exact}");
+        }
+
+        write!(f, "{}..=", self.min)?;
+        let mut terms =
+            max.iter().enumerate().rev().filter(|&(degree, &coefficient)| {
+                coefficient != 0 || (degree == 0 && max.len() == 1)
+            });
+        if let Some(term) = terms.next() {
+            write_term(f, term)?;
+        }
+        for term in terms {
+            f.write_str("+")?;
+            write_term(f, term)?;
+        }
+        Ok(())
+    }
+}
+
+/// Writes the term of degree `degree` of a polynomial in `n`.
+fn write_term(f: &mut fmt::Formatter<'_>, (degree, &coefficient): (usize, &u64)) -> fmt::Result {
+    match (degree, coefficient) {
+        (0, coefficient) => write!(f, "{coefficient}"),
+        (1, 1) => f.write_str("n"),
+        (1, coefficient) => write!(f, "{coefficient}n"),
+        (degree, 1) => write!(f, "n^{degree}"),
+        (degree, coefficient) => write!(f, "{coefficient}n^{degree}"),
+    }
+}
+
+/// Combines the coefficients of two polynomials pairwise with `op`, treating missing coefficients
+/// as zeros.
+fn combine(mut a: Vec<u64>, mut b: Vec<u64>, op: impl Fn(u64, u64) -> u64) -> Vec<u64> {
+    if a.len() < b.len() {
+        core::mem::swap(&mut a, &mut b);
+    }
+    for (a, b) in a.iter_mut().zip(b) {
+        *a = op(*a, b);
+    }
+    while a.len() > 1 && a.last() == Some(&0) {
+        a.pop();
+    }
+    a
+}
+
+// CYCLE ESTIMATOR
+// ================================================================================================
+
+/// Computes [`CycleEstimate`]s for the nodes of a [`MastForest`].
+///
+/// The cost of each node is computed once and cached, so that estimating several procedures which
+/// share subtrees does not visit these subtrees again.
+///
+/// The cost model follows the VM's decoder:
+/// - a basic block takes one cycle for `SPAN`, one for each `RESPAN` between its operation batches,
+///   one for each operation (including the `NOOP`s the decoder inserts after immediate values at
+///   the end of an operation group and to pad batches to a power-of-two number of groups), and one
+///   for `END`.
+/// - `join`, `split`, `loop`, `call`, `syscall`, `dyn` and `dyncall` nodes take one cycle to start
+///   and one for `END`, and loops take an additional `REPEAT` cycle for every iteration after the
+///   first one. The number of iterations of loops is not known statically, so the upper bound of a
+///   loop is a polynomial in the maximum number of iterations (see [`CycleEstimate::repeated`]).
+/// - an external node takes as many cycles as the procedure it references; if that procedure is not
+///   in the forest, its cost is unknown.
+#[derive(Debug)]
+pub struct CycleEstimator<'a> {
+    forest: &'a MastForest,
+    estimates: BTreeMap<MastNodeId, CycleEstimate>,
+}
+
+impl<'a> CycleEstimator<'a> {
+    /// Returns a new estimator for the nodes of `forest`.
+    pub fn new(forest: &'a MastForest) -> Self {
+        Self { forest, estimates: BTreeMap::new() }
+    }
+
+    /// Returns the estimated number of cycles needed to execute the node `node_id`.
+    ///
+    /// Nodes which (directly or indirectly) reference themselves, which can only happen in a
+    /// malformed forest, are treated as having no upper bound.
+    ///
+    /// # Panics
+    /// Panics if `node_id` is not a node of the forest.
+    pub fn estimate(&mut self, node_id: MastNodeId) -> CycleEstimate {
+        // nodes are visited in post-order using an explicit stack, so that deeply nested trees
+        // don't overflow the call stack; a node is pushed a second time (with `true`) once all of
+        // its dependencies have been pushed
+        let mut in_progress = Vec::new();
+        let mut stack = vec![(node_id, false)];
+        while let Some((node_id, dependencies_ready)) = stack.pop() {
+            if self.estimates.contains_key(&node_id) {
+                continue;
+            }
+
+            if dependencies_ready {
+                let estimate = self.compute(node_id);
+                self.estimates.insert(node_id, estimate);
+                in_progress.retain(|&id| id != node_id);
+                continue;
+            }
+
+            if in_progress.contains(&node_id) {
+                // a cycle in the forest; break it by assigning an unbounded cost
+                self.estimates.insert(node_id, CycleEstimate::unbounded(0));
+                continue;
+            }
+
+            in_progress.push(node_id);
+            stack.push((node_id, true));
+            for dependency in self.dependencies(node_id) {
+                if !self.estimates.contains_key(&dependency) {
+                    stack.push((dependency, false));
+                }
+            }
+        }
+
+        self.estimates[&node_id].clone()
+    }
+
+    /// Returns the nodes whose estimates are needed to compute the estimate of `node_id`.
+    fn dependencies(&self, node_id: MastNodeId) -> Vec<MastNodeId> {
+        let mut dependencies = Vec::new();
+        match &self.forest[node_id] {
+            MastNode::External(external) => {
+                dependencies.extend(self.resolve_external(node_id, external.digest()))
+            },
+            node => node.append_children_to(&mut dependencies),
+        }
+        dependencies
+    }
+
+    /// Returns the local procedure root referenced by the external node `node_id`, if any.
+    fn resolve_external(&self, node_id: MastNodeId, digest: Word) -> Option<MastNodeId> {
+        self.forest
+            .find_procedure_root(digest)
+            .filter(|&root| root != node_id && !self.forest[root].is_external())
+    }
+
+    /// Computes the estimate of `node_id`, assuming that the estimates of all its dependencies are
+    /// known.
+    fn compute(&self, node_id: MastNodeId) -> CycleEstimate {
+        match &self.forest[node_id] {
+            MastNode::Block(block) => CycleEstimate::exact(basic_block_cycles(block)),
+            MastNode::Join(join) => {
+                control_block_overhead()
+                    + self.estimates[&join.first()].clone()
+                    + self.estimates[&join.second()].clone()
+            },
+            MastNode::Split(split) => {
+                control_block_overhead()
+                    + self.estimates[&split.on_true()]
+                        .clone()
+                        .union(self.estimates[&split.on_false()].clone())
+            },
+            MastNode::Loop(loop_node) => {
+                CycleEstimate::repeated(&self.estimates[&loop_node.body()])
+            },
+            MastNode::Call(call) => {
+                control_block_overhead() + self.estimates[&call.callee()].clone()
+            },
+            MastNode::Dyn(_) => control_block_overhead() + CycleEstimate::unbounded(0),
+            MastNode::External(external) => {
+                match self.resolve_external(node_id, external.digest()) {
+                    Some(root) => self.estimates[&root].clone(),
+                    None => CycleEstimate::unbounded(0),
+                }
+            },
+        }
+    }
+}
+
+/// Returns the cycles for the instruction starting a control block and for its `END`.
+fn control_block_overhead() -> CycleEstimate {
+    CycleEstimate::exact(2)
+}
+
+/// Returns the exact number of cycles needed to execute `block`.
+fn basic_block_cycles(block: &BasicBlockNode) -> u64 {
+    let batches = block.op_batches();
+
+    // `SPAN`, `END`, and a `RESPAN` for every batch after the first one
+    let overhead = 2 + batches.len().saturating_sub(1) as u64;
+    overhead + batches.iter().map(op_batch_cycles).sum::<u64>()
+}
+
+/// Returns the number of cycles needed to execute the operations of `batch`, including the `NOOP`s
+/// inserted by the decoder.
+fn op_batch_cycles(batch: &OpBatch) -> u64 {
+    let op_counts = batch.op_counts();
+    let mut cycles = batch.ops().len() as u64;
+
+    // an operation with an immediate value at the end of a group is followed by a `NOOP`, and its
+    // immediate value occupies the next group
+    let mut op_idx_in_group = 0;
+    let mut group_idx = 0;
+    let mut next_group_idx = 1;
+    for op in batch.ops() {
+        let has_imm = op.imm_value().is_some();
+        if has_imm {
+            next_group_idx += 1;
+        }
+
+        if op_idx_in_group == op_counts[group_idx] - 1 {
+            if has_imm {
+                cycles += 1;
+            }
+            group_idx = next_group_idx;
+            next_group_idx += 1;
+            op_idx_in_group = 0;
+        } else {
+            op_idx_in_group += 1;
+        }
+    }
+
+    // the number of groups is padded to a power of two, with one `NOOP` executed per empty group
+    cycles + (batch.num_groups().next_power_of_two() - group_idx) as u64
+}
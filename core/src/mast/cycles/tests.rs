@@ -0,0 +1,121 @@
+use alloc::string::ToString;
+
+use miden_crypto::ONE;
+
+use super::*;
+use crate::Operation;
+
+#[test]
+fn basic_block_cycles_include_decoder_overhead() {
+    let mut forest = MastForest::new();
+
+    // SPAN, ADD, END
+    let block = forest.add_block(vec![Operation::Add], None).unwrap();
+    assert_eq!(forest.estimate_cycles(block), CycleEstimate::exact(3));
+
+    // the immediate value of the PUSH is in the second group, and a NOOP is executed after the
+    // PUSH since it is the last operation of the first group
+    let block = forest.add_block(vec![Operation::Push(ONE)], None).unwrap();
+    assert_eq!(forest.estimate_cycles(block), CycleEstimate::exact(2 + 2));
+
+    // three groups (two for the immediate values) are padded to four with a NOOP
+    let block = forest
+        .add_block(vec![Operation::Push(ONE), Operation::Push(ONE)], None)
+        .unwrap();
+    assert_eq!(forest.estimate_cycles(block), CycleEstimate::exact(2 + 2 + 1 + 1));
+
+    // 80 operations don't fit into a single batch of 8 groups of 9 operations, so a RESPAN is
+    // needed; the second batch has a single group, which doesn't need to be padded
+    let block = forest.add_block(vec![Operation::Add; 80], None).unwrap();
+    assert_eq!(forest.estimate_cycles(block), CycleEstimate::exact(2 + 1 + 80));
+}
+
+#[test]
+fn control_flow_cycles() {
+    let mut forest = MastForest::new();
+    let small = forest.add_block(vec![Operation::Add], None).unwrap();
+    let large = forest.add_block(vec![Operation::Add, Operation::Mul], None).unwrap();
+
+    let join = forest.add_join(small, large).unwrap();
+    assert_eq!(forest.estimate_cycles(join), CycleEstimate::exact(2 + 3 + 4));
+
+    let split = forest.add_split(small, large).unwrap();
+    let estimate = forest.estimate_cycles(split);
+    assert_eq!((estimate.min(), estimate.max()), (2 + 3, Some(2 + 4)));
+    assert_eq!(estimate.as_exact(), None);
+    assert!(estimate.contains(6) && !estimate.contains(7));
+
+    let call = forest.add_call(join).unwrap();
+    assert_eq!(forest.estimate_cycles(call), CycleEstimate::exact(2 + 9));
+
+    let loop_node = forest.add_loop(small).unwrap();
+    let estimate = forest.estimate_cycles(loop_node);
+    assert_eq!((estimate.min(), estimate.max()), (2, None));
+    assert!(estimate.depends_on_loop_iterations());
+
+    let dyn_node = forest.add_dyncall().unwrap();
+    let join = forest.add_join(small, dyn_node).unwrap();
+    assert_eq!(forest.estimate_cycles(join), CycleEstimate::unbounded(2 + 3 + 2));
+}
+
+#[test]
+fn loop_cycles_are_bounded_by_the_number_of_iterations() {
+    let mut forest = MastForest::new();
+    let body = forest.add_block(vec![Operation::Add], None).unwrap();
+
+    // LOOP, 3 cycles per iteration and a REPEAT for each iteration after the first one, END,
+    // bounded by counting a REPEAT for the first iteration too
+    let loop_node = forest.add_loop(body).unwrap();
+    let estimate = forest.estimate_cycles(loop_node);
+    assert_eq!(estimate.to_string(), "2..=4n+2");
+    assert_eq!(estimate.with_loop_iterations(0), CycleEstimate::exact(2));
+    assert_eq!(estimate.with_loop_iterations(1).max(), Some(2 + 3 + 1));
+    assert_eq!(estimate.with_loop_iterations(10).max(), Some(2 + 10 * 3 + 10));
+
+    // the bound of a loop in a sequence is offset by the cost of the other nodes
+    let join = forest.add_join(body, loop_node).unwrap();
+    let estimate = forest.estimate_cycles(join);
+    assert_eq!(estimate.to_string(), "7..=4n+7");
+    assert_eq!(estimate.with_loop_iterations(3).max(), Some(2 + 3 + 2 + 3 * 3 + 3));
+
+    // nested loops have a bound of higher degree
+    let outer = forest.add_loop(join).unwrap();
+    let estimate = forest.estimate_cycles(outer);
+    assert_eq!(estimate.to_string(), "2..=4n^2+8n+2");
+    assert_eq!(estimate.with_loop_iterations(2).max(), Some(2 + 2 * ((7 + 4 * 2) + 1)));
+
+    // loops which are not entered are bounded, even in a branch cheaper than the other one
+    let loops = forest.add_join(loop_node, loop_node).unwrap();
+    let split = forest.add_split(loops, body).unwrap();
+    let estimate = forest.estimate_cycles(split);
+    assert_eq!(estimate.with_loop_iterations(0).max(), Some(1 + (2 + 2 + 2) + 1));
+
+    // loops with an unbounded body remain unbounded
+    let dyn_node = forest.add_dyn().unwrap();
+    let loop_node = forest.add_loop(dyn_node).unwrap();
+    let estimate = forest.estimate_cycles(loop_node);
+    assert_eq!(estimate, CycleEstimate::unbounded(2));
+    assert_eq!(estimate.with_loop_iterations(5), CycleEstimate::unbounded(2));
+}
+
+#[test]
+fn external_nodes_are_resolved_to_local_procedures() {
+    let mut forest = MastForest::new();
+    let block = forest.add_block(vec![Operation::Add], None).unwrap();
+    forest.make_root(block);
+
+    let local = forest.add_external(forest[block].digest()).unwrap();
+    assert_eq!(forest.estimate_cycles(local), CycleEstimate::exact(3));
+
+    // a procedure which is not in the forest
+    let remote_digest = MastNode::new_basic_block(vec![Operation::Mul], None).unwrap().digest();
+    let remote = forest.add_external(remote_digest).unwrap();
+    assert_eq!(forest.estimate_cycles(remote), CycleEstimate::unbounded(0));
+}
+
+#[test]
+fn cycle_estimate_display() {
+    assert_eq!(CycleEstimate::exact(5).to_string(), "5");
+    assert_eq!(CycleEstimate::exact(5).union(CycleEstimate::exact(7)).to_string(), "5..=7");
+    assert_eq!((CycleEstimate::exact(5) + CycleEstimate::unbounded(2)).to_string(), "7..");
+}
@@ -0,0 +1,411 @@
+use alloc::{collections::BTreeMap, vec::Vec};
+use core::fmt;
+
+use crate::{
+    Operation, Word,
+    mast::{
+        MastForest, MastForestError, MastNode, MastNodeFingerprint, MastNodeId,
+        node_fingerprint::compute_fingerprints,
+    },
+};
+
+#[cfg(test)]
+mod tests;
+
+// MAST FOREST DIFF
+// ================================================================================================
+
+/// A structural comparison of the procedures in two [`MastForest`]s.
+///
+/// Procedures are matched by a key of type `K`: when comparing two raw forests via
+/// [`MastForest::diff`] the key is the MAST root of the procedure, but higher-level containers
+/// (e.g. libraries) can match procedures by their exported name via [`MastForestDiff::compute`].
+///
+/// Two procedures with the same key are considered equal if their [`MastNodeFingerprint`]s are
+/// equal, i.e. they have the same MAST root and the same decorators. For every changed procedure,
+/// the diff records the path to the first node at which the two procedures diverge.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MastForestDiff<K> {
+    added: Vec<(K, Word)>,
+    removed: Vec<(K, Word)>,
+    changed: Vec<ProcedureChange<K>>,
+}
+
+// ------------------------------------------------------------------------------------------------
+/// Constructors
+impl<K: Ord + Clone> MastForestDiff<K> {
+    /// Compares the procedures `old_procedures` of `old_forest` against the procedures
+    /// `new_procedures` of `new_forest`, matching them by their keys.
+    ///
+    /// # Errors
+    /// Returns an error if the fingerprint of a node in either forest could not be computed, which
+    /// happens if a node references a child which comes after it in the forest.
+    pub fn compute(
+        old_forest: &MastForest,
+        old_procedures: impl IntoIterator<Item = (K, MastNodeId)>,
+        new_forest: &MastForest,
+        new_procedures: impl IntoIterator<Item = (K, MastNodeId)>,
+    ) -> Result<Self, MastForestError> {
+        let old_fingerprints = compute_fingerprints(old_forest)?;
+        let new_fingerprints = compute_fingerprints(new_forest)?;
+        let old_procedures = BTreeMap::from_iter(old_procedures);
+        let mut new_procedures = BTreeMap::from_iter(new_procedures);
+
+        let mut diff = Self {
+            added: Vec::new(),
+            removed: Vec::new(),
+            changed: Vec::new(),
+        };
+
+        for (key, old_id) in old_procedures {
+            let old_digest = old_forest[old_id].digest();
+            let Some(new_id) = new_procedures.remove(&key) else {
+                diff.removed.push((key, old_digest));
+                continue;
+            };
+
+            let comparison = NodeComparison {
+                old_forest,
+                old_fingerprints: &old_fingerprints,
+                new_forest,
+                new_fingerprints: &new_fingerprints,
+            };
+            if let Some(node_diff) = comparison.first_difference(old_id, new_id) {
+                diff.changed.push(ProcedureChange {
+                    key,
+                    old_digest,
+                    new_digest: new_forest[new_id].digest(),
+                    node_diff,
+                });
+            }
+        }
+
+        diff.added.extend(
+            new_procedures
+                .into_iter()
+                .map(|(key, new_id)| (key, new_forest[new_id].digest())),
+        );
+
+        Ok(diff)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+/// Public accessors
+impl<K> MastForestDiff<K> {
+    /// Returns true if no procedures were added, removed or changed.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    /// Returns the keys and MAST roots of procedures which are only present in the new forest.
+    pub fn added(&self) -> &[(K, Word)] {
+        &self.added
+    }
+
+    /// Returns the keys and MAST roots of procedures which are only present in the old forest.
+    pub fn removed(&self) -> &[(K, Word)] {
+        &self.removed
+    }
+
+    /// Returns the procedures which are present in both forests, but differ.
+    pub fn changed(&self) -> &[ProcedureChange<K>] {
+        &self.changed
+    }
+}
+
+impl<K: fmt::Display> fmt::Display for MastForestDiff<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "no differences");
+        }
+
+        for (key, digest) in self.removed.iter() {
+            writeln!(f, "- {key} ({})", digest.to_hex())?;
+        }
+        for (key, digest) in self.added.iter() {
+            writeln!(f, "+ {key} ({})", digest.to_hex())?;
+        }
+        for change in self.changed.iter() {
+            writeln!(f, "{change}")?;
+        }
+
+        Ok(())
+    }
+}
+
+// PROCEDURE CHANGE
+// ================================================================================================
+
+/// A procedure which is present in both compared forests, but whose contents differ.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProcedureChange<K> {
+    key: K,
+    old_digest: Word,
+    new_digest: Word,
+    node_diff: NodeDiff,
+}
+
+impl<K> ProcedureChange<K> {
+    /// Returns the key by which the two procedures were matched.
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    /// Returns the MAST root of the procedure in the old forest.
+    pub fn old_digest(&self) -> Word {
+        self.old_digest
+    }
+
+    /// Returns the MAST root of the procedure in the new forest.
+    pub fn new_digest(&self) -> Word {
+        self.new_digest
+    }
+
+    /// Returns the first point at which the two procedures diverge.
+    pub fn node_diff(&self) -> &NodeDiff {
+        &self.node_diff
+    }
+}
+
+impl<K: fmt::Display> fmt::Display for ProcedureChange<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.old_digest == self.new_digest {
+            writeln!(f, "~ {} ({})", self.key, self.old_digest.to_hex())?;
+        } else {
+            writeln!(
+                f,
+                "~ {} ({} -> {})",
+                self.key,
+                self.old_digest.to_hex(),
+                self.new_digest.to_hex()
+            )?;
+        }
+        write!(f, "    {}", self.node_diff)
+    }
+}
+
+// NODE DIFF
+// ================================================================================================
+
+/// The first point of divergence between two MAST nodes.
+///
+/// The path leads from the compared procedure roots down to the pair of nodes which differ; at
+/// each step both trees descend into the same child.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeDiff {
+    path: Vec<NodePathStep>,
+    kind: NodeDiffKind,
+}
+
+impl NodeDiff {
+    /// Returns the path from the procedure roots to the differing nodes.
+    pub fn path(&self) -> &[NodePathStep] {
+        &self.path
+    }
+
+    /// Returns the way in which the nodes at the end of [`Self::path`] differ.
+    pub fn kind(&self) -> &NodeDiffKind {
+        &self.kind
+    }
+}
+
+impl fmt::Display for NodeDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("at root")?;
+        for step in self.path.iter() {
+            write!(f, "/{step}")?;
+        }
+        write!(f, ": {}", self.kind)
+    }
+}
+
+/// A single step from a control flow node into one of its children.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodePathStep {
+    /// The first child of a join node.
+    JoinFirst,
+    /// The second child of a join node.
+    JoinSecond,
+    /// The `true` branch of a split node.
+    SplitTrue,
+    /// The `false` branch of a split node.
+    SplitFalse,
+    /// The body of a loop node.
+    LoopBody,
+    /// The callee of a call or syscall node.
+    Callee,
+}
+
+impl fmt::Display for NodePathStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::JoinFirst => f.write_str("join.0"),
+            Self::JoinSecond => f.write_str("join.1"),
+            Self::SplitTrue => f.write_str("split.true"),
+            Self::SplitFalse => f.write_str("split.false"),
+            Self::LoopBody => f.write_str("loop.body"),
+            Self::Callee => f.write_str("call.callee"),
+        }
+    }
+}
+
+/// Describes how two MAST nodes at the same position differ.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NodeDiffKind {
+    /// The nodes are of different kinds (e.g. a join node and a split node).
+    NodeKind { old: &'static str, new: &'static str },
+    /// Both nodes are basic blocks, and they differ at the operation with the given index.
+    ///
+    /// An operation is `None` if the corresponding basic block ends before `index`.
+    Operation {
+        index: usize,
+        old: Option<Operation>,
+        new: Option<Operation>,
+    },
+    /// Both nodes are external nodes referencing different procedures.
+    External { old: Word, new: Word },
+    /// The nodes have the same MAST root, but different decorators.
+    Decorators,
+}
+
+impl fmt::Display for NodeDiffKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NodeKind { old, new } => write!(f, "node kind changed from {old} to {new}"),
+            Self::Operation { index, old, new } => {
+                write!(f, "operation {index} changed from ")?;
+                match old {
+                    Some(op) => write!(f, "{op}")?,
+                    None => f.write_str("<end of block>")?,
+                }
+                f.write_str(" to ")?;
+                match new {
+                    Some(op) => write!(f, "{op}"),
+                    None => f.write_str("<end of block>"),
+                }
+            },
+            Self::External { old, new } => {
+                write!(f, "external reference changed from {} to {}", old.to_hex(), new.to_hex())
+            },
+            Self::Decorators => f.write_str("decorators changed"),
+        }
+    }
+}
+
+// HELPERS
+// ================================================================================================
+
+/// The pair of forests (and their node fingerprints) being compared.
+struct NodeComparison<'a> {
+    old_forest: &'a MastForest,
+    old_fingerprints: &'a BTreeMap<MastNodeId, MastNodeFingerprint>,
+    new_forest: &'a MastForest,
+    new_fingerprints: &'a BTreeMap<MastNodeId, MastNodeFingerprint>,
+}
+
+impl NodeComparison<'_> {
+    /// Returns true if the old node `old_id` and the new node `new_id` are equal, including their
+    /// decorators.
+    fn is_equal(&self, old_id: MastNodeId, new_id: MastNodeId) -> bool {
+        self.old_fingerprints[&old_id] == self.new_fingerprints[&new_id]
+    }
+
+    /// Walks down both trees in lockstep, and returns the first point at which they differ, or
+    /// `None` if they are equal.
+    fn first_difference(&self, old_id: MastNodeId, new_id: MastNodeId) -> Option<NodeDiff> {
+        if self.is_equal(old_id, new_id) {
+            return None;
+        }
+
+        let mut path = Vec::new();
+        let (mut old_id, mut new_id) = (old_id, new_id);
+
+        // invariant: the nodes `old_id` and `new_id` are not equal
+        loop {
+            let children = match (&self.old_forest[old_id], &self.new_forest[new_id]) {
+                (MastNode::Block(old), MastNode::Block(new)) => {
+                    let kind = old
+                        .operations()
+                        .map(Some)
+                        .chain(core::iter::repeat(None))
+                        .zip(new.operations().map(Some).chain(core::iter::repeat(None)))
+                        .take(old.num_operations().max(new.num_operations()) as usize)
+                        .enumerate()
+                        .find(|(_, (old, new))| old != new)
+                        .map(|(index, (old, new))| NodeDiffKind::Operation {
+                            index,
+                            old: old.copied(),
+                            new: new.copied(),
+                        })
+                        .unwrap_or(NodeDiffKind::Decorators);
+                    return Some(NodeDiff { path, kind });
+                },
+                (MastNode::Join(old), MastNode::Join(new)) => vec![
+                    (NodePathStep::JoinFirst, old.first(), new.first()),
+                    (NodePathStep::JoinSecond, old.second(), new.second()),
+                ],
+                (MastNode::Split(old), MastNode::Split(new)) => vec![
+                    (NodePathStep::SplitTrue, old.on_true(), new.on_true()),
+                    (NodePathStep::SplitFalse, old.on_false(), new.on_false()),
+                ],
+                (MastNode::Loop(old), MastNode::Loop(new)) => {
+                    vec![(NodePathStep::LoopBody, old.body(), new.body())]
+                },
+                (MastNode::Call(old), MastNode::Call(new))
+                    if old.is_syscall() == new.is_syscall() =>
+                {
+                    vec![(NodePathStep::Callee, old.callee(), new.callee())]
+                },
+                (MastNode::Dyn(old), MastNode::Dyn(new))
+                    if old.is_dyncall() == new.is_dyncall() =>
+                {
+                    vec![]
+                },
+                (MastNode::External(old), MastNode::External(new)) => {
+                    let kind = if old.digest() == new.digest() {
+                        NodeDiffKind::Decorators
+                    } else {
+                        NodeDiffKind::External { old: old.digest(), new: new.digest() }
+                    };
+                    return Some(NodeDiff { path, kind });
+                },
+                (old, new) => {
+                    let kind = NodeDiffKind::NodeKind {
+                        old: node_kind_name(old),
+                        new: node_kind_name(new),
+                    };
+                    return Some(NodeDiff { path, kind });
+                },
+            };
+
+            match children
+                .into_iter()
+                .find(|&(_, old_child, new_child)| !self.is_equal(old_child, new_child))
+            {
+                Some((step, old_child, new_child)) => {
+                    path.push(step);
+                    old_id = old_child;
+                    new_id = new_child;
+                },
+                // all children are equal, so the nodes differ only in their own decorators
+                None => return Some(NodeDiff { path, kind: NodeDiffKind::Decorators }),
+            }
+        }
+    }
+}
+
+/// Returns a human-readable name of the kind of the node.
+fn node_kind_name(node: &MastNode) -> &'static str {
+    match node {
+        MastNode::Block(_) => "basic block",
+        MastNode::Join(_) => "join",
+        MastNode::Split(_) => "split",
+        MastNode::Loop(_) => "loop",
+        MastNode::Call(node) if node.is_syscall() => "syscall",
+        MastNode::Call(_) => "call",
+        MastNode::Dyn(node) if node.is_dyncall() => "dyncall",
+        MastNode::Dyn(_) => "dyn",
+        MastNode::External(_) => "external",
+    }
+}
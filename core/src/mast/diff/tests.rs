@@ -0,0 +1,183 @@
+use alloc::string::ToString;
+
+use miden_crypto::ONE;
+
+use super::*;
+use crate::Decorator;
+
+fn block_foo() -> MastNode {
+    MastNode::new_basic_block(vec![Operation::Mul, Operation::Add], None).unwrap()
+}
+
+fn block_bar() -> MastNode {
+    MastNode::new_basic_block(vec![Operation::And, Operation::Eq], None).unwrap()
+}
+
+fn block_bar_modified() -> MastNode {
+    MastNode::new_basic_block(vec![Operation::And, Operation::Swap, Operation::Eq], None).unwrap()
+}
+
+/// Builds a forest with a single procedure `join(foo, split(bar_or_modified, foo))`.
+fn forest_with_join_split(modified: bool) -> (MastForest, MastNodeId) {
+    let mut forest = MastForest::new();
+    let foo = forest.add_node(block_foo()).unwrap();
+    let bar = if modified {
+        forest.add_node(block_bar_modified()).unwrap()
+    } else {
+        forest.add_node(block_bar()).unwrap()
+    };
+    let split = forest.add_split(bar, foo).unwrap();
+    let join = forest.add_join(foo, split).unwrap();
+    forest.make_root(join);
+
+    (forest, join)
+}
+
+#[test]
+fn diff_identical_forests_is_empty() {
+    let (forest, _) = forest_with_join_split(false);
+
+    let diff = forest.diff(&forest.clone()).unwrap();
+    assert!(diff.is_empty());
+}
+
+#[test]
+fn diff_by_digest_reports_added_and_removed() {
+    let (old_forest, old_root) = forest_with_join_split(false);
+    let (new_forest, new_root) = forest_with_join_split(true);
+
+    let diff = old_forest.diff(&new_forest).unwrap();
+    assert!(diff.changed().is_empty());
+    let old_digest = old_forest[old_root].digest();
+    let new_digest = new_forest[new_root].digest();
+    assert_eq!(diff.removed(), &[(old_digest, old_digest)]);
+    assert_eq!(diff.added(), &[(new_digest, new_digest)]);
+}
+
+#[test]
+fn diff_by_key_reports_first_differing_operation() {
+    let (old_forest, old_root) = forest_with_join_split(false);
+    let (new_forest, new_root) = forest_with_join_split(true);
+
+    let diff = MastForestDiff::compute(
+        &old_forest,
+        [("proc", old_root)],
+        &new_forest,
+        [("proc", new_root)],
+    )
+    .unwrap();
+    assert!(diff.added().is_empty());
+    assert!(diff.removed().is_empty());
+    assert_eq!(diff.changed().len(), 1);
+
+    let change = &diff.changed()[0];
+    assert_eq!(*change.key(), "proc");
+    assert_ne!(change.old_digest(), change.new_digest());
+    assert_eq!(change.node_diff().path(), &[NodePathStep::JoinSecond, NodePathStep::SplitTrue]);
+    assert_eq!(
+        change.node_diff().kind(),
+        &NodeDiffKind::Operation {
+            index: 1,
+            old: Some(Operation::Eq),
+            new: Some(Operation::Swap),
+        }
+    );
+    assert_eq!(
+        change.node_diff().to_string(),
+        "at root/join.1/split.true: operation 1 changed from eq to swap"
+    );
+}
+
+#[test]
+fn diff_reports_node_kind_change() {
+    let mut old_forest = MastForest::new();
+    let foo = old_forest.add_node(block_foo()).unwrap();
+    let old_loop = old_forest.add_loop(foo).unwrap();
+    old_forest.make_root(old_loop);
+
+    let mut new_forest = MastForest::new();
+    let foo = new_forest.add_node(block_foo()).unwrap();
+    let new_call = new_forest.add_call(foo).unwrap();
+    new_forest.make_root(new_call);
+
+    let diff = MastForestDiff::compute(&old_forest, [(0, old_loop)], &new_forest, [(0, new_call)])
+        .unwrap();
+    let change = &diff.changed()[0];
+    assert!(change.node_diff().path().is_empty());
+    assert_eq!(change.node_diff().kind(), &NodeDiffKind::NodeKind { old: "loop", new: "call" });
+}
+
+#[test]
+fn diff_reports_changed_external_reference() {
+    let mut old_forest = MastForest::new();
+    let old_external = old_forest.add_external(block_foo().digest()).unwrap();
+    let old_call = old_forest.add_call(old_external).unwrap();
+    old_forest.make_root(old_call);
+
+    let mut new_forest = MastForest::new();
+    let new_external = new_forest.add_external(block_bar().digest()).unwrap();
+    let new_call = new_forest.add_call(new_external).unwrap();
+    new_forest.make_root(new_call);
+
+    let diff = MastForestDiff::compute(&old_forest, [(0, old_call)], &new_forest, [(0, new_call)])
+        .unwrap();
+    let change = &diff.changed()[0];
+    assert_eq!(change.node_diff().path(), &[NodePathStep::Callee]);
+    assert_eq!(
+        change.node_diff().kind(),
+        &NodeDiffKind::External {
+            old: block_foo().digest(),
+            new: block_bar().digest(),
+        }
+    );
+}
+
+#[test]
+fn diff_reports_decorator_only_changes() {
+    let (old_forest, _) = forest_with_join_split(false);
+
+    let mut new_forest = MastForest::new();
+    let foo = new_forest.add_node(block_foo()).unwrap();
+    let bar = new_forest
+        .add_block_with_raw_decorators(
+            vec![Operation::And, Operation::Eq],
+            vec![(1, Decorator::Trace(1))],
+        )
+        .unwrap();
+    let split = new_forest.add_split(bar, foo).unwrap();
+    let new_root = new_forest.add_join(foo, split).unwrap();
+    new_forest.make_root(new_root);
+
+    // matched by digest, the procedure is unchanged except for its decorators
+    let diff = old_forest.diff(&new_forest).unwrap();
+    assert!(diff.added().is_empty());
+    assert!(diff.removed().is_empty());
+
+    let change = &diff.changed()[0];
+    assert_eq!(change.old_digest(), change.new_digest());
+    assert_eq!(change.node_diff().path(), &[NodePathStep::JoinSecond, NodePathStep::SplitTrue]);
+    assert_eq!(change.node_diff().kind(), &NodeDiffKind::Decorators);
+}
+
+#[test]
+fn diff_reports_operation_past_end_of_block() {
+    let mut old_forest = MastForest::new();
+    let old_block = old_forest.add_block(vec![Operation::Add], None).unwrap();
+    old_forest.make_root(old_block);
+
+    let mut new_forest = MastForest::new();
+    let new_block = new_forest.add_block(vec![Operation::Add, Operation::Push(ONE)], None).unwrap();
+    new_forest.make_root(new_block);
+
+    let diff =
+        MastForestDiff::compute(&old_forest, [(0, old_block)], &new_forest, [(0, new_block)])
+            .unwrap();
+    assert_eq!(
+        diff.changed()[0].node_diff().kind(),
+        &NodeDiffKind::Operation {
+            index: 1,
+            old: None,
+            new: Some(Operation::Push(ONE)),
+        }
+    );
+}
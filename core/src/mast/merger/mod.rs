@@ -1,10 +1,11 @@
 use alloc::{collections::BTreeMap, vec::Vec};
+use core::mem;
 
 use miden_crypto::hash::blake::Blake3Digest;
 
 use crate::mast::{
     DecoratorId, MastForest, MastForestError, MastNode, MastNodeFingerprint, MastNodeId,
-    MultiMastForestIteratorItem, MultiMastForestNodeIter,
+    MastNodeStore, MultiMastForestIteratorItem, MultiMastForestNodeIter,
 };
 
 #[cfg(test)]
@@ -52,6 +53,9 @@ impl MastForestMerger {
             node_id_mappings,
         };
 
+        for other_forest in forests.iter() {
+            merger.merge_advice_map(other_forest)?;
+        }
         merger.merge_inner(forests.clone())?;
 
         let Self { mast_forest, node_id_mappings, .. } = merger;
@@ -61,15 +65,61 @@ impl MastForestMerger {
         Ok((mast_forest, root_maps))
     }
 
+    /// Creates a new merger whose forest and indices are taken from the provided
+    /// [`MastNodeStore`], and merges all provided [`MastForest`]s into it.
+    ///
+    /// Nodes and decorators of the merged forests which are already present in the store are
+    /// deduplicated against the store's contents. Advice maps of the merged forests are ignored.
+    ///
+    /// The contents of the store are updated even if merging fails, in which case the store may
+    /// contain some nodes of the forests which could not be merged.
+    pub(crate) fn merge_into_store<'forest>(
+        store: &mut MastNodeStore,
+        forests: impl IntoIterator<Item = &'forest MastForest>,
+    ) -> Result<MastForestRootMap, MastForestError> {
+        let forests = forests.into_iter().collect::<Vec<_>>();
+        let decorator_id_mappings = Vec::with_capacity(forests.len());
+        let node_id_mappings = vec![MastForestNodeIdMap::new(); forests.len()];
+
+        let mut merger = Self {
+            node_id_by_hash: mem::take(&mut store.node_id_by_hash),
+            hash_by_node_id: mem::take(&mut store.hash_by_node_id),
+            decorators_by_hash: mem::take(&mut store.decorators_by_hash),
+            mast_forest: mem::take(&mut store.forest),
+            decorator_id_mappings,
+            node_id_mappings,
+        };
+
+        let result = merger.merge_inner(forests.clone());
+
+        let Self {
+            mast_forest,
+            node_id_by_hash,
+            hash_by_node_id,
+            decorators_by_hash,
+            node_id_mappings,
+            ..
+        } = merger;
+        store.forest = mast_forest;
+        store.node_id_by_hash = node_id_by_hash;
+        store.hash_by_node_id = hash_by_node_id;
+        store.decorators_by_hash = decorators_by_hash;
+        result?;
+
+        Ok(MastForestRootMap::from_node_id_map(node_id_mappings, forests))
+    }
+
     /// Merges all `forests` into self.
     ///
+    /// Advice maps are not merged by this method, since merging into a [`MastNodeStore`] ignores
+    /// them; [`Self::merge`] merges them beforehand, checking for key collisions.
+    ///
     /// It does this in three steps:
     ///
-    /// 1. Merge all advice maps, checking for key collisions.
-    /// 2. Merge all decorators, which is a case of deduplication and creating a decorator id
+    /// 1. Merge all decorators, which is a case of deduplication and creating a decorator id
     ///    mapping which contains how existing [`DecoratorId`]s map to [`DecoratorId`]s in the
     ///    merged forest.
-    /// 3. Merge all nodes of forests.
+    /// 2. Merge all error codes and nodes of forests.
     ///    - Similar to decorators, node indices might move during merging, so the merger keeps a
     ///      node id mapping as it merges nodes.
     ///    - This is a depth-first traversal over all forests to ensure all children are processed
@@ -91,13 +141,10 @@ impl MastForestMerger {
     ///        `replacement` node. Now we can simply add a mapping from the external node to the
     ///        `replacement` node in our node id mapping which means all nodes that referenced the
     ///        external node will point to the `replacement` instead.
-    /// 4. Finally, we merge all roots of all forests. Here we map the existing root indices to
+    /// 3. Finally, we merge all roots of all forests. Here we map the existing root indices to
     ///    their potentially new indices in the merged forest and add them to the forest,
     ///    deduplicating in the process, too.
     fn merge_inner(&mut self, forests: Vec<&MastForest>) -> Result<(), MastForestError> {
-        for other_forest in forests.iter() {
-            self.merge_advice_map(other_forest)?;
-        }
         for other_forest in forests.iter() {
             self.merge_decorators(other_forest)?;
         }
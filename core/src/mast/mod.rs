@@ -23,6 +23,15 @@ use crate::{
 
 mod serialization;
 
+mod call_graph;
+pub use call_graph::{CallGraphEdge, CallGraphProcedure, CallKind, MastCallGraph};
+
+mod cycles;
+pub use cycles::{CycleEstimate, CycleEstimator};
+
+mod diff;
+pub use diff::{MastForestDiff, NodeDiff, NodeDiffKind, NodePathStep, ProcedureChange};
+
 mod merger;
 pub(crate) use merger::MastForestMerger;
 pub use merger::MastForestRootMap;
@@ -33,6 +42,12 @@ pub(crate) use multi_forest_node_iterator::*;
 mod node_fingerprint;
 pub use node_fingerprint::{DecoratorFingerprint, MastNodeFingerprint};
 
+mod store;
+pub use store::MastNodeStore;
+
+mod validation;
+pub use validation::MastForestViolation;
+
 #[cfg(test)]
 mod tests;
 
@@ -269,6 +284,50 @@ impl MastForest {
         MastForestMerger::merge(forests)
     }
 
+    /// Merges all `forests` into the provided [`MastNodeStore`], and returns the locations of their
+    /// roots in the store.
+    ///
+    /// This works like [`MastForest::merge`], with the store's forest acting as the forest into
+    /// which all `forests` are merged: any node or decorator which is already present in the store
+    /// is deduplicated against the existing entry, so that subtrees shared between forests written
+    /// into the same store are only stored once. The returned [`MastForestRootMap`] maps the roots
+    /// of each of the `forests` to their [`MastNodeId`]s in the store, which can later be used to
+    /// read the forests back via [`MastNodeStore::extract`].
+    ///
+    /// Since the store is not content-addressed with respect to advice data, advice maps of the
+    /// merged forests are ignored.
+    pub fn merge_into_store<'forest>(
+        forests: impl IntoIterator<Item = &'forest MastForest>,
+        store: &mut MastNodeStore,
+    ) -> Result<MastForestRootMap, MastForestError> {
+        MastForestMerger::merge_into_store(store, forests)
+    }
+
+    /// Compares the procedures of this forest against the procedures of `other`.
+    ///
+    /// Procedures are matched by their MAST roots, hence a procedure is reported as changed only
+    /// if its decorators differ between the two forests. To match procedures by some other key
+    /// (e.g. by their exported names), use [`MastForestDiff::compute`] directly.
+    pub fn diff(&self, other: &MastForest) -> Result<MastForestDiff<Word>, MastForestError> {
+        MastForestDiff::compute(
+            self,
+            self.roots.iter().map(|&root_id| (self[root_id].digest(), root_id)),
+            other,
+            other.roots.iter().map(|&root_id| (other[root_id].digest(), root_id)),
+        )
+    }
+
+    /// Returns static bounds on the number of VM cycles needed to execute the node `node_id`.
+    ///
+    /// To estimate several nodes of the same forest, use a [`CycleEstimator`], which caches the
+    /// estimates of subtrees shared between the nodes.
+    ///
+    /// # Panics
+    /// Panics if `node_id` is not a node of this forest.
+    pub fn estimate_cycles(&self, node_id: MastNodeId) -> CycleEstimate {
+        CycleEstimator::new(self).estimate(node_id)
+    }
+
     /// Adds a basic block node to the forest, and returns the [`MastNodeId`] associated with it.
     ///
     /// It is assumed that the decorators have not already been added to the MAST forest. If they
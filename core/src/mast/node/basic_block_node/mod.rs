@@ -201,6 +201,20 @@ impl BasicBlockNode {
     pub fn iter(&self) -> impl Iterator<Item = OperationOrDecorator<'_>> {
         OperationOrDecoratorIterator::new(self)
     }
+
+    /// Returns the index of the first operation batch of this block which differs from the batch
+    /// its operations would be grouped into according to the batching rules, if any.
+    ///
+    /// If this block has fewer or more batches than expected, the index of the first missing or
+    /// extra batch is returned.
+    pub(crate) fn first_invalid_batch(&self) -> Option<usize> {
+        let expected = batch_ops(self.operations().copied().collect());
+        let num_common = expected.len().min(self.op_batches.len());
+
+        (0..num_common)
+            .find(|&batch_idx| expected[batch_idx] != self.op_batches[batch_idx])
+            .or_else(|| (expected.len() != self.op_batches.len()).then_some(num_common))
+    }
 }
 
 /// Mutators
@@ -331,6 +331,24 @@ fn operation_or_decorator_iterator() {
     assert_eq!(iterator.next(), None);
 }
 
+#[test]
+fn first_invalid_batch() {
+    let mut node = BasicBlockNode::new(vec![Operation::Add; 100], None).unwrap();
+    assert_eq!(node.num_op_batches(), 2);
+    assert_eq!(node.first_invalid_batch(), None);
+
+    // the same operations, split into batches at the wrong place
+    let mut op_batches = super::batch_ops(vec![Operation::Add; 50]);
+    op_batches.extend(super::batch_ops(vec![Operation::Add; 50]));
+    node.op_batches = op_batches;
+    assert_eq!(node.first_invalid_batch(), Some(0));
+
+    // batches in the wrong order
+    let mut node = BasicBlockNode::new(vec![Operation::Mul; 100], None).unwrap();
+    node.op_batches.swap(0, 1);
+    assert_eq!(node.first_invalid_batch(), Some(0));
+}
+
 // TEST HELPERS
 // --------------------------------------------------------------------------------------------
 
@@ -196,3 +196,18 @@ fn fingerprint_from_parts(
         Ok(MastNodeFingerprint::with_decorator_root(node_digest, decorator_root))
     }
 }
+
+/// Computes the fingerprints of all nodes in the forest.
+///
+/// This relies on the children of a node being located before the node in the forest.
+pub(crate) fn compute_fingerprints(
+    forest: &MastForest,
+) -> Result<BTreeMap<MastNodeId, MastNodeFingerprint>, MastForestError> {
+    let mut fingerprints = BTreeMap::new();
+    for (node_idx, node) in forest.nodes().iter().enumerate() {
+        let fingerprint = MastNodeFingerprint::from_mast_node(forest, &fingerprints, node)?;
+        fingerprints.insert(MastNodeId::new_unchecked(node_idx as u32), fingerprint);
+    }
+
+    Ok(fingerprints)
+}
@@ -0,0 +1,317 @@
+use alloc::{
+    collections::{BTreeMap, BTreeSet},
+    vec::Vec,
+};
+
+use crate::{
+    DecoratorList, Operation, Word,
+    mast::{
+        DecoratorFingerprint, DecoratorId, MastForest, MastForestError, MastForestMerger,
+        MastForestRootMap, MastNode, MastNodeFingerprint, MastNodeId, Remapping,
+        node_fingerprint::compute_fingerprints,
+    },
+    utils::{ByteReader, ByteWriter, Deserializable, DeserializationError, Serializable},
+};
+
+#[cfg(test)]
+mod tests;
+
+// MAST NODE STORE
+// ================================================================================================
+
+/// A content-addressed store of [`MastNode`]s, which can be shared by multiple [`MastForest`]s.
+///
+/// Nodes in the store are keyed by their [`MastNodeFingerprint`], i.e. by their MAST root and
+/// decorators, so that a subtree which is present in several forests (e.g. a common helper
+/// procedure inlined into many libraries) is stored only once. Decorators are deduplicated in the
+/// same way.
+///
+/// Forests are written into the store with [`MastForest::merge_into_store`], which returns the
+/// location of their roots in the store, and read back with [`MastNodeStore::extract`].
+///
+/// Advice maps are not content-addressed, and hence are not kept in the store: it is up to the
+/// owner of the store to keep track of the advice maps of the forests written into it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MastNodeStore {
+    /// The deduplicated nodes, decorators and error codes of all forests written into the store.
+    ///
+    /// The roots of this forest are the roots of all forests written into the store.
+    pub(super) forest: MastForest,
+    pub(super) node_id_by_hash: BTreeMap<MastNodeFingerprint, MastNodeId>,
+    pub(super) hash_by_node_id: BTreeMap<MastNodeId, MastNodeFingerprint>,
+    pub(super) decorators_by_hash: BTreeMap<DecoratorFingerprint, DecoratorId>,
+}
+
+// ------------------------------------------------------------------------------------------------
+/// Constructors
+impl MastNodeStore {
+    /// Creates a new empty [`MastNodeStore`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a new [`MastNodeStore`] from the contents of the provided forest, discarding its
+    /// advice map.
+    ///
+    /// Duplicate nodes and decorators in `forest` are kept in the store, but only the first
+    /// occurrence of each of them is used for deduplication of nodes written into the store later.
+    ///
+    /// # Errors
+    /// Returns an error if a node of `forest` references a child which comes after it in the
+    /// forest.
+    pub fn from_forest(mut forest: MastForest) -> Result<Self, MastForestError> {
+        forest.advice_map = Default::default();
+
+        let hash_by_node_id = compute_fingerprints(&forest)?;
+        let mut node_id_by_hash = BTreeMap::new();
+        for (&node_id, &fingerprint) in hash_by_node_id.iter() {
+            node_id_by_hash.entry(fingerprint).or_insert(node_id);
+        }
+
+        let mut decorators_by_hash = BTreeMap::new();
+        for (decorator_idx, decorator) in forest.decorators.iter().enumerate() {
+            decorators_by_hash
+                .entry(decorator.fingerprint())
+                .or_insert(DecoratorId::new_unchecked(decorator_idx as u32));
+        }
+
+        Ok(Self {
+            forest,
+            node_id_by_hash,
+            hash_by_node_id,
+            decorators_by_hash,
+        })
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+/// Public accessors
+impl MastNodeStore {
+    /// Returns the [`MastForest`] holding all nodes of this store.
+    pub fn forest(&self) -> &MastForest {
+        &self.forest
+    }
+
+    /// Returns the number of distinct nodes in this store.
+    pub fn num_nodes(&self) -> u32 {
+        self.forest.num_nodes()
+    }
+
+    /// Returns the ID of the node with the given fingerprint, if any.
+    pub fn get(&self, fingerprint: &MastNodeFingerprint) -> Option<MastNodeId> {
+        self.node_id_by_hash.get(fingerprint).copied()
+    }
+
+    /// Returns the IDs of all nodes with the given MAST root.
+    ///
+    /// There may be several such nodes if they differ in their decorators.
+    pub fn find_by_digest(&self, digest: Word) -> impl Iterator<Item = MastNodeId> + '_ {
+        self.node_id_by_hash
+            .range(MastNodeFingerprint::new(digest)..)
+            .take_while(move |(fingerprint, _)| *fingerprint.mast_root() == digest)
+            .map(|(_, &node_id)| node_id)
+    }
+
+    /// Returns the fingerprint of the node with the given ID, if it exists in this store.
+    pub fn fingerprint(&self, node_id: MastNodeId) -> Option<&MastNodeFingerprint> {
+        self.hash_by_node_id.get(&node_id)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+/// Reading from the store
+impl MastNodeStore {
+    /// Builds a new [`MastForest`] consisting of the trees rooted at `roots`, and returns it along
+    /// with the mapping from the IDs of the nodes in this store to their IDs in the new forest.
+    ///
+    /// The nodes in `roots` become the procedure roots of the new forest, in the same order. The
+    /// new forest only contains the nodes and decorators reachable from these roots, and the error
+    /// codes referenced by their operations.
+    ///
+    /// # Errors
+    /// Returns an error if any of the `roots` is not a node of this store.
+    pub fn extract(
+        &self,
+        roots: impl IntoIterator<Item = MastNodeId>,
+    ) -> Result<(MastForest, Remapping), MastForestError> {
+        let roots: Vec<MastNodeId> = roots.into_iter().collect();
+        let num_nodes = self.forest.nodes.len();
+
+        // collect all nodes reachable from the requested roots
+        let mut reachable = BTreeSet::new();
+        let mut unvisited = roots.clone();
+        while let Some(node_id) = unvisited.pop() {
+            if node_id.as_usize() >= num_nodes {
+                return Err(MastForestError::NodeIdOverflow(node_id, num_nodes));
+            }
+            if reachable.insert(node_id) {
+                self.forest[node_id].append_children_to(&mut unvisited);
+            }
+        }
+
+        // the store guarantees that children come before their parents, so visiting the nodes in
+        // ascending order of their IDs visits all children first
+        let mut extracted = Extraction::new(&self.forest);
+        for node_id in reachable {
+            extracted.add_node(node_id)?;
+        }
+
+        let Extraction { mut forest, node_id_map, .. } = extracted;
+        for root in roots {
+            forest.make_root(node_id_map[&root]);
+        }
+
+        Ok((forest, node_id_map))
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+/// Writing to the store
+impl MastNodeStore {
+    /// Writes all `forests` into this store, and returns the locations of their roots in the store.
+    ///
+    /// See [`MastForest::merge_into_store`] for details.
+    pub fn insert<'forest>(
+        &mut self,
+        forests: impl IntoIterator<Item = &'forest MastForest>,
+    ) -> Result<MastForestRootMap, MastForestError> {
+        MastForestMerger::merge_into_store(self, forests)
+    }
+}
+
+impl Serializable for MastNodeStore {
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        // the indices can be recomputed from the forest when deserializing
+        self.forest.write_into(target);
+    }
+}
+
+impl Deserializable for MastNodeStore {
+    fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
+        let forest = MastForest::read_from(source)?;
+        Self::from_forest(forest).map_err(|err| {
+            DeserializationError::InvalidValue(format!("invalid MAST node store: {err}"))
+        })
+    }
+}
+
+// EXTRACTION
+// ================================================================================================
+
+/// The state of a forest being extracted from a [`MastNodeStore`].
+struct Extraction<'a> {
+    store_forest: &'a MastForest,
+    forest: MastForest,
+    node_id_map: Remapping,
+    decorator_id_map: BTreeMap<DecoratorId, DecoratorId>,
+}
+
+impl<'a> Extraction<'a> {
+    fn new(store_forest: &'a MastForest) -> Self {
+        Self {
+            store_forest,
+            forest: MastForest::new(),
+            node_id_map: BTreeMap::new(),
+            decorator_id_map: BTreeMap::new(),
+        }
+    }
+
+    /// Copies the node `node_id` of the store into the extracted forest.
+    ///
+    /// All children of the node must have been copied beforehand.
+    fn add_node(&mut self, node_id: MastNodeId) -> Result<(), MastForestError> {
+        let store_forest = self.store_forest;
+        let store_node = &store_forest[node_id];
+
+        let mut node = match store_node {
+            MastNode::Block(block) => {
+                let decorators = block
+                    .decorators()
+                    .iter()
+                    .map(|&(op_idx, decorator_id)| Ok((op_idx, self.map_decorator(decorator_id)?)))
+                    .collect::<Result<DecoratorList, MastForestError>>()?;
+                let operations: Vec<Operation> = block.operations().copied().collect();
+
+                for op in operations.iter() {
+                    if let Operation::U32assert2(err_code)
+                    | Operation::Assert(err_code)
+                    | Operation::MpVerify(err_code) = op
+                    {
+                        let err_code = err_code.as_int();
+                        if let Some(msg) = store_forest.error_codes.get(&err_code) {
+                            self.forest.error_codes.insert(err_code, msg.clone());
+                        }
+                    }
+                }
+
+                MastNode::new_basic_block(operations, Some(decorators))?
+            },
+            MastNode::Join(join) => MastNode::new_join(
+                self.map_node_id(join.first()),
+                self.map_node_id(join.second()),
+                &self.forest,
+            )?,
+            MastNode::Split(split) => MastNode::new_split(
+                self.map_node_id(split.on_true()),
+                self.map_node_id(split.on_false()),
+                &self.forest,
+            )?,
+            MastNode::Loop(loop_node) => {
+                MastNode::new_loop(self.map_node_id(loop_node.body()), &self.forest)?
+            },
+            MastNode::Call(call) if call.is_syscall() => {
+                MastNode::new_syscall(self.map_node_id(call.callee()), &self.forest)?
+            },
+            MastNode::Call(call) => {
+                MastNode::new_call(self.map_node_id(call.callee()), &self.forest)?
+            },
+            MastNode::Dyn(dyn_node) if dyn_node.is_dyncall() => MastNode::new_dyncall(),
+            MastNode::Dyn(_) => MastNode::new_dyn(),
+            MastNode::External(external) => MastNode::new_external(external.digest()),
+        };
+
+        // decorators of basic blocks were handled above
+        if !node.is_basic_block() {
+            let before_enter = self.map_decorators(store_node.before_enter())?;
+            let after_exit = self.map_decorators(store_node.after_exit())?;
+            node.append_before_enter(&before_enter);
+            node.append_after_exit(&after_exit);
+        }
+
+        let new_node_id = self.forest.add_node(node)?;
+        self.node_id_map.insert(node_id, new_node_id);
+
+        Ok(())
+    }
+
+    /// Returns the ID of the given (already copied) node of the store in the extracted forest.
+    fn map_node_id(&self, node_id: MastNodeId) -> MastNodeId {
+        self.node_id_map[&node_id]
+    }
+
+    /// Returns the ID of the given decorator of the store in the extracted forest, copying the
+    /// decorator if it has not been copied yet.
+    fn map_decorator(&mut self, decorator_id: DecoratorId) -> Result<DecoratorId, MastForestError> {
+        if let Some(&new_decorator_id) = self.decorator_id_map.get(&decorator_id) {
+            return Ok(new_decorator_id);
+        }
+
+        let decorator = self.store_forest.get_decorator_by_id(decorator_id).ok_or(
+            MastForestError::DecoratorIdOverflow(decorator_id, self.store_forest.decorators.len()),
+        )?;
+        let new_decorator_id = self.forest.add_decorator(decorator.clone())?;
+        self.decorator_id_map.insert(decorator_id, new_decorator_id);
+
+        Ok(new_decorator_id)
+    }
+
+    fn map_decorators(
+        &mut self,
+        decorator_ids: &[DecoratorId],
+    ) -> Result<Vec<DecoratorId>, MastForestError> {
+        decorator_ids
+            .iter()
+            .map(|&decorator_id| self.map_decorator(decorator_id))
+            .collect()
+    }
+}
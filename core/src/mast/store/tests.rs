@@ -0,0 +1,125 @@
+use miden_crypto::ONE;
+
+use super::*;
+use crate::{Decorator, utils::Deserializable};
+
+fn block_foo() -> MastNode {
+    MastNode::new_basic_block(vec![Operation::Mul, Operation::Add], None).unwrap()
+}
+
+fn block_bar() -> MastNode {
+    MastNode::new_basic_block(vec![Operation::And, Operation::Eq], None).unwrap()
+}
+
+/// Builds a forest with the shared helper `join(foo, bar)` called from a procedure which is unique
+/// to the forest, i.e. `join(call(helper), block(unique_ops))`.
+fn forest_with_shared_helper(unique_ops: Vec<Operation>) -> (MastForest, MastNodeId) {
+    let mut forest = MastForest::new();
+    let foo = forest.add_node(block_foo()).unwrap();
+    let bar = forest.add_node(block_bar()).unwrap();
+    let helper = forest.add_join(foo, bar).unwrap();
+    let call = forest.add_call(helper).unwrap();
+    let unique = forest.add_block(unique_ops, None).unwrap();
+    let root = forest.add_join(call, unique).unwrap();
+    forest.make_root(helper);
+    forest.make_root(root);
+
+    (forest, root)
+}
+
+#[test]
+fn store_shares_subtrees_between_forests() {
+    let (forest_a, root_a) = forest_with_shared_helper(vec![Operation::Swap]);
+    let (forest_b, root_b) = forest_with_shared_helper(vec![Operation::Push(ONE), Operation::Drop]);
+
+    let mut store = MastNodeStore::new();
+    let root_map_a = MastForest::merge_into_store([&forest_a], &mut store).unwrap();
+    let root_map_b = store.insert([&forest_b]).unwrap();
+
+    // foo, bar, helper and the call are shared, only the unique blocks and roots are duplicated
+    assert_eq!(store.num_nodes(), 4 + 2 * 2);
+
+    let store_root_a = root_map_a.map_root(0, &root_a).unwrap();
+    let store_root_b = root_map_b.map_root(0, &root_b).unwrap();
+    assert_eq!(store.forest()[store_root_a].digest(), forest_a[root_a].digest());
+    assert_eq!(store.forest()[store_root_b].digest(), forest_b[root_b].digest());
+    assert_eq!(
+        store.find_by_digest(forest_a[root_a].digest()).collect::<Vec<_>>(),
+        [store_root_a]
+    );
+
+    // reading a forest back yields only its own nodes
+    let (extracted_a, _) = store.extract([store_root_a]).unwrap();
+    assert_eq!(extracted_a.num_nodes(), 6);
+    assert_eq!(extracted_a.procedure_digests().collect::<Vec<_>>(), [forest_a[root_a].digest()]);
+
+    let (extracted_b, _) = store.extract([store_root_b]).unwrap();
+    assert_eq!(extracted_b.num_nodes(), 6);
+    assert_eq!(extracted_b.procedure_digests().collect::<Vec<_>>(), [forest_b[root_b].digest()]);
+}
+
+#[test]
+fn store_keeps_nodes_with_different_decorators_apart() {
+    let mut forest_a = MastForest::new();
+    let block_a = forest_a
+        .add_block_with_raw_decorators(vec![Operation::Add], vec![(0, Decorator::Trace(1))])
+        .unwrap();
+    forest_a.make_root(block_a);
+
+    let mut forest_b = MastForest::new();
+    let block_b = forest_b
+        .add_block_with_raw_decorators(vec![Operation::Add], vec![(0, Decorator::Trace(2))])
+        .unwrap();
+    forest_b.make_root(block_b);
+
+    let mut store = MastNodeStore::new();
+    let root_map = store.insert([&forest_a, &forest_b]).unwrap();
+    assert_eq!(store.num_nodes(), 2);
+    assert_eq!(store.find_by_digest(forest_a[block_a].digest()).count(), 2);
+
+    let store_block_b = root_map.map_root(1, &block_b).unwrap();
+    let (extracted_b, _) = store.extract([store_block_b]).unwrap();
+    assert_eq!(extracted_b.decorators(), &[Decorator::Trace(2)]);
+}
+
+#[test]
+fn store_extract_keeps_referenced_error_codes() {
+    let mut forest = MastForest::new();
+    let err_code = forest.register_error("some error".into());
+    forest.register_error("unused error".into());
+    let block = forest.add_block(vec![Operation::Assert(err_code)], None).unwrap();
+    forest.make_root(block);
+
+    let mut store = MastNodeStore::new();
+    let root_map = store.insert([&forest]).unwrap();
+    let (extracted, _) = store.extract([root_map.map_root(0, &block).unwrap()]).unwrap();
+
+    assert_eq!(extracted.resolve_error_message(err_code).as_deref(), Some("some error"));
+    assert_eq!(extracted.error_codes.len(), 1);
+}
+
+#[test]
+fn store_extract_rejects_unknown_roots() {
+    let store = MastNodeStore::new();
+    assert_matches!(
+        store.extract([MastNodeId::new_unchecked(0)]),
+        Err(MastForestError::NodeIdOverflow(_, 0))
+    );
+}
+
+#[test]
+fn store_serialization_rebuilds_indices() {
+    let (forest_a, root_a) = forest_with_shared_helper(vec![Operation::Swap]);
+    let (forest_b, _) = forest_with_shared_helper(vec![Operation::Push(ONE), Operation::Drop]);
+
+    let mut store = MastNodeStore::new();
+    store.insert([&forest_a]).unwrap();
+
+    let mut deserialized = MastNodeStore::read_from_bytes(&store.to_bytes()).unwrap();
+    assert_eq!(deserialized, store);
+
+    // writing into the deserialized store still deduplicates against its contents
+    deserialized.insert([&forest_b]).unwrap();
+    assert_eq!(deserialized.num_nodes(), 8);
+    assert!(deserialized.find_by_digest(forest_a[root_a].digest()).next().is_some());
+}
@@ -0,0 +1,231 @@
+use alloc::{collections::BTreeSet, vec::Vec};
+
+use crate::{
+    Word,
+    mast::{DecoratorId, MastForest, MastForestError, MastNode, MastNodeId},
+};
+
+#[cfg(test)]
+mod tests;
+
+// MAST FOREST VIOLATION
+// ================================================================================================
+
+/// A violation of the well-formedness rules of a [`MastForest`], as reported by
+/// [`MastForest::validate`].
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum MastForestViolation {
+    #[error("procedure root {root} is greater than or equal to forest length {num_nodes}")]
+    RootOutOfBounds { root: MastNodeId, num_nodes: usize },
+    #[error("node {0} is listed more than once as a procedure root")]
+    DuplicateRoot(MastNodeId),
+    #[error(
+        "child {child_id} of node {node_id} is greater than or equal to forest length {num_nodes}"
+    )]
+    ChildOutOfBounds {
+        node_id: MastNodeId,
+        child_id: MastNodeId,
+        num_nodes: usize,
+    },
+    #[error("child {child_id} of node {node_id} does not come before its parent in the forest")]
+    ChildNotBeforeParent {
+        node_id: MastNodeId,
+        child_id: MastNodeId,
+    },
+    #[error("digest of node {node_id} is {actual}, but its contents hash to {expected}")]
+    DigestMismatch {
+        node_id: MastNodeId,
+        expected: Word,
+        actual: Word,
+    },
+    #[error("operation batch {batch_idx} of basic block {node_id} is not grouped correctly")]
+    InvalidOpBatch { node_id: MastNodeId, batch_idx: usize },
+    #[error(
+        "node {node_id} references decorator {decorator_id}, but the forest only has {num_decorators} decorators"
+    )]
+    DecoratorOutOfBounds {
+        node_id: MastNodeId,
+        decorator_id: DecoratorId,
+        num_decorators: usize,
+    },
+    #[error("decorators of basic block {node_id} are not sorted by operation index")]
+    UnsortedDecorators { node_id: MastNodeId },
+    #[error(
+        "decorator of basic block {node_id} is attached to operation {op_idx}, but the block has only {num_operations} operations"
+    )]
+    DecoratorPositionOutOfBounds {
+        node_id: MastNodeId,
+        op_idx: usize,
+        num_operations: usize,
+    },
+    #[error(
+        "external node {node_id} with digest {digest} is neither a procedure root nor referenced by another node"
+    )]
+    DanglingExternal { node_id: MastNodeId, digest: Word },
+}
+
+// VALIDATION
+// ================================================================================================
+
+impl MastForest {
+    /// Checks that this forest is well-formed, and returns all the violations found otherwise.
+    ///
+    /// Forests built via the [`MastForest`] API are always well-formed, but deserialized forests
+    /// are only checked for basic consistency when they are read, and hence may be inconsistent in
+    /// ways which only surface during execution or proving. Specifically, this checks that:
+    /// - the procedure roots are nodes of the forest, and are not duplicated.
+    /// - the children of every node come before the node in the forest.
+    /// - the digest of every node matches its contents (i.e. the operations of basic blocks, and
+    ///   the digests of the children of control flow nodes).
+    /// - the operations of every basic block are grouped into batches according to the batching
+    ///   rules.
+    /// - all decorator references are valid, and the decorators of basic blocks are sorted and
+    ///   attached to existing operations.
+    /// - every external node is either a procedure root or referenced by another node.
+    ///
+    /// Note that the operation batches of a basic block are currently always derived from its
+    /// operations, both when the block is built and when it is deserialized, so the batching check
+    /// cannot fail for forests built through the public API. It guards the batch and group
+    /// invariants which the processor relies on, should blocks ever carry batches from elsewhere.
+    pub fn validate(&self) -> Result<(), Vec<MastForestViolation>> {
+        let mut violations = Vec::new();
+        let num_nodes = self.nodes.len();
+
+        let mut roots = BTreeSet::new();
+        for &root in self.roots.iter() {
+            if root.as_usize() >= num_nodes {
+                violations.push(MastForestViolation::RootOutOfBounds { root, num_nodes });
+            } else if !roots.insert(root) {
+                violations.push(MastForestViolation::DuplicateRoot(root));
+            }
+        }
+
+        let mut referenced = BTreeSet::new();
+        for (node_idx, node) in self.nodes.iter().enumerate() {
+            let node_id = MastNodeId::new_unchecked(node_idx as u32);
+
+            let mut children = Vec::new();
+            node.append_children_to(&mut children);
+            let mut children_valid = true;
+            for child_id in children {
+                if child_id.as_usize() >= num_nodes {
+                    violations.push(MastForestViolation::ChildOutOfBounds {
+                        node_id,
+                        child_id,
+                        num_nodes,
+                    });
+                    children_valid = false;
+                } else if child_id >= node_id {
+                    violations
+                        .push(MastForestViolation::ChildNotBeforeParent { node_id, child_id });
+                }
+                referenced.insert(child_id);
+            }
+
+            self.validate_decorators(node_id, node, &mut violations);
+
+            if let MastNode::Block(block) = node {
+                if let Some(batch_idx) = block.first_invalid_batch() {
+                    violations.push(MastForestViolation::InvalidOpBatch { node_id, batch_idx });
+                }
+            }
+
+            // the digest of a control flow node can only be recomputed if its children exist
+            if children_valid {
+                let expected = self
+                    .recompute_digest(node)
+                    .expect("children of the node should be in the forest");
+                if expected != node.digest() {
+                    violations.push(MastForestViolation::DigestMismatch {
+                        node_id,
+                        expected,
+                        actual: node.digest(),
+                    });
+                }
+            }
+        }
+
+        for (node_idx, node) in self.nodes.iter().enumerate() {
+            let node_id = MastNodeId::new_unchecked(node_idx as u32);
+            let MastNode::External(external) = node else {
+                continue;
+            };
+            if !roots.contains(&node_id) && !referenced.contains(&node_id) {
+                violations.push(MastForestViolation::DanglingExternal {
+                    node_id,
+                    digest: external.digest(),
+                });
+            }
+        }
+
+        if violations.is_empty() { Ok(()) } else { Err(violations) }
+    }
+
+    /// Checks the decorator references of `node`.
+    fn validate_decorators(
+        &self,
+        node_id: MastNodeId,
+        node: &MastNode,
+        violations: &mut Vec<MastForestViolation>,
+    ) {
+        let num_decorators = self.decorators.len();
+        let mut check_decorator = |decorator_id: DecoratorId| {
+            if decorator_id.as_usize() >= num_decorators {
+                violations.push(MastForestViolation::DecoratorOutOfBounds {
+                    node_id,
+                    decorator_id,
+                    num_decorators,
+                });
+            }
+        };
+
+        node.before_enter().iter().copied().for_each(&mut check_decorator);
+        node.after_exit().iter().copied().for_each(&mut check_decorator);
+
+        let MastNode::Block(block) = node else {
+            return;
+        };
+        block
+            .decorators()
+            .iter()
+            .for_each(|&(_, decorator_id)| check_decorator(decorator_id));
+
+        let decorators = block.decorators();
+        if decorators.windows(2).any(|pair| pair[0].0 > pair[1].0) {
+            violations.push(MastForestViolation::UnsortedDecorators { node_id });
+        }
+
+        // decorators may be attached after the last operation of the block
+        let num_operations = block.num_operations() as usize;
+        if let Some(&(op_idx, _)) = decorators.iter().find(|(op_idx, _)| *op_idx > num_operations) {
+            violations.push(MastForestViolation::DecoratorPositionOutOfBounds {
+                node_id,
+                op_idx,
+                num_operations,
+            });
+        }
+    }
+
+    /// Computes the digest of `node` from its contents.
+    ///
+    /// The digests of external nodes cannot be recomputed, and are returned unchanged.
+    fn recompute_digest(&self, node: &MastNode) -> Result<Word, MastForestError> {
+        let rebuilt = match node {
+            MastNode::Block(block) => {
+                MastNode::new_basic_block(block.operations().copied().collect(), None)?
+            },
+            MastNode::Join(join) => MastNode::new_join(join.first(), join.second(), self)?,
+            MastNode::Split(split) => MastNode::new_split(split.on_true(), split.on_false(), self)?,
+            MastNode::Loop(loop_node) => MastNode::new_loop(loop_node.body(), self)?,
+            MastNode::Call(call) if call.is_syscall() => {
+                MastNode::new_syscall(call.callee(), self)?
+            },
+            MastNode::Call(call) => MastNode::new_call(call.callee(), self)?,
+            MastNode::Dyn(dyn_node) if dyn_node.is_dyncall() => MastNode::new_dyncall(),
+            MastNode::Dyn(_) => MastNode::new_dyn(),
+            MastNode::External(external) => return Ok(external.digest()),
+        };
+
+        Ok(rebuilt.digest())
+    }
+}
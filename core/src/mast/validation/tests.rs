@@ -0,0 +1,136 @@
+use miden_crypto::ONE;
+
+use super::*;
+use crate::{
+    Decorator, Operation,
+    mast::{BasicBlockNode, JoinNode},
+};
+
+fn valid_forest() -> MastForest {
+    let mut forest = MastForest::new();
+    let block = forest
+        .add_block_with_raw_decorators(
+            vec![Operation::Push(ONE), Operation::Add],
+            vec![(0, Decorator::Trace(1)), (2, Decorator::Trace(2))],
+        )
+        .unwrap();
+    let external = forest.add_external(Word::default()).unwrap();
+    let call = forest.add_call(external).unwrap();
+    let join = forest.add_join(block, call).unwrap();
+    let loop_node = forest.add_loop(join).unwrap();
+    forest.make_root(loop_node);
+
+    forest
+}
+
+#[test]
+fn validate_accepts_well_formed_forest() {
+    assert_eq!(valid_forest().validate(), Ok(()));
+}
+
+#[test]
+fn validate_reports_digest_mismatches() {
+    let mut forest = valid_forest();
+
+    // a basic block whose digest doesn't match its operations
+    let block_id = MastNodeId::new_unchecked(0);
+    let expected = forest[block_id].digest();
+    forest.nodes[0] = MastNode::Block(BasicBlockNode::new_unsafe(
+        vec![Operation::Push(ONE), Operation::Add],
+        vec![],
+        Word::default(),
+    ));
+
+    // a join whose digest doesn't match its (modified) children
+    let join_id = MastNodeId::new_unchecked(3);
+    let join_digest = forest[join_id].digest();
+
+    let violations = forest.validate().unwrap_err();
+    assert_eq!(violations.len(), 2);
+    assert_eq!(
+        violations[0],
+        MastForestViolation::DigestMismatch {
+            node_id: block_id,
+            expected,
+            actual: Word::default(),
+        }
+    );
+    assert_matches!(
+        violations[1],
+        MastForestViolation::DigestMismatch { node_id, actual, .. }
+            if node_id == join_id && actual == join_digest
+    );
+}
+
+#[test]
+fn validate_reports_invalid_structure() {
+    let mut forest = valid_forest();
+    let block_id = MastNodeId::new_unchecked(0);
+    let join_id = MastNodeId::new_unchecked(3);
+    let loop_id = MastNodeId::new_unchecked(4);
+
+    // a child which comes after its parent
+    let join_digest = forest[join_id].digest();
+    forest.nodes[3] = MastNode::Join(JoinNode::new_unsafe([block_id, loop_id], join_digest));
+    // a child which is not in the forest
+    let out_of_bounds = MastNodeId::new_unchecked(10);
+    forest
+        .nodes
+        .push(MastNode::Join(JoinNode::new_unsafe([block_id, out_of_bounds], join_digest)));
+    // duplicate and out of bounds roots
+    forest.roots.push(loop_id);
+    forest.roots.push(MastNodeId::new_unchecked(10));
+    // a decorator which is not in the forest, and decorators out of order
+    if let MastNode::Block(block) = &mut forest.nodes[0] {
+        block.set_decorators(vec![
+            (2, DecoratorId::new_unchecked(0)),
+            (0, DecoratorId::new_unchecked(7)),
+        ]);
+    }
+    // an external node which is never used
+    let dangling_digest = Word::from([ONE; 4]);
+    forest.nodes.push(MastNode::new_external(dangling_digest));
+
+    let violations = forest.validate().unwrap_err();
+    assert!(violations.contains(&MastForestViolation::DuplicateRoot(loop_id)));
+    assert!(violations.contains(&MastForestViolation::RootOutOfBounds {
+        root: MastNodeId::new_unchecked(10),
+        num_nodes: 7,
+    }));
+    assert!(violations.contains(&MastForestViolation::ChildNotBeforeParent {
+        node_id: join_id,
+        child_id: loop_id,
+    }));
+    assert!(violations.contains(&MastForestViolation::ChildOutOfBounds {
+        node_id: MastNodeId::new_unchecked(5),
+        child_id: MastNodeId::new_unchecked(10),
+        num_nodes: 7,
+    }));
+    assert!(violations.contains(&MastForestViolation::DecoratorOutOfBounds {
+        node_id: block_id,
+        decorator_id: DecoratorId::new_unchecked(7),
+        num_decorators: 2,
+    }));
+    assert!(violations.contains(&MastForestViolation::UnsortedDecorators { node_id: block_id }));
+    assert!(violations.contains(&MastForestViolation::DanglingExternal {
+        node_id: MastNodeId::new_unchecked(6),
+        digest: dangling_digest,
+    }));
+}
+
+#[test]
+fn validate_reports_decorators_past_end_of_block() {
+    let mut forest = valid_forest();
+    if let MastNode::Block(block) = &mut forest.nodes[0] {
+        block.set_decorators(vec![(3, DecoratorId::new_unchecked(0))]);
+    }
+
+    assert_eq!(
+        forest.validate(),
+        Err(vec![MastForestViolation::DecoratorPositionOutOfBounds {
+            node_id: MastNodeId::new_unchecked(0),
+            op_idx: 3,
+            num_operations: 2,
+        }])
+    );
+}
@@ -1,13 +1,14 @@
-use alloc::{sync::Arc, vec::Vec};
+use alloc::{string::String, sync::Arc, vec::Vec};
 use core::fmt;
 
 use math::FieldElement;
 use miden_crypto::{Felt, WORD_SIZE, Word};
 
-use super::Kernel;
+use super::{Kernel, KernelVersion};
 use crate::{
     AdviceMap,
-    mast::{MastForest, MastNode, MastNodeId},
+    errors::KernelError,
+    mast::{CycleEstimate, MastCallGraph, MastForest, MastNode, MastNodeId},
     utils::{
         ByteReader, ByteWriter, Deserializable, DeserializationError, Serializable, ToElements,
     },
@@ -20,13 +21,14 @@ use crate::{
 ///
 /// A program consists of a MAST forest, an entrypoint defining the MAST node at which the program
 /// execution begins, and a definition of the kernel against which the program must be executed
-/// (the kernel can be an empty kernel).
+/// (the kernel can be an empty kernel), along with the version of that kernel, if it is known.
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Program {
     mast_forest: Arc<MastForest>,
     /// The "entrypoint" is the node where execution of the program begins.
     entrypoint: MastNodeId,
     kernel: Kernel,
+    kernel_version: Option<KernelVersion>,
 }
 
 /// Constructors
@@ -54,7 +56,21 @@ impl Program {
         assert!(mast_forest.get_node_by_id(entrypoint).is_some(), "invalid entrypoint");
         assert!(mast_forest.is_procedure_root(entrypoint), "entrypoint not a procedure");
 
-        Self { mast_forest, entrypoint, kernel }
+        Self {
+            mast_forest,
+            entrypoint,
+            kernel,
+            kernel_version: None,
+        }
+    }
+
+    /// Produces a new program which records that it was assembled against version
+    /// `kernel_version` of its kernel.
+    pub fn with_kernel_version(self, kernel_version: KernelVersion) -> Self {
+        Self {
+            kernel_version: Some(kernel_version),
+            ..self
+        }
     }
 
     /// Produces a new program with the existing [`MastForest`] and where all key/values in the
@@ -94,6 +110,18 @@ impl Program {
         &self.kernel
     }
 
+    /// Returns the version of the kernel this program was assembled against, if it is known.
+    pub fn kernel_version(&self) -> Option<&KernelVersion> {
+        self.kernel_version.as_ref()
+    }
+
+    /// Returns an error if `kernel` does not contain all the procedures of the kernel this program
+    /// was assembled against, i.e. if the syscalls of this program may fail when it is executed
+    /// against `kernel`.
+    pub fn check_kernel(&self, kernel: &Kernel) -> Result<(), KernelError> {
+        kernel.check_compatible(&self.kernel, self.kernel_version.as_ref())
+    }
+
     /// Returns the [`MastNode`] associated with the provided [`MastNodeId`] if valid, or else
     /// `None`.
     ///
@@ -113,6 +141,17 @@ impl Program {
     pub fn num_procedures(&self) -> u32 {
         self.mast_forest.num_procedures()
     }
+
+    /// Returns static bounds on the number of VM cycles needed to execute this program.
+    pub fn estimate_cycles(&self) -> CycleEstimate {
+        self.mast_forest.estimate_cycles(self.entrypoint)
+    }
+
+    /// Builds the call graph of the procedures in this program, with the entrypoint named
+    /// `entrypoint`.
+    pub fn call_graph(&self) -> MastCallGraph {
+        MastCallGraph::build(&self.mast_forest, [(String::from("entrypoint"), self.entrypoint)])
+    }
 }
 
 // ------------------------------------------------------------------------------------------------
@@ -156,6 +195,7 @@ impl Serializable for Program {
         self.mast_forest.write_into(target);
         self.kernel.write_into(target);
         target.write_u32(self.entrypoint.as_u32());
+        self.kernel_version.write_into(target);
     }
 }
 
@@ -164,6 +204,7 @@ impl Deserializable for Program {
         let mast_forest = Arc::new(source.read()?);
         let kernel = source.read()?;
         let entrypoint = MastNodeId::from_u32_safe(source.read_u32()?, &mast_forest)?;
+        let kernel_version = Option::<KernelVersion>::read_from(source)?;
 
         if !mast_forest.is_procedure_root(entrypoint) {
             return Err(DeserializationError::InvalidValue(format!(
@@ -171,7 +212,8 @@ impl Deserializable for Program {
             )));
         }
 
-        Ok(Self::with_kernel(mast_forest, entrypoint, kernel))
+        let program = Self::with_kernel(mast_forest, entrypoint, kernel);
+        Ok(Self { kernel_version, ..program })
     }
 }
 
@@ -205,16 +247,33 @@ impl fmt::Display for Program {
 /// security guarantees provided by the kernel. We also allow the user to easily prove the
 /// membership of a given kernel procedure for a given proof, without compromising its
 /// zero-knowledge properties.
+///
+/// The version of the kernel, if known, is only used to report kernel mismatches: it is not part
+/// of the public inputs of the proof.
 #[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct ProgramInfo {
     program_hash: Word,
     kernel: Kernel,
+    kernel_version: Option<KernelVersion>,
 }
 
 impl ProgramInfo {
     /// Creates a new instance of a program info.
     pub const fn new(program_hash: Word, kernel: Kernel) -> Self {
-        Self { program_hash, kernel }
+        Self {
+            program_hash,
+            kernel,
+            kernel_version: None,
+        }
+    }
+
+    /// Produces a new program info which records that the program was assembled against version
+    /// `kernel_version` of its kernel.
+    pub fn with_kernel_version(self, kernel_version: KernelVersion) -> Self {
+        Self {
+            kernel_version: Some(kernel_version),
+            ..self
+        }
     }
 
     /// Returns the program hash computed from its code block root.
@@ -231,14 +290,26 @@ impl ProgramInfo {
     pub fn kernel_procedures(&self) -> &[Word] {
         self.kernel.proc_hashes()
     }
+
+    /// Returns the version of the kernel used during the compilation, if it is known.
+    pub fn kernel_version(&self) -> Option<&KernelVersion> {
+        self.kernel_version.as_ref()
+    }
+
+    /// Returns an error if `kernel` does not contain all the procedures of the kernel used during
+    /// the compilation, i.e. if the syscalls of the program may fail when it is executed against
+    /// `kernel`.
+    pub fn check_kernel(&self, kernel: &Kernel) -> Result<(), KernelError> {
+        kernel.check_compatible(&self.kernel, self.kernel_version.as_ref())
+    }
 }
 
 impl From<Program> for ProgramInfo {
     fn from(program: Program) -> Self {
         let program_hash = program.hash();
-        let kernel = program.kernel().clone();
+        let Program { kernel, kernel_version, .. } = program;
 
-        Self { program_hash, kernel }
+        Self { program_hash, kernel, kernel_version }
     }
 }
 
@@ -249,6 +320,7 @@ impl Serializable for ProgramInfo {
     fn write_into<W: ByteWriter>(&self, target: &mut W) {
         self.program_hash.write_into(target);
         self.kernel.write_into(target);
+        self.kernel_version.write_into(target);
     }
 }
 
@@ -256,7 +328,8 @@ impl Deserializable for ProgramInfo {
     fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
         let program_hash = source.read()?;
         let kernel = source.read()?;
-        Ok(Self { program_hash, kernel })
+        let kernel_version = source.read()?;
+        Ok(Self { program_hash, kernel, kernel_version })
     }
 }
 
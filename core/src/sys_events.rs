@@ -28,6 +28,17 @@ mod constants {
     pub const EVENT_FALCON_DIV: u32                   = 3419226155;
 }
 
+/// Derives the ID of the event called `name`, i.e. the value of `event("<name>")` in Miden Assembly
+/// constant expressions.
+///
+/// The ID is the low 32 bits of the first element of the RPO digest of `name`, so that hosts can
+/// compute the IDs of the events they handle from the same names as the programs emitting them.
+pub fn event_id_from_name(name: &str) -> u32 {
+    use crate::crypto::hash::Rpo256;
+
+    Rpo256::hash(name.as_bytes())[0].as_int() as u32
+}
+
 /// Defines a set of actions which can be initiated from the VM to inject new data into the advice
 /// provider.
 ///
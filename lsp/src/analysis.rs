@@ -0,0 +1,544 @@
+//! Analysis of a single document, in the context of the rest of the workspace.
+
+use std::{ops::ControlFlow, sync::Arc};
+
+use assembly::{
+    DefaultSourceManager, GlobalProcedureIndex, LibraryNamespace, LibraryPath, SourceManager,
+    Spanned,
+    ast::{
+        Export, InvocationTarget, InvokeKind, Module, ModuleKind, ProcedureName,
+        QualifiedProcedureName,
+        visit::{self, Visit},
+    },
+    diagnostics::{ByteIndex, Diagnostic, Severity, SourceFile, SourceLanguage, SourceSpan, Uri},
+    linker::{CallerInfo, LinkLibrary, Linker, ModuleLink, NameResolver, PreLinkModule},
+};
+use miden_assembly_syntax::{
+    Word,
+    ast::ProcedureSignature,
+    parser::{Lexer, Scanner, Token},
+};
+use serde_json::{Value, json};
+
+use crate::{
+    protocol::{self, client_uri},
+    workspace::Workspace,
+};
+
+/// The `SymbolKind` of procedures in document symbols.
+const SYMBOL_KIND_FUNCTION: u32 = 12;
+/// The `CompletionItemKind` of procedures.
+const COMPLETION_KIND_FUNCTION: u32 = 3;
+/// The `CompletionItemKind` of imported modules.
+const COMPLETION_KIND_MODULE: u32 = 9;
+/// The `CompletionItemKind` of instructions and other keywords.
+const COMPLETION_KIND_KEYWORD: u32 = 14;
+
+/// The keywords which may appear in a procedure body besides instructions.
+const CONTROL_FLOW_KEYWORDS: [&str; 13] = [
+    "if.true",
+    "if.false",
+    "else",
+    "while.true",
+    "repeat",
+    "for",
+    "match",
+    "case",
+    "default",
+    "end",
+    "exec",
+    "call",
+    "syscall",
+];
+
+// ANALYSIS
+// ================================================================================================
+
+/// The result of parsing a document, and linking it with the other sources and libraries of the
+/// workspace.
+///
+/// Each analysis uses its own source manager, so that the sources of previous versions of the
+/// documents do not accumulate over the lifetime of the server.
+pub struct Analysis {
+    source_manager: Arc<DefaultSourceManager>,
+    /// The document being analyzed
+    source: Arc<SourceFile>,
+    /// The diagnostics raised while parsing the document
+    diagnostics: Vec<Value>,
+    linker: Linker,
+    /// The modules parsed from the sources of the workspace, including the document
+    modules: Vec<PreLinkModule>,
+    /// The index in `modules` of the module parsed from the document, unless parsing failed
+    document: Option<usize>,
+    /// The signatures of the procedures of the compiled libraries of the workspace
+    library_signatures: Vec<(QualifiedProcedureName, ProcedureSignature)>,
+}
+
+impl Analysis {
+    /// Analyzes `text`, the contents of the document identified by `uri`.
+    pub fn new(workspace: &Workspace, uri: &Uri, text: String) -> Self {
+        let source_manager = Arc::new(DefaultSourceManager::default());
+        let source = source_manager.load(SourceLanguage::Masm, uri.clone(), text);
+        let mut linker = Linker::new(source_manager.clone());
+        let mut modules = Vec::new();
+        let mut document = None;
+        let mut diagnostics = Vec::new();
+
+        let kind = if is_executable(&source) {
+            ModuleKind::Executable
+        } else {
+            ModuleKind::Library
+        };
+        let path = workspace.module_path(uri).unwrap_or_else(|| match kind {
+            ModuleKind::Executable => LibraryNamespace::Exec.into(),
+            _ => LibraryNamespace::Anon.into(),
+        });
+        match Module::parser(kind).parse_with_warnings(path, source.clone()) {
+            Ok((module, warnings)) => {
+                if let Some(warnings) = warnings {
+                    collect_diagnostics(&source, &*warnings, &mut diagnostics);
+                }
+                if let Ok(module_index) = linker.link_module(module.clone()) {
+                    document = Some(modules.len());
+                    modules.push(PreLinkModule { module, module_index });
+                }
+            },
+            Err(report) => collect_diagnostics(&source, &*report, &mut diagnostics),
+        }
+
+        // The other modules of the workspace are only needed to resolve references, so any
+        // errors in them are reported when they are opened themselves
+        for (path, file) in workspace.source_files() {
+            let Some((file_uri, text)) = workspace.read_source(&file) else {
+                continue;
+            };
+            if &file_uri == uri {
+                continue;
+            }
+            let source = source_manager.load(SourceLanguage::Masm, file_uri, text);
+            if let Ok((module, _)) =
+                Module::parser(ModuleKind::Library).parse_with_warnings(path, source)
+            {
+                if let Ok(module_index) = linker.link_module(module.clone()) {
+                    modules.push(PreLinkModule { module, module_index });
+                }
+            }
+        }
+
+        // Libraries providing modules which are also defined by the sources of the workspace are
+        // skipped, so that the sources are used instead
+        for library in workspace.libraries() {
+            let shadowed = library
+                .module_infos()
+                .any(|info| modules.iter().any(|m| m.module.path() == info.path()));
+            if !shadowed {
+                let _ = linker.link_library(LinkLibrary::dynamic(library.clone()));
+            }
+        }
+        let library_signatures = workspace
+            .libraries()
+            .iter()
+            .flat_map(|library| {
+                library.signatures().map(|(name, signature)| (name.clone(), signature.clone()))
+            })
+            .collect();
+
+        let mut analysis = Self {
+            source_manager,
+            source,
+            diagnostics,
+            linker,
+            modules,
+            document,
+            library_signatures,
+        };
+        analysis.check_references();
+        analysis
+    }
+
+    /// Returns the document being analyzed.
+    pub fn source(&self) -> &Arc<SourceFile> {
+        &self.source
+    }
+
+    /// Returns the module parsed from the document, if parsing succeeded.
+    pub fn module(&self) -> Option<&Module> {
+        self.document.map(|index| &*self.modules[index].module)
+    }
+
+    /// Returns the diagnostics raised for the document.
+    pub fn diagnostics(&self) -> &[Value] {
+        &self.diagnostics
+    }
+
+    /// Reports the invocation targets in the document which do not resolve to a procedure.
+    ///
+    /// Syscalls are not checked, as the kernel against which they are resolved is not known.
+    fn check_references(&mut self) {
+        let Some(module) = self.module() else {
+            return;
+        };
+        let mut diagnostics = Vec::new();
+        for (kind, target) in invocation_targets(module) {
+            if let Err(error) = self.resolve(kind, &target) {
+                let diagnostic = error.related().and_then(|mut related| related.next());
+                let diagnostic = diagnostic.unwrap_or(&error as &dyn Diagnostic);
+                let mut message = diagnostic.to_string();
+                let label = diagnostic.labels().and_then(|mut labels| labels.next());
+                if let Some(label) = label.as_ref().and_then(|label| label.label()) {
+                    message = format!("{message}: {label}");
+                }
+                diagnostics.push(json!({
+                    "range": protocol::range(&self.source, target.span()),
+                    "severity": 1,
+                    "source": "miden",
+                    "message": message,
+                }));
+            }
+        }
+        self.diagnostics.extend(diagnostics);
+    }
+
+    /// Resolves `target`, invoked from the document using `kind`, to the procedure it refers to.
+    fn resolve(
+        &self,
+        kind: InvokeKind,
+        target: &InvocationTarget,
+    ) -> Result<Option<GlobalProcedureIndex>, assembly::LinkerError> {
+        let Some(document) = self.document.map(|index| &self.modules[index]) else {
+            return Ok(None);
+        };
+        if matches!(kind, InvokeKind::SysCall) {
+            return Ok(None);
+        }
+        let mut resolver = NameResolver::new(&self.linker);
+        for module in self.modules.iter() {
+            resolver.push_pending(module);
+        }
+        let caller = CallerInfo {
+            span: target.span(),
+            module: document.module_index,
+            kind,
+        };
+        resolver
+            .resolve_target(&caller, target)
+            .map(|resolved| resolved.into_global_id())
+    }
+
+    /// Returns the definition of the procedure identified by `gid`.
+    fn definition(&self, gid: GlobalProcedureIndex) -> Option<Definition<'_>> {
+        if let Some(pending) = self.modules.iter().find(|m| m.module_index == gid.module) {
+            let export = pending.module.get(gid.index)?;
+            return Some(Definition::Source { module: &pending.module, export });
+        }
+        match &self.linker[gid.module] {
+            ModuleLink::Info(info) => {
+                let procedure = info.get_procedure_by_index(gid.index)?;
+                Some(Definition::Library {
+                    name: QualifiedProcedureName::new(info.path().clone(), procedure.name.clone()),
+                    digest: procedure.digest,
+                })
+            },
+            ModuleLink::Ast(_) => None,
+        }
+    }
+
+    /// Returns the procedure referenced, or defined, at `offset` in the document.
+    fn definition_at(&self, offset: ByteIndex) -> Option<(SourceSpan, Definition<'_>)> {
+        let module = self.module()?;
+        for (kind, target) in invocation_targets(module) {
+            if contains(target.span(), offset) {
+                let gid = self.resolve(kind, &target).ok()??;
+                return Some((target.span(), self.definition(gid)?));
+            }
+        }
+        module
+            .procedures()
+            .find(|export| contains(export.name().span(), offset))
+            .map(|export| (export.name().span(), Definition::Source { module, export }))
+    }
+
+    /// Returns the span of the module imported by the import statement at `offset`, if any.
+    fn import_at(&self, offset: ByteIndex) -> Option<SourceSpan> {
+        let import = self.module()?.imports().find(|import| contains(import.span, offset))?;
+        self.modules
+            .iter()
+            .find(|m| m.module.path() == &import.path)
+            .map(|m| m.module.span())
+    }
+}
+
+/// Requests
+impl Analysis {
+    /// Returns the location of the definition of the procedure or module referenced at `offset`.
+    pub fn goto_definition(&self, offset: ByteIndex) -> Value {
+        let span = match self.definition_at(offset) {
+            Some((_, Definition::Source { export, .. })) => export.name().span(),
+            Some((_, Definition::Library { .. })) => return Value::Null,
+            None => match self.import_at(offset) {
+                Some(span) => SourceSpan::at(span.source_id(), 0),
+                None => return Value::Null,
+            },
+        };
+        match self.source_manager.get(span.source_id()) {
+            Ok(file) => json!({
+                "uri": client_uri(file.uri()),
+                "range": protocol::range(&file, span),
+            }),
+            Err(_) => Value::Null,
+        }
+    }
+
+    /// Describes the procedure referenced, or defined, at `offset`.
+    pub fn hover(&self, offset: ByteIndex) -> Value {
+        let Some((span, definition)) = self.definition_at(offset) else {
+            return Value::Null;
+        };
+        let text = match definition {
+            Definition::Source { module, export } => {
+                let mut text = match export {
+                    Export::Procedure(procedure) => {
+                        let mut header = format!("{}.{}", procedure.visibility(), procedure.name());
+                        if procedure.num_locals() > 0 {
+                            header.push_str(&format!(".{}", procedure.num_locals()));
+                        }
+                        format!("```masm\n{header}\n```\n\nDefined in `{}`", module.path())
+                    },
+                    Export::Alias(alias) => format!(
+                        "```masm\nexport.{}->{}\n```\n\nDefined in `{}`",
+                        alias.target(),
+                        alias.name(),
+                        module.path()
+                    ),
+                };
+                if let Some(signature) = export.signature() {
+                    text.push_str(&format!("\n\nSignature: `{signature}`"));
+                }
+                if let Some(docs) = export.docs() {
+                    text.push_str(&format!("\n\n---\n\n{}", docs.trim()));
+                }
+                text
+            },
+            Definition::Library { name, digest } => {
+                let mut text = format!(
+                    "```masm\nexport.{}\n```\n\nDefined in `{}`\n\nMAST root: `{digest}`",
+                    name.name, name.module
+                );
+                let signature = self
+                    .library_signatures
+                    .iter()
+                    .find(|(qualified, _)| qualified == &name)
+                    .map(|(_, signature)| signature);
+                if let Some(signature) = signature {
+                    text.push_str(&format!("\n\nSignature: `{signature}`"));
+                }
+                text
+            },
+        };
+        json!({
+            "contents": { "kind": "markdown", "value": text },
+            "range": protocol::range(&self.source, span),
+        })
+    }
+
+    /// Returns the completions of `word`, the text preceding the cursor on its line, up to the
+    /// previous whitespace.
+    ///
+    /// Following `exec.`, `call.`, `syscall.` or `procref.`, the procedures of the module and the
+    /// imported modules are completed, or the exported procedures of an imported module following
+    /// `alias::`. Otherwise, the instructions are completed.
+    pub fn completions(&self, word: &str) -> Value {
+        let invoked = word.split_once('.').and_then(|(keyword, target)| {
+            ["exec", "call", "syscall", "procref"].contains(&keyword).then_some(target)
+        });
+        let Some(target) = invoked else {
+            let items = Token::instruction_keywords()
+                .chain(CONTROL_FLOW_KEYWORDS)
+                .map(|keyword| json!({ "label": keyword, "kind": COMPLETION_KIND_KEYWORD }));
+            return Value::Array(items.collect());
+        };
+
+        let Some(module) = self.module() else {
+            return json!([]);
+        };
+        let mut items = Vec::new();
+        match target.rsplit_once("::") {
+            Some((alias, _)) => {
+                let path = module
+                    .imports()
+                    .find(|import| import.name.as_str() == alias)
+                    .map(|import| &import.path);
+                for (name, detail) in path.map(|path| self.exports(path)).unwrap_or_default() {
+                    items.push(json!({
+                        "label": name.as_str(),
+                        "kind": COMPLETION_KIND_FUNCTION,
+                        "detail": detail,
+                    }));
+                }
+            },
+            None => {
+                for export in module.procedures().filter(|export| !export.is_main()) {
+                    items.push(json!({
+                        "label": export.name().as_str(),
+                        "kind": COMPLETION_KIND_FUNCTION,
+                        "detail": export.signature().map(ToString::to_string).unwrap_or_default(),
+                    }));
+                }
+                for import in module.imports() {
+                    items.push(json!({
+                        "label": import.name.as_str(),
+                        "kind": COMPLETION_KIND_MODULE,
+                        "detail": import.path.to_string(),
+                        "insertText": format!("{}::", import.name),
+                    }));
+                }
+            },
+        }
+        Value::Array(items)
+    }
+
+    /// Returns the procedures defined in the document, other than its entrypoint.
+    pub fn document_symbols(&self) -> Value {
+        let Some(module) = self.module() else {
+            return json!([]);
+        };
+        let symbols = module.procedures().filter(|export| !export.is_main()).map(|export| {
+            json!({
+                "name": export.name().as_str(),
+                "detail": export.signature().map(ToString::to_string).unwrap_or_default(),
+                "kind": SYMBOL_KIND_FUNCTION,
+                "range": protocol::range(&self.source, export.span()),
+                "selectionRange": protocol::range(&self.source, export.name().span()),
+            })
+        });
+        Value::Array(symbols.collect())
+    }
+
+    /// Returns the names of the procedures exported by the module at `path`, along with their
+    /// signatures, or an empty string for procedures without a signature.
+    fn exports(&self, path: &LibraryPath) -> Vec<(ProcedureName, String)> {
+        if let Some(pending) = self.modules.iter().find(|m| m.module.path() == path) {
+            return pending
+                .module
+                .procedures()
+                .filter(|export| export.visibility().is_exported())
+                .map(|export| {
+                    let signature = export.signature().map(ToString::to_string);
+                    (export.name().clone(), signature.unwrap_or_default())
+                })
+                .collect();
+        }
+        match self.linker.find_module(path) {
+            Some(ModuleLink::Info(info)) => info
+                .procedures()
+                .map(|(_, procedure)| {
+                    let name = QualifiedProcedureName::new(path.clone(), procedure.name.clone());
+                    let signature = self
+                        .library_signatures
+                        .iter()
+                        .find(|(qualified, _)| qualified == &name)
+                        .map(|(_, signature)| signature.to_string());
+                    (procedure.name.clone(), signature.unwrap_or_default())
+                })
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+// DEFINITION
+// ================================================================================================
+
+/// A procedure referenced from the document being analyzed.
+enum Definition<'a> {
+    /// A procedure defined in the sources of the workspace
+    Source { module: &'a Module, export: &'a Export },
+    /// A procedure of a compiled library, whose source is not available
+    Library {
+        name: QualifiedProcedureName,
+        digest: Word,
+    },
+}
+
+// HELPERS
+// ================================================================================================
+
+/// Returns true if `source` contains a `begin` block, i.e. is the source of an executable module.
+fn is_executable(source: &SourceFile) -> bool {
+    Lexer::new(source.id(), Scanner::new(source.as_str()))
+        .any(|token| matches!(token, Ok((_, Token::Begin, _))))
+}
+
+/// Returns true if `offset` is in `span`, including its end, so that the cursor is considered
+/// to be on a name while placed right after it.
+fn contains(span: SourceSpan, offset: ByteIndex) -> bool {
+    span.start() <= offset && offset <= span.end()
+}
+
+/// Returns the targets invoked in `module`, and how they are invoked.
+fn invocation_targets(module: &Module) -> Vec<(InvokeKind, InvocationTarget)> {
+    struct Targets(Vec<(InvokeKind, InvocationTarget)>);
+
+    impl Visit for Targets {
+        fn visit_exec(&mut self, target: &InvocationTarget) -> ControlFlow<()> {
+            self.0.push((InvokeKind::Exec, target.clone()));
+            ControlFlow::Continue(())
+        }
+        fn visit_call(&mut self, target: &InvocationTarget) -> ControlFlow<()> {
+            self.0.push((InvokeKind::Call, target.clone()));
+            ControlFlow::Continue(())
+        }
+        fn visit_syscall(&mut self, target: &InvocationTarget) -> ControlFlow<()> {
+            self.0.push((InvokeKind::SysCall, target.clone()));
+            ControlFlow::Continue(())
+        }
+        fn visit_procref(&mut self, target: &InvocationTarget) -> ControlFlow<()> {
+            self.0.push((InvokeKind::ProcRef, target.clone()));
+            ControlFlow::Continue(())
+        }
+    }
+
+    let mut targets = Targets(Vec::new());
+    let _ = visit::visit_module(&mut targets, module);
+    targets.0
+}
+
+/// Converts `diagnostic`, raised while parsing `source`, to diagnostics in the format of the
+/// protocol, flattening diagnostics which only group related diagnostics.
+fn collect_diagnostics(source: &SourceFile, diagnostic: &dyn Diagnostic, out: &mut Vec<Value>) {
+    let related = diagnostic.related().map(|related| related.collect::<Vec<_>>());
+    if let Some(related) = related.filter(|related| !related.is_empty()) {
+        for diagnostic in related {
+            collect_diagnostics(source, diagnostic, out);
+        }
+        return;
+    }
+
+    let severity = match diagnostic.severity().unwrap_or(Severity::Error) {
+        Severity::Error => 1,
+        Severity::Warning => 2,
+        Severity::Advice => 3,
+    };
+    let mut message = diagnostic.to_string();
+    let label = diagnostic.labels().and_then(|mut labels| labels.next());
+    let span = match label {
+        Some(label) => {
+            if let Some(text) = label.label() {
+                message.push_str(&format!("\n{text}"));
+            }
+            let start = label.offset().min(source.as_str().len()) as u32;
+            let end = (label.offset() + label.len()).min(source.as_str().len()) as u32;
+            SourceSpan::new(source.id(), start..end)
+        },
+        None => SourceSpan::at(source.id(), 0),
+    };
+    if let Some(help) = diagnostic.help() {
+        message.push_str(&format!("\nhelp: {help}"));
+    }
+    out.push(json!({
+        "range": protocol::range(source, span),
+        "severity": severity,
+        "source": "miden",
+        "message": message,
+    }));
+}
@@ -0,0 +1,25 @@
+//! A [Language Server Protocol](https://microsoft.github.io/language-server-protocol/) server for
+//! Miden Assembly.
+//!
+//! The server provides the following features:
+//!
+//! * Diagnostics, i.e. the errors and warnings raised while parsing a document and resolving the
+//!   procedures it invokes, published when the document is opened or saved.
+//! * Go to definition of the procedures invoked by a document, across the modules of the workspace,
+//!   and of the modules it imports.
+//! * Hover, describing the procedure under the cursor, i.e. its docstring, signature and number of
+//!   locals, or its MAST root for procedures of compiled libraries.
+//! * Completion of instructions, and of the procedures and imported modules following `exec.`,
+//!   `call.`, `syscall.` and `procref.`.
+//! * Document symbols, i.e. the procedures defined by a document.
+//!
+//! See [workspace::Workspace] for how the server is configured.
+
+mod analysis;
+mod protocol;
+mod server;
+#[cfg(test)]
+mod tests;
+pub mod workspace;
+
+pub use self::server::Server;
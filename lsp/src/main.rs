@@ -0,0 +1,21 @@
+use std::{io, process::ExitCode};
+
+use miden_lsp::Server;
+
+/// Runs the server over stdin and stdout, as started by an editor.
+fn main() -> ExitCode {
+    let mut server = Server::new();
+    let stdin = io::stdin();
+    if let Err(err) = server.run(&mut stdin.lock(), &mut io::stdout().lock()) {
+        eprintln!("miden-lsp: {err}");
+        return ExitCode::FAILURE;
+    }
+
+    // As required by the protocol, exit with an error if the client did not request the server to
+    // shut down before notifying it to exit
+    if server.was_shut_down() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
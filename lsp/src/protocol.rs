@@ -0,0 +1,173 @@
+//! Encoding and decoding of the messages exchanged with the client, and conversions between the
+//! representations of locations used by the protocol and by the assembler.
+
+use std::{
+    error::Error,
+    io::{self, BufRead, Write},
+    path::{Path, PathBuf},
+};
+
+use assembly::diagnostics::{ByteIndex, LineIndex, SourceFile, SourceSpan, Uri};
+use serde_json::{Value, json};
+
+// ERROR CODES
+// ================================================================================================
+
+/// The request is not a valid request object, or was sent after the server was shut down.
+pub const INVALID_REQUEST: i64 = -32600;
+/// The requested method is not supported by the server.
+pub const METHOD_NOT_FOUND: i64 = -32601;
+/// The parameters of the request are missing or malformed.
+pub const INVALID_PARAMS: i64 = -32602;
+
+// MESSAGES
+// ================================================================================================
+
+/// Reads the next message from `input`.
+///
+/// Returns `Ok(None)` if `input` was closed before the start of a message.
+pub fn read_message(input: &mut impl BufRead) -> io::Result<Option<Value>> {
+    let mut content_length = None;
+    let mut header = String::new();
+    loop {
+        header.clear();
+        if input.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = Some(value.trim().parse::<usize>().map_err(invalid_data)?);
+            }
+        }
+    }
+
+    let content_length =
+        content_length.ok_or_else(|| invalid_data("message has no Content-Length header"))?;
+    let mut content = vec![0; content_length];
+    input.read_exact(&mut content)?;
+    serde_json::from_slice(&content).map(Some).map_err(invalid_data)
+}
+
+/// Writes `message` to `output`, preceded by its header.
+pub fn write_message(output: &mut impl Write, message: &Value) -> io::Result<()> {
+    let content = message.to_string();
+    write!(output, "Content-Length: {}\r\n\r\n{content}", content.len())?;
+    output.flush()
+}
+
+/// Returns a successful response to the request identified by `id`.
+pub fn response(id: Value, result: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "result": result })
+}
+
+/// Returns a response reporting the failure of the request identified by `id`.
+pub fn error_response(id: Value, code: i64, message: impl Into<String>) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message.into() } })
+}
+
+/// Returns a notification of `method` with the given parameters.
+pub fn notification(method: &str, params: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "method": method, "params": params })
+}
+
+fn invalid_data(error: impl Into<Box<dyn Error + Send + Sync>>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error)
+}
+
+// URIS
+// ================================================================================================
+
+/// Returns the [Uri] under which the document identified by the client as `uri` is loaded.
+///
+/// Documents backed by a file are identified by their path, like the sources loaded from disk
+/// while assembling, while any other document keeps the URI given by the client.
+pub fn document_uri(uri: &str) -> Uri {
+    match file_uri_to_path(uri) {
+        Some(path) => Uri::from(path.as_path()),
+        None => Uri::from(uri.to_string()),
+    }
+}
+
+/// The inverse of [document_uri], i.e. returns the URI by which the client knows `uri`.
+pub fn client_uri(uri: &Uri) -> String {
+    match uri.scheme() {
+        Some(_) => uri.as_str().to_string(),
+        None => path_to_file_uri(Path::new(uri.as_str())),
+    }
+}
+
+/// Converts a `file://` URI to the path it refers to.
+///
+/// Returns `None` if `uri` is not a `file://` URI.
+pub fn file_uri_to_path(uri: &str) -> Option<PathBuf> {
+    let path = uri.strip_prefix("file://")?;
+    // Skip the authority, which is empty for local files
+    let path = &path[path.find('/')?..];
+
+    let mut bytes = Vec::with_capacity(path.len());
+    let mut chars = path.bytes();
+    while let Some(byte) = chars.next() {
+        if byte == b'%' {
+            let hex = [chars.next()?, chars.next()?];
+            let hex = std::str::from_utf8(&hex).ok()?;
+            bytes.push(u8::from_str_radix(hex, 16).ok()?);
+        } else {
+            bytes.push(byte);
+        }
+    }
+    String::from_utf8(bytes).ok().map(PathBuf::from)
+}
+
+/// Converts an absolute path to a `file://` URI.
+pub fn path_to_file_uri(path: &Path) -> String {
+    let mut uri = String::from("file://");
+    for byte in path.to_string_lossy().bytes() {
+        if byte.is_ascii_alphanumeric() || b"/-._~".contains(&byte) {
+            uri.push(byte as char);
+        } else {
+            uri.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    uri
+}
+
+// POSITIONS
+// ================================================================================================
+
+/// Converts `offset`, a byte offset in `source`, to a position, whose character offset is counted
+/// in UTF-16 code units, as required by the protocol.
+pub fn position(source: &SourceFile, offset: ByteIndex) -> Value {
+    let content = source.content();
+    let line = content.line_index(offset);
+    let line_start = content.line_start(line).unwrap_or_default();
+    let text = &source.as_str()[line_start.to_usize()..offset.to_usize()];
+    json!({ "line": line.to_u32(), "character": text.encode_utf16().count() })
+}
+
+/// Converts `span`, a span of `source`, to a range.
+pub fn range(source: &SourceFile, span: SourceSpan) -> Value {
+    json!({ "start": position(source, span.start()), "end": position(source, span.end()) })
+}
+
+/// Converts `position`, a position in `source`, to a byte offset.
+///
+/// Character offsets past the end of the line are clamped to the end of the line.
+pub fn offset(source: &SourceFile, position: &Value) -> Option<ByteIndex> {
+    let line = u32::try_from(position.get("line")?.as_u64()?).ok()?;
+    let character = usize::try_from(position.get("character")?.as_u64()?).ok()?;
+    let line_range = source.content().line_range(LineIndex(line))?;
+    let text = &source.as_str()[line_range.start.to_usize()..line_range.end.to_usize()];
+
+    let mut units = 0;
+    for (index, c) in text.char_indices() {
+        if units >= character || c == '\r' || c == '\n' {
+            return Some(line_range.start + index as u32);
+        }
+        units += c.len_utf16();
+    }
+    Some(line_range.end)
+}
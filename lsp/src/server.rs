@@ -0,0 +1,279 @@
+use std::{
+    collections::BTreeMap,
+    io::{self, BufRead, Write},
+};
+
+use assembly::diagnostics::{ByteIndex, Uri};
+use serde_json::{Value, json};
+
+use crate::{
+    analysis::Analysis,
+    protocol::{
+        self, INVALID_PARAMS, INVALID_REQUEST, METHOD_NOT_FOUND, document_uri, error_response,
+        notification, read_message, response, write_message,
+    },
+    workspace::Workspace,
+};
+
+/// The `MessageType` of warnings shown to the user.
+const MESSAGE_TYPE_WARNING: u32 = 2;
+
+// SERVER
+// ================================================================================================
+
+/// A language server for Miden Assembly.
+///
+/// The server is driven by the messages sent by the client, either one at a time via
+/// [Server::handle], or by [Server::run], which exchanges messages with the client over a pair of
+/// streams, e.g. stdin and stdout.
+#[derive(Default)]
+pub struct Server {
+    workspace: Workspace,
+    /// The analyses of the open documents, which are rebuilt when the contents of a document
+    /// change, rather than for each request
+    analyses: BTreeMap<Uri, Analysis>,
+    /// Set once the client has requested the server to shut down
+    shutdown: bool,
+    /// Set once the client has notified the server to exit
+    exited: bool,
+}
+
+impl Server {
+    /// Creates a new server, which must be initialized by the client before use.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns true if the client requested the server to shut down before notifying it to exit.
+    pub fn was_shut_down(&self) -> bool {
+        self.shutdown
+    }
+
+    /// Handles the messages read from `input`, writing the messages sent in reply to `output`,
+    /// until the client notifies the server to exit, or closes `input`.
+    pub fn run(&mut self, input: &mut impl BufRead, output: &mut impl Write) -> io::Result<()> {
+        while !self.exited {
+            let Some(message) = read_message(input)? else {
+                break;
+            };
+            for reply in self.handle(message) {
+                write_message(output, &reply)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Handles `message`, a request or notification from the client, and returns the messages to
+    /// send to the client in reply.
+    pub fn handle(&mut self, message: Value) -> Vec<Value> {
+        let Some(method) = message.get("method").and_then(Value::as_str) else {
+            // Responses to requests sent by the server are ignored, as it sends none
+            return Vec::new();
+        };
+        let params = message.get("params").unwrap_or(&Value::Null);
+        match message.get("id") {
+            Some(id) => self.handle_request(id.clone(), method, params),
+            None => self.handle_notification(method, params),
+        }
+    }
+
+    fn handle_request(&mut self, id: Value, method: &str, params: &Value) -> Vec<Value> {
+        if self.shutdown {
+            return vec![error_response(id, INVALID_REQUEST, "the server was shut down")];
+        }
+
+        let result = match method {
+            "initialize" => return self.initialize(id, params),
+            "shutdown" => {
+                self.shutdown = true;
+                Ok(Value::Null)
+            },
+            "textDocument/definition" => {
+                self.analyze_at(params, |analysis, offset| analysis.goto_definition(offset))
+            },
+            "textDocument/hover" => {
+                self.analyze_at(params, |analysis, offset| analysis.hover(offset))
+            },
+            "textDocument/completion" => self.complete(params),
+            "textDocument/documentSymbol" => {
+                self.analyze(params, |analysis| Ok(analysis.document_symbols()))
+            },
+            _ => {
+                let message = format!("unsupported method `{method}`");
+                return vec![error_response(id, METHOD_NOT_FOUND, message)];
+            },
+        };
+        match result {
+            Ok(result) => vec![response(id, result)],
+            Err(message) => vec![error_response(id, INVALID_PARAMS, message)],
+        }
+    }
+
+    fn handle_notification(&mut self, method: &str, params: &Value) -> Vec<Value> {
+        let client_uri = params.pointer("/textDocument/uri").and_then(Value::as_str);
+        match (method, client_uri) {
+            ("textDocument/didOpen", Some(client_uri)) => {
+                let text = params.pointer("/textDocument/text").and_then(Value::as_str);
+                self.set_document(client_uri, text.unwrap_or_default().to_string());
+                vec![self.publish_diagnostics(client_uri)]
+            },
+            ("textDocument/didChange", Some(client_uri)) => {
+                // The server only supports full synchronization, so the last change holds the
+                // whole contents of the document
+                let changes = params.get("contentChanges").and_then(Value::as_array);
+                let text = changes
+                    .and_then(|changes| changes.last())
+                    .and_then(|change| change.get("text"))
+                    .and_then(Value::as_str);
+                if let Some(text) = text {
+                    self.set_document(client_uri, text.to_string());
+                }
+                Vec::new()
+            },
+            ("textDocument/didSave", Some(client_uri)) => {
+                if let Some(text) = params.get("text").and_then(Value::as_str) {
+                    self.set_document(client_uri, text.to_string());
+                }
+                vec![self.publish_diagnostics(client_uri)]
+            },
+            ("textDocument/didClose", Some(client_uri)) => {
+                self.workspace.remove_document(&document_uri(client_uri));
+                // The other documents are analyzed against the contents of the file on disk from
+                // now on
+                self.analyses.clear();
+                let params = json!({ "uri": client_uri, "diagnostics": [] });
+                vec![notification("textDocument/publishDiagnostics", params)]
+            },
+            ("exit", _) => {
+                self.exited = true;
+                Vec::new()
+            },
+            _ => Vec::new(),
+        }
+    }
+
+    fn initialize(&mut self, id: Value, params: &Value) -> Vec<Value> {
+        let errors = self.workspace.configure(params);
+        let result = json!({
+            "capabilities": {
+                "textDocumentSync": {
+                    "openClose": true,
+                    "change": 1,
+                    "save": { "includeText": true },
+                },
+                "definitionProvider": true,
+                "hoverProvider": true,
+                "completionProvider": { "triggerCharacters": [".", ":"] },
+                "documentSymbolProvider": true,
+            },
+            "serverInfo": { "name": "miden-lsp", "version": env!("CARGO_PKG_VERSION") },
+        });
+
+        let mut replies = vec![response(id, result)];
+        replies.extend(errors.into_iter().map(|message| {
+            let params = json!({ "type": MESSAGE_TYPE_WARNING, "message": message });
+            notification("window/showMessage", params)
+        }));
+        replies
+    }
+
+    /// Sets the contents of the document identified by `client_uri`, and analyzes it.
+    fn set_document(&mut self, client_uri: &str, text: String) {
+        let uri = document_uri(client_uri);
+        self.workspace.set_document(uri.clone(), text.clone());
+        // The other documents may refer to this one, so they are analyzed again when needed
+        self.analyses.clear();
+        let analysis = Analysis::new(&self.workspace, &uri, text);
+        self.analyses.insert(uri, analysis);
+    }
+
+    fn publish_diagnostics(&mut self, client_uri: &str) -> Value {
+        let uri = document_uri(client_uri);
+        // Documents which are not open have nothing to report
+        let diagnostics = self.analysis(&uri).map(Analysis::diagnostics).unwrap_or_default();
+        let params = json!({ "uri": client_uri, "diagnostics": diagnostics });
+        notification("textDocument/publishDiagnostics", params)
+    }
+
+    /// Returns the analysis of the open document identified by `uri`, analyzing it if the
+    /// contents of the documents changed since it was last analyzed.
+    ///
+    /// Returns `None` if the document is not open.
+    fn analysis(&mut self, uri: &Uri) -> Option<&Analysis> {
+        if !self.analyses.contains_key(uri) {
+            let text = self.workspace.document(uri)?.to_string();
+            let analysis = Analysis::new(&self.workspace, uri, text);
+            self.analyses.insert(uri.clone(), analysis);
+        }
+        self.analyses.get(uri)
+    }
+
+    /// Calls `f` with the analysis of the document identified by the `textDocument` parameter.
+    ///
+    /// Documents which are not open in the client are analyzed from their contents on disk.
+    fn analyze<T>(
+        &mut self,
+        params: &Value,
+        f: impl FnOnce(&Analysis) -> Result<T, String>,
+    ) -> Result<T, String> {
+        let client_uri = params
+            .pointer("/textDocument/uri")
+            .and_then(Value::as_str)
+            .ok_or("missing document uri")?;
+        let uri = document_uri(client_uri);
+        if let Some(analysis) = self.analysis(&uri) {
+            return f(analysis);
+        }
+        let text = std::fs::read_to_string(uri.as_str())
+            .map_err(|_| format!("unknown document `{client_uri}`"))?;
+        f(&Analysis::new(&self.workspace, &uri, text))
+    }
+
+    /// Like [Self::analyze], but also passes `f` the offset in the document corresponding to the
+    /// `position` parameter.
+    fn analyze_at<T>(
+        &mut self,
+        params: &Value,
+        f: impl FnOnce(&Analysis, ByteIndex) -> T,
+    ) -> Result<T, String> {
+        let position = params.get("position").ok_or("missing position")?;
+        self.analyze(params, |analysis| {
+            let offset = protocol::offset(analysis.source(), position).ok_or("invalid position")?;
+            Ok(f(analysis, offset))
+        })
+    }
+
+    fn complete(&mut self, params: &Value) -> Result<Value, String> {
+        // Either the completions, or the word being completed and the contents of the document
+        // with that word replaced by a placeholder
+        let completions = self.analyze_at(params, |analysis, offset| {
+            // The word being completed is the text preceding the cursor, up to the previous
+            // whitespace
+            let text = analysis.source().as_str();
+            let content = analysis.source().content();
+            let line_start = content.line_start(content.line_index(offset)).unwrap_or_default();
+            let line = &text[line_start.to_usize()..offset.to_usize()];
+            let word_start = line_start.to_usize()
+                + line
+                    .char_indices()
+                    .rev()
+                    .find(|(_, c)| c.is_whitespace())
+                    .map(|(index, c)| index + c.len_utf8())
+                    .unwrap_or(0);
+            let word = &text[word_start..offset.to_usize()];
+
+            if analysis.module().is_none() && !word.is_empty() {
+                let text = format!("{}nop{}", &text[..word_start], &text[offset.to_usize()..]);
+                return Err((word.to_string(), analysis.source().uri().clone(), text));
+            }
+            Ok(analysis.completions(word))
+        })?;
+
+        // An incomplete word usually prevents the document from being parsed, in which case it is
+        // analyzed again with the word replaced by a placeholder instruction, so that the
+        // procedures and imports of the document can still be completed
+        completions.or_else(|(word, uri, text)| {
+            Ok(Analysis::new(&self.workspace, &uri, text).completions(&word))
+        })
+    }
+}
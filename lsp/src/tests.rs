@@ -0,0 +1,392 @@
+use std::{
+    fs,
+    io::Cursor,
+    path::{Path, PathBuf},
+};
+
+use serde_json::{Value, json};
+
+use crate::{
+    Server,
+    protocol::{path_to_file_uri, read_message, write_message},
+};
+
+// HELPERS
+// ================================================================================================
+
+/// Creates a fresh directory named `name` in the temporary directory, containing `files`.
+fn workspace_dir(name: &str, files: &[(&str, &str)]) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("miden_lsp_{name}"));
+    let _ = fs::remove_dir_all(&dir);
+    for (path, text) in files {
+        let path = dir.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, text).unwrap();
+    }
+    dir
+}
+
+/// Returns a server initialized with `options`, for the workspace rooted at `root`.
+fn server(root: &Path, options: Value) -> Server {
+    let mut server = Server::new();
+    let replies = server.handle(json!({
+        "jsonrpc": "2.0",
+        "id": 0,
+        "method": "initialize",
+        "params": { "rootUri": path_to_file_uri(root), "initializationOptions": options },
+    }));
+    assert_eq!(replies.len(), 1, "unexpected replies: {replies:?}");
+    server
+}
+
+/// Opens the document `uri`, and returns the diagnostics published for it.
+fn open(server: &mut Server, uri: &str, text: &str) -> Vec<Value> {
+    let replies = server.handle(json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/didOpen",
+        "params": { "textDocument": { "uri": uri, "languageId": "masm", "version": 1, "text": text } },
+    }));
+    assert_eq!(replies.len(), 1);
+    assert_eq!(replies[0]["method"], "textDocument/publishDiagnostics");
+    assert_eq!(replies[0]["params"]["uri"], uri);
+    replies[0]["params"]["diagnostics"].as_array().unwrap().clone()
+}
+
+/// Sends a request regarding the document `uri`, and returns its result.
+fn request(server: &mut Server, method: &str, uri: &str, position: Option<Value>) -> Value {
+    let mut params = json!({ "textDocument": { "uri": uri } });
+    if let Some(position) = position {
+        params["position"] = position;
+    }
+    let replies =
+        server.handle(json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params }));
+    assert_eq!(replies.len(), 1);
+    assert_eq!(replies[0]["id"], 1);
+    assert!(replies[0].get("error").is_none(), "request failed: {}", replies[0]);
+    replies[0]["result"].clone()
+}
+
+/// Returns the position in `text` of the character `offset` bytes after the first occurrence of
+/// `needle`.
+fn position_of(text: &str, needle: &str, offset: usize) -> Value {
+    let index = text.find(needle).expect("needle not found") + offset;
+    let line = text[..index].matches('\n').count();
+    let line_start = text[..index].rfind('\n').map(|i| i + 1).unwrap_or(0);
+    json!({ "line": line, "character": index - line_start })
+}
+
+fn labels(items: &Value) -> Vec<&str> {
+    items
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|item| item["label"].as_str().unwrap())
+        .collect()
+}
+
+// LIFECYCLE
+// ================================================================================================
+
+#[test]
+fn lsp_lifecycle() {
+    let mut server = Server::new();
+    let replies = server.handle(json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "initialize",
+        "params": { "initializationOptions": { "libraries": ["missing.masl"] } },
+    }));
+    assert_eq!(replies.len(), 2);
+    let capabilities = &replies[0]["result"]["capabilities"];
+    assert_eq!(capabilities["textDocumentSync"]["change"], 1);
+    assert_eq!(capabilities["definitionProvider"], true);
+    assert_eq!(capabilities["completionProvider"]["triggerCharacters"], json!([".", ":"]));
+    assert_eq!(replies[1]["method"], "window/showMessage");
+    assert!(replies[1]["params"]["message"].as_str().unwrap().contains("missing.masl"));
+
+    let replies = server.handle(json!({ "jsonrpc": "2.0", "id": 2, "method": "unknown/method" }));
+    assert_eq!(replies[0]["error"]["code"], -32601);
+
+    let replies = server.handle(json!({ "jsonrpc": "2.0", "id": 3, "method": "shutdown" }));
+    assert_eq!(replies[0]["result"], Value::Null);
+    assert!(server.was_shut_down());
+    let replies = server.handle(json!({ "jsonrpc": "2.0", "id": 4, "method": "shutdown" }));
+    assert_eq!(replies[0]["error"]["code"], -32600);
+}
+
+#[test]
+fn lsp_run_over_streams() {
+    let mut input = Vec::new();
+    for message in [
+        json!({ "jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {} }),
+        json!({ "jsonrpc": "2.0", "id": 2, "method": "shutdown" }),
+        json!({ "jsonrpc": "2.0", "method": "exit" }),
+        json!({ "jsonrpc": "2.0", "id": 3, "method": "shutdown" }),
+    ] {
+        write_message(&mut input, &message).unwrap();
+    }
+
+    let mut output = Vec::new();
+    let mut server = Server::new();
+    server.run(&mut Cursor::new(input), &mut output).unwrap();
+    assert!(server.was_shut_down());
+
+    // Messages following the exit notification are not handled
+    let mut output = Cursor::new(output);
+    assert_eq!(read_message(&mut output).unwrap().unwrap()["id"], 1);
+    assert_eq!(read_message(&mut output).unwrap().unwrap()["id"], 2);
+    assert!(read_message(&mut output).unwrap().is_none());
+}
+
+// DIAGNOSTICS
+// ================================================================================================
+
+#[test]
+fn lsp_diagnostics() {
+    let dir = workspace_dir("diagnostics", &[]);
+    let mut server = server(&dir, json!({}));
+    let uri = path_to_file_uri(&dir.join("main.masm"));
+
+    let text = "begin\n    push.1 add.\nend\n";
+    let diagnostics = open(&mut server, &uri, text);
+    assert_eq!(diagnostics.len(), 1, "{diagnostics:?}");
+    assert_eq!(diagnostics[0]["severity"], 1);
+    assert!(diagnostics[0]["message"].as_str().unwrap().starts_with("invalid syntax"));
+    assert_eq!(diagnostics[0]["range"]["start"], json!({ "line": 2, "character": 0 }));
+
+    // Each procedure with a syntax error gets its own diagnostic
+    let text = "proc.foo\n    push.1 add.\nend\n\nbegin\n    mul.\nend\n";
+    let diagnostics = open(&mut server, &uri, text);
+    assert_eq!(diagnostics.len(), 2, "{diagnostics:?}");
+    assert_eq!(diagnostics[0]["range"]["start"], json!({ "line": 2, "character": 0 }));
+    assert_eq!(diagnostics[1]["range"]["start"], json!({ "line": 6, "character": 0 }));
+
+    let text = "use.std::math::missing\nconst.UNUSED=1\n\nbegin\n    exec.missing::foo\nend\n";
+    let diagnostics = open(&mut server, &uri, text);
+    assert_eq!(diagnostics.len(), 2, "{diagnostics:?}");
+    assert_eq!(diagnostics[0]["severity"], 2);
+    assert!(diagnostics[0]["message"].as_str().unwrap().contains("unused"));
+    assert_eq!(diagnostics[0]["range"]["start"], json!({ "line": 1, "character": 0 }));
+    assert_eq!(diagnostics[1]["severity"], 1);
+    assert_eq!(diagnostics[1]["message"], "undefined module 'std::math::missing'");
+    assert_eq!(diagnostics[1]["range"]["start"], position_of(text, "foo", 0));
+
+    // Diagnostics are published again on save, and cleared on close
+    let replies = server.handle(json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/didSave",
+        "params": { "textDocument": { "uri": uri }, "text": "begin\n    push.1\nend\n" },
+    }));
+    assert_eq!(replies[0]["params"]["diagnostics"], json!([]));
+    let replies = server.handle(json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/didClose",
+        "params": { "textDocument": { "uri": uri } },
+    }));
+    assert_eq!(replies[0]["params"]["diagnostics"], json!([]));
+}
+
+// NAVIGATION
+// ================================================================================================
+
+const MATH: &str = "\
+#! Arithmetic helpers.
+
+#! Doubles the value on top of the stack.
+@inputs(felt)
+@outputs(felt)
+export.double.1
+    loc_store.0 loc_load.0 loc_load.0 add
+end
+
+export.triple
+    dup dup add add
+end
+";
+
+const MAIN: &str = "\
+use.app::math
+use.std::math::u64
+
+proc.helper
+    exec.math::double
+end
+
+begin
+    push.1 exec.helper
+    push.2.0.3.0 exec.u64::wrapping_add
+end
+";
+
+#[test]
+fn lsp_goto_definition() {
+    let dir = workspace_dir("definition", &[("src/math.masm", MATH)]);
+    let mut server = server(&dir, json!({ "sources": { "app": "src" } }));
+    let uri = path_to_file_uri(&dir.join("main.masm"));
+    assert_eq!(open(&mut server, &uri, MAIN), Vec::<Value>::new());
+
+    let math_uri = path_to_file_uri(&dir.join("src/math.masm"));
+    let location = request(
+        &mut server,
+        "textDocument/definition",
+        &uri,
+        Some(position_of(MAIN, "math::double", 8)),
+    );
+    assert_eq!(location["uri"], math_uri);
+    assert_eq!(location["range"]["start"], json!({ "line": 5, "character": 7 }));
+    assert_eq!(location["range"]["end"], json!({ "line": 5, "character": 13 }));
+
+    let location = request(
+        &mut server,
+        "textDocument/definition",
+        &uri,
+        Some(position_of(MAIN, "exec.helper", 7)),
+    );
+    assert_eq!(location["uri"], uri);
+    assert_eq!(location["range"]["start"], json!({ "line": 3, "character": 5 }));
+
+    // Imports resolve to the imported module
+    let location =
+        request(&mut server, "textDocument/definition", &uri, Some(position_of(MAIN, "app", 0)));
+    assert_eq!(location["uri"], math_uri);
+
+    // Procedures of compiled libraries have no source
+    let location = request(
+        &mut server,
+        "textDocument/definition",
+        &uri,
+        Some(position_of(MAIN, "wrapping_add", 0)),
+    );
+    assert_eq!(location, Value::Null);
+}
+
+#[test]
+fn lsp_hover() {
+    let dir = workspace_dir("hover", &[("src/math.masm", MATH)]);
+    let mut server = server(&dir, json!({ "sources": { "app": "src" } }));
+    let uri = path_to_file_uri(&dir.join("main.masm"));
+    open(&mut server, &uri, MAIN);
+
+    let hover =
+        request(&mut server, "textDocument/hover", &uri, Some(position_of(MAIN, "double", 0)));
+    let text = hover["contents"]["value"].as_str().unwrap();
+    assert!(text.starts_with("```masm\nexport.double.1\n```"), "{text}");
+    assert!(text.contains("Defined in `app::math`"), "{text}");
+    assert!(text.contains("Signature: `(felt) -> (felt)`"), "{text}");
+    assert!(text.ends_with("Doubles the value on top of the stack."), "{text}");
+    assert_eq!(hover["range"]["start"], position_of(MAIN, "double", 0));
+
+    let hover = request(
+        &mut server,
+        "textDocument/hover",
+        &uri,
+        Some(position_of(MAIN, "wrapping_add", 3)),
+    );
+    let text = hover["contents"]["value"].as_str().unwrap();
+    assert!(text.starts_with("```masm\nexport.wrapping_add\n```"), "{text}");
+    assert!(text.contains("Defined in `std::math::u64`"), "{text}");
+    assert!(text.contains("MAST root: `0x"), "{text}");
+
+    let hover =
+        request(&mut server, "textDocument/hover", &uri, Some(position_of(MAIN, "push.1", 0)));
+    assert_eq!(hover, Value::Null);
+}
+
+#[test]
+fn lsp_analysis_is_reused_until_documents_change() {
+    let dir = workspace_dir("cache", &[("src/math.masm", MATH)]);
+    let mut server = server(&dir, json!({ "sources": { "app": "src" } }));
+    let uri = path_to_file_uri(&dir.join("main.masm"));
+    open(&mut server, &uri, MAIN);
+    let hover_text = |server: &mut Server| {
+        let hover =
+            request(server, "textDocument/hover", &uri, Some(position_of(MAIN, "double", 0)));
+        hover["contents"]["value"].as_str().unwrap().to_string()
+    };
+
+    // Changes to the workspace on disk are only picked up once the document is analyzed again
+    let math = MATH.replace("Doubles the value", "Multiplies by two the value");
+    fs::write(dir.join("src/math.masm"), &math).unwrap();
+    assert!(hover_text(&mut server).ends_with("Doubles the value on top of the stack."));
+    server.handle(json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/didChange",
+        "params": { "textDocument": { "uri": uri, "version": 2 }, "contentChanges": [{ "text": MAIN }] },
+    }));
+    assert!(hover_text(&mut server).ends_with("Multiplies by two the value on top of the stack."));
+
+    // Changes to other open documents are picked up immediately
+    let math_uri = path_to_file_uri(&dir.join("src/math.masm"));
+    open(&mut server, &math_uri, &math.replace("Multiplies by two", "Doubles"));
+    assert!(hover_text(&mut server).ends_with("Doubles the value on top of the stack."));
+}
+
+// EDITING
+// ================================================================================================
+
+#[test]
+fn lsp_completion() {
+    let dir = workspace_dir("completion", &[("src/math.masm", MATH)]);
+    let mut server = server(&dir, json!({ "sources": { "app": "src" } }));
+    let uri = path_to_file_uri(&dir.join("main.masm"));
+    open(&mut server, &uri, MAIN);
+
+    let items = request(
+        &mut server,
+        "textDocument/completion",
+        &uri,
+        Some(position_of(MAIN, "push.1", 0)),
+    );
+    assert!(labels(&items).contains(&"u32split"));
+    assert!(labels(&items).contains(&"if.true"));
+
+    // The document can not be parsed while the invocation target is incomplete
+    let text = MAIN.replace("exec.helper", "exec.");
+    server.handle(json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/didChange",
+        "params": { "textDocument": { "uri": uri, "version": 2 }, "contentChanges": [{ "text": text }] },
+    }));
+    let items = request(
+        &mut server,
+        "textDocument/completion",
+        &uri,
+        Some(position_of(&text, "exec.\n", 5)),
+    );
+    assert_eq!(labels(&items), ["helper", "math", "u64"]);
+    assert_eq!(items[1]["insertText"], "math::");
+    assert_eq!(items[2]["detail"], "std::math::u64");
+
+    let text = MAIN.replace("exec.helper", "exec.math::");
+    server.handle(json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/didChange",
+        "params": { "textDocument": { "uri": uri, "version": 3 }, "contentChanges": [{ "text": text }] },
+    }));
+    let items = request(
+        &mut server,
+        "textDocument/completion",
+        &uri,
+        Some(position_of(&text, "exec.math::\n", 11)),
+    );
+    assert_eq!(labels(&items), ["double", "triple"]);
+    assert_eq!(items[0]["detail"], "(felt) -> (felt)");
+}
+
+#[test]
+fn lsp_document_symbols() {
+    let dir = workspace_dir("symbols", &[]);
+    let mut server = server(&dir, json!({}));
+    let uri = path_to_file_uri(&dir.join("math.masm"));
+    open(&mut server, &uri, MATH);
+
+    let symbols = request(&mut server, "textDocument/documentSymbol", &uri, None);
+    let symbols = symbols.as_array().unwrap();
+    assert_eq!(symbols.len(), 2);
+    assert_eq!(symbols[0]["name"], "double");
+    assert_eq!(symbols[0]["detail"], "(felt) -> (felt)");
+    assert_eq!(symbols[0]["kind"], 12);
+    assert_eq!(symbols[0]["selectionRange"]["start"], json!({ "line": 5, "character": 7 }));
+    assert_eq!(symbols[1]["name"], "triple");
+    assert_eq!(symbols[1]["detail"], "");
+}
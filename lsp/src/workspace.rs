@@ -0,0 +1,194 @@
+//! The state of the workspace: its configuration, and the documents open in the client.
+
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use assembly::{Library, LibraryNamespace, LibraryPath, ast::Module, diagnostics::Uri};
+use serde_json::Value;
+use stdlib::StdLibrary;
+
+use crate::protocol::file_uri_to_path;
+
+// WORKSPACE
+// ================================================================================================
+
+/// The sources and libraries against which documents are analyzed, and the contents of the
+/// documents open in the client.
+///
+/// The workspace is configured using the `initializationOptions` of the `initialize` request:
+///
+/// * `sources`: an object mapping library namespaces to the directories containing the sources of
+///   those libraries, laid out like the directories passed to
+///   [assembly::Assembler::assemble_library_from_dir].
+/// * `libraries`: an array of paths to compiled libraries (i.e. `.masl` files).
+/// * `stdlib`: whether to resolve references against the Miden standard library. Defaults to true,
+///   unless the sources of the `std` namespace are part of the workspace.
+///
+/// Relative paths are resolved against the root of the workspace.
+pub struct Workspace {
+    /// The directory against which relative paths in the configuration are resolved
+    root: Option<PathBuf>,
+    /// The namespaces of the libraries whose sources are part of the workspace, and the
+    /// directories containing those sources
+    sources: Vec<(LibraryNamespace, PathBuf)>,
+    /// The compiled libraries against which references are resolved
+    libraries: Vec<Arc<Library>>,
+    /// The contents of the documents open in the client
+    documents: BTreeMap<Uri, String>,
+}
+
+impl Default for Workspace {
+    fn default() -> Self {
+        Self {
+            root: None,
+            sources: Vec::new(),
+            libraries: vec![Arc::new(StdLibrary::default().into())],
+            documents: BTreeMap::new(),
+        }
+    }
+}
+
+/// Configuration
+impl Workspace {
+    /// Configures the workspace using the parameters of the `initialize` request.
+    ///
+    /// Returns a description of each setting which could not be applied.
+    pub fn configure(&mut self, params: &Value) -> Vec<String> {
+        let mut errors = Vec::new();
+
+        self.root = params
+            .get("rootUri")
+            .and_then(Value::as_str)
+            .and_then(file_uri_to_path)
+            .or_else(|| params.get("rootPath").and_then(Value::as_str).map(PathBuf::from));
+        let options = params.get("initializationOptions").unwrap_or(&Value::Null);
+
+        self.sources.clear();
+        for (namespace, dir) in
+            options.get("sources").and_then(Value::as_object).into_iter().flatten()
+        {
+            let Some(dir) = dir.as_str() else {
+                errors.push(format!("the source directory of `{namespace}` must be a string"));
+                continue;
+            };
+            match namespace.parse::<LibraryNamespace>() {
+                Ok(namespace) => self.sources.push((namespace, self.resolve_path(dir))),
+                Err(err) => errors.push(format!("invalid namespace `{namespace}`: {err}")),
+            }
+        }
+
+        self.libraries.clear();
+        let has_std_sources = self.sources.iter().any(|(namespace, _)| namespace.as_str() == "std");
+        let stdlib = options.get("stdlib").and_then(Value::as_bool).unwrap_or(!has_std_sources);
+        if stdlib {
+            self.libraries.push(Arc::new(StdLibrary::default().into()));
+        }
+        for path in options.get("libraries").and_then(Value::as_array).into_iter().flatten() {
+            let Some(path) = path.as_str() else {
+                errors.push("library paths must be strings".to_string());
+                continue;
+            };
+            match Library::deserialize_from_file(self.resolve_path(path)) {
+                Ok(library) => self.libraries.push(Arc::new(library)),
+                Err(err) => errors.push(format!("failed to load library `{path}`: {err}")),
+            }
+        }
+
+        errors
+    }
+
+    /// Returns the compiled libraries against which references are resolved.
+    pub fn libraries(&self) -> &[Arc<Library>] {
+        &self.libraries
+    }
+
+    fn resolve_path(&self, path: &str) -> PathBuf {
+        match &self.root {
+            Some(root) => root.join(path),
+            None => PathBuf::from(path),
+        }
+    }
+}
+
+/// Documents
+impl Workspace {
+    /// Sets the contents of the document identified by `uri`.
+    pub fn set_document(&mut self, uri: Uri, text: String) {
+        self.documents.insert(uri, text);
+    }
+
+    /// Forgets the document identified by `uri`, which was closed by the client.
+    pub fn remove_document(&mut self, uri: &Uri) {
+        self.documents.remove(uri);
+    }
+
+    /// Returns the contents of the open document identified by `uri`.
+    pub fn document(&self, uri: &Uri) -> Option<&str> {
+        self.documents.get(uri).map(String::as_str)
+    }
+
+    /// Returns the contents of the source file at `path`, preferring the contents of the open
+    /// document for that file, if any, over its contents on disk.
+    pub fn read_source(&self, path: &Path) -> Option<(Uri, String)> {
+        let uri = Uri::from(path);
+        match self.documents.get(&uri) {
+            Some(text) => Some((uri, text.clone())),
+            None => fs::read_to_string(path).ok().map(|text| (uri, text)),
+        }
+    }
+
+    /// Returns the path of the module defined by the document identified by `uri`, if that
+    /// document is part of the sources of the workspace.
+    pub fn module_path(&self, uri: &Uri) -> Option<LibraryPath> {
+        let path = Path::new(uri.as_str());
+        self.sources.iter().find_map(|(namespace, dir)| {
+            let relative = path.strip_prefix(dir).ok()?;
+            module_path(namespace, relative)
+        })
+    }
+
+    /// Returns the paths of all source files in the workspace, and of the modules they define.
+    pub fn source_files(&self) -> Vec<(LibraryPath, PathBuf)> {
+        let mut files = Vec::new();
+        for (namespace, dir) in self.sources.iter() {
+            let mut stack = vec![dir.clone()];
+            while let Some(current) = stack.pop() {
+                let Ok(entries) = fs::read_dir(&current) else {
+                    continue;
+                };
+                for path in entries.filter_map(|entry| entry.ok().map(|entry| entry.path())) {
+                    if path.is_dir() {
+                        stack.push(path);
+                    } else if path.extension().is_some_and(|ext| ext == Module::FILE_EXTENSION) {
+                        let module = path
+                            .strip_prefix(dir)
+                            .ok()
+                            .and_then(|relative| module_path(namespace, relative));
+                        if let Some(module) = module {
+                            files.push((module, path));
+                        }
+                    }
+                }
+            }
+        }
+        files.sort();
+        files
+    }
+}
+
+/// Returns the path of the module defined by the file at `relative`, a path relative to the root
+/// directory of the sources of `namespace`.
+fn module_path(namespace: &LibraryNamespace, relative: &Path) -> Option<LibraryPath> {
+    let mut path = LibraryPath::from(namespace.clone());
+    for component in relative.with_extension("").iter() {
+        path.push(component.to_str()?).ok()?;
+    }
+    if path.last() == Module::ROOT {
+        path.pop();
+    }
+    Some(path)
+}
@@ -1,10 +1,12 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use assembly::{
-    Assembler, Library, LibraryNamespace,
+    Assembler, CfgFlags, CompilationCache, InliningReport, Library, LibraryNamespace,
     diagnostics::{IntoDiagnostic, Report},
 };
 use clap::Parser;
+use package::Version;
+use processor::KernelVersion;
 use stdlib::StdLibrary;
 
 #[derive(Debug, Clone, Parser)]
@@ -33,6 +35,18 @@ pub struct BundleCmd {
     /// Path of the output `.masl` file.
     #[arg(short, long)]
     output: Option<PathBuf>,
+    /// Flags against which `@cfg` attributes are evaluated, e.g. `--cfg debug`. The flags are
+    /// recorded in the library.
+    #[arg(long = "cfg", value_name = "FLAG")]
+    cfg: Vec<String>,
+    /// Path of a compilation cache file. If given, procedures which did not change since the cache
+    /// was last written are reused rather than recompiled, and the cache is updated afterwards.
+    #[arg(long, value_name = "PATH")]
+    cache: Option<PathBuf>,
+    /// Print which procedures were inlined into their callers, and the resulting impact on code
+    /// size and cycle count.
+    #[arg(long)]
+    inlining_report: bool,
 }
 
 impl BundleCmd {
@@ -41,7 +55,19 @@ impl BundleCmd {
         println!("Build library");
         println!("============================================================");
 
-        let mut assembler = Assembler::default().with_debug_mode(!self.release);
+        let cfg = self.cfg.iter().map(String::as_str).collect::<CfgFlags>();
+        let mut assembler = Assembler::default()
+            .with_debug_mode(!self.release)
+            .with_cfg_flags(cfg)
+            .with_source_root(self.source_root().to_string_lossy());
+        let cache = self.cache.as_deref().map(read_cache);
+        if let Some(cache) = &cache {
+            assembler = assembler.with_compilation_cache(cache.clone());
+        }
+        let inlining_report = self.inlining_report.then(InliningReport::new);
+        if let Some(report) = &inlining_report {
+            assembler = assembler.with_inlining_report(report.clone());
+        }
 
         if self.dir.is_file() {
             return Err(Report::msg("`dir` must be a directory."));
@@ -58,13 +84,17 @@ impl BundleCmd {
             },
         };
 
+        let version = self.version.parse::<Version>().into_diagnostic()?;
         match &self.kernel {
             Some(kernel) => {
                 if !kernel.is_file() {
                     return Err(Report::msg("`kernel` must be a file"));
                 };
                 assembler.link_dynamic_library(StdLibrary::default())?;
-                let library = assembler.assemble_kernel_from_dir(kernel, Some(&self.dir))?;
+                let name = self.namespace.as_deref().unwrap_or("kernel");
+                let library = assembler
+                    .assemble_kernel_from_dir(kernel, Some(&self.dir))?
+                    .with_version(KernelVersion::new(name, version));
                 library.write_to_file(output_file).into_diagnostic()?;
                 println!(
                     "Built kernel module {} with library {}",
@@ -79,12 +109,46 @@ impl BundleCmd {
                 };
                 let library_namespace = namespace.parse::<LibraryNamespace>()?;
                 assembler.link_dynamic_library(StdLibrary::default())?;
-                let library = assembler.assemble_library_from_dir(&self.dir, library_namespace)?;
+                let library = assembler
+                    .assemble_library_from_dir(&self.dir, library_namespace)?
+                    .with_version(version);
                 library.write_to_file(output_file).into_diagnostic()?;
                 println!("Built library {namespace}");
             },
         }
 
+        if let Some(report) = &inlining_report {
+            println!("Inlining report:\n{report}");
+        }
+
+        if let (Some(cache), Some(path)) = (&cache, &self.cache) {
+            println!("Compilation cache: {}", cache.report());
+            cache.write_to_file(path).into_diagnostic()?;
+        }
+
         Ok(())
     }
 }
+
+impl BundleCmd {
+    /// Returns the directory against which the source map of the library is computed, i.e. the
+    /// library directory, or its parent when building a kernel, since the kernel module is then
+    /// expected to be next to the library directory.
+    fn source_root(&self) -> &Path {
+        match (&self.kernel, self.dir.parent()) {
+            (Some(_), Some(parent)) => parent,
+            _ => &self.dir,
+        }
+    }
+}
+
+/// Reads the compilation cache at `path`, or starts a new one if there is no valid cache there.
+fn read_cache(path: &Path) -> CompilationCache {
+    if !path.exists() {
+        return CompilationCache::new();
+    }
+    CompilationCache::deserialize_from_file(path).unwrap_or_else(|err| {
+        println!("Discarding compilation cache at {}: {err}", path.display());
+        CompilationCache::new()
+    })
+}
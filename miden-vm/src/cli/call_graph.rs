@@ -0,0 +1,59 @@
+use std::{fs, path::PathBuf};
+
+use assembly::diagnostics::{IntoDiagnostic, Report, WrapErr};
+use clap::{Parser, ValueEnum};
+use package::MastArtifact;
+
+use super::utils::get_mast_artifact;
+
+#[derive(Debug, Clone, Parser)]
+#[command(
+    about = "Render the call graph of a compiled library, program or package",
+    long_about = "Render the call graph of a compiled library (.masl), program (.masb) or package \
+                  (.masp), including procedures invoked via exec, call and syscall, references to \
+                  procedures of other libraries, and dynamic call sites."
+)]
+pub struct CallGraphCmd {
+    /// Path to the .masl, .masb or .masp file
+    #[arg(value_parser)]
+    input: PathBuf,
+    /// Format in which to render the call graph
+    #[arg(short = 'f', long = "format", value_enum, default_value_t = CallGraphFormat::Dot)]
+    format: CallGraphFormat,
+    /// Path to output file; the call graph is printed to stdout if not provided
+    #[arg(short = 'o', long = "output", value_parser)]
+    output_file: Option<PathBuf>,
+}
+
+/// The formats in which a call graph can be rendered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum CallGraphFormat {
+    /// Graphviz DOT
+    Dot,
+    /// JSON
+    Json,
+}
+
+impl CallGraphCmd {
+    pub fn execute(&self) -> Result<(), Report> {
+        let call_graph = match get_mast_artifact(&self.input)? {
+            MastArtifact::Library(library) => library.call_graph(),
+            MastArtifact::Executable(program) => program.call_graph(),
+        };
+
+        let rendered = match self.format {
+            CallGraphFormat::Dot => call_graph.to_dot(),
+            CallGraphFormat::Json => call_graph.to_json(),
+        };
+
+        match &self.output_file {
+            Some(path) => fs::write(path, rendered)
+                .into_diagnostic()
+                .wrap_err_with(|| format!("Failed to write file `{}`", path.display())),
+            None => {
+                println!("{rendered}");
+                Ok(())
+            },
+        }
+    }
+}
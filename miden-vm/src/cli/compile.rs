@@ -1,6 +1,9 @@
 use std::path::PathBuf;
 
-use assembly::diagnostics::{IntoDiagnostic, Report, WrapErr};
+use assembly::{
+    CfgFlags,
+    diagnostics::{IntoDiagnostic, Report, WrapErr},
+};
 use clap::Parser;
 
 use super::data::{Debug, Libraries, ProgramFile};
@@ -17,6 +20,9 @@ pub struct CompileCmd {
     /// Path to output file
     #[arg(short = 'o', long = "output", value_parser)]
     output_file: Option<PathBuf>,
+    /// Flags against which `@cfg` attributes are evaluated, e.g. `--cfg debug`
+    #[arg(long = "cfg", value_name = "FLAG")]
+    cfg: Vec<String>,
 }
 
 impl CompileCmd {
@@ -26,7 +32,8 @@ impl CompileCmd {
         println!("============================================================");
 
         // load the program from file and parse it
-        let program = ProgramFile::read(&self.assembly_file)?;
+        let cfg = self.cfg.iter().map(String::as_str).collect::<CfgFlags>();
+        let program = ProgramFile::read(&self.assembly_file, &cfg)?;
 
         // load libraries from files
         let libraries = Libraries::new(&self.library_paths)?;
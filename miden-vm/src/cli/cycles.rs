@@ -0,0 +1,52 @@
+use std::path::PathBuf;
+
+use assembly::diagnostics::Report;
+use clap::Parser;
+use package::MastArtifact;
+use vm_core::mast::CycleEstimate;
+
+use super::utils::get_mast_artifact;
+
+#[derive(Debug, Clone, Parser)]
+#[command(
+    about = "Estimate the cycle counts of a compiled library, program or package",
+    long_about = "Statically estimate the number of VM cycles needed to execute the exported \
+                  procedures of a compiled library (.masl), or the entrypoint of a compiled \
+                  program (.masb) or package (.masp). Exact counts are reported for procedures \
+                  without control flow; otherwise, a range `min..=max` is reported, where `max` \
+                  is a polynomial in the maximum number of iterations `n` of each loop, or \
+                  `min..` if the procedure calls procedures unknown at compile time."
+)]
+pub struct CyclesCmd {
+    /// Path to the .masl, .masb or .masp file
+    #[arg(value_parser)]
+    input: PathBuf,
+    /// Evaluate the bounds of loops assuming that each loop iterates at most this many times
+    #[arg(short = 'n', long = "loop-iterations")]
+    loop_iterations: Option<u64>,
+}
+
+impl CyclesCmd {
+    pub fn execute(&self) -> Result<(), Report> {
+        match get_mast_artifact(&self.input)? {
+            MastArtifact::Library(library) => {
+                for (name, estimate) in library.estimate_cycles() {
+                    println!("{name}: {}", self.evaluate(estimate));
+                }
+            },
+            MastArtifact::Executable(program) => {
+                println!("entrypoint: {}", self.evaluate(program.estimate_cycles()));
+            },
+        }
+
+        Ok(())
+    }
+
+    /// Evaluates the loop bounds of `estimate` if a number of loop iterations was specified.
+    fn evaluate(&self, estimate: CycleEstimate) -> CycleEstimate {
+        match self.loop_iterations {
+            Some(iterations) => estimate.with_loop_iterations(iterations),
+            None => estimate,
+        }
+    }
+}
@@ -6,7 +6,7 @@ use std::{
 };
 
 use assembly::{
-    Assembler, Library, LibraryNamespace, SourceManager,
+    Assembler, CfgFlags, Library, LibraryNamespace, SourceManager,
     ast::{Module, ModuleKind},
     diagnostics::{Report, WrapErr},
     report,
@@ -114,22 +114,25 @@ pub struct ProgramFile {
 
 /// Helper methods to interact with masm program file.
 impl ProgramFile {
-    /// Reads the masm file at the specified path and parses it into a [ProgramFile].
-    pub fn read(path: impl AsRef<Path>) -> Result<Self, Report> {
+    /// Reads the masm file at the specified path and parses it into a [ProgramFile], evaluating
+    /// its `@cfg` attributes against `cfg`.
+    pub fn read(path: impl AsRef<Path>, cfg: &CfgFlags) -> Result<Self, Report> {
         let source_manager = Arc::new(assembly::DefaultSourceManager::default());
-        Self::read_with(path, source_manager)
+        Self::read_with(path, source_manager, cfg)
     }
 
     /// Reads the masm file at the specified path and parses it into a [ProgramFile], using the
     /// provided [assembly::SourceManager] implementation.
-    #[instrument(name = "read_program_file", skip(source_manager), fields(path = %path.as_ref().display()))]
+    #[instrument(name = "read_program_file", skip(source_manager, cfg), fields(path = %path.as_ref().display()))]
     pub fn read_with(
         path: impl AsRef<Path>,
         source_manager: Arc<dyn SourceManager + Send + Sync>,
+        cfg: &CfgFlags,
     ) -> Result<Self, Report> {
         // parse the program into an AST
         let path = path.as_ref();
         let mut parser = Module::parser(ModuleKind::Executable);
+        parser.set_cfg_flags(cfg.clone());
         let ast = parser
             .parse_file(LibraryNamespace::Exec.into(), path, &source_manager)
             .wrap_err_with(|| format!("Failed to parse program file `{}`", path.display()))?;
@@ -11,14 +11,14 @@ pub struct DebugExecutor {
     vm_state_iter: VmStateIterator,
     vm_state: VmState,
     // TODO(pauls): Use this to render source-level diagnostics when program errors are encountered
-    #[allow(unused)]
     source_manager: Arc<dyn assembly::SourceManager>,
 }
 
 impl DebugExecutor {
     // CONSTRUCTOR
     // --------------------------------------------------------------------------------------------
-    /// Returns a new DebugExecutor for the specified program, stack and advice inputs.
+    /// Returns a new DebugExecutor for the specified program, stack and advice inputs, executed
+    /// against `host`.
     ///
     /// # Errors
     /// Returns an error if the command cannot be parsed.
@@ -26,13 +26,14 @@ impl DebugExecutor {
         program: Program,
         stack_inputs: StackInputs,
         advice_inputs: AdviceInputs,
+        mut host: DefaultHost,
         source_manager: Arc<dyn assembly::SourceManager>,
     ) -> Result<Self, String> {
         let mut vm_state_iter = processor::execute_iter(
             &program,
             stack_inputs,
             advice_inputs,
-            &mut DefaultHost::default(),
+            &mut host,
             source_manager.clone(),
         );
         let vm_state = vm_state_iter
@@ -127,9 +128,18 @@ impl DebugExecutor {
     // ACCESSORS
     // --------------------------------------------------------------------------------------------
 
-    /// Prints general VM state information.
+    /// Prints general VM state information, and the source location of the current instruction
+    /// if it is known.
     fn print_vm_state(&self) {
-        println!("{}", self.vm_state)
+        println!("{}", self.vm_state);
+        let location = self.vm_state.asmop.as_ref().and_then(|asmop| asmop.as_ref().location());
+        if let Some(span) =
+            location.and_then(|location| self.source_manager.location_to_span(location.clone()))
+        {
+            if let Ok(file_line_col) = self.source_manager.file_line_col(span) {
+                println!("at {file_line_col}");
+            }
+        }
     }
 
     /// Prints all stack items.
@@ -1,9 +1,13 @@
 use std::{path::PathBuf, sync::Arc};
 
-use assembly::{DefaultSourceManager, SourceManager, diagnostics::Report};
+use assembly::{
+    DefaultSourceManager, SourceManager,
+    diagnostics::{IntoDiagnostic, Report, SourceManagerExt, WrapErr},
+};
 use clap::Parser;
-use miden_vm::internal::InputFile;
+use miden_vm::{DefaultHost, internal::InputFile};
 use rustyline::{Config, DefaultEditor, EditMode, error::ReadlineError};
+use stdlib::StdLibrary;
 
 use super::data::Libraries;
 
@@ -33,6 +37,11 @@ pub struct DebugCmd {
     /// Paths to .masl library files
     #[arg(short = 'l', long = "libraries", value_parser)]
     library_paths: Vec<PathBuf>,
+
+    /// Directory against which the source maps of the libraries are resolved, so that the source
+    /// locations of their procedures can be displayed
+    #[arg(long = "source-root", value_name = "DIR")]
+    source_root: Option<PathBuf>,
 }
 
 impl DebugCmd {
@@ -63,6 +72,16 @@ impl DebugCmd {
         };
         let program_hash: [u8; 32] = program.hash().into();
 
+        // load the sources of the libraries, so that their procedures can be debugged
+        if let Some(source_root) = &self.source_root {
+            for library in libraries.libraries.iter() {
+                source_manager
+                    .load_source_map(library.source_map(), source_root)
+                    .into_diagnostic()
+                    .wrap_err("Failed to load the sources of a library")?;
+            }
+        }
+
         println!("Debugging program with hash {}...", hex::encode(program_hash));
 
         // load input data from file
@@ -72,9 +91,16 @@ impl DebugCmd {
         let stack_inputs = input_data.parse_stack_inputs().map_err(Report::msg)?;
         let advice_inputs = input_data.parse_advice_inputs().map_err(Report::msg)?;
 
+        let mut host = DefaultHost::default();
+        host.load_mast_forest(StdLibrary::default().mast_forest().clone())
+            .into_diagnostic()?;
+        for library in libraries.libraries.iter() {
+            host.load_mast_forest(library.mast_forest().clone()).into_diagnostic()?;
+        }
+
         // instantiate DebugExecutor
         let mut debug_executor =
-            DebugExecutor::new(program, stack_inputs, advice_inputs, source_manager)
+            DebugExecutor::new(program, stack_inputs, advice_inputs, host, source_manager)
                 .map_err(Report::msg)?;
 
         // build readline config
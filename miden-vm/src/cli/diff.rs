@@ -0,0 +1,51 @@
+use std::path::PathBuf;
+
+use assembly::diagnostics::{IntoDiagnostic, Report};
+use clap::Parser;
+use package::MastArtifact;
+use vm_core::mast::MastForestDiff;
+
+use super::utils::get_mast_artifact;
+
+#[derive(Debug, Clone, Parser)]
+#[command(
+    about = "Compare the procedures of two compiled libraries, programs or packages",
+    long_about = "Compare the procedures of two compiled libraries (.masl), programs (.masb) or \
+                  packages (.masp). Library procedures are matched by their exported names, and \
+                  for every changed procedure the first differing node is reported."
+)]
+pub struct DiffCmd {
+    /// Path to the old .masl, .masb or .masp file
+    #[arg(value_parser)]
+    old: PathBuf,
+    /// Path to the new .masl, .masb or .masp file
+    #[arg(value_parser)]
+    new: PathBuf,
+}
+
+impl DiffCmd {
+    pub fn execute(&self) -> Result<(), Report> {
+        let old = get_mast_artifact(&self.old)?;
+        let new = get_mast_artifact(&self.new)?;
+
+        match (old, new) {
+            (MastArtifact::Library(old), MastArtifact::Library(new)) => {
+                let diff = old.diff(&new).into_diagnostic()?;
+                print!("{diff}");
+            },
+            (MastArtifact::Executable(old), MastArtifact::Executable(new)) => {
+                let diff = MastForestDiff::compute(
+                    old.mast_forest(),
+                    [("entrypoint", old.entrypoint())],
+                    new.mast_forest(),
+                    [("entrypoint", new.entrypoint())],
+                )
+                .into_diagnostic()?;
+                print!("{diff}");
+            },
+            _ => return Err(Report::msg("cannot compare a library with a program")),
+        }
+
+        Ok(())
+    }
+}
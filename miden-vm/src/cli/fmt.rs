@@ -0,0 +1,89 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use assembly::{
+    DefaultSourceManager,
+    diagnostics::{IntoDiagnostic, Report, SourceManagerExt, WrapErr},
+    format::format_source,
+};
+use clap::Parser;
+
+#[derive(Debug, Clone, Parser)]
+#[command(
+    about = "Format Miden Assembly source files",
+    long_about = "Format Miden Assembly source files in place, preserving comments and \
+                  docstrings. Directories are searched recursively for .masm files. With \
+                  `--check`, files are left untouched, and the command fails if any of them is \
+                  not formatted."
+)]
+pub struct FmtCmd {
+    /// Paths to .masm files, or directories containing them
+    #[arg(value_parser, required = true)]
+    paths: Vec<PathBuf>,
+    /// Only check whether the files are formatted, without modifying them
+    #[arg(long)]
+    check: bool,
+}
+
+impl FmtCmd {
+    pub fn execute(&self) -> Result<(), Report> {
+        let mut files = Vec::new();
+        for path in self.paths.iter() {
+            collect_masm_files(path, &mut files)?;
+        }
+
+        let source_manager = DefaultSourceManager::default();
+        let mut unformatted = 0;
+        for file in files {
+            let source = source_manager
+                .load_file(&file)
+                .into_diagnostic()
+                .wrap_err_with(|| format!("failed to load source file `{}`", file.display()))?;
+            let formatted = format_source(source.clone())
+                .wrap_err_with(|| format!("failed to format `{}`", file.display()))?;
+            if formatted == source.as_str() {
+                continue;
+            }
+
+            if self.check {
+                println!("{} is not formatted", file.display());
+                unformatted += 1;
+            } else {
+                fs::write(&file, formatted)
+                    .into_diagnostic()
+                    .wrap_err_with(|| format!("failed to write `{}`", file.display()))?;
+            }
+        }
+
+        if unformatted > 0 {
+            return Err(Report::msg(format!("{unformatted} file(s) are not formatted")));
+        }
+
+        Ok(())
+    }
+}
+
+/// Adds `path` to `files` if it is a file, or all .masm files under `path` if it is a directory.
+fn collect_masm_files(path: &Path, files: &mut Vec<PathBuf>) -> Result<(), Report> {
+    if !path.is_dir() {
+        files.push(path.to_path_buf());
+        return Ok(());
+    }
+
+    let mut entries = fs::read_dir(path)
+        .into_diagnostic()
+        .wrap_err_with(|| format!("failed to read directory `{}`", path.display()))?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<Vec<_>, _>>()
+        .into_diagnostic()?;
+    entries.sort();
+    for entry in entries {
+        if entry.is_dir() || entry.extension().is_some_and(|ext| ext == "masm") {
+            collect_masm_files(&entry, files)?;
+        }
+    }
+
+    Ok(())
+}
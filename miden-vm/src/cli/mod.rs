@@ -1,7 +1,11 @@
 mod bundle;
+mod call_graph;
 mod compile;
+mod cycles;
 pub mod data;
 mod debug;
+mod diff;
+mod fmt;
 mod prove;
 mod repl;
 mod run;
@@ -9,8 +13,12 @@ pub mod utils;
 mod verify;
 
 pub use bundle::BundleCmd;
+pub use call_graph::CallGraphCmd;
 pub use compile::CompileCmd;
+pub use cycles::CyclesCmd;
 pub use debug::DebugCmd;
+pub use diff::DiffCmd;
+pub use fmt::FmtCmd;
 pub use prove::ProveCmd;
 pub use repl::ReplCmd;
 pub use run::RunCmd;
@@ -1,9 +1,10 @@
 use std::{fs, path::Path, sync::Arc};
 
 use assembly::{
-    SourceManager,
+    CfgFlags, Library, SourceManager,
     diagnostics::{IntoDiagnostic, Report, WrapErr},
 };
+use miden_vm::Program;
 use package::{MastArtifact, Package};
 use prover::utils::Deserializable;
 
@@ -32,8 +33,39 @@ pub fn get_masm_program(
     debug_on: bool,
 ) -> Result<(vm_core::Program, Arc<dyn SourceManager>), Report> {
     let debug_mode = if debug_on { Debug::On } else { Debug::Off };
-    let program_file = ProgramFile::read(path)?;
+    let program_file = ProgramFile::read(path, &CfgFlags::default())?;
     let program = program_file.compile(debug_mode, &libraries.libraries)?;
 
     Ok((program, program_file.source_manager().clone()))
 }
+
+/// Returns a `MastArtifact` from a `.masl` library, `.masb` program or `.masp` package file, based
+/// on the extension of the file.
+pub fn get_mast_artifact(path: &Path) -> Result<MastArtifact, Report> {
+    let ext = path.extension().and_then(|s| s.to_str()).unwrap_or("").to_lowercase();
+    let bytes = fs::read(path)
+        .into_diagnostic()
+        .wrap_err_with(|| format!("Failed to read file `{}`", path.display()))?;
+
+    let artifact = match ext.as_str() {
+        "masl" => Library::read_from_bytes(&bytes)
+            .map(|library| MastArtifact::Library(Arc::new(library)))
+            .into_diagnostic()
+            .wrap_err("Failed to deserialize library")?,
+        "masb" => Program::read_from_bytes(&bytes)
+            .map(|program| MastArtifact::Executable(Arc::new(program)))
+            .into_diagnostic()
+            .wrap_err("Failed to deserialize program")?,
+        "masp" => Package::read_from_bytes(&bytes)
+            .into_diagnostic()
+            .wrap_err("Failed to deserialize package")?
+            .into_mast_artifact(),
+        _ => {
+            return Err(Report::msg(
+                "The provided file must have a .masl, .masb or .masp extension",
+            ));
+        },
+    };
+
+    Ok(artifact)
+}
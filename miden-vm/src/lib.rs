@@ -11,8 +11,8 @@ pub use assembly::{
 };
 pub use processor::{
     AdviceInputs, AdviceProvider, AsmOpInfo, AsyncHost, BaseHost, DefaultHost, ExecutionError,
-    ExecutionTrace, Kernel, Operation, Program, ProgramInfo, StackInputs, SyncHost, VmState,
-    VmStateIterator, ZERO, crypto, execute, execute_iter, utils,
+    ExecutionTrace, Kernel, KernelVersion, Operation, Program, ProgramInfo, StackInputs, SyncHost,
+    VmState, VmStateIterator, ZERO, crypto, execute, execute_iter, utils,
 };
 pub use prover::{
     ExecutionProof, FieldExtension, HashFunction, InputError, Proof, ProvingOptions, StackOutputs,
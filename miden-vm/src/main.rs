@@ -30,8 +30,12 @@ pub struct Cli {
 pub enum Actions {
     Analyze(tools::Analyze),
     Compile(cli::CompileCmd),
+    Cycles(cli::CyclesCmd),
     Bundle(cli::BundleCmd),
+    CallGraph(cli::CallGraphCmd),
     Debug(cli::DebugCmd),
+    Diff(cli::DiffCmd),
+    Fmt(cli::FmtCmd),
     Prove(cli::ProveCmd),
     Run(cli::RunCmd),
     Verify(cli::VerifyCmd),
@@ -45,8 +49,12 @@ impl Cli {
         match &self.action {
             Actions::Analyze(analyze) => analyze.execute(),
             Actions::Compile(compile) => compile.execute(),
+            Actions::Cycles(cycles) => cycles.execute(),
             Actions::Bundle(compile) => compile.execute(),
+            Actions::CallGraph(call_graph) => call_graph.execute(),
             Actions::Debug(debug) => debug.execute(),
+            Actions::Diff(diff) => diff.execute(),
+            Actions::Fmt(fmt) => fmt.execute(),
             Actions::Prove(prove) => prove.execute(),
             Actions::Run(run) => run.execute(),
             Actions::Verify(verify) => verify.execute(),
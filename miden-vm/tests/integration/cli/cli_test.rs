@@ -104,6 +104,33 @@ fn cli_bundle_kernel_noexports() {
     fs::remove_file(&output_file).unwrap()
 }
 
+/// Internal procedures are not exported, so a library with only internal procedures has no
+/// exports either.
+#[test]
+fn cli_bundle_internal_only() {
+    let mut cmd = bin_under_test().command();
+    cmd.arg("bundle").arg("./tests/integration/cli/data/lib_internal");
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("library must contain at least one exported procedure"));
+}
+
+/// A kernel can bundle with a library with only internal procedures.
+#[test]
+fn cli_bundle_kernel_internal_only() {
+    let output_file = std::env::temp_dir().join("cli_bundle_kernel_internal_only.masl");
+
+    let mut cmd = bin_under_test().command();
+    cmd.arg("bundle")
+        .arg("./tests/integration/cli/data/lib_internal")
+        .arg("--kernel")
+        .arg("./tests/integration/cli/data/kernel_main.masm")
+        .arg("--output")
+        .arg(output_file.as_path());
+    cmd.assert().success();
+    fs::remove_file(&output_file).unwrap()
+}
+
 #[test]
 fn cli_bundle_output() {
     let mut cmd = bin_under_test().command();
@@ -147,8 +174,10 @@ fn test_debug_adv_stack_all() -> Result<(), Box<dyn std::error::Error>> {
         .arg("./tests/integration/cli/data/debug_adv_stack.inputs");
     cmd.assert().success();
 
+    // `push.12 drop` is removed by the peephole optimizer, so the decorator is executed before
+    // the first operation of the block
     cmd.assert().stdout(predicate::str::contains(
-        "Advice Stack state before step 2:
+        "Advice Stack state before step 1:
 ├──  0: 42
 └──  1: 21
 ",
@@ -166,8 +195,10 @@ fn test_debug_adv_stack_prefix() -> Result<(), Box<dyn std::error::Error>> {
         .arg("./tests/integration/cli/data/debug_adv_stack.inputs");
     cmd.assert().success();
 
+    // `push.12 drop` is removed by the peephole optimizer, so the decorator is executed before
+    // the first operation of the block
     cmd.assert().stdout(predicate::str::contains(
-        "Advice Stack state before step 2:
+        "Advice Stack state before step 1:
 └──  0: 42
 ",
     ));
@@ -182,3 +213,157 @@ fn test_advmap_cli() -> Result<(), Box<dyn std::error::Error>> {
     cmd.assert().success();
     Ok(())
 }
+
+#[test]
+fn cli_diff_libraries() -> Result<(), Box<dyn std::error::Error>> {
+    let old_lib = std::env::temp_dir().join("cli_diff_old.masl");
+    let new_dir = std::env::temp_dir().join("cli_diff_new");
+    let new_lib = std::env::temp_dir().join("cli_diff_new.masl");
+
+    let mut cmd = bin_under_test().command();
+    cmd.arg("bundle")
+        .arg("./tests/integration/cli/data/lib")
+        .arg("--namespace")
+        .arg("lib")
+        .arg("--output")
+        .arg(old_lib.as_path());
+    cmd.assert().success();
+
+    fs::create_dir_all(&new_dir)?;
+    fs::write(new_dir.join("lib.masm"), "export.lib_proc\n    swap drop\nend\n")?;
+    let mut cmd = bin_under_test().command();
+    cmd.arg("bundle")
+        .arg(new_dir.as_path())
+        .arg("--namespace")
+        .arg("lib")
+        .arg("--output")
+        .arg(new_lib.as_path());
+    cmd.assert().success();
+
+    let mut cmd = bin_under_test().command();
+    cmd.arg("diff").arg(old_lib.as_path()).arg(old_lib.as_path());
+    cmd.assert().success().stdout(predicate::str::contains("no differences"));
+
+    let mut cmd = bin_under_test().command();
+    cmd.arg("diff").arg(old_lib.as_path()).arg(new_lib.as_path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("~ lib::lib::lib_proc"))
+        .stdout(predicate::str::contains("operation 1 changed from <end of block> to drop"));
+
+    fs::remove_file(&old_lib)?;
+    fs::remove_file(&new_lib)?;
+    fs::remove_dir_all(&new_dir)?;
+    Ok(())
+}
+
+#[test]
+fn cli_call_graph() -> Result<(), Box<dyn std::error::Error>> {
+    let lib_dir = std::env::temp_dir().join("cli_call_graph");
+    let lib_file = std::env::temp_dir().join("cli_call_graph.masl");
+
+    fs::create_dir_all(&lib_dir)?;
+    fs::write(
+        lib_dir.join("lib.masm"),
+        "export.helper\n    swap drop\nend\n\nexport.main\n    call.helper dynexec\nend\n",
+    )?;
+    let mut cmd = bin_under_test().command();
+    cmd.arg("bundle")
+        .arg(lib_dir.as_path())
+        .arg("--namespace")
+        .arg("test")
+        .arg("--output")
+        .arg(lib_file.as_path());
+    cmd.assert().success();
+
+    let mut cmd = bin_under_test().command();
+    cmd.arg("call-graph").arg(lib_file.as_path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("digraph callgraph"))
+        .stdout(predicate::str::contains("p0 [label=\"test::lib::helper\"]"))
+        .stdout(predicate::str::contains("p1 -> p0 [label=\"call\"]"))
+        .stdout(predicate::str::contains("p1 -> dynamic [label=\"dyn\"]"));
+
+    let mut cmd = bin_under_test().command();
+    cmd.arg("call-graph").arg(lib_file.as_path()).arg("--format").arg("json");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("\"names\":[\"test::lib::main\"]"))
+        .stdout(predicate::str::contains("{\"caller\":1,\"callee\":0,\"kind\":\"call\"}"));
+
+    fs::remove_file(&lib_file)?;
+    fs::remove_dir_all(&lib_dir)?;
+    Ok(())
+}
+
+#[test]
+fn cli_cycles() -> Result<(), Box<dyn std::error::Error>> {
+    let lib_dir = std::env::temp_dir().join("cli_cycles");
+    let lib_file = std::env::temp_dir().join("cli_cycles.masl");
+
+    fs::create_dir_all(&lib_dir)?;
+    fs::write(
+        lib_dir.join("lib.masm"),
+        "export.straight\n    swap drop\nend\n\n\
+         export.branch\n    if.true\n        add\n    else\n        add mul\n    end\nend\n\n\
+         export.looping\n    while.true\n        add\n    end\nend\n",
+    )?;
+    let mut cmd = bin_under_test().command();
+    cmd.arg("bundle")
+        .arg(lib_dir.as_path())
+        .arg("--namespace")
+        .arg("test")
+        .arg("--output")
+        .arg(lib_file.as_path());
+    cmd.assert().success();
+
+    let mut cmd = bin_under_test().command();
+    cmd.arg("cycles").arg(lib_file.as_path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("test::lib::straight: 4\n"))
+        .stdout(predicate::str::contains("test::lib::branch: 5..=6\n"))
+        .stdout(predicate::str::contains("test::lib::looping: 2..=4n+2\n"));
+
+    let mut cmd = bin_under_test().command();
+    cmd.arg("cycles").arg(lib_file.as_path()).arg("--loop-iterations").arg("3");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("test::lib::straight: 4\n"))
+        .stdout(predicate::str::contains("test::lib::looping: 2..=14\n"));
+
+    fs::remove_file(&lib_file)?;
+    fs::remove_dir_all(&lib_dir)?;
+    Ok(())
+}
+
+#[test]
+fn cli_fmt() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = std::env::temp_dir().join("cli_fmt");
+    let file = dir.join("lib.masm");
+
+    fs::create_dir_all(&dir)?;
+    fs::write(&file, "use.std::mem\nuse.std::math::u64\nexport.foo # a comment\n  add\nend\n")?;
+
+    let mut cmd = bin_under_test().command();
+    cmd.arg("fmt").arg("--check").arg(dir.as_path());
+    cmd.assert()
+        .failure()
+        .stdout(predicate::str::contains("lib.masm is not formatted"));
+
+    let mut cmd = bin_under_test().command();
+    cmd.arg("fmt").arg(dir.as_path());
+    cmd.assert().success();
+    assert_eq!(
+        fs::read_to_string(&file)?,
+        "use.std::math::u64\nuse.std::mem\nexport.foo # a comment\n    add\nend\n"
+    );
+
+    let mut cmd = bin_under_test().command();
+    cmd.arg("fmt").arg("--check").arg(file.as_path());
+    cmd.assert().success();
+
+    fs::remove_dir_all(&dir)?;
+    Ok(())
+}
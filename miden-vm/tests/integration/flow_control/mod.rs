@@ -4,7 +4,7 @@ use assembly::{
     Assembler, LibraryPath, Report, SourceManager, ast::ModuleKind, diagnostics::SourceLanguage,
 };
 use miden_vm::Module;
-use processor::ExecutionError;
+use processor::{ExecutionError, MastNode};
 use prover::Word;
 use stdlib::StdLibrary;
 use test_utils::{StackInputs, Test, build_test, expect_exec_error_matches, push_inputs};
@@ -598,3 +598,395 @@ fn procref() -> Result<(), Report> {
     test.prove_and_verify(vec![], false);
     Ok(())
 }
+
+// MATCH
+// ================================================================================================
+
+#[test]
+fn match_execution() {
+    // the value is consumed by the match, so the result is moved over the padding of the stack
+    let source = "
+        const.TWO=2
+
+        begin
+            match
+                case.0 push.10
+                case.TWO push.20
+                case.7 push.70
+                case.1 push.11
+            default
+                push.99
+            end
+            swap drop
+        end";
+
+    for (input, output) in [(0, 10), (1, 11), (2, 20), (3, 99), (7, 70), (8, 99), (u32::MAX, 99)] {
+        let test = build_test!(source, &[input as u64]);
+        test.expect_stack(&[output]);
+    }
+
+    // a match without any case only executes the default arm
+    let source = "begin match default push.5 end swap drop end";
+    let test = build_test!(source, &[3]);
+    test.expect_stack(&[5]);
+
+    // matches with a single case compare the value to it
+    let source = "begin match case.4 push.1 default push.0 end swap drop end";
+    let test = build_test!(source, &[4]);
+    test.expect_stack(&[1]);
+    let test = build_test!(source, &[5]);
+    test.expect_stack(&[0]);
+}
+
+#[test]
+fn match_value_must_be_u32() {
+    for source in [
+        "begin match case.1 push.1 case.2 push.2 default push.0 end end",
+        "begin match case.1 push.1 default push.0 end end",
+        "begin match default push.0 end end",
+    ] {
+        let test = build_test!(source, &[u32::MAX as u64 + 1]);
+        expect_exec_error_matches!(test, ExecutionError::NotU32Value { .. });
+    }
+}
+
+/// The body of a loop which adds a step to the value on top of the stack modulo 16, then
+/// dispatches on the result, updating an accumulator stored in a local.
+const MATCH_LOOP_BODY: &str = "
+    dup.1 dup.1 u32wrapping_add u32mod.16 swap.2 drop
+    dup.1
+    match
+        case.0 loc_load.0 add.1 loc_store.0
+        case.1 loc_load.0 add.2 loc_store.0
+        case.2 loc_load.0 add.3 loc_store.0
+        case.3 loc_load.0 add.4 loc_store.0
+        case.4 loc_load.0 add.5 loc_store.0
+        case.5 loc_load.0 add.6 loc_store.0
+        case.6 loc_load.0 add.7 loc_store.0
+        case.7 loc_load.0 add.8 loc_store.0
+        case.8 loc_load.0 add.9 loc_store.0
+        case.9 loc_load.0 add.10 loc_store.0
+        case.10 loc_load.0 add.11 loc_store.0
+        case.12 loc_load.0 add.13 loc_store.0
+        case.13 loc_load.0 add.14 loc_store.0
+        case.14 loc_load.0 add.15 loc_store.0
+        case.15 loc_load.0 add.16 loc_store.0
+    default
+        loc_load.0 mul.2 loc_store.0
+    end";
+
+/// Returns a program executing [MATCH_LOOP_BODY] 16 times, either in a `repeat.16` loop, or in 16
+/// `repeat.1` loops.
+fn match_in_loop(unrolled: bool) -> String {
+    let body = if unrolled {
+        format!("repeat.1 {MATCH_LOOP_BODY} end\n").repeat(16)
+    } else {
+        format!("repeat.16 {MATCH_LOOP_BODY} end")
+    };
+    format!(
+        "
+        proc.dispatch.1
+            push.0 loc_store.0
+            {body}
+            loc_load.0 movdn.2 drop drop
+        end
+
+        begin
+            exec.dispatch
+        end"
+    )
+}
+
+/// Computes the result of [match_in_loop] for the given inputs.
+fn match_in_loop_result(value: u64, step: u64) -> u64 {
+    let (mut value, mut acc) = (value, 0);
+    for _ in 0..16 {
+        value = (value + step) % 16;
+        acc = if value == 11 { acc * 2 } else { acc + value + 1 };
+    }
+    acc
+}
+
+#[test]
+fn match_jump_table() {
+    for unrolled in [false, true] {
+        let source = match_in_loop(unrolled);
+        for (value, step) in [(0, 1), (3, 5), (11, 0), (15, 4)] {
+            let test = build_test!(&source, &[value, step]);
+            test.expect_stack(&[match_in_loop_result(value, step)]);
+        }
+
+        // the match is lowered to a jump table only when it is executed often enough per
+        // invocation of the procedure for its setup to be amortized
+        let test = build_test!(&source, &[0, 1]);
+        let (program, _) = test.compile().unwrap();
+        let has_dyn = program.mast_forest().nodes().iter().any(|node| node.is_dyn());
+        assert_eq!(has_dyn, !unrolled);
+    }
+}
+
+#[test]
+fn match_cycle_report() {
+    // dispatches on the value on top of the stack, either via a `match` or via a chain of
+    // `if.true` blocks testing each case in turn
+    let cases = (0..16).map(|case| format!("push.{}", case * 10)).collect::<Vec<_>>();
+    let with_match = format!(
+        "begin match {} default push.999 end swap drop end",
+        cases
+            .iter()
+            .enumerate()
+            .map(|(case, body)| format!("case.{case} {body}"))
+            .collect::<Vec<_>>()
+            .join(" ")
+    );
+    let with_ifs = format!(
+        "begin {} drop push.999 {}swap drop end",
+        cases
+            .iter()
+            .enumerate()
+            .map(|(case, body)| format!("dup eq.{case} if.true drop {body} else"))
+            .collect::<Vec<_>>()
+            .join(" "),
+        "end ".repeat(cases.len())
+    );
+
+    std::println!("value | match (tree) | if.true chain");
+    for value in [0, 3, 7, 12, 15, 16] {
+        let cycles = |source: &str| {
+            let test = build_test!(source, &[value]);
+            let trace = test.execute().unwrap();
+            let expected = if value < 16 { value * 10 } else { 999 };
+            assert_eq!(trace.stack_outputs().get_stack_item(0).unwrap().as_int(), expected);
+            trace.trace_len_summary().main_trace_len()
+        };
+        let (match_cycles, if_cycles) = (cycles(&with_match), cycles(&with_ifs));
+        std::println!("{value:>5} | {match_cycles:>12} | {if_cycles:>13}");
+        // a binary search only does worse than testing the first few cases in turn
+        if value >= 7 {
+            assert!(match_cycles < if_cycles, "{match_cycles} >= {if_cycles} for {value}");
+        }
+    }
+
+    // once its setup is amortized, the jump table is cheaper than the tree
+    std::println!("step | jump table | tree");
+    for step in [1, 3, 7] {
+        let cycles = |unrolled: bool| {
+            let test = build_test!(&match_in_loop(unrolled), &[0, step]);
+            test.execute().unwrap().trace_len_summary().main_trace_len()
+        };
+        let (table_cycles, tree_cycles) = (cycles(false), cycles(true));
+        std::println!("{step:>4} | {table_cycles:>10} | {tree_cycles:>4}");
+        assert!(table_cycles < tree_cycles, "{table_cycles} >= {tree_cycles} for step {step}");
+    }
+}
+
+// FOR LOOPS
+// ================================================================================================
+
+#[test]
+fn for_loop_execution() {
+    // the counter is pushed on the stack before each iteration, and consumed by the body
+    let source = "begin push.0 for.5 add end swap drop end";
+    let test = build_test!(source, &[]);
+    test.expect_stack(&[10]);
+
+    let source = "
+        const.START=3
+
+        begin
+            push.0 for.START..7 add end swap drop
+        end";
+    let test = build_test!(source, &[]);
+    test.expect_stack(&[18]);
+
+    // nested loops have counters of their own
+    let source = "
+        begin
+            push.0
+            for.3
+                for.1..3 dup.1 mul movup.2 add swap end
+                drop
+            end
+            swap drop
+        end";
+    let test = build_test!(source, &[]);
+    test.expect_stack(&[9]);
+
+    // the locals declared by the procedure are unaffected by the counter
+    let source = "
+        proc.foo.1
+            push.7 loc_store.0
+            push.0 for.4 loc_load.0 add add end
+            swap drop
+        end
+
+        begin
+            exec.foo
+        end";
+    let test = build_test!(source, &[]);
+    test.expect_stack(&[34]);
+
+    // the body is not unrolled
+    let (program, _) = test.compile().unwrap();
+    assert!(
+        program
+            .mast_forest()
+            .nodes()
+            .iter()
+            .any(|node| matches!(node, MastNode::Loop(_)))
+    );
+}
+
+#[test]
+fn for_loop_with_match() {
+    // a match executed in a for loop is lowered to a jump table, stored in the same frame as the
+    // loop counter
+    let cases = (0..16)
+        .map(|case| format!("case.{case} push.{}", case * 10))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let source = format!(
+        "
+        proc.foo.1
+            push.1 loc_store.0
+            push.0 for.64 u32mod.16 match {cases} default push.999 end add end
+            loc_load.0 add
+        end
+
+        begin
+            exec.foo swap drop
+        end"
+    );
+    let test = build_test!(&source, &[]);
+    test.expect_stack(&[4 * (0..16).map(|case| case * 10).sum::<u64>() + 1]);
+
+    let (program, _) = test.compile().unwrap();
+    assert!(program.mast_forest().nodes().iter().any(|node| node.is_dyn()));
+}
+
+#[test]
+fn repeat_rolling() {
+    let source = "
+        proc.foo.1
+            push.2 loc_store.0
+            push.1 repeat.20 loc_load.0 mul end
+            repeat.3 push.1 add end
+        end
+
+        begin
+            exec.foo swap drop
+        end";
+
+    let execute = |assembler: Assembler| {
+        let program = assembler.assemble_program(source).unwrap();
+        let outputs = processor::execute(
+            &program,
+            StackInputs::default(),
+            Default::default(),
+            &mut miden_vm::DefaultHost::default(),
+            Default::default(),
+            Arc::new(assembly::DefaultSourceManager::default()),
+        )
+        .unwrap()
+        .stack_outputs()
+        .get_stack_item(0)
+        .unwrap();
+        let num_loops = program
+            .mast_forest()
+            .nodes()
+            .iter()
+            .filter(|node| matches!(node, MastNode::Loop(_)))
+            .count();
+        (outputs.as_int(), num_loops)
+    };
+
+    // only loops iterating at least as many times as the threshold are rolled
+    assert_eq!(execute(Assembler::default()), ((1 << 20) + 3, 0));
+    assert_eq!(
+        execute(Assembler::default().with_repeat_rolling_threshold(10)),
+        ((1 << 20) + 3, 1)
+    );
+    assert_eq!(
+        execute(Assembler::default().with_repeat_rolling_threshold(3)),
+        ((1 << 20) + 3, 2)
+    );
+}
+
+// STATIC CYCLE ESTIMATES
+// ================================================================================================
+
+#[test]
+fn static_cycle_estimates_match_execution() {
+    // programs without control flow have exact estimates
+    let exact_sources = [
+        "begin push.1 push.2 add drop end",
+        "begin repeat.49 swap dup.1 add end end",
+        "begin push.1.2.3.4 push.5 dropw drop end",
+        "proc.foo add end begin push.1 exec.foo push.1 call.foo drop end",
+    ];
+    for source in exact_sources {
+        let test = build_test!(source, &[1, 2, 3]);
+        let (program, _) = test.compile().unwrap();
+        let trace = test.execute().unwrap();
+
+        let estimate = program.estimate_cycles();
+        assert_eq!(
+            estimate.as_exact(),
+            Some(trace.trace_len_summary().main_trace_len() as u64),
+            "wrong estimate for `{source}`"
+        );
+    }
+
+    // branches and loops are bounded by the estimate
+    let bounded_sources = [
+        ("begin dup.1 dup.1 eq if.true add else push.1 push.2 mul mul end end", &[2, 3]),
+        ("begin dup.1 dup.1 eq if.true add else push.1 push.2 mul mul end end", &[3, 3]),
+        ("begin dup eq.0 while.true add end end", &[10, 3]),
+        ("begin push.1 while.true push.0 end end", &[1, 1]),
+    ];
+    for (source, inputs) in bounded_sources {
+        let test = build_test!(source, inputs);
+        let (program, _) = test.compile().unwrap();
+        let trace = test.execute().unwrap();
+
+        let estimate = program.estimate_cycles();
+        let cycles = trace.trace_len_summary().main_trace_len() as u64;
+        assert!(estimate.contains(cycles), "{cycles} cycles not in {estimate} for `{source}`");
+    }
+
+    // loops are bounded by the estimate evaluated at their maximum number of iterations
+    let loop_sources = [
+        ("begin push.0 while.true push.0 end end", &[][..], 0),
+        ("begin push.1 while.true push.0 end end", &[], 1),
+        ("begin push.1 while.true sub.1 dup neq.0 end drop end", &[5], 5),
+        (
+            "begin push.1 while.true push.3 push.1 while.true sub.1 dup neq.0 end drop sub.1 dup neq.0 end drop end",
+            &[4],
+            4,
+        ),
+    ];
+    for (source, inputs, iterations) in loop_sources {
+        let test = build_test!(source, inputs);
+        let (program, _) = test.compile().unwrap();
+        let trace = test.execute().unwrap();
+
+        let estimate = program.estimate_cycles().with_loop_iterations(iterations);
+        let cycles = trace.trace_len_summary().main_trace_len() as u64;
+        assert!(estimate.contains(cycles), "{cycles} cycles not in {estimate} for `{source}`");
+    }
+    // the estimate evaluated at 0 iterations is exact for programs whose loops are not entered
+    let unentered_loop_sources = [
+        "begin push.0 while.true add end end",
+        "begin push.0 while.true add end push.0 while.true mul end end",
+        "begin push.1 if.true push.0 while.true add end push.0 while.true mul end else add end end",
+    ];
+    for source in unentered_loop_sources {
+        let test = build_test!(source, &[1, 2]);
+        let (program, _) = test.compile().unwrap();
+        let trace = test.execute().unwrap();
+
+        let estimate = program.estimate_cycles().with_loop_iterations(0);
+        let cycles = trace.trace_len_summary().main_trace_len() as u64;
+        assert_eq!(estimate.max(), Some(cycles), "wrong estimate {estimate} for `{source}`");
+    }
+}
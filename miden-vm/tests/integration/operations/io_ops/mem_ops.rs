@@ -228,3 +228,53 @@ fn read_after_write() {
     let test = build_op_test!("mem_storew.0 dropw mem_loadw.0", &[1, 2, 3, 4, 5, 6, 7, 8]);
     test.expect_stack(&[8, 7, 6, 5]);
 }
+
+// DATA SEGMENTS
+// ================================================================================================
+
+#[test]
+fn data_segments_are_initialized() {
+    // one segment with an even number of words, and one with an odd number of words
+    let source = format!(
+        "
+        {TRUNCATE_STACK_PROC}
+
+        data.8 = [1, 2, 3, 4, 5, 6, 7, 8]
+        data.100 = [9, 10, 11, 12]
+
+        begin
+            mem_load.8 mem_load.15 padw mem_loadw.100
+            exec.truncate_stack
+        end"
+    );
+
+    let test = build_test!(source, &[]);
+    test.expect_stack(&[12, 11, 10, 9, 8, 1]);
+
+    // one segment spanning three words
+    let source = format!(
+        "
+        {TRUNCATE_STACK_PROC}
+
+        data.0 = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12]
+
+        begin
+            padw mem_loadw.8 padw mem_loadw.0
+            exec.truncate_stack
+        end"
+    );
+
+    let test = build_test!(source, &[]);
+    test.expect_stack(&[4, 3, 2, 1, 12, 11, 10, 9]);
+
+    // the operand stack is left unchanged by the initialization
+    let source = "
+        data.0 = [1, 2, 3, 4]
+
+        begin
+            nop
+        end";
+
+    let test = build_test!(source, &[5, 6, 7]);
+    test.expect_stack(&[7, 6, 5]);
+}
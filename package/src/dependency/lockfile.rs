@@ -0,0 +1,230 @@
+use alloc::{
+    collections::BTreeMap,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::{fmt, str::FromStr};
+
+use miden_assembly_syntax::{
+    Version,
+    diagnostics::{Diagnostic, miette},
+};
+use miden_core::Word;
+
+// LOCK FILE
+// ================================================================================================
+
+/// A record of the versions selected when resolving the dependencies of a package, see
+/// [crate::PackageRegistry::resolve], so that the same versions can be selected again later.
+///
+/// The lock file is stored in a TOML-compatible text format, produced by its [fmt::Display]
+/// implementation and parsed by its [FromStr] implementation:
+///
+/// ```toml
+/// version = 1
+///
+/// [[package]]
+/// name = "std"
+/// version = "0.15.0"
+/// digest = "0x..."
+/// dependencies = []
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Lockfile {
+    packages: BTreeMap<String, LockedDependency>,
+}
+
+/// A package recorded in a [Lockfile].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LockedDependency {
+    /// The name of the package.
+    pub name: String,
+    /// The version of the package which was selected.
+    pub version: Version,
+    /// The digest of the MAST artifact of the package.
+    pub digest: Word,
+    /// The names of the dependencies of the package, in alphabetical order.
+    pub dependencies: Vec<String>,
+}
+
+impl Lockfile {
+    /// The version of the lock file format.
+    pub const FORMAT_VERSION: u32 = 1;
+
+    /// Creates a lock file recording `packages`.
+    pub fn new(packages: impl IntoIterator<Item = LockedDependency>) -> Self {
+        let packages = packages.into_iter().map(|package| (package.name.clone(), package));
+        Self { packages: packages.collect() }
+    }
+
+    /// Returns the locked package named `name`, if any.
+    pub fn get(&self, name: &str) -> Option<&LockedDependency> {
+        self.packages.get(name)
+    }
+
+    /// Returns an iterator over the locked packages, in order of their names.
+    pub fn packages(&self) -> impl Iterator<Item = &LockedDependency> + '_ {
+        self.packages.values()
+    }
+}
+
+impl fmt::Display for Lockfile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "# This file is generated by the package dependency resolver.")?;
+        writeln!(f, "# It is not intended for manual editing.")?;
+        writeln!(f, "version = {}", Self::FORMAT_VERSION)?;
+        for package in self.packages.values() {
+            writeln!(f)?;
+            writeln!(f, "[[package]]")?;
+            writeln!(f, "name = \"{}\"", package.name)?;
+            writeln!(f, "version = \"{}\"", package.version)?;
+            writeln!(f, "digest = \"{}\"", package.digest)?;
+            let dependencies = package
+                .dependencies
+                .iter()
+                .map(|name| format!("\"{name}\""))
+                .collect::<Vec<_>>();
+            writeln!(f, "dependencies = [{}]", dependencies.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Represents errors that occur when parsing a [Lockfile]
+#[derive(Debug, thiserror::Error, Diagnostic)]
+pub enum LockfileError {
+    #[error("invalid lock file: line {line}: {message}")]
+    #[diagnostic()]
+    Invalid { line: usize, message: String },
+    #[error("invalid lock file: unsupported format version {0}, expected {expected}", expected = Lockfile::FORMAT_VERSION)]
+    #[diagnostic(help("the lock file was generated by a different version of the resolver"))]
+    UnsupportedVersion(u32),
+}
+
+impl FromStr for Lockfile {
+    type Err = LockfileError;
+
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        let mut format_version = None;
+        let mut packages = Vec::new();
+        let mut current: Option<PartialDependency> = None;
+
+        for (index, line) in source.lines().enumerate() {
+            let line_number = index + 1;
+            let invalid = |message: &str| LockfileError::Invalid {
+                line: line_number,
+                message: message.to_string(),
+            };
+
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if line == "[[package]]" {
+                if let Some(package) = current.take() {
+                    packages.push(package.finish().map_err(&invalid)?);
+                }
+                current = Some(PartialDependency::default());
+                continue;
+            }
+
+            let (key, value) =
+                line.split_once('=').ok_or_else(|| invalid("expected `key = value`"))?;
+            let (key, value) = (key.trim(), value.trim());
+            match current.as_mut() {
+                None if key == "version" => {
+                    let version =
+                        value.parse::<u32>().map_err(|_| invalid("invalid format version"))?;
+                    format_version = Some(version);
+                },
+                None => return Err(invalid("unexpected key outside of a [[package]] table")),
+                Some(package) => match key {
+                    "name" => {
+                        package.name = Some(
+                            parse_string(value)
+                                .ok_or_else(|| invalid("expected a string"))?
+                                .to_string(),
+                        )
+                    },
+                    "version" => {
+                        let version =
+                            parse_string(value).ok_or_else(|| invalid("expected a string"))?;
+                        package.version = Some(
+                            version
+                                .parse::<Version>()
+                                .map_err(|error| invalid(&error.to_string()))?,
+                        );
+                    },
+                    "digest" => {
+                        let digest =
+                            parse_string(value).ok_or_else(|| invalid("expected a string"))?;
+                        package.digest =
+                            Some(Word::try_from(digest).map_err(|_| invalid("invalid digest"))?);
+                    },
+                    "dependencies" => {
+                        let list = value
+                            .strip_prefix('[')
+                            .and_then(|value| value.strip_suffix(']'))
+                            .ok_or_else(|| invalid("expected a list of strings"))?;
+                        package.dependencies = list
+                            .split(',')
+                            .map(str::trim)
+                            .filter(|item| !item.is_empty())
+                            .map(|item| {
+                                parse_string(item)
+                                    .map(ToString::to_string)
+                                    .ok_or_else(|| invalid("expected a string"))
+                            })
+                            .collect::<Result<_, _>>()?;
+                    },
+                    _ => return Err(invalid(&format!("unexpected key '{key}'"))),
+                },
+            }
+        }
+        if let Some(package) = current.take() {
+            let line = source.lines().count();
+            packages.push(package.finish().map_err(|message| LockfileError::Invalid {
+                line,
+                message: message.to_string(),
+            })?);
+        }
+
+        match format_version {
+            Some(Self::FORMAT_VERSION) => Ok(Self::new(packages)),
+            Some(version) => Err(LockfileError::UnsupportedVersion(version)),
+            None => Err(LockfileError::Invalid {
+                line: 1,
+                message: "missing format version".to_string(),
+            }),
+        }
+    }
+}
+
+/// A [LockedDependency] whose fields are being parsed.
+#[derive(Default)]
+struct PartialDependency {
+    name: Option<String>,
+    version: Option<Version>,
+    digest: Option<Word>,
+    dependencies: Vec<String>,
+}
+
+impl PartialDependency {
+    fn finish(self) -> Result<LockedDependency, &'static str> {
+        Ok(LockedDependency {
+            name: self.name.ok_or("missing package name")?,
+            version: self.version.ok_or("missing package version")?,
+            digest: self.digest.ok_or("missing package digest")?,
+            dependencies: self.dependencies,
+        })
+    }
+}
+
+/// Returns the contents of a double-quoted string without escape sequences.
+fn parse_string(value: &str) -> Option<&str> {
+    value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+        .filter(|value| !value.contains(['"', '\\']))
+}
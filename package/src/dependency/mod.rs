@@ -1,18 +1,45 @@
-use alloc::string::String;
+use alloc::{
+    format,
+    string::{String, ToString},
+};
+use core::fmt;
 
+use miden_assembly_syntax::VersionReq;
 use miden_core::utils::{
     ByteReader, ByteWriter, Deserializable, DeserializationError, Serializable,
 };
 
-use crate::Word;
+use crate::{Package, Word};
 
+pub(crate) mod lockfile;
+pub(crate) mod registry;
 pub(crate) mod resolver;
+#[cfg(test)]
+mod tests;
 
 /// The name of a dependency
 #[derive(Debug, Clone, PartialEq, Eq, derive_more::From)]
 #[cfg_attr(feature = "arbitrary", derive(proptest_derive::Arbitrary))]
 pub struct DependencyName(String);
 
+impl AsRef<str> for DependencyName {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<&str> for DependencyName {
+    fn from(name: &str) -> Self {
+        Self(name.to_string())
+    }
+}
+
+impl fmt::Display for DependencyName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
 impl Serializable for DependencyName {
     fn write_into<W: ByteWriter>(&self, target: &mut W) {
         self.0.write_into(target);
@@ -33,15 +60,52 @@ pub struct Dependency {
     /// The name of the dependency.
     /// Serves as a human-readable identifier for the dependency and a search hint for the resolver
     pub name: DependencyName,
-    /// The digest of the dependency.
+    /// The versions of the dependency which are compatible with the package declaring it.
+    /// Used by the resolver to select a version of the dependency when its digest is not known.
+    #[cfg_attr(feature = "arbitrary", proptest(value = "VersionReq::any()"))]
+    pub version: VersionReq,
+    /// The digest of the dependency, if it is pinned to a specific artifact.
     /// Serves as an ultimate source of truth for identifying the dependency.
-    #[cfg_attr(feature = "arbitrary", proptest(value = "Word::default()"))]
-    pub digest: Word,
+    #[cfg_attr(feature = "arbitrary", proptest(value = "Some(Word::default())"))]
+    pub digest: Option<Word>,
+}
+
+impl Dependency {
+    /// Creates a dependency on the package `name`, with a version matching `version`.
+    pub fn new(name: impl Into<DependencyName>, version: VersionReq) -> Self {
+        Self { name: name.into(), version, digest: None }
+    }
+
+    /// Creates a dependency on the package `name`, pinned to the artifact with the given `digest`.
+    pub fn with_digest(name: impl Into<DependencyName>, digest: Word) -> Self {
+        Self {
+            name: name.into(),
+            version: VersionReq::any(),
+            digest: Some(digest),
+        }
+    }
+
+    /// Returns true if `package` satisfies this dependency, i.e. if it has the expected name, a
+    /// version matching the version requirement, and the expected digest, if any.
+    pub fn matches(&self, package: &Package) -> bool {
+        package.name == self.name.as_ref()
+            && self.version.matches(&package.version)
+            && self.digest.is_none_or(|digest| digest == package.digest())
+    }
+
+    /// Returns a description of the packages satisfying this dependency, used in error messages.
+    pub(crate) fn requirement(&self) -> String {
+        match self.digest {
+            Some(digest) => format!("{} with digest {digest}", self.version),
+            None => self.version.to_string(),
+        }
+    }
 }
 
 impl Serializable for Dependency {
     fn write_into<W: ByteWriter>(&self, target: &mut W) {
         self.name.0.write_into(target);
+        self.version.write_into(target);
         self.digest.write_into(target);
     }
 }
@@ -49,7 +113,8 @@ impl Serializable for Dependency {
 impl Deserializable for Dependency {
     fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
         let name = DependencyName(String::read_from(source)?);
-        let digest = Word::read_from(source)?;
-        Ok(Self { name, digest })
+        let version = VersionReq::read_from(source)?;
+        let digest = Option::<Word>::read_from(source)?;
+        Ok(Self { name, version, digest })
     }
 }
@@ -0,0 +1,306 @@
+use alloc::{
+    collections::BTreeMap,
+    format,
+    string::{String, ToString},
+    sync::Arc,
+    vec::Vec,
+};
+
+use miden_assembly_syntax::{
+    Report, Version,
+    diagnostics::{Diagnostic, miette},
+};
+
+use super::{
+    Dependency,
+    lockfile::{LockedDependency, Lockfile},
+    resolver::{DependencyResolver, ResolvedDependency},
+};
+use crate::Package;
+
+// PACKAGE REGISTRY
+// ================================================================================================
+
+/// A set of packages, indexed by name and version, from which the dependencies of a package are
+/// resolved according to their version requirements.
+///
+/// A registry can be loaded from a local directory of `.masp` files with
+/// [PackageRegistry::load_dir].
+#[derive(Debug, Default, Clone)]
+pub struct PackageRegistry {
+    packages: BTreeMap<String, BTreeMap<Version, Arc<Package>>>,
+}
+
+impl PackageRegistry {
+    /// Adds `package` to this registry.
+    ///
+    /// Returns an error if a different package with the same name and version is already in the
+    /// registry.
+    pub fn add(&mut self, package: Arc<Package>) -> Result<(), Report> {
+        let versions = self.packages.entry(package.name.clone()).or_default();
+        match versions.get(&package.version) {
+            Some(prev) if prev.digest() != package.digest() => Err(Report::msg(format!(
+                "the registry contains two different packages named '{}' with version {}",
+                package.name, package.version
+            ))),
+            Some(_) => Ok(()),
+            None => {
+                versions.insert(package.version, package);
+                Ok(())
+            },
+        }
+    }
+
+    /// Returns the package named `name` with the given `version`, if it is in this registry.
+    pub fn get(&self, name: &str, version: &Version) -> Option<&Arc<Package>> {
+        self.packages.get(name).and_then(|versions| versions.get(version))
+    }
+
+    /// Returns the versions of the packages named `name` in this registry, in increasing order.
+    pub fn versions(&self, name: &str) -> impl Iterator<Item = &Version> + '_ {
+        self.packages.get(name).into_iter().flat_map(|versions| versions.keys())
+    }
+
+    /// Selects a version of each of the transitive dependencies of `package` in this registry,
+    /// see [PackageRegistry::resolve_dependencies].
+    pub fn resolve(
+        &self,
+        package: &Package,
+        lockfile: Option<&Lockfile>,
+    ) -> Result<Resolution, DependencyResolutionError> {
+        let root = format!("{}@{}", package.name, package.version);
+        self.resolve_dependencies(&root, &package.manifest.dependencies, lockfile)
+    }
+
+    /// Selects a version of each of the transitive `dependencies` of `root` in this registry.
+    ///
+    /// A single version is selected for each package name, which satisfies the requirements of all
+    /// the packages depending on it. When several versions satisfy them, the version recorded in
+    /// `lockfile`, if any, is preferred, and otherwise the greatest one. Choices which lead to
+    /// conflicting requirements further down the dependency graph are revisited, so an error is
+    /// only returned if there is no way to satisfy all the requirements.
+    pub fn resolve_dependencies(
+        &self,
+        root: &str,
+        dependencies: &[Dependency],
+        lockfile: Option<&Lockfile>,
+    ) -> Result<Resolution, DependencyResolutionError> {
+        let pending = dependencies
+            .iter()
+            .rev()
+            .map(|dependency| (dependency.clone(), root.to_string()))
+            .collect();
+        let mut selected = BTreeMap::new();
+        self.select(pending, &mut selected, lockfile)?;
+
+        let packages = selected.into_iter().map(|(name, selection)| (name, selection.package));
+        Ok(Resolution { packages: packages.collect() })
+    }
+
+    /// Selects a version for each of the `pending` dependencies, given the versions `selected`
+    /// so far, backtracking on conflicts.
+    fn select(
+        &self,
+        mut pending: Vec<(Dependency, String)>,
+        selected: &mut BTreeMap<String, Selection>,
+        lockfile: Option<&Lockfile>,
+    ) -> Result<(), DependencyResolutionError> {
+        let Some((dependency, required_by)) = pending.pop() else {
+            return Ok(());
+        };
+        let name = dependency.name.as_ref();
+
+        if let Some(selection) = selected.get(name) {
+            if !dependency.matches(&selection.package) {
+                return Err(DependencyResolutionError::Conflict {
+                    name: name.to_string(),
+                    requirement: dependency.requirement(),
+                    required_by,
+                    selected: selection.package.version,
+                    selected_by: selection.required_by.clone(),
+                });
+            }
+            return self.select(pending, selected, lockfile);
+        }
+
+        let locked = lockfile.and_then(|lockfile| lockfile.get(name));
+        let mut candidates = self
+            .packages
+            .get(name)
+            .into_iter()
+            .flat_map(|versions| versions.values().rev())
+            .filter(|package| dependency.matches(package))
+            .collect::<Vec<_>>();
+        if let Some(locked) = locked {
+            // try the locked version first, if it is still a candidate
+            candidates.sort_by_key(|package| package.version != locked.version);
+        }
+
+        let mut first_error = None;
+        for package in candidates {
+            let mut pending = pending.clone();
+            let requirer = format!("{}@{}", package.name, package.version);
+            pending.extend(
+                package
+                    .manifest
+                    .dependencies
+                    .iter()
+                    .rev()
+                    .map(|dependency| (dependency.clone(), requirer.clone())),
+            );
+            let selection = Selection {
+                package: Arc::clone(package),
+                required_by: required_by.clone(),
+            };
+            selected.insert(name.to_string(), selection);
+            match self.select(pending, selected, lockfile) {
+                Ok(()) => return Ok(()),
+                Err(error) => {
+                    selected.remove(name);
+                    first_error.get_or_insert(error);
+                },
+            }
+        }
+
+        Err(first_error.unwrap_or_else(|| DependencyResolutionError::NotFound {
+            name: name.to_string(),
+            requirement: dependency.requirement(),
+            required_by,
+            available: self.available_versions(name),
+        }))
+    }
+
+    fn available_versions(&self, name: &str) -> String {
+        let versions = self.versions(name).map(|version| version.to_string()).collect::<Vec<_>>();
+        if versions.is_empty() {
+            format!("the registry does not contain any package named '{name}'")
+        } else {
+            format!("available versions: {}", versions.join(", "))
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl PackageRegistry {
+    /// Loads a registry from the packages stored in the `.masp` files of the directory `dir`, and
+    /// its subdirectories.
+    pub fn load_dir(dir: impl AsRef<std::path::Path>) -> Result<Self, Report> {
+        use miden_assembly_syntax::diagnostics::{IntoDiagnostic, WrapErr};
+        use miden_core::utils::Deserializable;
+
+        let mut registry = Self::default();
+        let mut dirs = std::vec![dir.as_ref().to_path_buf()];
+        while let Some(dir) = dirs.pop() {
+            let entries = std::fs::read_dir(&dir)
+                .into_diagnostic()
+                .wrap_err_with(|| format!("failed to read registry directory {}", dir.display()))?;
+            for entry in entries {
+                let path = entry.into_diagnostic()?.path();
+                if path.is_dir() {
+                    dirs.push(path);
+                } else if path.extension().is_some_and(|extension| extension == "masp") {
+                    let bytes = std::fs::read(&path).into_diagnostic()?;
+                    let package = Package::read_from_bytes(&bytes)
+                        .into_diagnostic()
+                        .wrap_err_with(|| format!("failed to read package {}", path.display()))?;
+                    registry.add(Arc::new(package))?;
+                }
+            }
+        }
+        Ok(registry)
+    }
+}
+
+impl DependencyResolver for PackageRegistry {
+    /// Resolves `dependency` to the greatest version matching it in this registry, without
+    /// taking its own dependencies into account.
+    fn resolve(&self, dependency: &Dependency) -> Option<ResolvedDependency> {
+        let versions = self.packages.get(dependency.name.as_ref())?;
+        versions
+            .values()
+            .rev()
+            .find(|package| dependency.matches(package))
+            .map(|package| ResolvedDependency::from(Arc::clone(package)))
+    }
+}
+
+/// A package selected during dependency resolution, and the package whose requirement it was
+/// first selected for.
+#[derive(Debug)]
+struct Selection {
+    package: Arc<Package>,
+    required_by: String,
+}
+
+// RESOLUTION
+// ================================================================================================
+
+/// The packages selected by [PackageRegistry::resolve], one for each name.
+#[derive(Debug, Clone)]
+pub struct Resolution {
+    packages: BTreeMap<String, Arc<Package>>,
+}
+
+impl Resolution {
+    /// Returns the package selected for `name`, if it is a dependency.
+    pub fn get(&self, name: &str) -> Option<&Arc<Package>> {
+        self.packages.get(name)
+    }
+
+    /// Returns an iterator over the selected packages, in order of their names.
+    pub fn packages(&self) -> impl Iterator<Item = &Arc<Package>> + '_ {
+        self.packages.values()
+    }
+
+    /// Returns the lock file recording this resolution, so that it can be reproduced later.
+    pub fn to_lockfile(&self) -> Lockfile {
+        let packages = self.packages.values().map(|package| {
+            let mut dependencies = package
+                .manifest
+                .dependencies
+                .iter()
+                .map(|dependency| dependency.name.to_string())
+                .collect::<Vec<_>>();
+            dependencies.sort();
+            dependencies.dedup();
+            LockedDependency {
+                name: package.name.clone(),
+                version: package.version,
+                digest: package.digest(),
+                dependencies,
+            }
+        });
+        Lockfile::new(packages)
+    }
+}
+
+// ERRORS
+// ================================================================================================
+
+/// An error raised when the dependencies of a package cannot be resolved.
+#[derive(Debug, thiserror::Error, Diagnostic)]
+pub enum DependencyResolutionError {
+    #[error("no package named '{name}' matches '{requirement}', required by '{required_by}'")]
+    #[diagnostic(help("{available}"))]
+    NotFound {
+        name: String,
+        requirement: String,
+        required_by: String,
+        available: String,
+    },
+    #[error(
+        "conflicting requirements on '{name}': '{required_by}' requires '{requirement}', but \
+        version {selected} was selected for '{selected_by}'"
+    )]
+    #[diagnostic(help(
+        "only one version of each package can be used, so the requirements of '{required_by}' \
+        and '{selected_by}' must have a version in common"
+    ))]
+    Conflict {
+        name: String,
+        requirement: String,
+        required_by: String,
+        selected: Version,
+        selected_by: String,
+    },
+}
@@ -67,6 +67,6 @@ impl MemDependencyResolverByDigest {
 
 impl DependencyResolver for MemDependencyResolverByDigest {
     fn resolve(&self, dependency: &Dependency) -> Option<ResolvedDependency> {
-        self.resolved.get(&dependency.digest).cloned()
+        dependency.digest.and_then(|digest| self.resolved.get(&digest).cloned())
     }
 }
@@ -0,0 +1,143 @@
+use alloc::{
+    collections::BTreeSet,
+    format,
+    string::{String, ToString},
+    sync::Arc,
+    vec,
+    vec::Vec,
+};
+
+use miden_assembly_syntax::diagnostics::Diagnostic;
+use miden_core::Operation;
+
+use crate::{
+    Dependency, DependencyResolutionError, Lockfile, MastArtifact, MastForest, Package,
+    PackageManifest, PackageRegistry, Program, Version, VersionReq,
+};
+
+// HELPERS
+// ================================================================================================
+
+fn registry_package(name: &str, version: &str, dependencies: &[(&str, &str)]) -> Arc<Package> {
+    let mut forest = MastForest::new();
+    let entrypoint = forest.add_block(vec![Operation::Add], None).unwrap();
+    forest.make_root(entrypoint);
+    let program = Program::new(Arc::new(forest), entrypoint);
+
+    let dependencies = dependencies
+        .iter()
+        .map(|(name, req)| Dependency::new(*name, req.parse::<VersionReq>().unwrap()))
+        .collect();
+    Arc::new(Package {
+        name: name.to_string(),
+        version: version.parse().unwrap(),
+        mast: MastArtifact::Executable(Arc::new(program)),
+        manifest: PackageManifest { exports: BTreeSet::new(), dependencies },
+        account_component_metadata_bytes: None,
+    })
+}
+
+fn registry_of(packages: impl IntoIterator<Item = Arc<Package>>) -> PackageRegistry {
+    let mut registry = PackageRegistry::default();
+    for package in packages {
+        registry.add(package).unwrap();
+    }
+    registry
+}
+
+fn help_of(error: &DependencyResolutionError) -> String {
+    error.help().map(|help| help.to_string()).unwrap_or_default()
+}
+
+// DEPENDENCY RESOLUTION
+// ================================================================================================
+
+#[test]
+fn dependency_resolution_selects_compatible_versions() {
+    let registry = registry_of([
+        registry_package("a", "1.0.0", &[]),
+        registry_package("a", "1.1.0", &[]),
+        registry_package("a", "2.0.0", &[]),
+        registry_package("b", "2.1.0", &[("a", ">=1.0.0, <1.1.0")]),
+        registry_package("b", "3.0.0", &[]),
+    ]);
+    let app = registry_package("app", "0.1.0", &[("a", "^1"), ("b", "^2")]);
+
+    // the greatest version of `a` compatible with `^1` conflicts with the requirement of `b`, so
+    // the resolver falls back to the previous one
+    let resolution = registry.resolve(&app, None).unwrap();
+    let versions = resolution
+        .packages()
+        .map(|package| format!("{}@{}", package.name, package.version))
+        .collect::<Vec<_>>();
+    assert_eq!(versions, ["a@1.0.0", "b@2.1.0"]);
+
+    // the lock file survives a round trip through its text format
+    let lockfile = resolution.to_lockfile();
+    let text = lockfile.to_string();
+    assert!(text.contains("[[package]]\nname = \"b\"\nversion = \"2.1.0\""), "{text}");
+    assert!(text.contains("dependencies = [\"a\"]"), "{text}");
+    assert_eq!(text.parse::<Lockfile>().unwrap(), lockfile);
+}
+
+#[test]
+fn dependency_resolution_prefers_locked_versions() {
+    let mut registry = registry_of([registry_package("a", "1.0.0", &[])]);
+    let app = registry_package("app", "0.1.0", &[("a", "^1")]);
+    let lockfile = registry.resolve(&app, None).unwrap().to_lockfile();
+
+    // a newer compatible version is only selected without the lock file
+    registry.add(registry_package("a", "1.2.0", &[])).unwrap();
+    let locked = registry.resolve(&app, Some(&lockfile)).unwrap();
+    assert_eq!(locked.get("a").unwrap().version, Version { major: 1, minor: 0, patch: 0 });
+    let unlocked = registry.resolve(&app, None).unwrap();
+    assert_eq!(unlocked.get("a").unwrap().version, Version { major: 1, minor: 2, patch: 0 });
+}
+
+#[test]
+fn dependency_resolution_errors() {
+    let registry = registry_of([
+        registry_package("a", "1.0.0", &[]),
+        registry_package("a", "2.0.0", &[]),
+        registry_package("b", "1.0.0", &[("a", "^2")]),
+    ]);
+
+    let app = registry_package("app", "0.1.0", &[("a", "~1.0"), ("b", "1")]);
+    let error = registry.resolve(&app, None).unwrap_err();
+    assert!(matches!(error, DependencyResolutionError::Conflict { .. }));
+    assert_eq!(
+        error.to_string(),
+        "conflicting requirements on 'a': 'b@1.0.0' requires '^2', but version 1.0.0 was \
+        selected for 'app@0.1.0'"
+    );
+
+    let app = registry_package("app", "0.1.0", &[("a", "^3")]);
+    let error = registry.resolve(&app, None).unwrap_err();
+    assert_eq!(error.to_string(), "no package named 'a' matches '^3', required by 'app@0.1.0'");
+    assert!(help_of(&error).contains("available versions: 1.0.0, 2.0.0"), "{error:?}");
+
+    let app = registry_package("app", "0.1.0", &[("c", "*")]);
+    let error = registry.resolve(&app, None).unwrap_err();
+    assert!(
+        help_of(&error).contains("the registry does not contain any package named 'c'"),
+        "{error:?}"
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn dependency_registry_loads_directory() {
+    use miden_core::utils::Serializable;
+
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir_all(dir.path().join("a")).unwrap();
+    for version in ["1.0.0", "1.3.0"] {
+        let package = registry_package("a", version, &[]);
+        let path = dir.path().join("a").join(format!("{version}.masp"));
+        std::fs::write(path, package.to_bytes()).unwrap();
+    }
+
+    let registry = PackageRegistry::load_dir(dir.path()).unwrap();
+    let versions = registry.versions("a").map(ToString::to_string).collect::<Vec<_>>();
+    assert_eq!(versions, ["1.0.0", "1.3.0"]);
+}
@@ -8,11 +8,11 @@ mod artifact;
 mod dependency;
 mod package;
 
-#[cfg(test)]
+#[cfg(any(test, feature = "std"))]
 extern crate std;
 
 pub use miden_assembly_syntax::{
-    Library, LibraryPath,
+    Library, LibraryPath, Version, VersionReq,
     ast::{ProcedureName, QualifiedProcedureName},
 };
 pub use miden_core::{Program, Word, mast::MastForest};
@@ -21,6 +21,8 @@ pub use self::{
     artifact::MastArtifact,
     dependency::{
         Dependency, DependencyName,
+        lockfile::{LockedDependency, Lockfile, LockfileError},
+        registry::{DependencyResolutionError, PackageRegistry, Resolution},
         resolver::{
             DependencyResolver, LocalResolvedDependency, MemDependencyResolverByDigest,
             ResolvedDependency,
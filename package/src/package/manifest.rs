@@ -1,7 +1,10 @@
 use alloc::{collections::BTreeSet, vec::Vec};
 use core::fmt;
 
-use miden_assembly_syntax::ast::QualifiedProcedureName;
+use miden_assembly_syntax::{
+    Library,
+    ast::{ProcedureSignature, QualifiedProcedureName},
+};
 use miden_core::{Word, utils::DisplayHex};
 
 use crate::Dependency;
@@ -21,8 +24,24 @@ pub struct PackageManifest {
     pub dependencies: Vec<Dependency>,
 }
 
-/// A procedure exported by a package, along with its digest and signature (will be added after
-/// MASM type attributes are implemented).
+impl PackageManifest {
+    /// Creates a manifest exporting all procedures exported by `library`, along with their
+    /// signatures if known, and without any dependencies.
+    pub fn from_library(library: &Library) -> Self {
+        let exports = library
+            .exports()
+            .map(|name| PackageExport {
+                name: name.clone(),
+                digest: library.mast_forest()[library.get_export_node_id(name)].digest(),
+                signature: library.get_procedure_signature(name).cloned(),
+            })
+            .collect();
+
+        Self { exports, dependencies: Vec::new() }
+    }
+}
+
+/// A procedure exported by a package, along with its digest and signature.
 #[derive(Clone, PartialEq, Eq, Ord, PartialOrd)]
 #[cfg_attr(feature = "arbitrary", derive(proptest_derive::Arbitrary))]
 pub struct PackageExport {
@@ -31,14 +50,21 @@ pub struct PackageExport {
     /// The digest of the procedure exported by this package
     #[cfg_attr(feature = "arbitrary", proptest(value = "Word::default()"))]
     pub digest: Word,
+    /// The signature of the procedure exported by this package, if known
+    pub signature: Option<ProcedureSignature>,
 }
 
 impl fmt::Debug for PackageExport {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let Self { name, digest } = self;
-        f.debug_struct("PackageExport")
+        let Self { name, digest, signature } = self;
+        let mut builder = f.debug_struct("PackageExport");
+        builder
             .field("name", &format_args!("{name}"))
-            .field("digest", &format_args!("{}", DisplayHex::new(&digest.as_bytes())))
-            .finish()
+            .field("digest", &format_args!("{}", DisplayHex::new(&digest.as_bytes())));
+        match signature {
+            Some(signature) => builder.field("signature", &format_args!("{signature}")),
+            None => builder.field("signature", &None::<()>),
+        };
+        builder.finish()
     }
 }
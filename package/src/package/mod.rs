@@ -3,7 +3,7 @@ mod serialization;
 
 use alloc::{collections::BTreeSet, format, string::String, sync::Arc, vec::Vec};
 
-use miden_assembly_syntax::{Library, Report, ast::QualifiedProcedureName};
+use miden_assembly_syntax::{Library, Report, Version, ast::QualifiedProcedureName};
 use miden_core::{Program, Word};
 
 pub use self::manifest::{PackageExport, PackageManifest};
@@ -17,6 +17,9 @@ use crate::MastArtifact;
 pub struct Package {
     /// Name of the package
     pub name: String,
+    /// Version of the package, against which the version requirements of the packages depending
+    /// on it are matched
+    pub version: Version,
     /// The MAST artifact ([Program] or [Library]) of the package
     pub mast: MastArtifact,
     /// The package manifest, containing the set of exported procedures and their signatures,
@@ -98,6 +101,7 @@ impl Package {
 
             Ok(Self {
                 name: self.name.clone(),
+                version: self.version,
                 mast: MastArtifact::Executable(Arc::new(Program::new(
                     library.mast_forest().clone(),
                     node_id,
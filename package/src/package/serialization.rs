@@ -4,8 +4,9 @@
 //! - `MAGIC_PACKAGE`
 //! - `VERSION`
 //!
-//! (Package Name)
+//! (Package Name and Version)
 //! - `name` (`String`)
+//! - `version` (`Version`)
 //!
 //! (MAST Artifact)
 //! - `mast` (`MastArtifact`)
@@ -28,15 +29,20 @@
 //!     - `export` (`PackageExport`)
 //!       - `name` (`QualifiedProcedureName`)
 //!       - `digest` (`Word`)
+//!       - `signature` (`Option<ProcedureSignature>`)
 //!   - `dependencies_len` (`usize`)
 //!   - For each dependency:
 //!     - `dependency` (`Dependency`)
 //!       - `name` (`String`)
-//!       - `digest` (`Word`)
+//!       - `version` (`VersionReq`)
+//!       - `digest` (`Option<Word>`)
 
 use alloc::{collections::BTreeSet, format, string::String, sync::Arc, vec::Vec};
 
-use miden_assembly_syntax::{Library, ast::QualifiedProcedureName};
+use miden_assembly_syntax::{
+    Library, Version,
+    ast::{ProcedureSignature, QualifiedProcedureName},
+};
 use miden_core::{
     Program, Word,
     utils::{ByteReader, ByteWriter, Deserializable, DeserializationError, Serializable},
@@ -59,7 +65,7 @@ const MAGIC_LIBRARY: &[u8; 4] = b"LIB\0";
 /// The format version.
 ///
 /// If future modifications are made to this format, the version should be incremented by 1.
-const VERSION: [u8; 3] = [0, 0, 0];
+const VERSION: [u8; 3] = [0, 0, 5];
 
 // PACKAGE SERIALIZATION/DESERIALIZATION
 // ================================================================================================
@@ -70,8 +76,9 @@ impl Serializable for Package {
         target.write_bytes(MAGIC_PACKAGE);
         target.write_bytes(&VERSION);
 
-        // Write package name
+        // Write package name and version
         self.name.write_into(target);
+        self.version.write_into(target);
 
         // Write MAST artifact
         self.mast.write_into(target);
@@ -101,8 +108,9 @@ impl Deserializable for Package {
             )));
         }
 
-        // Read package name
+        // Read package name and version
         let name = String::read_from(source)?;
+        let version = Version::read_from(source)?;
 
         // Read MAST artifact
         let mast = MastArtifact::read_from(source)?;
@@ -115,6 +123,7 @@ impl Deserializable for Package {
 
         Ok(Self {
             name,
+            version,
             mast,
             manifest,
             account_component_metadata_bytes,
@@ -202,6 +211,7 @@ impl Serializable for PackageExport {
     fn write_into<W: ByteWriter>(&self, target: &mut W) {
         self.name.write_into(target);
         self.digest.write_into(target);
+        self.signature.write_into(target);
     }
 }
 
@@ -209,6 +219,7 @@ impl Deserializable for PackageExport {
     fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
         let name = QualifiedProcedureName::read_from(source)?;
         let digest = Word::read_from(source)?;
-        Ok(Self { name, digest })
+        let signature = Option::<ProcedureSignature>::read_from(source)?;
+        Ok(Self { name, digest, signature })
     }
 }
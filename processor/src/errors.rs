@@ -4,7 +4,9 @@ use core::error::Error;
 use miden_air::RowIndex;
 use miette::Diagnostic;
 use vm_core::{
+    KernelVersion,
     debuginfo::{SourceFile, SourceManager, SourceSpan},
+    errors::describe_kernel,
     mast::{DecoratorId, MastForest, MastNodeExt, MastNodeId},
     stack::MIN_STACK_DEPTH,
     utils::to_hex,
@@ -113,6 +115,41 @@ pub enum ExecutionError {
         source_file: Option<Arc<SourceFile>>,
         depth: usize,
     },
+    #[error(
+        "syscall failed: the program was assembled against {kernel}, but the procedure with root \
+        {hex} of that kernel is not available from the host",
+        kernel = describe_kernel(kernel_version.as_ref()),
+        hex = to_hex(proc_root.as_bytes())
+    )]
+    #[diagnostic(help(
+        "the host was likely loaded with a different version of the kernel, in which case the \
+        program must be reassembled against it"
+    ))]
+    KernelProcedureNotFound {
+        #[label]
+        label: SourceSpan,
+        #[source_code]
+        source_file: Option<Arc<SourceFile>>,
+        proc_root: Word,
+        kernel_version: Option<KernelVersion>,
+    },
+    #[error(
+        "syscall failed: the program was assembled against kernel '{kernel_version}', but the \
+        procedure with root {hex} of that kernel is not in the kernel it is executed against",
+        hex = to_hex(proc_root.as_bytes())
+    )]
+    #[diagnostic(help(
+        "the program must be executed against the kernel it was assembled against, or a newer \
+        version of that kernel which still provides all of its procedures"
+    ))]
+    KernelMismatch {
+        #[label]
+        label: SourceSpan,
+        #[source_code]
+        source_file: Option<Arc<SourceFile>>,
+        proc_root: Word,
+        kernel_version: KernelVersion,
+    },
     #[error("attempted to calculate integer logarithm with zero argument at clock cycle {clk}")]
     #[diagnostic()]
     LogArgumentZero {
@@ -406,6 +443,44 @@ impl ExecutionError {
         Self::SyscallTargetNotInKernel { label, source_file, proc_root }
     }
 
+    /// Converts `self`, raised by a syscall to the kernel procedure `proc_root` of the kernel
+    /// identified by `kernel_version`, into an error naming that kernel, i.e.:
+    /// - [ExecutionError::KernelMismatch] if the procedure is not in the kernel of the process, and
+    ///   the version of the kernel the program was assembled against is known.
+    /// - [ExecutionError::KernelProcedureNotFound] if the host does not provide the procedure.
+    pub fn into_syscall_error(
+        self,
+        proc_root: Word,
+        kernel_version: Option<&KernelVersion>,
+        err_ctx: &impl ErrorContext,
+    ) -> Self {
+        match self {
+            Self::SyscallTargetNotInKernel { label, source_file, proc_root: root }
+                if root == proc_root =>
+            {
+                match kernel_version {
+                    Some(kernel_version) => Self::KernelMismatch {
+                        label,
+                        source_file,
+                        proc_root,
+                        kernel_version: kernel_version.clone(),
+                    },
+                    None => Self::SyscallTargetNotInKernel { label, source_file, proc_root },
+                }
+            },
+            Self::NoMastForestWithProcedure { root_digest, .. } if root_digest == proc_root => {
+                let (label, source_file) = err_ctx.label_and_source_file();
+                Self::KernelProcedureNotFound {
+                    label,
+                    source_file,
+                    proc_root,
+                    kernel_version: kernel_version.cloned(),
+                }
+            },
+            err => err,
+        }
+    }
+
     pub fn failed_arithmetic_evaluation(err_ctx: &impl ErrorContext, error: AceError) -> Self {
         let (label, source_file) = err_ctx.label_and_source_file();
         Self::AceChipError { label, source_file, error }
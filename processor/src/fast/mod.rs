@@ -4,8 +4,8 @@ use core::cmp::min;
 use memory::Memory;
 use miden_air::RowIndex;
 use vm_core::{
-    Decorator, DecoratorIterator, EMPTY_WORD, Felt, Kernel, ONE, Operation, Program, StackOutputs,
-    WORD_SIZE, Word, ZERO,
+    Decorator, DecoratorIterator, EMPTY_WORD, Felt, Kernel, KernelVersion, ONE, Operation, Program,
+    StackOutputs, WORD_SIZE, Word, ZERO,
     debuginfo::{DefaultSourceManager, SourceManager},
     mast::{
         BasicBlockNode, CallNode, DynNode, ExternalNode, JoinNode, LoopNode, MastForest, MastNode,
@@ -149,6 +149,9 @@ pub struct FastProcessor {
 
     /// The source manager (providing information about the location of each instruction).
     source_manager: Arc<dyn SourceManager>,
+
+    /// The version of the kernel the program being executed was assembled against, if known.
+    kernel_version: Option<KernelVersion>,
 }
 
 impl FastProcessor {
@@ -217,6 +220,7 @@ impl FastProcessor {
             ace: Ace::default(),
             in_debug_mode,
             source_manager,
+            kernel_version: None,
         }
     }
 
@@ -330,6 +334,11 @@ impl FastProcessor {
         program: &Program,
         host: &mut impl AsyncHost,
     ) -> Result<StackOutputs, ExecutionError> {
+        self.kernel_version = program.kernel_version().cloned();
+        self.advice
+            .merge_advice_map(program.mast_forest().advice_map())
+            .map_err(|err| ExecutionError::advice_error(err, RowIndex::from(0), &()))?;
+
         self.execute_mast_node(program.entrypoint(), program.mast_forest(), program.kernel(), host)
             .await?;
 
@@ -532,7 +541,8 @@ impl FastProcessor {
         if call_node.is_syscall() {
             // check if the callee is in the kernel
             if !kernel.contains_proc(callee_hash) {
-                return Err(ExecutionError::syscall_target_not_in_kernel(callee_hash, &err_ctx));
+                return Err(ExecutionError::syscall_target_not_in_kernel(callee_hash, &err_ctx)
+                    .into_syscall_error(callee_hash, self.kernel_version.as_ref(), &err_ctx));
             }
 
             // set the system registers to the syscall context
@@ -547,7 +557,14 @@ impl FastProcessor {
         }
 
         // Execute the callee.
-        self.execute_mast_node(call_node.callee(), program, kernel, host).await?;
+        let result = self.execute_mast_node(call_node.callee(), program, kernel, host).await;
+        if call_node.is_syscall() {
+            result.map_err(|err| {
+                err.into_syscall_error(callee_hash, self.kernel_version.as_ref(), &err_ctx)
+            })?;
+        } else {
+            result?;
+        }
 
         // when returning from a function call or a syscall, restore the context of the
         // system registers and the operand stack to what it was prior to
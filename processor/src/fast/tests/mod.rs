@@ -237,6 +237,33 @@ fn test_syscall_fail() {
     );
 }
 
+/// Tests that a syscall to a procedure which is not in the kernel reports the version of the
+/// kernel the program was assembled against, if it is known.
+#[test]
+fn test_syscall_fail_with_kernel_version() {
+    let mut host = DefaultHost::default();
+
+    let stack_inputs = vec![5_u32.into()];
+    let program = {
+        let mut program = MastForest::new();
+        let basic_block_id = program.add_block(vec![Operation::Add], None).unwrap();
+        let root_id = program.add_syscall(basic_block_id).unwrap();
+        program.make_root(root_id);
+
+        Program::new(program.into(), root_id)
+            .with_kernel_version(KernelVersion::new("kernel", "1.0.0"))
+    };
+
+    let processor = FastProcessor::new(&stack_inputs);
+
+    let err = processor.execute_sync(&program, &mut host).unwrap_err();
+    assert_matches!(
+        err,
+        ExecutionError::KernelMismatch { ref kernel_version, .. }
+            if kernel_version.to_string() == "kernel@1.0.0"
+    );
+}
+
 #[test]
 fn test_assert() {
     let mut host = DefaultHost::default();
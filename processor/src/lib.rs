@@ -16,8 +16,8 @@ use miden_air::trace::{
 pub use miden_air::{ExecutionOptions, ExecutionOptionsError, RowIndex};
 use utils::resolve_external_node;
 pub use vm_core::{
-    AssemblyOp, EMPTY_WORD, Felt, Kernel, ONE, Operation, Program, ProgramInfo, QuadExtension,
-    StackInputs, StackOutputs, Word, ZERO,
+    AssemblyOp, EMPTY_WORD, Felt, Kernel, KernelVersion, ONE, Operation, Program, ProgramInfo,
+    QuadExtension, StackInputs, StackOutputs, Word, ZERO,
     crypto::merkle::SMT_DEPTH,
     debuginfo::{DefaultSourceManager, SourceManager, SourceSpan},
     errors::InputError,
@@ -234,6 +234,7 @@ pub struct Process {
     max_cycles: u32,
     enable_tracing: bool,
     source_manager: Arc<dyn SourceManager>,
+    kernel_version: Option<KernelVersion>,
 }
 
 #[cfg(any(test, feature = "testing"))]
@@ -247,6 +248,7 @@ pub struct Process {
     pub max_cycles: u32,
     pub enable_tracing: bool,
     pub source_manager: Arc<dyn SourceManager>,
+    pub kernel_version: Option<KernelVersion>,
 }
 
 impl Process {
@@ -294,6 +296,7 @@ impl Process {
             max_cycles: execution_options.max_cycles(),
             enable_tracing: execution_options.enable_tracing(),
             source_manager,
+            kernel_version: None,
         }
     }
 
@@ -315,6 +318,7 @@ impl Process {
         if self.system.clk() != 0 {
             return Err(ExecutionError::ProgramAlreadyExecuted);
         }
+        self.kernel_version = program.kernel_version().cloned();
 
         self.advice
             .merge_advice_map(program.mast_forest().advice_map())
@@ -474,17 +478,28 @@ impl Process {
         }
 
         // if this is a syscall, make sure the call target exists in the kernel
-        if call_node.is_syscall() {
+        let err_ctx = err_ctx!(program, call_node, self.source_manager.clone());
+        let syscall_target = if call_node.is_syscall() {
             let callee = program.get_node_by_id(call_node.callee()).ok_or_else(|| {
                 ExecutionError::MastNodeNotFoundInForest { node_id: call_node.callee() }
             })?;
-            let err_ctx = err_ctx!(program, call_node, self.source_manager.clone());
-            self.chiplets.kernel_rom.access_proc(callee.digest(), &err_ctx)?;
-        }
-        let err_ctx = err_ctx!(program, call_node, self.source_manager.clone());
+            self.chiplets.kernel_rom.access_proc(callee.digest(), &err_ctx).map_err(|err| {
+                err.into_syscall_error(callee.digest(), self.kernel_version.as_ref(), &err_ctx)
+            })?;
+            Some(callee.digest())
+        } else {
+            None
+        };
 
         self.start_call_node(call_node, program, host)?;
-        self.execute_mast_node(call_node.callee(), program, host)?;
+        self.execute_mast_node(call_node.callee(), program, host).map_err(|err| {
+            match syscall_target {
+                Some(proc_root) => {
+                    err.into_syscall_error(proc_root, self.kernel_version.as_ref(), &err_ctx)
+                },
+                None => err,
+            }
+        })?;
         self.end_call_node(call_node, program, host, &err_ctx)
     }
 
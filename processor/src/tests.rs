@@ -106,7 +106,7 @@ fn test_diagnostic_advice_map_key_not_found_1() {
     let err = build_test.execute().expect_err("expected error");
     assert_diagnostic_lines!(
         err,
-        "advice provider error at clock cycle 3",
+        "advice provider error at clock cycle 1",
         "value for key 0x0000000000000000000000000000000001000000000000000200000000000000 not present in the advice map",
         regex!(r#",-\[test[\d]+:3:31\]"#),
         " 2 |         begin",
@@ -128,7 +128,7 @@ fn test_diagnostic_advice_map_key_not_found_2() {
     let err = build_test.execute().expect_err("expected error");
     assert_diagnostic_lines!(
         err,
-        "advice provider error at clock cycle 3",
+        "advice provider error at clock cycle 1",
         "value for key 0x0000000000000000000000000000000001000000000000000200000000000000 not present in the advice map",
         regex!(r#",-\[test[\d]+:3:31\]"#),
         " 2 |         begin",
@@ -980,7 +980,7 @@ fn test_diagnostic_syscall_target_not_in_kernel() {
     let err = process.execute(&program, &mut DefaultHost::default()).unwrap_err();
     assert_diagnostic_lines!(
         err,
-        "syscall failed: procedure with root d754f5422c74afd0b094889be6b288f9ffd2cc630e3c44d412b1408b2be3b99c was not found in the kernel",
+        "syscall failed: procedure with root f0db3924f3e2d677a51924b09ecef8a12416a6ceb09fadd39785bb4f685cab66 was not found in the kernel",
         regex!(r#",-\[\$exec:3:13\]"#),
         " 2 |         begin",
         " 3 |             syscall.dummy_proc",
@@ -990,6 +990,82 @@ fn test_diagnostic_syscall_target_not_in_kernel() {
     );
 }
 
+// KernelMismatch
+// -------------------------------------------------------------------------------------------------
+
+#[test]
+fn test_diagnostic_kernel_mismatch() {
+    let source_manager = Arc::new(DefaultSourceManager::default());
+
+    let kernel_library = Assembler::new(source_manager.clone())
+        .assemble_kernel("export.foo add.1 end")
+        .unwrap()
+        .with_version(KernelVersion::new("kernel", "1.0.0"));
+    let program = Assembler::with_kernel(source_manager.clone(), kernel_library)
+        .with_debug_mode(true)
+        .assemble_program("begin syscall.foo end")
+        .unwrap();
+
+    // the program is executed against a newer kernel in which `foo` changed
+    let kernel_library = Assembler::new(source_manager.clone())
+        .assemble_kernel("export.foo add.2 end")
+        .unwrap();
+    let mut host = DefaultHost::default();
+    host.load_mast_forest(kernel_library.mast_forest().clone()).unwrap();
+    let mut process = Process::new(
+        kernel_library.kernel().clone(),
+        StackInputs::default(),
+        AdviceInputs::default(),
+        ExecutionOptions::default().with_debugging(true),
+    )
+    .with_source_manager(source_manager.clone());
+    let err = process.execute(&program, &mut host).unwrap_err();
+    assert_diagnostic_lines!(
+        err,
+        "syscall failed: the program was assembled against kernel 'kernel@1.0.0', but the procedure with root 173961e7521f6da4a21785a047fb2908466b2ee168f6cbf7aa98cd37f064c328 of that kernel is not in the",
+        "| kernel it is executed against",
+        regex!(r#",-\[\$exec:1:7\]"#),
+        " 1 | begin syscall.foo end",
+        "   :       ^^^^^^^^^^^",
+        "   `----",
+        "  help: the program must be executed against the kernel it was assembled against, or a newer version of that kernel which still provides all of its procedures"
+    );
+}
+
+#[test]
+fn test_diagnostic_kernel_procedure_not_found() {
+    let source_manager = Arc::new(DefaultSourceManager::default());
+
+    let kernel_library = Assembler::new(source_manager.clone())
+        .assemble_kernel("export.foo push.1 drop end")
+        .unwrap()
+        .with_version(KernelVersion::new("kernel", "1.0.0"));
+    let program = Assembler::with_kernel(source_manager.clone(), kernel_library.clone())
+        .with_debug_mode(true)
+        .assemble_program("begin syscall.foo end")
+        .unwrap();
+
+    // the kernel is provided to the process, but its MAST is not loaded into the host
+    let mut process = Process::new(
+        kernel_library.kernel().clone(),
+        StackInputs::default(),
+        AdviceInputs::default(),
+        ExecutionOptions::default().with_debugging(true),
+    )
+    .with_source_manager(source_manager.clone());
+    let err = process.execute(&program, &mut DefaultHost::default()).unwrap_err();
+    assert_diagnostic_lines!(
+        err,
+        "syscall failed: the program was assembled against kernel 'kernel@1.0.0', but the procedure with root f0db3924f3e2d677a51924b09ecef8a12416a6ceb09fadd39785bb4f685cab66 of that kernel is not",
+        "| available from the host",
+        regex!(r#",-\[\$exec:1:7\]"#),
+        " 1 | begin syscall.foo end",
+        "   :       ^^^^^^^^^^^",
+        "   `----",
+        "  help: the host was likely loaded with a different version of the kernel, in which case the program must be reassembled against it"
+    );
+}
+
 // Tests that the original error message is reported to the user together with
 // the error code in case of assert failure.
 #[test]
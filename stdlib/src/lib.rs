@@ -170,4 +170,10 @@ mod tests {
 
         assert!(exists);
     }
+
+    #[test]
+    fn test_mast_forest_is_well_formed() {
+        let stdlib = StdLibrary::default();
+        assert_eq!(stdlib.mast_forest().validate(), Ok(()));
+    }
 }